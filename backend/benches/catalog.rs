@@ -0,0 +1,172 @@
+//! Lecture du catalogue: requête `EXISTS` corrélée (avant) contre catalogue en
+//! mémoire + ids capturés de l'utilisateur (après).
+//!
+//! Base seedée requise (TEST_DATABASE_URL ou DATABASE_URL):
+//! `cargo bench --bench catalog`
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use pokedex_rncp_backend::catalog::{self, CatalogCache};
+use pokedex_rncp_backend::config::DatabaseConfig;
+use pokedex_rncp_backend::db::init_db;
+use pokedex_rncp_backend::models::pokemon::PokemonWithCaught;
+use sqlx::PgPool;
+use tokio::runtime::Runtime;
+use uuid::Uuid;
+
+const SEARCH: &str = "Pi";
+
+// Handlers avant le cache, à l'identique
+async fn list_all_sql(pool: &PgPool, user_id: Uuid) -> Vec<PokemonWithCaught> {
+    sqlx::query_as::<_, PokemonWithCaught>(
+        r#"
+        SELECT
+            p.id          AS id,
+            p.name        AS name,
+            p.type1       AS type1,
+            p.type2       AS type2,
+            p.dex_no      AS dex_no,
+            p.image_url   AS image_url,
+            EXISTS (
+                SELECT 1 FROM user_pokemon up
+                WHERE up.user_id = $1 AND up.pokemon_id = p.id
+            )            AS caught
+        FROM pokemon p
+        ORDER BY p.id
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+    .unwrap()
+}
+
+async fn search_sql(pool: &PgPool, user_id: Uuid, q: &str) -> Vec<PokemonWithCaught> {
+    sqlx::query_as::<_, PokemonWithCaught>(
+        r#"
+        SELECT
+            p.id          AS id,
+            p.name        AS name,
+            p.type1       AS type1,
+            p.type2       AS type2,
+            p.dex_no      AS dex_no,
+            p.image_url   AS image_url,
+            EXISTS (
+                SELECT 1 FROM user_pokemon up
+                WHERE up.user_id = $1 AND up.pokemon_id = p.id
+            )            AS caught
+        FROM pokemon p
+        WHERE p.name ILIKE $2
+        ORDER BY p.name
+        LIMIT 10
+        "#,
+    )
+    .bind(user_id)
+    .bind(format!("{q}%"))
+    .fetch_all(pool)
+    .await
+    .unwrap()
+}
+
+// Chemin des handlers avec le cache
+async fn list_all_cached(
+    pool: &PgPool,
+    cache: &CatalogCache,
+    user_id: Uuid,
+) -> Vec<PokemonWithCaught> {
+    let collection = catalog::collection(pool, user_id).await.unwrap();
+    let snapshot = cache
+        .at_least(pool, collection.catalog_version)
+        .await
+        .unwrap();
+    snapshot.list(&collection.caught, 0, snapshot.len())
+}
+
+async fn search_cached(
+    pool: &PgPool,
+    cache: &CatalogCache,
+    user_id: Uuid,
+    q: &str,
+) -> Vec<PokemonWithCaught> {
+    let collection = catalog::collection(pool, user_id).await.unwrap();
+    let snapshot = cache
+        .at_least(pool, collection.catalog_version)
+        .await
+        .unwrap();
+    snapshot.search(q, &collection.caught, 10)
+}
+
+/// Utilisateur de mesure qui a capturé un Pokémon sur trois.
+async fn bench_user(pool: &PgPool) -> Uuid {
+    let username = format!("bench_{}", &Uuid::new_v4().simple().to_string()[..8]);
+    let user_id: Uuid = sqlx::query_scalar(
+        "INSERT INTO users (username, email, password) VALUES ($1, $1 || '@example.com', 'x') RETURNING id",
+    )
+    .bind(&username)
+    .fetch_one(pool)
+    .await
+    .unwrap();
+    sqlx::query(
+        r#"
+        INSERT INTO user_pokemon (user_id, pokemon_id)
+        SELECT $1, id FROM pokemon WHERE id % 3 = 0
+        "#,
+    )
+    .bind(user_id)
+    .execute(pool)
+    .await
+    .unwrap();
+    user_id
+}
+
+fn catalog_reads(c: &mut Criterion) {
+    let _ = dotenvy::dotenv();
+    let Ok(url) = std::env::var("TEST_DATABASE_URL").or_else(|_| std::env::var("DATABASE_URL"))
+    else {
+        eprintln!("TEST_DATABASE_URL absente: benchmark du catalogue ignoré");
+        return;
+    };
+    let rt = Runtime::new().unwrap();
+    let pool = rt.block_on(init_db(&url, &DatabaseConfig::default()));
+    let user_id = rt.block_on(bench_user(&pool));
+    let cache = CatalogCache::default();
+    let snapshot = rt.block_on(cache.load(&pool)).unwrap();
+    assert!(!snapshot.is_empty(), "catalogue vide: lancer le seed");
+
+    // Mêmes réponses par les deux chemins
+    assert_eq!(
+        serde_json::to_value(rt.block_on(list_all_sql(&pool, user_id))).unwrap(),
+        serde_json::to_value(rt.block_on(list_all_cached(&pool, &cache, user_id))).unwrap()
+    );
+    assert_eq!(
+        serde_json::to_value(rt.block_on(search_sql(&pool, user_id, SEARCH))).unwrap(),
+        serde_json::to_value(rt.block_on(search_cached(&pool, &cache, user_id, SEARCH))).unwrap()
+    );
+
+    let mut group = c.benchmark_group(format!("catalogue ({} Pokémon)", snapshot.len()));
+    group.bench_function("list_all/sql", |b| {
+        b.to_async(&rt).iter(|| list_all_sql(&pool, user_id))
+    });
+    group.bench_function("list_all/cache", |b| {
+        b.to_async(&rt)
+            .iter(|| list_all_cached(&pool, &cache, user_id))
+    });
+    group.bench_function("search/sql", |b| {
+        b.to_async(&rt).iter(|| search_sql(&pool, user_id, SEARCH))
+    });
+    group.bench_function("search/cache", |b| {
+        b.to_async(&rt)
+            .iter(|| search_cached(&pool, &cache, user_id, SEARCH))
+    });
+    group.finish();
+
+    rt.block_on(async {
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+    });
+}
+
+criterion_group!(benches, catalog_reads);
+criterion_main!(benches);
@@ -0,0 +1,10 @@
+-- Migration: add_daily_challenges_and_streaks (DOWN)
+-- Supprime les tables de défis/séries et le fuseau horaire utilisateur
+
+DROP INDEX IF EXISTS idx_user_pokemon_user_discovered;
+
+DROP TABLE IF EXISTS user_streaks;
+DROP TABLE IF EXISTS reward_grants;
+DROP TABLE IF EXISTS daily_challenge_completions;
+
+ALTER TABLE users DROP COLUMN IF EXISTS timezone;
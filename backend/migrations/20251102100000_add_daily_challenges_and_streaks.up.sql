@@ -0,0 +1,34 @@
+-- Migration: add_daily_challenges_and_streaks (UP)
+-- Fuseau horaire utilisateur, défis quotidiens complétés, récompenses et séries
+
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS timezone VARCHAR(64) NOT NULL DEFAULT 'UTC';
+
+-- Un défi complété par utilisateur et par jour (jour calculé dans le fuseau de l'utilisateur)
+CREATE TABLE IF NOT EXISTS daily_challenge_completions (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    day DATE NOT NULL,
+    challenge_key VARCHAR(100) NOT NULL,
+    completed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, day)
+);
+
+-- Récompenses accordées (source unique par utilisateur, ex: "daily:2025-11-02")
+CREATE TABLE IF NOT EXISTS reward_grants (
+    id SERIAL PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    source VARCHAR(100) NOT NULL,
+    points INTEGER NOT NULL,
+    granted_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE(user_id, source)
+);
+
+-- Série de jours consécutifs avec un défi complété
+CREATE TABLE IF NOT EXISTS user_streaks (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    current_streak INTEGER NOT NULL DEFAULT 0,
+    best_streak INTEGER NOT NULL DEFAULT 0,
+    last_completed_on DATE
+);
+
+CREATE INDEX IF NOT EXISTS idx_user_pokemon_user_discovered ON user_pokemon(user_id, discovered_at);
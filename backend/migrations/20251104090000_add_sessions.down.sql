@@ -0,0 +1,4 @@
+-- Migration: add_sessions (DOWN)
+
+DROP INDEX IF EXISTS idx_sessions_user_id;
+DROP TABLE IF EXISTS sessions;
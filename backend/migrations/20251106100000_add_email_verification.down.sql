@@ -0,0 +1,3 @@
+-- Migration: add_email_verification (DOWN)
+
+ALTER TABLE users DROP COLUMN IF EXISTS email_verified_at;
@@ -0,0 +1,5 @@
+-- Migration: add_email_verification (UP)
+-- Date de vérification de l'email (NULL = non vérifié, remis à NULL si l'email change)
+
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS email_verified_at TIMESTAMPTZ;
@@ -0,0 +1,7 @@
+-- Migration: add_password_reset_tokens (DOWN)
+
+DROP INDEX IF EXISTS idx_password_reset_requests_user_id;
+DROP INDEX IF EXISTS idx_password_reset_requests_ip;
+DROP TABLE IF EXISTS password_reset_requests;
+DROP INDEX IF EXISTS idx_password_reset_tokens_user_id;
+DROP TABLE IF EXISTS password_reset_tokens;
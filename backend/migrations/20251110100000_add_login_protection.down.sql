@@ -0,0 +1,9 @@
+-- Migration: add_login_protection (DOWN)
+
+DROP INDEX IF EXISTS idx_login_attempts_user_id;
+DROP INDEX IF EXISTS idx_login_attempts_ip;
+DROP TABLE IF EXISTS login_attempts;
+ALTER TABLE users
+    DROP COLUMN IF EXISTS locked_until,
+    DROP COLUMN IF EXISTS last_failed_login_at,
+    DROP COLUMN IF EXISTS failed_login_count;
@@ -0,0 +1,23 @@
+-- Migration: add_login_protection (UP)
+-- Verrouillage temporaire des comptes et journal des tentatives de connexion
+
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS failed_login_count INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS last_failed_login_at TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS locked_until TIMESTAMPTZ;
+
+CREATE TABLE IF NOT EXISTS login_attempts (
+    id BIGSERIAL PRIMARY KEY,
+    -- NULL si l'identifiant ne correspond à aucun compte
+    user_id UUID REFERENCES users(id) ON DELETE CASCADE,
+    login VARCHAR(255) NOT NULL,
+    ip VARCHAR(64),
+    user_agent TEXT,
+    success BOOLEAN NOT NULL,
+    -- success | invalid_password | unknown_account | throttled | locked
+    reason VARCHAR(32) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_login_attempts_ip ON login_attempts(ip, created_at);
+CREATE INDEX IF NOT EXISTS idx_login_attempts_user_id ON login_attempts(user_id, created_at);
@@ -0,0 +1,4 @@
+-- Migration: add_api_tokens (DOWN)
+
+DROP INDEX IF EXISTS idx_api_tokens_user_id;
+DROP TABLE IF EXISTS api_tokens;
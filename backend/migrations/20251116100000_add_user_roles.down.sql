@@ -0,0 +1,7 @@
+-- Migration: add_user_roles (DOWN)
+
+DROP INDEX IF EXISTS idx_users_role;
+ALTER TABLE users
+    DROP COLUMN IF EXISTS disabled_reason,
+    DROP COLUMN IF EXISTS disabled_at,
+    DROP COLUMN IF EXISTS role;
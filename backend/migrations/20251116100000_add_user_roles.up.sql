@@ -0,0 +1,12 @@
+-- Migration: add_user_roles (UP)
+-- Rôles (user, moderator, admin) et suspension de compte par un modérateur
+
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS role VARCHAR(16) NOT NULL DEFAULT 'user'
+        CHECK (role IN ('user', 'moderator', 'admin')),
+    -- Suspension manuelle: indépendante du verrouillage anti brute-force (locked_until),
+    -- elle ne se lève ni avec le temps, ni par email, ni par un reset du mot de passe
+    ADD COLUMN IF NOT EXISTS disabled_at TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS disabled_reason TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_users_role ON users(role) WHERE role <> 'user';
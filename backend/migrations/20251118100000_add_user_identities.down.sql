@@ -0,0 +1,4 @@
+-- Migration: add_user_identities (DOWN)
+
+DROP INDEX IF EXISTS idx_user_identities_user_id;
+DROP TABLE IF EXISTS user_identities;
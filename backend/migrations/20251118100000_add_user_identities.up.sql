@@ -0,0 +1,17 @@
+-- Migration: add_user_identities (UP)
+-- Identités externes (OpenID Connect) rattachées à un compte
+
+CREATE TABLE IF NOT EXISTS user_identities (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    -- Nom du fournisseur dans la configuration (OIDC_PROVIDERS)
+    provider VARCHAR(50) NOT NULL,
+    -- Claim "sub" de l'ID token: identifiant stable chez le fournisseur
+    subject VARCHAR(255) NOT NULL,
+    email VARCHAR(255),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_login_at TIMESTAMPTZ,
+    UNIQUE (provider, subject)
+);
+
+CREATE INDEX IF NOT EXISTS idx_user_identities_user_id ON user_identities(user_id);
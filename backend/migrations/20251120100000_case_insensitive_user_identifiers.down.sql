@@ -0,0 +1,3 @@
+-- Migration: case_insensitive_user_identifiers (DOWN)
+DROP INDEX IF EXISTS users_email_lower_key;
+DROP INDEX IF EXISTS users_username_lower_key;
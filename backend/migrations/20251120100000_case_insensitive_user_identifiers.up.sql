@@ -0,0 +1,5 @@
+-- Migration: case_insensitive_user_identifiers (UP)
+-- Unicité insensible à la casse: "Sacha" et "sacha" désignent le même compte.
+-- Échoue si des doublons existent déjà (voir docs/MIGRATIONS.md pour les repérer).
+CREATE UNIQUE INDEX IF NOT EXISTS users_username_lower_key ON users (LOWER(username));
+CREATE UNIQUE INDEX IF NOT EXISTS users_email_lower_key ON users (LOWER(email));
@@ -0,0 +1,40 @@
+-- Migration: add_audit_events (UP)
+-- Journal d'audit des actions sensibles (connexions, mots de passe, profil, suppression)
+
+CREATE TABLE IF NOT EXISTS audit_events (
+    id BIGSERIAL PRIMARY KEY,
+    -- Auteur de l'action (NULL: anonyme ou tâche système). Pas de clé étrangère:
+    -- l'historique survit à la suppression du compte.
+    actor_id UUID,
+    -- login | password.changed | password_reset.completed | email.changed | …
+    action VARCHAR(64) NOT NULL,
+    -- Compte concerné
+    target_id UUID,
+    ip VARCHAR(64),
+    user_agent TEXT,
+    -- success | failure
+    outcome VARCHAR(16) NOT NULL,
+    metadata JSONB NOT NULL DEFAULT '{}'::jsonb,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_events_target ON audit_events(target_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_audit_events_actor ON audit_events(actor_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_audit_events_action ON audit_events(action, created_at);
+CREATE INDEX IF NOT EXISTS idx_audit_events_created_at ON audit_events(created_at);
+
+-- Ajout seul: aucune modification, suppression uniquement par la purge de rétention
+-- (qui active audit.allow_prune dans sa transaction)
+CREATE OR REPLACE FUNCTION audit_events_append_only() RETURNS trigger AS $$
+BEGIN
+    IF TG_OP = 'DELETE' AND current_setting('audit.allow_prune', true) = 'on' THEN
+        RETURN OLD;
+    END IF;
+    RAISE EXCEPTION 'audit_events: journal en ajout seul (% refusé)', TG_OP;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS audit_events_append_only ON audit_events;
+CREATE TRIGGER audit_events_append_only
+    BEFORE UPDATE OR DELETE ON audit_events
+    FOR EACH ROW EXECUTE FUNCTION audit_events_append_only();
@@ -0,0 +1,6 @@
+-- Migration: add_account_deletion (DOWN)
+
+DROP INDEX IF EXISTS idx_users_purge_after;
+ALTER TABLE users
+    DROP COLUMN IF EXISTS purge_after,
+    DROP COLUMN IF EXISTS deleted_at;
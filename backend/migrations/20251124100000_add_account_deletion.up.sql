@@ -0,0 +1,10 @@
+-- Migration: add_account_deletion (UP)
+-- Suppression de compte en deux temps: désactivation immédiate, purge après un délai de grâce
+
+ALTER TABLE users
+    -- Demande de suppression: connexion par session ou token refusée, réactivée par un login réussi
+    ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ,
+    -- Date à partir de laquelle la tâche de purge supprime définitivement la ligne
+    ADD COLUMN IF NOT EXISTS purge_after TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_users_purge_after ON users(purge_after) WHERE purge_after IS NOT NULL;
@@ -0,0 +1,12 @@
+-- Migration: add_catalog_versions (DOWN)
+
+DROP TRIGGER IF EXISTS user_pokemon_collection_version ON user_pokemon;
+DROP FUNCTION IF EXISTS bump_collection_version();
+DROP TRIGGER IF EXISTS pokemon_catalog_version_truncate ON pokemon;
+DROP TRIGGER IF EXISTS pokemon_catalog_version_update ON pokemon;
+DROP TRIGGER IF EXISTS pokemon_catalog_version ON pokemon;
+DROP FUNCTION IF EXISTS bump_catalog_version();
+ALTER TABLE users
+    DROP COLUMN IF EXISTS collection_updated_at,
+    DROP COLUMN IF EXISTS collection_version;
+DROP TABLE IF EXISTS catalog_version;
@@ -0,0 +1,56 @@
+-- Migration: add_catalog_versions (UP)
+-- Compteurs de version pour les ETag du catalogue: version du catalogue (seed,
+-- édition admin) et version de la collection de chaque utilisateur (captures)
+
+CREATE TABLE IF NOT EXISTS catalog_version (
+    -- Ligne unique
+    id BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    version BIGINT NOT NULL DEFAULT 1,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+INSERT INTO catalog_version (id) VALUES (TRUE) ON CONFLICT DO NOTHING;
+
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS collection_version BIGINT NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS collection_updated_at TIMESTAMPTZ;
+
+-- Toute modification effective du catalogue, quelle qu'en soit l'origine, change la version
+CREATE OR REPLACE FUNCTION bump_catalog_version() RETURNS TRIGGER AS $$
+BEGIN
+    UPDATE catalog_version SET version = version + 1, updated_at = NOW();
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS pokemon_catalog_version ON pokemon;
+-- Par ligne: un INSERT … ON CONFLICT qui n'insère rien ne compte pas
+CREATE TRIGGER pokemon_catalog_version
+    AFTER INSERT OR DELETE ON pokemon
+    FOR EACH ROW EXECUTE FUNCTION bump_catalog_version();
+
+DROP TRIGGER IF EXISTS pokemon_catalog_version_update ON pokemon;
+-- Le seed réécrit chaque ligne au démarrage (ON CONFLICT DO UPDATE): seules
+-- les valeurs réellement changées comptent
+CREATE TRIGGER pokemon_catalog_version_update
+    AFTER UPDATE ON pokemon
+    FOR EACH ROW WHEN (OLD.* IS DISTINCT FROM NEW.*)
+    EXECUTE FUNCTION bump_catalog_version();
+
+DROP TRIGGER IF EXISTS pokemon_catalog_version_truncate ON pokemon;
+CREATE TRIGGER pokemon_catalog_version_truncate
+    AFTER TRUNCATE ON pokemon
+    FOR EACH STATEMENT EXECUTE FUNCTION bump_catalog_version();
+
+CREATE OR REPLACE FUNCTION bump_collection_version() RETURNS TRIGGER AS $$
+BEGIN
+    -- Sans effet si l'utilisateur est supprimé par la même requête (cascade)
+    UPDATE users SET collection_version = collection_version + 1, collection_updated_at = NOW()
+    WHERE id = COALESCE(NEW.user_id, OLD.user_id);
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS user_pokemon_collection_version ON user_pokemon;
+CREATE TRIGGER user_pokemon_collection_version
+    AFTER INSERT OR UPDATE OR DELETE ON user_pokemon
+    FOR EACH ROW EXECUTE FUNCTION bump_collection_version();
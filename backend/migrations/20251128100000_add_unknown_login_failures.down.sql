@@ -0,0 +1,5 @@
+-- Migration: add_unknown_login_failures (DOWN)
+
+DROP INDEX IF EXISTS idx_login_attempts_created_at;
+DROP INDEX IF EXISTS idx_unknown_login_failures_last;
+DROP TABLE IF EXISTS unknown_login_failures;
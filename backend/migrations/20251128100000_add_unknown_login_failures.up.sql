@@ -0,0 +1,16 @@
+-- Migration: add_unknown_login_failures (UP)
+-- Compteur d'échecs des identifiants sans compte: même backoff et même
+-- verrouillage qu'un compte existant (pas d'oracle d'existence par le 429/423)
+
+CREATE TABLE IF NOT EXISTS unknown_login_failures (
+    -- Identifiant soumis, en minuscules
+    login VARCHAR(255) PRIMARY KEY,
+    failed_login_count INTEGER NOT NULL DEFAULT 0,
+    last_failed_login_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    locked_until TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_unknown_login_failures_last ON unknown_login_failures(last_failed_login_at);
+
+-- Purge de rétention du journal des tentatives
+CREATE INDEX IF NOT EXISTS idx_login_attempts_created_at ON login_attempts(created_at);
@@ -0,0 +1,4 @@
+-- Migration: add_mfa_pending_logins (DOWN)
+
+DROP INDEX IF EXISTS idx_mfa_pending_logins_expires_at;
+DROP TABLE IF EXISTS mfa_pending_logins;
@@ -0,0 +1,12 @@
+-- Migration: add_mfa_pending_logins (UP)
+-- Logins en attente du second facteur: le token "mfa pending" porte un jti
+-- consommé au premier code valide (pas de rejeu pendant sa durée de vie)
+
+CREATE TABLE IF NOT EXISTS mfa_pending_logins (
+    jti UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_mfa_pending_logins_expires_at ON mfa_pending_logins(expires_at);
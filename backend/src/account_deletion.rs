@@ -0,0 +1,112 @@
+//! Suppression de compte avec délai de grâce.
+//!
+//! `DELETE /api/users/{id}` ferme les sessions et programme la purge
+//! (ACCOUNT_DELETION_GRACE_DAYS, défaut 30). Un login réussi avant
+//! l'échéance annule la demande; ensuite une tâche horaire supprime la
+//! ligne `users` (et, par cascade, collection, sessions, tokens…).
+
+use serde_json::json;
+use sqlx::PgPool;
+use std::time::Duration;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::audit::{self, Event};
+use crate::clock::{Clock, SystemClock};
+use crate::helpers::ClientInfo;
+use crate::sessions::revoke_all;
+
+pub struct ScheduledDeletion {
+    pub username: String,
+    pub email: Option<String>,
+    pub purge_after: OffsetDateTime,
+}
+
+/// Désactive le compte et programme sa purge; `None` s'il est inconnu ou déjà en cours de suppression.
+pub async fn schedule(
+    pool: &PgPool,
+    clock: &dyn Clock,
+    user_id: Uuid,
+    grace_days: i64,
+) -> Result<Option<ScheduledDeletion>, sqlx::Error> {
+    let row = sqlx::query_as::<_, (String, Option<String>, OffsetDateTime)>(
+        r#"
+        UPDATE users SET deleted_at = $3, purge_after = $3 + make_interval(days => $2)
+        WHERE id = $1 AND deleted_at IS NULL
+        RETURNING username, email, purge_after
+        "#,
+    )
+    .bind(user_id)
+    .bind(grace_days as i32)
+    .bind(clock.now())
+    .fetch_optional(pool)
+    .await?;
+    let Some((username, email, purge_after)) = row else {
+        return Ok(None);
+    };
+    revoke_all(pool, user_id, "account_deleted").await?;
+    Ok(Some(ScheduledDeletion {
+        username,
+        email,
+        purge_after,
+    }))
+}
+
+/// Annule une suppression programmée, à appeler après une authentification réussie.
+pub async fn reactivate_on_login(
+    pool: &PgPool,
+    client: &ClientInfo,
+    user_id: Uuid,
+) -> Result<bool, sqlx::Error> {
+    let res = sqlx::query(
+        "UPDATE users SET deleted_at = NULL, purge_after = NULL WHERE id = $1 AND deleted_at IS NOT NULL",
+    )
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+    let reactivated = res.rows_affected() > 0;
+    if reactivated {
+        audit::log(
+            pool,
+            client,
+            Event::own(audit::ACCOUNT_REACTIVATED, user_id),
+        )
+        .await;
+    }
+    Ok(reactivated)
+}
+
+/// Supprime définitivement les comptes dont le délai de grâce est écoulé.
+pub async fn purge_due(
+    pool: &PgPool,
+    clock: &dyn Clock,
+) -> Result<Vec<(Uuid, String)>, sqlx::Error> {
+    sqlx::query_as::<_, (Uuid, String)>(
+        "DELETE FROM users WHERE purge_after <= $1 RETURNING id, username",
+    )
+    .bind(clock.now())
+    .fetch_all(pool)
+    .await
+}
+
+/// Purge horaire des comptes arrivés à échéance (horloge système).
+pub fn spawn_purge(pool: PgPool) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(3600));
+        loop {
+            interval.tick().await;
+            match purge_due(&pool, &SystemClock).await {
+                Ok(purged) => {
+                    for (user_id, username) in purged {
+                        let event = Event::new(audit::ACCOUNT_PURGED)
+                            .target(user_id)
+                            .meta(json!({ "username": username }));
+                        audit::log(&pool, &ClientInfo::default(), event).await;
+                        println!("🧹 Compte {username} supprimé définitivement");
+                    }
+                }
+                Err(e) => eprintln!("❌ Purge des comptes supprimés: {e}"),
+            }
+        }
+    });
+}
@@ -0,0 +1,137 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::auth::ApiTokenInfo;
+use crate::password_reset::hash_token;
+use crate::rng::Rng;
+
+/// Préfixe des tokens d'accès personnels: distingue un PAT d'un JWT sans décodage.
+pub const TOKEN_PREFIX: &str = "pkr_";
+
+/// Lecture du catalogue Pokémon
+pub const SCOPE_POKEDEX_READ: &str = "pokedex:read";
+/// Lecture de sa collection et du défi du jour
+pub const SCOPE_COLLECTION_READ: &str = "collection:read";
+/// Capture de Pokémon
+pub const SCOPE_COLLECTION_WRITE: &str = "collection:write";
+/// Lecture du profil (/api/auth/me)
+pub const SCOPE_PROFILE_READ: &str = "profile:read";
+
+pub const SCOPES: [&str; 4] = [
+    SCOPE_POKEDEX_READ,
+    SCOPE_COLLECTION_READ,
+    SCOPE_COLLECTION_WRITE,
+    SCOPE_PROFILE_READ,
+];
+
+/// Scope exigé d'un token d'accès personnel sur une route, posé avec
+/// `.layer(Extension(RequiredScope(SCOPE_…)))`. Sans lui, `CurrentUser`
+/// refuse les tokens d'accès personnels (seuls les JWT de session passent).
+#[derive(Clone, Copy, Debug)]
+pub struct RequiredScope(pub &'static str);
+
+/// Identité portée par un token d'accès personnel valide.
+#[derive(Clone, Debug)]
+pub struct ApiTokenIdentity {
+    pub token_id: Uuid,
+    pub user_id: Uuid,
+    pub scopes: Vec<String>,
+}
+
+impl ApiTokenIdentity {
+    pub fn allows(&self, scope: &str) -> bool {
+        self.scopes.iter().any(|s| s == scope)
+    }
+}
+
+pub fn is_api_token(token: &str) -> bool {
+    token.starts_with(TOKEN_PREFIX)
+}
+
+/// Crée un token; il n'est renvoyé en clair qu'ici.
+pub async fn create(
+    pool: &PgPool,
+    rng: &dyn Rng,
+    user_id: Uuid,
+    name: &str,
+    scopes: &[String],
+    expires_in_days: Option<i64>,
+) -> Result<(String, ApiTokenInfo), sqlx::Error> {
+    let token = format!("{TOKEN_PREFIX}{}", rng.hex(32));
+    let info = sqlx::query_as::<_, ApiTokenInfo>(
+        r#"
+        INSERT INTO api_tokens (user_id, name, token_hash, prefix, scopes, expires_at)
+        VALUES ($1, $2, $3, $4, $5, NOW() + make_interval(days => $6))
+        RETURNING id, name, prefix, scopes, created_at, last_used_at, expires_at
+        "#,
+    )
+    .bind(user_id)
+    .bind(name)
+    .bind(hash_token(&token))
+    .bind(&token[..TOKEN_PREFIX.len() + 8])
+    .bind(scopes)
+    .bind(expires_in_days.map(|d| d as i32))
+    .fetch_one(pool)
+    .await?;
+    Ok((token, info))
+}
+
+/// Tokens encore utilisables de l'utilisateur, le plus récent en premier.
+pub async fn list_active(pool: &PgPool, user_id: Uuid) -> Result<Vec<ApiTokenInfo>, sqlx::Error> {
+    sqlx::query_as::<_, ApiTokenInfo>(
+        r#"
+        SELECT id, name, prefix, scopes, created_at, last_used_at, expires_at
+        FROM api_tokens
+        WHERE user_id = $1 AND revoked_at IS NULL
+          AND (expires_at IS NULL OR expires_at > NOW())
+        ORDER BY created_at DESC
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Révoque un token de l'utilisateur; `false` s'il est inconnu, déjà révoqué ou à un autre.
+pub async fn revoke_owned(
+    pool: &PgPool,
+    user_id: Uuid,
+    token_id: Uuid,
+) -> Result<bool, sqlx::Error> {
+    let res = sqlx::query(
+        r#"
+        UPDATE api_tokens SET revoked_at = NOW()
+        WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL
+        "#,
+    )
+    .bind(token_id)
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+    Ok(res.rows_affected() > 0)
+}
+
+/// Vérifie un token présenté en Bearer (compte non suspendu ni en cours de suppression) et note sa dernière utilisation.
+pub async fn authenticate(
+    pool: &PgPool,
+    token: &str,
+) -> Result<Option<ApiTokenIdentity>, sqlx::Error> {
+    let row = sqlx::query_as::<_, (Uuid, Uuid, Vec<String>)>(
+        r#"
+        UPDATE api_tokens t SET last_used_at = NOW()
+        FROM users u
+        WHERE t.token_hash = $1 AND t.revoked_at IS NULL
+          AND (t.expires_at IS NULL OR t.expires_at > NOW())
+          AND u.id = t.user_id AND u.disabled_at IS NULL AND u.deleted_at IS NULL
+        RETURNING t.id, t.user_id, t.scopes
+        "#,
+    )
+    .bind(hash_token(token))
+    .fetch_optional(pool)
+    .await?;
+    Ok(row.map(|(token_id, user_id, scopes)| ApiTokenIdentity {
+        token_id,
+        user_id,
+        scopes,
+    }))
+}
@@ -1,22 +1,92 @@
-use crate::{helpers, routes};
+use crate::config::AppConfig;
+use crate::rate_limit::{self, Policy, RateLimiter};
+use crate::state::AppState;
+use crate::versioning::{self, ApiVersion};
+use crate::{csrf, handlers, helpers, metrics, openapi, routes};
 use axum::{Router, routing::get};
-use sqlx::PgPool;
 use tower_http::services::{ServeDir, ServeFile};
 
-pub fn build_routes() -> Router<PgPool> {
+/// Routes de l'API v1, aussi servies par l'alias `/api`.
+fn api_routes(config: &'static AppConfig, pokemons: &RateLimiter) -> Router<AppState> {
+    Router::new()
+        .route("/", get(|| async { "Bienvenue sur le pokeRncp" }))
+        .nest("/auth", routes::auth::user_routes(config))
+        .nest("/users", routes::user::user_routes(config))
+        .nest("/me", routes::me::me_routes())
+        .nest("/admin", routes::admin::admin_routes())
+        // Limitation de débit par défaut (les routes sensibles ajoutent leur propre politique)
+        .route_layer(axum::middleware::from_fn_with_state(
+            RateLimiter::new(config, Policy::DEFAULT),
+            rate_limit::rate_limit,
+        ))
+        // Catalogue: politique large dédiée, ajoutée après la politique par défaut
+        .nest(
+            "/pokemons",
+            routes::pokemon::pokemon_routes(pokemons.clone()),
+        )
+}
+
+/// Endpoints dont la réponse change en v2, et eux seuls: le reste de l'API
+/// n'existe que sous `/api/v1`, une requête `/api/v2/…` sans v2 dédiée reçoit 404.
+fn api_v2_routes(pokemons: &RateLimiter) -> Router<AppState> {
+    Router::new()
+        .nest(
+            "/pokemons",
+            routes::pokemon::pokemon_v2_routes(pokemons.clone()),
+        )
+        .fallback(|| async { helpers::not_found("Endpoint sans version 2: utiliser /api/v1") })
+}
+
+pub fn build_routes(config: &'static AppConfig) -> Router<AppState> {
+    // Compteurs de débit partagés entre v1, v2 et l'alias /api
+    let pokemons = routes::pokemon::pokemon_limiter(config);
+    let routes = api_routes(config, &pokemons);
     let api = Router::new()
-        .route("/api", get(|| async { "Bienvenue sur le pokeRncp" }))
-        .nest("/api/auth", routes::auth::user_routes())
-        .nest("/api/users", routes::user::user_routes())
-        .nest("/api/pokemons", routes::pokemon::pokemon_routes());
+        .route("/.well-known/jwks.json", get(handlers::auth::jwks))
+        .nest(ApiVersion::V1.prefix(), routes.clone())
+        .nest(ApiVersion::V2.prefix(), api_v2_routes(&pokemons));
+    // Alias historique de v1, annoncé obsolète (API_LEGACY_ALIAS, API_LEGACY_SUNSET)
+    let api = if config.api.legacy_alias {
+        api.nest(
+            "/api",
+            routes.route_layer(axum::middleware::from_fn_with_state(
+                config,
+                versioning::deprecated,
+            )),
+        )
+    } else {
+        api
+    };
+    let api = api
+        // Sondes (healthchecks docker-compose, orchestrateurs): jamais limitées
+        .route("/healthz", get(handlers::health::healthz))
+        .route("/readyz", get(handlers::health::readyz))
+        .route("/version", get(handlers::health::version))
+        // Spécification OpenAPI et Swagger UI
+        .merge(openapi::docs_routes());
+    // /metrics sur le port public seulement sur demande (METRICS_PUBLIC) et sans écouteur dédié
+    let api = if config.metrics.on_main_listener() {
+        api.merge(metrics_routes())
+    } else {
+        api
+    };
+    // Compteurs et latences par modèle de route: après toutes les routes
+    let api = api.route_layer(axum::middleware::from_fn(metrics::track));
 
     // Service des fichiers statiques (frontend Yew buildé)
     // Utilise un fallback_service à la racine pour supporter le routage SPA (Axum >= 0.8)
     let spa_service = ServeDir::new("static").fallback(ServeFile::new("static/index.html"));
 
     api
+        // CSRF: mutations authentifiées par cookie (double-submit X-CSRF-Token)
+        .layer(axum::middleware::from_fn_with_state(config, csrf::protect))
         // Nettoyage du contexte utilisateur au début de chaque requête
         .layer(axum::middleware::from_fn(helpers::clear_user_mw))
         // Toute requête non prise par /api tombera sur le service statique
         .fallback_service(spa_service)
 }
+
+/// Routeur de l'écouteur dédié aux métriques (METRICS_BIND).
+pub fn metrics_routes() -> Router<AppState> {
+    Router::new().route("/metrics", get(handlers::health::metrics))
+}
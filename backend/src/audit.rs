@@ -0,0 +1,157 @@
+//! Journal d'audit des actions sensibles (table `audit_events`, en ajout seul).
+//!
+//! L'écriture ne bloque jamais l'action auditée: une erreur est journalisée
+//! sur stderr. Les événements plus anciens que AUDIT_RETENTION_DAYS sont
+//! purgés par une tâche quotidienne.
+
+use serde_json::{Value, json};
+use sqlx::PgPool;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::helpers::ClientInfo;
+
+pub const LOGIN: &str = "login";
+/// Mot de passe correct, connexion en attente du code 2FA
+pub const LOGIN_MFA_REQUIRED: &str = "login.mfa_required";
+pub const ACCOUNT_CREATED: &str = "account.created";
+pub const ACCOUNT_DELETED: &str = "account.deleted";
+/// Suppression annulée par un login pendant le délai de grâce
+pub const ACCOUNT_REACTIVATED: &str = "account.reactivated";
+/// Fin du délai de grâce: ligne `users` supprimée
+pub const ACCOUNT_PURGED: &str = "account.purged";
+pub const DATA_EXPORTED: &str = "account.data_exported";
+pub const PASSWORD_CHANGED: &str = "password.changed";
+pub const PASSWORD_RESET_REQUESTED: &str = "password_reset.requested";
+pub const PASSWORD_RESET_COMPLETED: &str = "password_reset.completed";
+pub const USERNAME_CHANGED: &str = "username.changed";
+pub const EMAIL_CHANGED: &str = "email.changed";
+pub const AUDIT_PRUNED: &str = "audit.pruned";
+/// Suspension par un modérateur ou un admin (`disabled_at`)
+pub const ACCOUNT_LOCKED: &str = "account.locked";
+/// Levée de la suspension et du verrouillage anti brute-force
+pub const ACCOUNT_UNLOCKED: &str = "account.unlocked";
+/// Mot de passe invalidé par un admin, lien de reset envoyé
+pub const PASSWORD_RESET_FORCED: &str = "password_reset.forced";
+pub const ROLE_CHANGED: &str = "role.changed";
+/// Correction d'une fiche Pokémon (sans compte visé)
+pub const POKEMON_UPDATED: &str = "pokemon.updated";
+
+pub const OUTCOMES: [&str; 2] = ["success", "failure"];
+
+#[derive(Clone, Debug)]
+pub struct Event {
+    pub action: &'static str,
+    pub actor: Option<Uuid>,
+    pub target: Option<Uuid>,
+    pub success: bool,
+    pub metadata: Value,
+}
+
+impl Event {
+    pub fn new(action: &'static str) -> Self {
+        Event {
+            action,
+            actor: None,
+            target: None,
+            success: true,
+            metadata: json!({}),
+        }
+    }
+
+    /// Action d'un utilisateur sur son propre compte.
+    pub fn own(action: &'static str, user_id: Uuid) -> Self {
+        Event::new(action).actor(user_id).target(user_id)
+    }
+
+    pub fn actor(mut self, user_id: Uuid) -> Self {
+        self.actor = Some(user_id);
+        self
+    }
+
+    pub fn target(mut self, user_id: Uuid) -> Self {
+        self.target = Some(user_id);
+        self
+    }
+
+    pub fn failed(mut self) -> Self {
+        self.success = false;
+        self
+    }
+
+    pub fn meta(mut self, metadata: Value) -> Self {
+        self.metadata = metadata;
+        self
+    }
+}
+
+pub async fn record(pool: &PgPool, client: &ClientInfo, event: &Event) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO audit_events (actor_id, action, target_id, ip, user_agent, outcome, metadata)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        "#,
+    )
+    .bind(event.actor)
+    .bind(event.action)
+    .bind(event.target)
+    .bind(&client.ip)
+    .bind(&client.user_agent)
+    .bind(if event.success { "success" } else { "failure" })
+    .bind(&event.metadata)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Comme `record`, sans faire échouer la requête en cours.
+pub async fn log(pool: &PgPool, client: &ClientInfo, event: Event) {
+    if let Err(e) = record(pool, client, &event).await {
+        eprintln!("❌ Audit {}: {e}", event.action);
+    }
+}
+
+/// Supprime les événements de plus de `older_than_days` jours.
+pub async fn prune(pool: &PgPool, older_than_days: i64) -> Result<u64, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    // Seule voie de suppression acceptée par le trigger d'ajout seul
+    sqlx::query("SET LOCAL audit.allow_prune = 'on'")
+        .execute(&mut *tx)
+        .await?;
+    let deleted = sqlx::query(
+        "DELETE FROM audit_events WHERE created_at < NOW() - make_interval(days => $1)",
+    )
+    .bind(older_than_days as i32)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+    tx.commit().await?;
+    Ok(deleted)
+}
+
+/// Événement tracé pour chaque purge effective.
+pub fn pruned(older_than_days: i64, deleted: u64) -> Event {
+    Event::new(AUDIT_PRUNED).meta(json!({ "older_than_days": older_than_days, "deleted": deleted }))
+}
+
+/// Purge quotidienne des événements de plus de `days` jours
+/// (AUDIT_RETENTION_DAYS, défaut 365; rien si 0).
+pub fn spawn_retention(pool: PgPool, days: i64) {
+    if days == 0 {
+        return;
+    }
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(24 * 3600));
+        loop {
+            interval.tick().await;
+            match prune(&pool, days).await {
+                Ok(0) => {}
+                Ok(n) => {
+                    log(&pool, &ClientInfo::default(), pruned(days, n)).await;
+                    println!("🧹 Audit: {n} événements de plus de {days} jours purgés");
+                }
+                Err(e) => eprintln!("❌ Purge du journal d'audit: {e}"),
+            }
+        }
+    });
+}
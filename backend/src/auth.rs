@@ -7,12 +7,18 @@ use argon2::{
 };
 
 use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation, decode, encode};
+use sqlx::PgPool;
 use uuid::Uuid;
 
-use axum::extract::FromRequestParts;
+use axum::extract::{FromRef, FromRequestParts};
 use axum::http::{HeaderMap, StatusCode, header, request::Parts};
 
-use crate::models::auth::{Claims, ResetClaims};
+use crate::api_tokens::{RequiredScope, is_api_token};
+use crate::clock::SharedClock;
+use crate::config::AppConfig;
+use crate::models::auth::{
+    Claims, EmailVerifyClaims, MfaPendingClaims, OidcFlowClaims, UnlockClaims,
+};
 
 pub fn hash_password(password: &str) -> Result<String, PHCError> {
     let salt = SaltString::generate(&mut OsRng);
@@ -32,112 +38,252 @@ pub fn verify_password(hash: &str, password: &str) -> bool {
     }
 }
 
-fn access_secret() -> String {
-    std::env::var("JWT_SECRET").expect("JWT_SECRET must be set")
+pub fn generate_access_token(
+    config: &AppConfig,
+    user_id: Uuid,
+) -> Result<String, jsonwebtoken::errors::Error> {
+    encode_access(config, user_id, None)
 }
 
-fn refresh_secret() -> String {
-    std::env::var("JWT_REFRESH_SECRET").unwrap_or_else(|_| access_secret())
+/// Access token rattaché à une session serveur (révocable via `sessions`).
+pub fn generate_session_access_token(
+    config: &AppConfig,
+    user_id: Uuid,
+    session_id: Uuid,
+) -> Result<String, jsonwebtoken::errors::Error> {
+    encode_access(config, user_id, Some(session_id))
 }
 
-fn access_ttl_secs() -> i64 {
-    std::env::var("JWT_EXP_SECONDS")
-        .ok()
-        .and_then(|s| s.parse::<i64>().ok())
-        .unwrap_or(900)
+fn encode_access(
+    config: &AppConfig,
+    user_id: Uuid,
+    session_id: Option<Uuid>,
+) -> Result<String, jsonwebtoken::errors::Error> {
+    let now = time::OffsetDateTime::now_utc().unix_timestamp();
+    let claims = Claims {
+        sub: user_id,
+        iat: now,
+        exp: now + config.auth.access_ttl_secs,
+        sid: session_id,
+        jti: None,
+    };
+    config.keys.loaded().access.sign(&claims)
 }
 
-fn refresh_ttl_secs() -> i64 {
-    std::env::var("JWT_REFRESH_EXP_SECONDS")
-        .ok()
-        .and_then(|s| s.parse::<i64>().ok())
-        .unwrap_or(2_592_000)
+/// Refresh token d'une session: `jti` doit correspondre au `current_jti` de la
+/// session et `exp` à son expiration absolue.
+pub fn generate_refresh_token(
+    config: &AppConfig,
+    user_id: Uuid,
+    session_id: Uuid,
+    jti: Uuid,
+    exp: i64,
+) -> Result<String, jsonwebtoken::errors::Error> {
+    let now = time::OffsetDateTime::now_utc().unix_timestamp();
+    let claims = Claims {
+        sub: user_id,
+        iat: now,
+        exp,
+        sid: Some(session_id),
+        jti: Some(jti),
+    };
+    config.keys.loaded().refresh.sign(&claims)
 }
 
-pub fn generate_access_token(user_id: Uuid) -> Result<String, jsonwebtoken::errors::Error> {
+pub fn verify_access(
+    config: &AppConfig,
+    token: &str,
+) -> Result<Claims, jsonwebtoken::errors::Error> {
+    config.keys.loaded().access.verify(token)
+}
+
+pub fn verify_refresh(
+    config: &AppConfig,
+    token: &str,
+) -> Result<Claims, jsonwebtoken::errors::Error> {
+    config.keys.loaded().refresh.verify(token)
+}
+
+// Clé dédiée à un type de token: à défaut de variable spécifique, elle est dérivée
+// de JWT_SECRET sans lui être égale, pour qu'un token à usage unique (vérification,
+// déverrouillage, 2FA) ne puisse jamais être présenté comme access token.
+fn scoped_secret(config: &AppConfig, var: &str, scope: &str) -> String {
+    match config.keys.scoped_secret(var) {
+        Some(secret) => secret.to_string(),
+        None => format!("{}:{scope}", config.keys.loaded().root),
+    }
+}
+
+/// Clé des jetons CSRF (double-submit lié à la session).
+pub(crate) fn csrf_secret(config: &AppConfig) -> String {
+    scoped_secret(config, "CSRF_SECRET", "csrf")
+}
+
+fn email_verify_secret(config: &AppConfig) -> String {
+    scoped_secret(config, "EMAIL_VERIFY_SECRET", "email_verify")
+}
+
+/// Token de vérification lié à l'adresse: il devient caduc si l'email change entre-temps.
+pub fn generate_email_verification_token(
+    config: &AppConfig,
+    user_id: Uuid,
+    email: &str,
+) -> Result<String, jsonwebtoken::errors::Error> {
     let now = time::OffsetDateTime::now_utc().unix_timestamp();
-    let claims = Claims {
+    let claims = EmailVerifyClaims {
         sub: user_id,
+        email: email.to_string(),
         iat: now,
-        exp: now + access_ttl_secs(),
+        exp: now + config.auth.email_verify_ttl_secs,
+        scope: "email_verify".to_string(),
     };
     encode(
         &Header::default(),
         &claims,
-        &EncodingKey::from_secret(access_secret().as_bytes()),
+        &EncodingKey::from_secret(email_verify_secret(config).as_bytes()),
     )
 }
 
-pub fn generate_refresh_token(user_id: Uuid) -> Result<String, jsonwebtoken::errors::Error> {
-    let now = time::OffsetDateTime::now_utc().unix_timestamp();
-    let claims = Claims {
+pub fn verify_email_token(
+    config: &AppConfig,
+    token: &str,
+) -> Result<EmailVerifyClaims, jsonwebtoken::errors::Error> {
+    let validation = Validation::default();
+    let data = decode::<EmailVerifyClaims>(
+        token,
+        &DecodingKey::from_secret(email_verify_secret(config).as_bytes()),
+        &validation,
+    )?;
+    if data.claims.scope != "email_verify" {
+        return Err(jsonwebtoken::errors::Error::from(
+            jsonwebtoken::errors::ErrorKind::InvalidToken,
+        ));
+    }
+    Ok(data.claims)
+}
+
+fn unlock_secret(config: &AppConfig) -> String {
+    scoped_secret(config, "UNLOCK_SECRET", "account_unlock")
+}
+
+/// Token de déverrouillage lié au verrou courant: il expire avec lui et ne sert
+/// plus après un déverrouillage ou un nouveau verrou.
+pub fn generate_unlock_token(
+    config: &AppConfig,
+    user_id: Uuid,
+    locked_until: i64,
+) -> Result<String, jsonwebtoken::errors::Error> {
+    let claims = UnlockClaims {
         sub: user_id,
-        iat: now,
-        exp: now + refresh_ttl_secs(),
+        lock: locked_until,
+        iat: time::OffsetDateTime::now_utc().unix_timestamp(),
+        exp: locked_until,
+        scope: "account_unlock".to_string(),
     };
     encode(
         &Header::default(),
         &claims,
-        &EncodingKey::from_secret(refresh_secret().as_bytes()),
+        &EncodingKey::from_secret(unlock_secret(config).as_bytes()),
     )
 }
 
-pub fn verify_access(token: &str) -> Result<Claims, jsonwebtoken::errors::Error> {
+pub fn verify_unlock_token(
+    config: &AppConfig,
+    token: &str,
+) -> Result<UnlockClaims, jsonwebtoken::errors::Error> {
     let validation = Validation::default();
-    let data = decode::<Claims>(
+    let data = decode::<UnlockClaims>(
         token,
-        &DecodingKey::from_secret(access_secret().as_bytes()),
+        &DecodingKey::from_secret(unlock_secret(config).as_bytes()),
         &validation,
     )?;
+    if data.claims.scope != "account_unlock" {
+        return Err(jsonwebtoken::errors::Error::from(
+            jsonwebtoken::errors::ErrorKind::InvalidToken,
+        ));
+    }
     Ok(data.claims)
 }
 
-pub fn verify_refresh(token: &str) -> Result<Claims, jsonwebtoken::errors::Error> {
+fn mfa_secret(config: &AppConfig) -> String {
+    scoped_secret(config, "MFA_SECRET", "mfa_pending")
+}
+
+/// Token intermédiaire du login en deux étapes: mot de passe validé, code 2FA attendu.
+/// Le `jti` doit être enregistré (`mfa::issue_pending_login`) pour être accepté.
+pub fn generate_mfa_pending_token(
+    config: &AppConfig,
+    user_id: Uuid,
+    jti: Uuid,
+) -> Result<String, jsonwebtoken::errors::Error> {
+    let now = time::OffsetDateTime::now_utc().unix_timestamp();
+    let claims = MfaPendingClaims {
+        sub: user_id,
+        jti,
+        iat: now,
+        exp: now + config.auth.mfa_pending_ttl_secs,
+        scope: "mfa_pending".to_string(),
+    };
+    encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(mfa_secret(config).as_bytes()),
+    )
+}
+
+pub fn verify_mfa_pending_token(
+    config: &AppConfig,
+    token: &str,
+) -> Result<MfaPendingClaims, jsonwebtoken::errors::Error> {
     let validation = Validation::default();
-    let data = decode::<Claims>(
+    let data = decode::<MfaPendingClaims>(
         token,
-        &DecodingKey::from_secret(refresh_secret().as_bytes()),
+        &DecodingKey::from_secret(mfa_secret(config).as_bytes()),
         &validation,
     )?;
+    if data.claims.scope != "mfa_pending" {
+        return Err(jsonwebtoken::errors::Error::from(
+            jsonwebtoken::errors::ErrorKind::InvalidToken,
+        ));
+    }
     Ok(data.claims)
 }
 
-fn reset_secret() -> String {
-    std::env::var("RESET_SECRET")
-        .ok()
-        .unwrap_or_else(access_secret)
+fn oidc_flow_secret(config: &AppConfig) -> String {
+    scoped_secret(config, "OIDC_STATE_SECRET", "oidc_flow")
 }
 
-fn reset_ttl_secs() -> i64 {
-    std::env::var("RESET_TOKEN_EXP_SECONDS")
-        .ok()
-        .and_then(|s| s.parse::<i64>().ok())
-        .unwrap_or(3600) // 1h
-}
+/// Durée max entre le départ vers le fournisseur OIDC et le retour sur le callback.
+pub const OIDC_FLOW_TTL_SECS: i64 = 600;
 
-pub fn generate_reset_token(user_id: Uuid) -> Result<String, jsonwebtoken::errors::Error> {
+/// Cookie signé qui accompagne le navigateur pendant le login OIDC (state, nonce,
+/// code_verifier PKCE): le callback n'est accepté que depuis ce même navigateur.
+pub fn generate_oidc_flow_token(
+    config: &AppConfig,
+    mut claims: OidcFlowClaims,
+) -> Result<String, jsonwebtoken::errors::Error> {
     let now = time::OffsetDateTime::now_utc().unix_timestamp();
-    let claims = ResetClaims {
-        sub: user_id,
-        iat: now,
-        exp: now + reset_ttl_secs(),
-        scope: "pwd_reset".to_string(),
-    };
+    claims.iat = now;
+    claims.exp = now + OIDC_FLOW_TTL_SECS;
+    claims.scope = "oidc_flow".to_string();
     encode(
         &Header::default(),
         &claims,
-        &EncodingKey::from_secret(reset_secret().as_bytes()),
+        &EncodingKey::from_secret(oidc_flow_secret(config).as_bytes()),
     )
 }
 
-pub fn verify_reset(token: &str) -> Result<ResetClaims, jsonwebtoken::errors::Error> {
+pub fn verify_oidc_flow_token(
+    config: &AppConfig,
+    token: &str,
+) -> Result<OidcFlowClaims, jsonwebtoken::errors::Error> {
     let validation = Validation::default();
-    let data = decode::<ResetClaims>(
+    let data = decode::<OidcFlowClaims>(
         token,
-        &DecodingKey::from_secret(reset_secret().as_bytes()),
+        &DecodingKey::from_secret(oidc_flow_secret(config).as_bytes()),
         &validation,
     )?;
-    if data.claims.scope != "pwd_reset" {
+    if data.claims.scope != "oidc_flow" {
         return Err(jsonwebtoken::errors::Error::from(
             jsonwebtoken::errors::ErrorKind::InvalidToken,
         ));
@@ -148,7 +294,7 @@ pub fn verify_reset(token: &str) -> Result<ResetClaims, jsonwebtoken::errors::Er
 #[derive(Clone, Copy, Debug)]
 pub struct CurrentUser(pub Uuid);
 
-fn get_cookie(headers: &HeaderMap, name: &str) -> Option<String> {
+pub(crate) fn get_cookie(headers: &HeaderMap, name: &str) -> Option<String> {
     let cookie_header = headers.get(header::COOKIE)?.to_str().ok()?;
     for part in cookie_header.split(';') {
         let p = part.trim();
@@ -159,27 +305,113 @@ fn get_cookie(headers: &HeaderMap, name: &str) -> Option<String> {
     None
 }
 
-fn get_bearer(headers: &HeaderMap) -> Option<String> {
+pub(crate) fn get_bearer(headers: &HeaderMap) -> Option<String> {
     let v = headers.get(header::AUTHORIZATION)?.to_str().ok()?;
     v.strip_prefix("Bearer ").map(|s| s.to_string())
 }
 
+/// Session (au sens table `sessions`) de l'access token courant, si le token en porte une.
+#[derive(Clone, Copy, Debug)]
+pub struct CurrentSession(pub Option<Uuid>);
+
 impl<S> FromRequestParts<S> for CurrentUser
 where
+    PgPool: FromRef<S>,
+    &'static AppConfig: FromRef<S>,
+    SharedClock: FromRef<S>,
     S: Send + Sync,
 {
     type Rejection = (StatusCode, String);
 
-    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
         let headers = &parts.headers;
         let token = get_cookie(headers, "auth")
             .or_else(|| get_bearer(headers))
             .ok_or((StatusCode::UNAUTHORIZED, "Token manquant".into()))?;
 
-        let claims = verify_access(&token)
+        if is_api_token(&token) {
+            return authenticate_api_token(parts, state, &token).await;
+        }
+
+        let config = <&'static AppConfig>::from_ref(state);
+        let claims = verify_access(config, &token)
             .map_err(|_| (StatusCode::UNAUTHORIZED, "Token invalide".into()))?;
+
+        // Session révoquée: refus (délai borné par le cache de `sessions::is_active`)
+        if let Some(sid) = claims.sid {
+            let pool = PgPool::from_ref(state);
+            let clock = SharedClock::from_ref(state);
+            let check_interval = config.auth.session_check_interval_secs;
+            let active = crate::sessions::is_active(&pool, &*clock, sid, check_interval)
+                .await
+                .map_err(crate::helpers::to_500)?;
+            if !active {
+                return Err((StatusCode::UNAUTHORIZED, "Session révoquée".into()));
+            }
+        }
+
         // Définit l'utilisateur courant pour enrichir les logs
         crate::helpers::set_current_user(Some(claims.sub));
+        parts.extensions.insert(CurrentSession(claims.sid));
         Ok(CurrentUser(claims.sub))
     }
 }
+
+// Token d'accès personnel: accepté seulement sur les routes qui déclarent un
+// `RequiredScope` couvert par le token.
+async fn authenticate_api_token<S>(
+    parts: &mut Parts,
+    state: &S,
+    token: &str,
+) -> Result<CurrentUser, (StatusCode, String)>
+where
+    PgPool: FromRef<S>,
+    S: Send + Sync,
+{
+    let pool = PgPool::from_ref(state);
+    let identity = crate::api_tokens::authenticate(&pool, token)
+        .await
+        .map_err(crate::helpers::to_500)?
+        .ok_or((StatusCode::UNAUTHORIZED, "Token invalide".into()))?;
+
+    match parts.extensions.get::<RequiredScope>() {
+        None => {
+            return Err((
+                StatusCode::FORBIDDEN,
+                "Route inaccessible avec un token d'API".into(),
+            ));
+        }
+        Some(RequiredScope(scope)) if !identity.allows(scope) => {
+            return Err((StatusCode::FORBIDDEN, format!("Scope requis: {scope}")));
+        }
+        Some(_) => {}
+    }
+
+    crate::helpers::set_current_user(Some(identity.user_id));
+    parts.extensions.insert(CurrentSession(None));
+    let user_id = identity.user_id;
+    parts.extensions.insert(identity);
+    Ok(CurrentUser(user_id))
+}
+
+impl<S> FromRequestParts<S> for CurrentSession
+where
+    PgPool: FromRef<S>,
+    &'static AppConfig: FromRef<S>,
+    SharedClock: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = (StatusCode, String);
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        if let Some(session) = parts.extensions.get::<CurrentSession>() {
+            return Ok(*session);
+        }
+        CurrentUser::from_request_parts(parts, state).await?;
+        Ok(parts
+            .extensions
+            .get::<CurrentSession>()
+            .copied()
+            .unwrap_or(CurrentSession(None)))
+    }
+}
@@ -0,0 +1,244 @@
+//! Catalogue en mémoire.
+//!
+//! La table `pokemon` ne change qu'au seed ou par une correction admin: elle est
+//! chargée une fois (au démarrage, puis à chaque changement de version) et
+//! partagée par les requêtes. Par requête, seule la collection de l'utilisateur
+//! est lue (ids capturés, via l'index de `user_pokemon`) puis superposée au
+//! catalogue pour calculer `caught`.
+//!
+//! L'invalidation suit `catalog_version` (incrémentée par trigger, voir
+//! `http_cache`): la requête de collection relit ce compteur, et un instantané
+//! plus ancien est rechargé. Chaque instance se met ainsi à jour seule, quelle
+//! que soit celle qui a modifié le catalogue.
+
+use sqlx::PgPool;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+use time::OffsetDateTime;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::http_cache::Validators;
+use crate::models::pokemon::{PokemonDetail, PokemonWithCaught};
+
+/// Catalogue à une version donnée.
+#[derive(Debug, Default)]
+pub struct Snapshot {
+    pub version: i64,
+    // Triés par id
+    pokemons: Vec<PokemonDetail>,
+    by_id: HashMap<i32, usize>,
+    // Indices dans l'ordre de `ORDER BY name` (collation de la base)
+    by_name: Vec<usize>,
+    // Noms en minuscules, pour la recherche par préfixe
+    lowercase: Vec<String>,
+}
+
+impl Snapshot {
+    fn new(version: i64, pokemons: Vec<PokemonDetail>, name_order: Vec<i32>) -> Self {
+        let by_id: HashMap<i32, usize> = pokemons
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (p.id, i))
+            .collect();
+        let by_name = name_order
+            .iter()
+            .filter_map(|id| by_id.get(id).copied())
+            .collect();
+        let lowercase = pokemons.iter().map(|p| p.name.to_lowercase()).collect();
+        Snapshot {
+            version,
+            pokemons,
+            by_id,
+            by_name,
+            lowercase,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.pokemons.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pokemons.is_empty()
+    }
+
+    /// Catalogue trié par id (`offset`/`limit` en v2), avec l'état de capture.
+    pub fn list(
+        &self,
+        caught: &HashSet<i32>,
+        offset: usize,
+        limit: usize,
+    ) -> Vec<PokemonWithCaught> {
+        self.pokemons
+            .iter()
+            .skip(offset)
+            .take(limit)
+            .map(|p| summary(p, caught))
+            .collect()
+    }
+
+    /// Pokémon dont le nom commence par `prefix` (sans tenir compte de la
+    /// casse), triés par nom.
+    pub fn search(
+        &self,
+        prefix: &str,
+        caught: &HashSet<i32>,
+        limit: usize,
+    ) -> Vec<PokemonWithCaught> {
+        let prefix = prefix.to_lowercase();
+        self.by_name
+            .iter()
+            .filter(|&&i| self.lowercase[i].starts_with(&prefix))
+            .take(limit)
+            .map(|&i| summary(&self.pokemons[i], caught))
+            .collect()
+    }
+
+    pub fn detail(&self, id: i32, caught: &HashSet<i32>) -> Option<PokemonDetail> {
+        let p = &self.pokemons[*self.by_id.get(&id)?];
+        Some(PokemonDetail {
+            caught: caught.contains(&p.id),
+            ..p.clone()
+        })
+    }
+}
+
+fn summary(p: &PokemonDetail, caught: &HashSet<i32>) -> PokemonWithCaught {
+    PokemonWithCaught {
+        id: p.id,
+        name: p.name.clone(),
+        type1: p.type1.clone(),
+        type2: p.type2.clone(),
+        dex_no: p.dex_no,
+        image_url: p.image_url.clone(),
+        caught: caught.contains(&p.id),
+    }
+}
+
+/// Cache partagé (dans `AppState`) du dernier instantané chargé.
+#[derive(Debug, Default)]
+pub struct CatalogCache {
+    current: RwLock<Arc<Snapshot>>,
+    // Un seul rechargement à la fois: les requêtes concurrentes l'attendent
+    reload: Mutex<()>,
+}
+
+pub type SharedCatalog = Arc<CatalogCache>;
+
+impl CatalogCache {
+    /// Instantané courant, sans vérifier sa version.
+    pub fn current(&self) -> Arc<Snapshot> {
+        self.current.read().unwrap().clone()
+    }
+
+    /// Instantané au moins aussi récent que `version`, rechargé si besoin.
+    pub async fn at_least(
+        &self,
+        pool: &PgPool,
+        version: i64,
+    ) -> Result<Arc<Snapshot>, sqlx::Error> {
+        let snapshot = self.current();
+        if snapshot.version >= version {
+            return Ok(snapshot);
+        }
+        let _reload = self.reload.lock().await;
+        // Rechargé par une autre requête pendant l'attente
+        let snapshot = self.current();
+        if snapshot.version >= version {
+            return Ok(snapshot);
+        }
+        self.load(pool).await
+    }
+
+    /// Recharge le catalogue depuis la base (démarrage, tests).
+    pub async fn load(&self, pool: &PgPool) -> Result<Arc<Snapshot>, sqlx::Error> {
+        let snapshot = Arc::new(load_snapshot(pool).await?);
+        *self.current.write().unwrap() = snapshot.clone();
+        Ok(snapshot)
+    }
+}
+
+async fn load_snapshot(pool: &PgPool) -> Result<Snapshot, sqlx::Error> {
+    // Version et lignes lues dans le même instantané de la base
+    let mut tx = pool.begin().await?;
+    sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ READ ONLY")
+        .execute(&mut *tx)
+        .await?;
+    let version: i64 = sqlx::query_scalar("SELECT version FROM catalog_version")
+        .fetch_one(&mut *tx)
+        .await?;
+    let pokemons = sqlx::query_as::<_, PokemonDetail>(
+        r#"
+        SELECT
+            p.id                 AS id,
+            p.name               AS name,
+            p.type1              AS type1,
+            p.type2              AS type2,
+            p.dex_no             AS dex_no,
+            p.image_url          AS image_url,
+            p.height_m           AS height_m,
+            p.weight_kg          AS weight_kg,
+            p.description        AS description,
+            p.base_hp            AS base_hp,
+            p.base_attack        AS base_attack,
+            p.base_defense       AS base_defense,
+            p.base_sp_attack     AS base_sp_attack,
+            p.base_sp_defense    AS base_sp_defense,
+            p.base_speed         AS base_speed,
+            FALSE                AS caught
+        FROM pokemon p
+        ORDER BY p.id
+        "#,
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+    let name_order: Vec<i32> = sqlx::query_scalar("SELECT id FROM pokemon ORDER BY name")
+        .fetch_all(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    Ok(Snapshot::new(version, pokemons, name_order))
+}
+
+/// Collection d'un utilisateur et validateurs HTTP de ses lectures du catalogue.
+#[derive(Debug)]
+pub struct Collection {
+    pub catalog_version: i64,
+    pub caught: HashSet<i32>,
+    pub validators: Validators,
+}
+
+/// Versions du catalogue et de la collection, et ids capturés: une seule requête,
+/// servie par la clé primaire `(user_id, pokemon_id)` de `user_pokemon`.
+pub async fn collection(pool: &PgPool, user_id: Uuid) -> Result<Collection, sqlx::Error> {
+    let (catalog_version, catalog_at, collection_version, collection_at, caught): (
+        i64,
+        OffsetDateTime,
+        Option<i64>,
+        Option<OffsetDateTime>,
+        Vec<i32>,
+    ) = sqlx::query_as(
+        r#"
+        SELECT c.version, c.updated_at, u.collection_version, u.collection_updated_at,
+               ARRAY(SELECT up.pokemon_id FROM user_pokemon up WHERE up.user_id = $1)
+        FROM catalog_version c
+        LEFT JOIN users u ON u.id = $1
+        "#,
+    )
+    .bind(user_id)
+    .fetch_one(pool)
+    .await?;
+
+    let last_modified = collection_at.map_or(catalog_at, |at| at.max(catalog_at));
+    Ok(Collection {
+        catalog_version,
+        caught: caught.into_iter().collect(),
+        validators: Validators::new(
+            user_id,
+            catalog_version,
+            collection_version.unwrap_or(0),
+            last_modified,
+        ),
+    })
+}
@@ -0,0 +1,26 @@
+use std::sync::Arc;
+use time::OffsetDateTime;
+
+/// Source de l'heure courante, passée en paramètre des requêtes SQL qui calculent
+/// une échéance: codes TOTP, export, backoff et verrouillage de connexion, tokens de
+/// reset, expiration des sessions, purge des comptes et jour du défi quotidien.
+/// Restent sur l'heure système: horodatages d'événements (audit, révocations), tâches
+/// de rétention en arrière-plan, `exp` des JWT, tokens d'API et connexions 2FA en attente.
+/// Le serveur lit l'horloge système; les tests peuvent injecter une horloge figée.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> OffsetDateTime;
+
+    fn unix_now(&self) -> i64 {
+        self.now().unix_timestamp()
+    }
+}
+
+pub type SharedClock = Arc<dyn Clock>;
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> OffsetDateTime {
+        OffsetDateTime::now_utc()
+    }
+}
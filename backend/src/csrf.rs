@@ -0,0 +1,125 @@
+//! Protection CSRF des requêtes authentifiées par cookie.
+//!
+//! Double-submit lié à la session: à la connexion (et à chaque refresh), le
+//! serveur pose un cookie `csrf` lisible par le frontend, de la forme
+//! `<nonce>.<HMAC(sid:nonce)>`. Toute mutation portée par les cookies de
+//! session doit renvoyer la même valeur dans l'en-tête `X-CSRF-Token`: un site
+//! tiers peut faire envoyer les cookies, mais ni les lire ni ajouter l'en-tête.
+//! Les appels en `Authorization: Bearer` ne sont pas concernés.
+
+use axum::extract::{Request, State};
+use axum::http::{HeaderMap, Method, header};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use uuid::Uuid;
+
+use crate::auth::{csrf_secret, get_bearer, get_cookie, verify_access, verify_refresh};
+use crate::config::AppConfig;
+use crate::helpers::forbidden;
+
+pub const COOKIE: &str = "csrf";
+pub const HEADER: &str = "x-csrf-token";
+
+type HmacSha256 = Hmac<Sha256>;
+
+fn mac(config: &AppConfig, binding: Uuid, nonce: &str) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(csrf_secret(config).as_bytes())
+        .expect("HMAC accepte toute taille de clé");
+    mac.update(format!("{binding}:{nonce}").as_bytes());
+    mac
+}
+
+/// Jeton CSRF lié à une session (ou, à défaut, à l'utilisateur).
+pub fn issue(config: &AppConfig, binding: Uuid) -> String {
+    let nonce = Uuid::new_v4().simple().to_string();
+    let tag = mac(config, binding, &nonce).finalize().into_bytes();
+    format!("{nonce}.{}", URL_SAFE_NO_PAD.encode(tag))
+}
+
+/// Vérification en temps constant de la signature du jeton.
+pub fn is_valid(config: &AppConfig, token: &str, binding: Uuid) -> bool {
+    let Some((nonce, tag)) = token.split_once('.') else {
+        return false;
+    };
+    let Ok(tag) = URL_SAFE_NO_PAD.decode(tag) else {
+        return false;
+    };
+    mac(config, binding, nonce).verify_slice(&tag).is_ok()
+}
+
+// Session portée par les cookies: access token valide, sinon refresh token
+// (access expiré, requête de refresh). Aucune: la requête n'est pas
+// authentifiée par cookie et n'a rien à protéger.
+fn cookie_binding(config: &AppConfig, headers: &HeaderMap) -> Option<Uuid> {
+    if let Some(claims) = get_cookie(headers, "auth").and_then(|t| verify_access(config, &t).ok()) {
+        return Some(claims.sid.unwrap_or(claims.sub));
+    }
+    get_cookie(headers, "refresh")
+        .and_then(|t| verify_refresh(config, &t).ok())
+        .map(|claims| claims.sid.unwrap_or(claims.sub))
+}
+
+fn allowed_origins(config: &AppConfig) -> Vec<String> {
+    let server = &config.server;
+    // localhost:3000 n'est autorisé que s'il est FRONTEND_ORIGIN (défaut de développement)
+    [
+        Some(server.frontend_origin.as_str()),
+        server.app_base_url.as_deref(),
+    ]
+    .into_iter()
+    .flatten()
+    .filter_map(origin_of)
+    .collect()
+}
+
+fn origin_of(url: &str) -> Option<String> {
+    let url = reqwest::Url::parse(url).ok()?;
+    Some(url.origin().ascii_serialization())
+}
+
+// Origin, sinon Referer; sans l'un ni l'autre (clients hors navigateur),
+// seul le jeton fait foi.
+fn origin_allowed(config: &AppConfig, headers: &HeaderMap) -> bool {
+    let value = |name| headers.get(name).and_then(|v| v.to_str().ok());
+    let origin = match (value(header::ORIGIN), value(header::REFERER)) {
+        (Some(origin), _) => Some(origin_of(origin).unwrap_or_else(|| origin.to_string())),
+        (None, Some(referer)) => Some(origin_of(referer).unwrap_or_default()),
+        (None, None) => None,
+    };
+    origin.is_none_or(|o| allowed_origins(config).contains(&o))
+}
+
+/// Middleware: refuse (403) les mutations authentifiées par cookie sans jeton valide.
+pub async fn protect(
+    State(config): State<&'static AppConfig>,
+    req: Request,
+    next: Next,
+) -> Response {
+    if matches!(*req.method(), Method::GET | Method::HEAD | Method::OPTIONS) {
+        return next.run(req).await;
+    }
+    let headers = req.headers();
+    // Un en-tête Authorization ne peut pas être forgé par un site tiers
+    if get_bearer(headers).is_some() {
+        return next.run(req).await;
+    }
+    let Some(binding) = cookie_binding(config, headers) else {
+        return next.run(req).await;
+    };
+
+    if !origin_allowed(config, headers) {
+        return forbidden("Origine de la requête non autorisée").into_response();
+    }
+    let token = headers.get(HEADER).and_then(|v| v.to_str().ok());
+    let cookie = get_cookie(headers, COOKIE);
+    match (token, cookie) {
+        (Some(token), Some(cookie)) if token == cookie && is_valid(config, token, binding) => {
+            next.run(req).await
+        }
+        _ => forbidden("Jeton CSRF manquant ou invalide").into_response(),
+    }
+}
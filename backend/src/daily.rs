@@ -0,0 +1,300 @@
+use sqlx::PgPool;
+use time::Date;
+use uuid::Uuid;
+
+use crate::clock::Clock;
+use crate::models::daily::{
+    ChallengeKind, ChallengePokemon, DailyChallenge, DailyStatus, Stat, Streak,
+};
+
+// Plages de dex_no par génération (1 à 5, cf. taille du seed)
+const GENERATIONS: [(i32, i32); 5] = [(1, 151), (152, 251), (252, 386), (387, 493), (494, 649)];
+const STAT_THRESHOLDS: [i32; 4] = [80, 90, 100, 110];
+const POINTS_PER_CATCH: i32 = 10;
+
+// splitmix64: mélange suffisant pour dériver un défi stable à partir d'une date
+fn mix(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+fn pick<T: Copy>(items: &[T], seed: u64) -> T {
+    items[(seed % items.len() as u64) as usize]
+}
+
+/// Génère le défi du jour. Le résultat ne dépend que de `day` et du contenu
+/// de la table `pokemon` (types et générations disponibles).
+pub async fn challenge_for_day(pool: &PgPool, day: Date) -> Result<DailyChallenge, sqlx::Error> {
+    let types: Vec<String> =
+        sqlx::query_scalar(r#"SELECT DISTINCT type1 FROM pokemon ORDER BY type1"#)
+            .fetch_all(pool)
+            .await?;
+    let max_dex: Option<i32> = sqlx::query_scalar(r#"SELECT MAX(dex_no) FROM pokemon"#)
+        .fetch_one(pool)
+        .await?;
+
+    Ok(build_challenge(day, &types, max_dex.unwrap_or(0)))
+}
+
+fn build_challenge(day: Date, types: &[String], max_dex: i32) -> DailyChallenge {
+    let seed = mix(day.to_julian_day() as u64);
+    let target = 1 + ((seed >> 40) % 3) as i32;
+    let generations = GENERATIONS
+        .iter()
+        .take_while(|(start, _)| *start <= max_dex)
+        .count();
+
+    let kind = match seed % 3 {
+        0 if !types.is_empty() => ChallengeKind::CatchType {
+            type_name: types[((seed >> 8) % types.len() as u64) as usize].clone(),
+        },
+        2 if generations > 0 => ChallengeKind::CatchGeneration {
+            generation: 1 + ((seed >> 8) % generations as u64) as i32,
+        },
+        _ => ChallengeKind::CatchStatAtLeast {
+            stat: pick(&Stat::ALL, seed >> 16),
+            min: pick(&STAT_THRESHOLDS, seed >> 24),
+        },
+    };
+
+    let (key, title) = match &kind {
+        ChallengeKind::CatchType { type_name } => (
+            format!("type:{type_name}:{target}"),
+            format!("Attrape {target} Pokémon de type {type_name}"),
+        ),
+        ChallengeKind::CatchStatAtLeast { stat, min } => (
+            format!("stat:{}:{min}:{target}", stat.label()),
+            format!("Attrape {target} Pokémon avec {} ≥ {min}", stat.label()),
+        ),
+        ChallengeKind::CatchGeneration { generation } => (
+            format!("gen:{generation}:{target}"),
+            format!("Attrape {target} Pokémon de la génération {generation}"),
+        ),
+    };
+
+    DailyChallenge {
+        key,
+        title,
+        kind,
+        target,
+        reward_points: POINTS_PER_CATCH * target,
+    }
+}
+
+impl DailyChallenge {
+    pub fn matches(&self, p: &ChallengePokemon) -> bool {
+        match &self.kind {
+            ChallengeKind::CatchType { type_name } => {
+                p.type1 == *type_name || p.type2.as_deref() == Some(type_name)
+            }
+            ChallengeKind::CatchStatAtLeast { stat, min } => {
+                let value = match stat {
+                    Stat::Hp => p.base_hp,
+                    Stat::Attack => p.base_attack,
+                    Stat::Defense => p.base_defense,
+                    Stat::SpAttack => p.base_sp_attack,
+                    Stat::SpDefense => p.base_sp_defense,
+                    Stat::Speed => p.base_speed,
+                };
+                value.is_some_and(|v| v >= *min)
+            }
+            ChallengeKind::CatchGeneration { generation } => {
+                let (start, end) = GENERATIONS[(*generation - 1) as usize];
+                p.dex_no.is_some_and(|n| (start..=end).contains(&n))
+            }
+        }
+    }
+}
+
+/// Jour courant et fuseau de l'utilisateur (les bornes de journée suivent son fuseau).
+pub async fn user_today(
+    pool: &PgPool,
+    clock: &dyn Clock,
+    user_id: Uuid,
+) -> Result<(Date, String), sqlx::Error> {
+    sqlx::query_as::<_, (Date, String)>(
+        r#"SELECT ($2 AT TIME ZONE timezone)::date, timezone FROM users WHERE id = $1"#,
+    )
+    .bind(user_id)
+    .bind(clock.now())
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn is_valid_timezone(pool: &PgPool, tz: &str) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar(r#"SELECT EXISTS (SELECT 1 FROM pg_timezone_names WHERE name = $1)"#)
+        .bind(tz)
+        .fetch_one(pool)
+        .await
+}
+
+async fn progress(
+    pool: &PgPool,
+    user_id: Uuid,
+    day: Date,
+    challenge: &DailyChallenge,
+) -> Result<i32, sqlx::Error> {
+    let caught = sqlx::query_as::<_, ChallengePokemon>(
+        r#"
+        SELECT
+            p.type1, p.type2, p.dex_no,
+            p.base_hp, p.base_attack, p.base_defense,
+            p.base_sp_attack, p.base_sp_defense, p.base_speed
+        FROM user_pokemon up
+        JOIN pokemon p ON p.id = up.pokemon_id
+        JOIN users u ON u.id = up.user_id
+        WHERE up.user_id = $1
+          AND (up.discovered_at AT TIME ZONE u.timezone)::date = $2
+        "#,
+    )
+    .bind(user_id)
+    .bind(day)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(caught.iter().filter(|p| challenge.matches(p)).count() as i32)
+}
+
+/// À appeler après une nouvelle insertion dans `user_pokemon`: valide le défi
+/// du jour si l'objectif est atteint, accorde la récompense et met à jour la série.
+pub async fn record_catch(
+    pool: &PgPool,
+    clock: &dyn Clock,
+    user_id: Uuid,
+) -> Result<(), sqlx::Error> {
+    let (day, _tz) = user_today(pool, clock, user_id).await?;
+    let challenge = challenge_for_day(pool, day).await?;
+    if progress(pool, user_id, day, &challenge).await? < challenge.target {
+        return Ok(());
+    }
+
+    let mut tx = pool.begin().await?;
+    let inserted = sqlx::query(
+        r#"
+        INSERT INTO daily_challenge_completions (user_id, day, challenge_key)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (user_id, day) DO NOTHING
+        "#,
+    )
+    .bind(user_id)
+    .bind(day)
+    .bind(&challenge.key)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+    if inserted == 0 {
+        return Ok(());
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO reward_grants (user_id, source, points)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (user_id, source) DO NOTHING
+        "#,
+    )
+    .bind(user_id)
+    .bind(format!("daily:{day}"))
+    .bind(challenge.reward_points)
+    .execute(&mut *tx)
+    .await?;
+
+    let previous = sqlx::query_as::<_, (i32, i32, Option<Date>)>(
+        r#"
+        SELECT current_streak, best_streak, last_completed_on
+        FROM user_streaks WHERE user_id = $1
+        FOR UPDATE
+        "#,
+    )
+    .bind(user_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let (current, best) = match previous {
+        Some((current, best, Some(last))) if last == day => (current, best),
+        Some((current, best, Some(last))) if day.previous_day() == Some(last) => {
+            (current + 1, best.max(current + 1))
+        }
+        Some((_, best, _)) => (1, best.max(1)),
+        None => (1, 1),
+    };
+
+    sqlx::query(
+        r#"
+        INSERT INTO user_streaks (user_id, current_streak, best_streak, last_completed_on)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (user_id) DO UPDATE SET
+            current_streak = EXCLUDED.current_streak,
+            best_streak = EXCLUDED.best_streak,
+            last_completed_on = EXCLUDED.last_completed_on
+        "#,
+    )
+    .bind(user_id)
+    .bind(current)
+    .bind(best)
+    .bind(day)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await
+}
+
+/// Série de l'utilisateur; la série courante retombe à 0 si la veille n'a pas été validée.
+pub async fn streak_for(
+    pool: &PgPool,
+    clock: &dyn Clock,
+    user_id: Uuid,
+) -> Result<Streak, sqlx::Error> {
+    let (today, _tz) = user_today(pool, clock, user_id).await?;
+    let row = sqlx::query_as::<_, (i32, i32, Option<Date>)>(
+        r#"SELECT current_streak, best_streak, last_completed_on FROM user_streaks WHERE user_id = $1"#,
+    )
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+
+    let Some((current, best, last)) = row else {
+        return Ok(Streak::default());
+    };
+    let alive = last.is_some_and(|d| d == today || today.previous_day() == Some(d));
+    Ok(Streak {
+        current: if alive { current } else { 0 },
+        best,
+        last_completed_on: last.map(|d| d.to_string()),
+    })
+}
+
+pub async fn daily_status(
+    pool: &PgPool,
+    clock: &dyn Clock,
+    user_id: Uuid,
+) -> Result<DailyStatus, sqlx::Error> {
+    let (day, timezone) = user_today(pool, clock, user_id).await?;
+    let challenge = challenge_for_day(pool, day).await?;
+    let progress = progress(pool, user_id, day, &challenge).await?;
+    let completed: bool = sqlx::query_scalar(
+        r#"SELECT EXISTS (SELECT 1 FROM daily_challenge_completions WHERE user_id = $1 AND day = $2)"#,
+    )
+    .bind(user_id)
+    .bind(day)
+    .fetch_one(pool)
+    .await?;
+    let reward_points_total: i64 = sqlx::query_scalar(
+        r#"SELECT COALESCE(SUM(points), 0)::BIGINT FROM reward_grants WHERE user_id = $1"#,
+    )
+    .bind(user_id)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(DailyStatus {
+        day: day.to_string(),
+        timezone,
+        progress: progress.min(challenge.target),
+        challenge,
+        completed,
+        streak: streak_for(pool, clock, user_id).await?,
+        reward_points_total,
+    })
+}
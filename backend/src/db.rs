@@ -1,13 +1,17 @@
 use sqlx::PgPool;
+use sqlx::migrate::Migrator;
 use sqlx::postgres::PgPoolOptions;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 use tokio::time::sleep;
 
-async fn connect_to_db(url: &str) -> Result<PgPool, sqlx::Error> {
+use crate::config::DatabaseConfig;
+
+async fn connect_to_db(url: &str, max_connections: u32) -> Result<PgPool, sqlx::Error> {
     let mut backoff = 1u64;
     for attempt in 1..=6 {
         match PgPoolOptions::new()
-            .max_connections(10)
+            .max_connections(max_connections)
             .acquire_timeout(Duration::from_secs(10))
             .connect(url)
             .await
@@ -23,16 +27,19 @@ async fn connect_to_db(url: &str) -> Result<PgPool, sqlx::Error> {
         }
     }
     PgPoolOptions::new()
-        .max_connections(10)
+        .max_connections(max_connections)
         .acquire_timeout(Duration::from_secs(30))
         .connect(url)
         .await
 }
 
+/// Migrations embarquées dans le binaire (voir docs/MIGRATIONS.md).
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
 async fn run_migrations(pool: &sqlx::PgPool) -> Result<(), sqlx::Error> {
     println!("🔄 Exécution des migrations...");
 
-    let migration_result = sqlx::migrate!("./migrations").run(pool).await;
+    let migration_result = MIGRATOR.run(pool).await;
 
     match migration_result {
         Ok(_) => println!("✅ Migrations exécutées avec succès!"),
@@ -42,7 +49,7 @@ async fn run_migrations(pool: &sqlx::PgPool) -> Result<(), sqlx::Error> {
     Ok(())
 }
 
-async fn seed(pool: &PgPool, json_files: &[&str]) -> Result<(), Box<dyn std::error::Error>> {
+async fn seed(pool: &PgPool, json_files: &[PathBuf]) -> Result<(), Box<dyn std::error::Error>> {
     let count: i64 = match sqlx::query_scalar("SELECT COUNT(*) FROM pokemon")
         .fetch_one(pool)
         .await
@@ -63,8 +70,7 @@ async fn seed(pool: &PgPool, json_files: &[&str]) -> Result<(), Box<dyn std::err
     Ok(())
 }
 
-async fn seed_from_json(pool: &PgPool, json_path: &str) -> Result<(), Box<dyn std::error::Error>> {
-    dotenvy::dotenv().ok();
+async fn seed_from_json(pool: &PgPool, json_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
     let len: usize = 649; // Taille des 5 premieres générations
     let data = std::fs::read_to_string(json_path)?;
     let value: serde_json::Value = serde_json::from_str(&data)?;
@@ -84,7 +90,8 @@ async fn seed_from_json(pool: &PgPool, json_path: &str) -> Result<(), Box<dyn st
 
     println!(
         "🌱 fichier: {} — insertion de {} pokémons...",
-        json_path, len
+        json_path.display(),
+        len
     );
     for item in arr_ref.iter().take(len) {
         let name = item
@@ -191,12 +198,13 @@ async fn seed_from_json(pool: &PgPool, json_path: &str) -> Result<(), Box<dyn st
     Ok(())
 }
 
-pub async fn init_db(url: &str) -> PgPool {
-    let pool = connect_to_db(url)
+/// Connexion, migrations et seed (database.max_connections, database.seed_files).
+pub async fn init_db(url: &str, database: &DatabaseConfig) -> PgPool {
+    let pool = connect_to_db(url, database.max_connections)
         .await
         .unwrap_or_else(|e| panic!("Echec connexion DB: {e}"));
     let _ = run_migrations(&pool).await;
-    if let Err(e) = seed(&pool, &["data/pokedex.json"]).await {
+    if let Err(e) = seed(&pool, &database.seed_files).await {
         eprintln!("🌱 seed ignoré: {e}");
     }
     pool
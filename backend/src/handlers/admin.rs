@@ -0,0 +1,636 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+use serde_json::{Value, json};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::audit::{self, Event};
+use crate::auth::hash_password;
+use crate::helpers::{ApiResult, ClientInfo, bad_request, not_found, ok, to_500};
+use crate::login_guard;
+use crate::mailer::{Lang, templates};
+use crate::models::admin::{
+    AdminUser, AdminUserPage, AdminUserQuery, SetRolePayload, SuspendUserPayload, SystemStats,
+    UpdatePokemonPayload,
+};
+use crate::models::audit::{AuditEvent, AuditPage, AuditQuery, PruneAuditParams};
+use crate::models::pokemon::PokemonDetail;
+use crate::password_reset;
+use crate::roles::{Admin, Moderator, RequireRole, Role};
+use crate::sessions::revoke_all;
+use crate::state::AppState;
+
+const PAGE_MAX: i64 = 100;
+
+const ADMIN_USER_SELECT: &str = r#"
+    SELECT u.id, u.username, u.email, u.role,
+           u.email_verified_at IS NOT NULL AS email_verified,
+           u.totp_enabled_at IS NOT NULL AS two_factor_enabled,
+           u.locked_until, u.disabled_at, u.disabled_reason, u.deleted_at, u.purge_after,
+           (SELECT COUNT(*) FROM user_pokemon up WHERE up.user_id = u.id) AS catches
+    FROM users u
+"#;
+
+async fn fetch_user(pool: &PgPool, user_id: Uuid) -> ApiResult<AdminUser> {
+    sqlx::query_as::<_, AdminUser>(&format!("{ADMIN_USER_SELECT} WHERE u.id = $1"))
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(to_500)?
+        .ok_or_else(|| not_found("Utilisateur introuvable"))
+}
+
+/// Liste paginée des comptes, filtrable par texte (nom ou email) et par rôle.
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/users",
+    tag = "admin",
+    params(AdminUserQuery),
+    security(("bearer" = []), ("cookie" = [])),
+    responses(
+        (status = 200, description = "Page de comptes", body = AdminUserPage),
+        (status = 400, description = "Rôle inconnu", body = String, content_type = "text/plain"),
+        (status = 401, description = "Non authentifié", body = String, content_type = "text/plain"),
+        (status = 403, description = "Rôle moderator requis", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn list_users(
+    _: RequireRole<Moderator>,
+    State(pool): State<PgPool>,
+    Query(params): Query<AdminUserQuery>,
+) -> ApiResult<Json<AdminUserPage>> {
+    if let Some(role) = params.role.as_deref()
+        && Role::parse(role).is_none()
+    {
+        return Err(bad_request("Rôle inconnu"));
+    }
+    let pattern = params
+        .q
+        .as_deref()
+        .map(str::trim)
+        .filter(|q| !q.is_empty())
+        .map(|q| format!("%{}%", q.replace('%', "\\%").replace('_', "\\_")));
+    let limit = params.limit.unwrap_or(50).clamp(1, PAGE_MAX);
+    let offset = params.offset.unwrap_or(0).max(0);
+
+    let filter = r#"
+        WHERE ($1::TEXT IS NULL OR u.username ILIKE $1 OR u.email ILIKE $1)
+          AND ($2::TEXT IS NULL OR u.role = $2)
+    "#;
+    let total: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM users u {filter}"))
+        .bind(&pattern)
+        .bind(&params.role)
+        .fetch_one(&pool)
+        .await
+        .map_err(to_500)?;
+    let users = sqlx::query_as::<_, AdminUser>(&format!(
+        "{ADMIN_USER_SELECT} {filter} ORDER BY u.created_at DESC, u.id LIMIT $3 OFFSET $4"
+    ))
+    .bind(&pattern)
+    .bind(&params.role)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(&pool)
+    .await
+    .map_err(to_500)?;
+
+    Ok(Json(AdminUserPage { total, users }))
+}
+
+/// Fiche d'un compte.
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/users/{id}",
+    tag = "admin",
+    params(("id" = Uuid, Path, description = "Identifiant du compte")),
+    security(("bearer" = []), ("cookie" = [])),
+    responses(
+        (status = 200, description = "Compte", body = AdminUser),
+        (status = 401, description = "Non authentifié", body = String, content_type = "text/plain"),
+        (status = 403, description = "Rôle moderator requis", body = String, content_type = "text/plain"),
+        (status = 404, description = "Utilisateur introuvable", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn get_user(
+    _: RequireRole<Moderator>,
+    State(pool): State<PgPool>,
+    Path(user_id): Path<Uuid>,
+) -> ApiResult<Json<AdminUser>> {
+    Ok(Json(fetch_user(&pool, user_id).await?))
+}
+
+// Un modérateur n'agit pas sur un compte de rang égal ou supérieur (ni suspension
+// ni levée d'une suspension décidée par un admin)
+fn check_rank(actor: Role, target: &AdminUser) -> ApiResult<()> {
+    if Role::parse(&target.role).is_some_and(|r| r >= actor) && actor != Role::Admin {
+        return Err((StatusCode::FORBIDDEN, "Droits insuffisants".into()));
+    }
+    Ok(())
+}
+
+/// Suspend un compte: connexion refusée et sessions révoquées jusqu'à `unlock`.
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/users/{id}/lock",
+    tag = "admin",
+    params(("id" = Uuid, Path, description = "Identifiant du compte")),
+    request_body = SuspendUserPayload,
+    security(("bearer" = []), ("cookie" = [])),
+    responses(
+        (status = 200, description = "Compte suspendu", body = AdminUser),
+        (status = 400, description = "Suspension de son propre compte", body = String, content_type = "text/plain"),
+        (status = 401, description = "Non authentifié", body = String, content_type = "text/plain"),
+        (status = 403, description = "Rôle moderator requis, ou compte de rang égal ou supérieur", body = String, content_type = "text/plain"),
+        (status = 404, description = "Utilisateur introuvable", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn lock_user(
+    actor: RequireRole<Moderator>,
+    State(pool): State<PgPool>,
+    client: ClientInfo,
+    Path(user_id): Path<Uuid>,
+    Json(payload): Json<SuspendUserPayload>,
+) -> ApiResult<Json<AdminUser>> {
+    if actor.user_id == user_id {
+        return Err(bad_request("Impossible de suspendre son propre compte"));
+    }
+    let target = fetch_user(&pool, user_id).await?;
+    check_rank(actor.role, &target)?;
+    let reason = payload.reason.as_deref().map(str::trim);
+
+    sqlx::query(
+        r#"
+        UPDATE users SET disabled_at = COALESCE(disabled_at, NOW()), disabled_reason = $2
+        WHERE id = $1
+        "#,
+    )
+    .bind(user_id)
+    .bind(reason)
+    .execute(&pool)
+    .await
+    .map_err(to_500)?;
+    revoke_all(&pool, user_id, "account_disabled")
+        .await
+        .map_err(to_500)?;
+    let event = Event::new(audit::ACCOUNT_LOCKED)
+        .actor(actor.user_id)
+        .target(user_id)
+        .meta(json!({ "reason": reason }));
+    audit::log(&pool, &client, event).await;
+
+    Ok(Json(fetch_user(&pool, user_id).await?))
+}
+
+/// Lève la suspension et le verrouillage anti brute-force éventuel.
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/users/{id}/unlock",
+    tag = "admin",
+    params(("id" = Uuid, Path, description = "Identifiant du compte")),
+    security(("bearer" = []), ("cookie" = [])),
+    responses(
+        (status = 200, description = "Suspension et verrouillage levés", body = AdminUser),
+        (status = 401, description = "Non authentifié", body = String, content_type = "text/plain"),
+        (status = 403, description = "Rôle moderator requis, ou compte de rang égal ou supérieur", body = String, content_type = "text/plain"),
+        (status = 404, description = "Utilisateur introuvable", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn unlock_user(
+    actor: RequireRole<Moderator>,
+    State(pool): State<PgPool>,
+    client: ClientInfo,
+    Path(user_id): Path<Uuid>,
+) -> ApiResult<Json<AdminUser>> {
+    let target = fetch_user(&pool, user_id).await?;
+    check_rank(actor.role, &target)?;
+    sqlx::query(r#"UPDATE users SET disabled_at = NULL, disabled_reason = NULL WHERE id = $1"#)
+        .bind(user_id)
+        .execute(&pool)
+        .await
+        .map_err(to_500)?;
+    login_guard::reset(&pool, user_id).await.map_err(to_500)?;
+    let event = Event::new(audit::ACCOUNT_UNLOCKED)
+        .actor(actor.user_id)
+        .target(user_id)
+        .meta(json!({
+            "was_disabled": target.disabled_at.is_some(),
+            "previous_reason": target.disabled_reason,
+            "was_locked": target.locked_until.is_some(),
+        }));
+    audit::log(&pool, &client, event).await;
+
+    Ok(Json(fetch_user(&pool, user_id).await?))
+}
+
+/// Invalide le mot de passe actuel, ferme les sessions et envoie un lien de reset.
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/users/{id}/force-password-reset",
+    tag = "admin",
+    params(("id" = Uuid, Path, description = "Identifiant du compte")),
+    security(("bearer" = []), ("cookie" = [])),
+    responses(
+        (status = 202, description = "`{ status, email_sent }`: mot de passe invalidé, sessions fermées, lien envoyé si le compte a un email", body = Object),
+        (status = 401, description = "Non authentifié", body = String, content_type = "text/plain"),
+        (status = 403, description = "Rôle admin requis", body = String, content_type = "text/plain"),
+        (status = 404, description = "Utilisateur introuvable", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn force_password_reset(
+    actor: RequireRole<Admin>,
+    State(state): State<AppState>,
+    (lang, client): (Lang, ClientInfo),
+    Path(user_id): Path<Uuid>,
+) -> ApiResult<(StatusCode, Json<Value>)> {
+    let AppState {
+        pool,
+        config,
+        mail,
+        clock,
+        rng,
+        ..
+    } = state;
+    let target = fetch_user(&pool, user_id).await?;
+
+    // Hash d'un secret jetable: plus aucun mot de passe ne correspond
+    let unusable = hash_password(&Uuid::new_v4().to_string()).map_err(to_500)?;
+    sqlx::query(r#"UPDATE users SET password = $2 WHERE id = $1"#)
+        .bind(user_id)
+        .bind(unusable)
+        .execute(&pool)
+        .await
+        .map_err(to_500)?;
+    revoke_all(&pool, user_id, "password_reset_forced")
+        .await
+        .map_err(to_500)?;
+
+    let token = password_reset::issue(&pool, &*clock, &*rng, user_id, config.auth.reset_ttl_secs)
+        .await
+        .map_err(to_500)?;
+    let email_sent = match &target.email {
+        Some(email) => {
+            let link = templates::app_link(config, &format!("/reset-password?token={token}"));
+            mail.enqueue(templates::password_reset(
+                lang,
+                email,
+                &target.username,
+                &link,
+                config.auth.reset_ttl_secs / 60,
+            ));
+            true
+        }
+        None => false,
+    };
+    let event = Event::new(audit::PASSWORD_RESET_FORCED)
+        .actor(actor.user_id)
+        .target(user_id)
+        .meta(json!({ "email_sent": email_sent }));
+    audit::log(&pool, &client, event).await;
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(json!({ "status": "ok", "email_sent": email_sent })),
+    ))
+}
+
+/// Change le rôle d'un compte.
+#[utoipa::path(
+    patch,
+    path = "/api/v1/admin/users/{id}/role",
+    tag = "admin",
+    params(("id" = Uuid, Path, description = "Identifiant du compte")),
+    request_body = SetRolePayload,
+    security(("bearer" = []), ("cookie" = [])),
+    responses(
+        (status = 200, description = "Rôle modifié", body = AdminUser),
+        (status = 400, description = "Rôle inconnu, ou retrait de son propre rôle admin", body = String, content_type = "text/plain"),
+        (status = 401, description = "Non authentifié", body = String, content_type = "text/plain"),
+        (status = 403, description = "Rôle admin requis", body = String, content_type = "text/plain"),
+        (status = 404, description = "Utilisateur introuvable", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn set_role(
+    actor: RequireRole<Admin>,
+    State(pool): State<PgPool>,
+    client: ClientInfo,
+    Path(user_id): Path<Uuid>,
+    Json(payload): Json<SetRolePayload>,
+) -> ApiResult<Json<AdminUser>> {
+    let role = Role::parse(&payload.role).ok_or_else(|| bad_request("Rôle inconnu"))?;
+    if actor.user_id == user_id && role != Role::Admin {
+        return Err(bad_request("Impossible de retirer son propre rôle admin"));
+    }
+    // Ancien rôle lu sous verrou dans la même requête, pour l'audit
+    let previous = sqlx::query_scalar::<_, String>(
+        r#"
+        WITH old AS (SELECT id, role FROM users WHERE id = $1 FOR UPDATE)
+        UPDATE users u SET role = $2 FROM old WHERE u.id = old.id
+        RETURNING old.role
+        "#,
+    )
+    .bind(user_id)
+    .bind(role.as_str())
+    .fetch_optional(&pool)
+    .await
+    .map_err(to_500)?;
+    let Some(previous) = previous else {
+        return Err(not_found("Utilisateur introuvable"));
+    };
+    let event = Event::new(audit::ROLE_CHANGED)
+        .actor(actor.user_id)
+        .target(user_id)
+        .meta(json!({ "from": previous, "to": role.as_str() }));
+    audit::log(&pool, &client, event).await;
+    Ok(Json(fetch_user(&pool, user_id).await?))
+}
+
+/// Suppression immédiate et définitive d'un compte.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/admin/users/{id}",
+    tag = "admin",
+    params(("id" = Uuid, Path, description = "Identifiant du compte")),
+    security(("bearer" = []), ("cookie" = [])),
+    responses(
+        (status = 200, description = "Utilisateur supprimé", body = String, content_type = "text/plain"),
+        (status = 400, description = "Suppression de son propre compte", body = String, content_type = "text/plain"),
+        (status = 401, description = "Non authentifié", body = String, content_type = "text/plain"),
+        (status = 403, description = "Rôle admin requis", body = String, content_type = "text/plain"),
+        (status = 404, description = "Utilisateur introuvable", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn delete_user(
+    actor: RequireRole<Admin>,
+    State(pool): State<PgPool>,
+    client: ClientInfo,
+    Path(user_id): Path<Uuid>,
+) -> ApiResult<(StatusCode, String)> {
+    if actor.user_id == user_id {
+        return Err(bad_request(
+            "Utiliser DELETE /api/users/{id} pour supprimer son propre compte",
+        ));
+    }
+    let deleted =
+        sqlx::query_scalar::<_, String>("DELETE FROM users WHERE id = $1 RETURNING username")
+            .bind(user_id)
+            .fetch_optional(&pool)
+            .await
+            .map_err(to_500)?;
+    let Some(username) = deleted else {
+        return Err(not_found("Utilisateur introuvable"));
+    };
+    let event = Event::new(audit::ACCOUNT_DELETED)
+        .actor(actor.user_id)
+        .target(user_id)
+        .meta(json!({ "username": username, "by_admin": true }));
+    audit::log(&pool, &client, event).await;
+    ok("Utilisateur supprimé")
+}
+
+/// Journal d'audit, filtrable par auteur, compte visé, action (ou préfixe), issue et période.
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/audit-events",
+    tag = "admin",
+    params(AuditQuery),
+    security(("bearer" = []), ("cookie" = [])),
+    responses(
+        (status = 200, description = "Page d'événements, du plus récent au plus ancien", body = AuditPage),
+        (status = 400, description = "Issue inconnue", body = String, content_type = "text/plain"),
+        (status = 401, description = "Non authentifié", body = String, content_type = "text/plain"),
+        (status = 403, description = "Rôle admin requis", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn list_audit_events(
+    _: RequireRole<Admin>,
+    State(pool): State<PgPool>,
+    Query(params): Query<AuditQuery>,
+) -> ApiResult<Json<AuditPage>> {
+    if let Some(outcome) = params.outcome.as_deref()
+        && !audit::OUTCOMES.contains(&outcome)
+    {
+        return Err(bad_request("Issue inconnue (success ou failure)"));
+    }
+    let limit = params.limit.unwrap_or(50).clamp(1, PAGE_MAX);
+    let offset = params.offset.unwrap_or(0).max(0);
+
+    let filter = r#"
+        WHERE ($1::UUID IS NULL OR actor_id = $1)
+          AND ($2::UUID IS NULL OR target_id = $2)
+          AND ($3::TEXT IS NULL OR action = $3 OR action LIKE $3 || '.%')
+          AND ($4::TEXT IS NULL OR outcome = $4)
+          AND ($5::TIMESTAMPTZ IS NULL OR created_at >= $5)
+          AND ($6::TIMESTAMPTZ IS NULL OR created_at < $6)
+    "#;
+    let total: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM audit_events {filter}"))
+        .bind(params.actor)
+        .bind(params.target)
+        .bind(&params.action)
+        .bind(&params.outcome)
+        .bind(params.since)
+        .bind(params.until)
+        .fetch_one(&pool)
+        .await
+        .map_err(to_500)?;
+    let events = sqlx::query_as::<_, AuditEvent>(&format!(
+        r#"
+        SELECT id, actor_id, action, target_id, ip, user_agent, outcome, metadata, created_at
+        FROM audit_events {filter}
+        ORDER BY created_at DESC, id DESC LIMIT $7 OFFSET $8
+        "#
+    ))
+    .bind(params.actor)
+    .bind(params.target)
+    .bind(&params.action)
+    .bind(&params.outcome)
+    .bind(params.since)
+    .bind(params.until)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(&pool)
+    .await
+    .map_err(to_500)?;
+
+    Ok(Json(AuditPage { total, events }))
+}
+
+/// Purge manuelle des événements plus anciens que `older_than_days` (tracée dans le journal).
+#[utoipa::path(
+    delete,
+    path = "/api/v1/admin/audit-events",
+    tag = "admin",
+    params(PruneAuditParams),
+    security(("bearer" = []), ("cookie" = [])),
+    responses(
+        (status = 200, description = "`{ deleted }`: nombre d'événements supprimés", body = Object),
+        (status = 400, description = "older_than_days inférieur à 1", body = String, content_type = "text/plain"),
+        (status = 401, description = "Non authentifié", body = String, content_type = "text/plain"),
+        (status = 403, description = "Rôle admin requis", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn prune_audit_events(
+    actor: RequireRole<Admin>,
+    State(pool): State<PgPool>,
+    client: ClientInfo,
+    Query(params): Query<PruneAuditParams>,
+) -> ApiResult<Json<Value>> {
+    if params.older_than_days < 1 {
+        return Err(bad_request("older_than_days doit être au moins 1"));
+    }
+    let deleted = audit::prune(&pool, params.older_than_days)
+        .await
+        .map_err(to_500)?;
+    let event = audit::pruned(params.older_than_days, deleted).actor(actor.user_id);
+    audit::log(&pool, &client, event).await;
+    Ok(Json(json!({ "deleted": deleted })))
+}
+
+/// Correction d'une fiche Pokémon (description, image, types, mensurations).
+/// `type2: null` retire le second type; un champ absent est conservé.
+#[utoipa::path(
+    patch,
+    path = "/api/v1/admin/pokemons/{id}",
+    tag = "admin",
+    params(("id" = i32, Path, description = "Identifiant du Pokémon")),
+    request_body = UpdatePokemonPayload,
+    security(("bearer" = []), ("cookie" = [])),
+    responses(
+        (status = 200, description = "Fiche corrigée", body = PokemonDetail),
+        (status = 400, description = "Valeur invalide", body = String, content_type = "text/plain"),
+        (status = 401, description = "Non authentifié", body = String, content_type = "text/plain"),
+        (status = 403, description = "Rôle moderator requis", body = String, content_type = "text/plain"),
+        (status = 404, description = "Pokémon introuvable", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn update_pokemon(
+    actor: RequireRole<Moderator>,
+    State(pool): State<PgPool>,
+    client: ClientInfo,
+    Path(pokemon_id): Path<i32>,
+    Json(payload): Json<UpdatePokemonPayload>,
+) -> ApiResult<Json<PokemonDetail>> {
+    if let Some(url) = payload.image_url.as_deref()
+        && !(url.starts_with("https://") || url.starts_with("http://") || url.starts_with('/'))
+    {
+        return Err(bad_request(
+            "image_url doit être une URL http(s) ou un chemin absolu",
+        ));
+    }
+    if [payload.height_m, payload.weight_kg]
+        .iter()
+        .flatten()
+        .any(|v| !v.is_finite() || *v <= 0.0)
+    {
+        return Err(bad_request("Mensurations invalides"));
+    }
+    let description = payload.description.as_deref().map(str::trim);
+    let image_url = payload.image_url.as_deref().map(str::trim);
+    let type1 = payload.type1.as_deref().map(str::trim);
+    let type2 = payload
+        .type2
+        .as_ref()
+        .map(|t| t.as_deref().map(str::trim).filter(|t| !t.is_empty()));
+
+    let detail = sqlx::query_as::<_, PokemonDetail>(
+        r#"
+        UPDATE pokemon p SET
+            description = COALESCE($2, p.description),
+            image_url   = COALESCE($3, p.image_url),
+            type1       = COALESCE($4, p.type1),
+            type2       = CASE WHEN $5 THEN $6 ELSE p.type2 END,
+            height_m    = COALESCE($7, p.height_m),
+            weight_kg   = COALESCE($8, p.weight_kg)
+        WHERE p.id = $1
+        RETURNING p.id, p.name, p.type1, p.type2, p.dex_no, p.image_url, p.height_m,
+                  p.weight_kg, p.description, p.base_hp, p.base_attack, p.base_defense,
+                  p.base_sp_attack, p.base_sp_defense, p.base_speed,
+                  EXISTS (
+                      SELECT 1 FROM user_pokemon up
+                      WHERE up.user_id = $9 AND up.pokemon_id = p.id
+                  ) AS caught
+        "#,
+    )
+    .bind(pokemon_id)
+    .bind(description)
+    .bind(image_url)
+    .bind(type1)
+    .bind(type2.is_some())
+    .bind(type2.flatten())
+    .bind(payload.height_m)
+    .bind(payload.weight_kg)
+    .bind(actor.user_id)
+    .fetch_optional(&pool)
+    .await
+    .map_err(to_500)?
+    .ok_or_else(|| not_found("Pokémon introuvable."))?;
+
+    // Seuls les champs envoyés figurent dans `changes`
+    let mut changes = serde_json::Map::new();
+    let fields = [
+        ("description", description.map(|v| json!(v))),
+        ("image_url", image_url.map(|v| json!(v))),
+        ("type1", type1.map(|v| json!(v))),
+        ("type2", type2.map(|v| json!(v))),
+        ("height_m", payload.height_m.map(|v| json!(v))),
+        ("weight_kg", payload.weight_kg.map(|v| json!(v))),
+    ];
+    for (field, value) in fields {
+        if let Some(value) = value {
+            changes.insert(field.into(), value);
+        }
+    }
+    let event = Event::new(audit::POKEMON_UPDATED)
+        .actor(actor.user_id)
+        .meta(json!({ "pokemon_id": pokemon_id, "name": &detail.name, "changes": changes }));
+    audit::log(&pool, &client, event).await;
+    Ok(Json(detail))
+}
+
+/// Compteurs globaux: comptes, sessions, connexions et captures.
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/stats",
+    tag = "admin",
+    security(("bearer" = []), ("cookie" = [])),
+    responses(
+        (status = 200, description = "Compteurs globaux", body = SystemStats),
+        (status = 401, description = "Non authentifié", body = String, content_type = "text/plain"),
+        (status = 403, description = "Rôle admin requis", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn stats(
+    _: RequireRole<Admin>,
+    State(pool): State<PgPool>,
+) -> ApiResult<Json<SystemStats>> {
+    let stats = sqlx::query_as::<_, SystemStats>(
+        r#"
+        SELECT
+            (SELECT COUNT(*) FROM users) AS users,
+            (SELECT COUNT(*) FROM users WHERE email_verified_at IS NOT NULL) AS users_verified,
+            (SELECT COUNT(*) FROM users WHERE totp_enabled_at IS NOT NULL) AS users_two_factor,
+            (SELECT COUNT(*) FROM users WHERE locked_until > NOW()) AS users_locked,
+            (SELECT COUNT(*) FROM users WHERE disabled_at IS NOT NULL) AS users_disabled,
+            (SELECT COUNT(*) FROM users WHERE created_at > NOW() - INTERVAL '7 days') AS signups_7d,
+            (SELECT COUNT(*) FROM sessions
+             WHERE revoked_at IS NULL AND expires_at > NOW()) AS active_sessions,
+            (SELECT COUNT(*) FROM api_tokens
+             WHERE revoked_at IS NULL AND (expires_at IS NULL OR expires_at > NOW())) AS api_tokens,
+            (SELECT COUNT(*) FROM login_attempts
+             WHERE success AND created_at > NOW() - INTERVAL '24 hours') AS logins_24h,
+            (SELECT COUNT(*) FROM login_attempts
+             WHERE reason IN ('invalid_password', 'invalid_mfa_code', 'unknown_account')
+               AND created_at > NOW() - INTERVAL '24 hours') AS failed_logins_24h,
+            (SELECT COUNT(*) FROM pokemon) AS pokemon,
+            (SELECT COUNT(*) FROM user_pokemon) AS catches,
+            (SELECT COUNT(*) FROM user_pokemon
+             WHERE discovered_at > NOW() - INTERVAL '24 hours') AS catches_24h
+        "#,
+    )
+    .fetch_one(&pool)
+    .await
+    .map_err(to_500)?;
+    Ok(Json(stats))
+}
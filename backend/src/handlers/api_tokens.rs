@@ -0,0 +1,122 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::api_tokens::{self, SCOPES};
+use crate::auth::CurrentUser;
+use crate::helpers::{ApiResult, bad_request, not_found, ok, to_500};
+use crate::models::auth::{ApiTokenInfo, CreateApiTokenPayload, CreatedApiToken};
+use crate::rng::SharedRng;
+
+const MAX_EXPIRY_DAYS: i64 = 365;
+
+/// Tokens d'accès personnels actifs du compte connecté.
+#[utoipa::path(
+    get,
+    path = "/api/v1/me/tokens",
+    tag = "me",
+    security(("bearer" = []), ("cookie" = [])),
+    responses(
+        (status = 200, description = "Tokens non révoqués et non expirés", body = Vec<ApiTokenInfo>),
+        (status = 401, description = "Non authentifié (un token d'accès personnel ne suffit pas)", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn list_tokens(
+    CurrentUser(user_id): CurrentUser,
+    State(pool): State<PgPool>,
+) -> ApiResult<Json<Vec<ApiTokenInfo>>> {
+    let tokens = api_tokens::list_active(&pool, user_id)
+        .await
+        .map_err(to_500)?;
+    Ok(Json(tokens))
+}
+
+/// Crée un token d'accès personnel; sa valeur n'est renvoyée qu'une fois.
+#[utoipa::path(
+    post,
+    path = "/api/v1/me/tokens",
+    tag = "me",
+    request_body = CreateApiTokenPayload,
+    security(("bearer" = []), ("cookie" = [])),
+    responses(
+        (status = 201, description = "Token créé", body = CreatedApiToken),
+        (status = 400, description = "Nom, scope ou durée invalide", body = String, content_type = "text/plain"),
+        (status = 401, description = "Non authentifié (un token d'accès personnel ne suffit pas)", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn create_token(
+    CurrentUser(user_id): CurrentUser,
+    State(pool): State<PgPool>,
+    State(rng): State<SharedRng>,
+    Json(payload): Json<CreateApiTokenPayload>,
+) -> ApiResult<(StatusCode, Json<CreatedApiToken>)> {
+    let name = payload.name.trim();
+    if name.is_empty() || name.chars().count() > 100 {
+        return Err(bad_request("Nom requis (100 caractères max)"));
+    }
+
+    let mut scopes = payload.scopes;
+    scopes.sort();
+    scopes.dedup();
+    if scopes.is_empty() {
+        return Err(bad_request("Au moins un scope est requis"));
+    }
+    if let Some(unknown) = scopes.iter().find(|s| !SCOPES.contains(&s.as_str())) {
+        return Err(bad_request(format!(
+            "Scope inconnu: {unknown} (disponibles: {})",
+            SCOPES.join(", ")
+        )));
+    }
+
+    if let Some(days) = payload.expires_in_days
+        && !(1..=MAX_EXPIRY_DAYS).contains(&days)
+    {
+        return Err(bad_request(format!(
+            "expires_in_days doit être compris entre 1 et {MAX_EXPIRY_DAYS}"
+        )));
+    }
+
+    let (token, info) = api_tokens::create(
+        &pool,
+        &*rng,
+        user_id,
+        name,
+        &scopes,
+        payload.expires_in_days,
+    )
+    .await
+    .map_err(to_500)?;
+
+    Ok((StatusCode::CREATED, Json(CreatedApiToken { info, token })))
+}
+
+/// Révoque un token du compte connecté.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/me/tokens/{id}",
+    tag = "me",
+    params(("id" = Uuid, Path, description = "Identifiant du token")),
+    security(("bearer" = []), ("cookie" = [])),
+    responses(
+        (status = 200, description = "Token révoqué", body = String, content_type = "text/plain"),
+        (status = 401, description = "Non authentifié (un token d'accès personnel ne suffit pas)", body = String, content_type = "text/plain"),
+        (status = 404, description = "Token introuvable", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn revoke_token(
+    CurrentUser(user_id): CurrentUser,
+    State(pool): State<PgPool>,
+    Path(token_id): Path<Uuid>,
+) -> ApiResult<(StatusCode, String)> {
+    if !api_tokens::revoke_owned(&pool, user_id, token_id)
+        .await
+        .map_err(to_500)?
+    {
+        return Err(not_found("Token introuvable"));
+    }
+    ok("Token révoqué")
+}
@@ -0,0 +1,48 @@
+use axum::{
+    Json,
+    extract::{Query, State},
+};
+use sqlx::PgPool;
+
+use crate::auth::CurrentUser;
+use crate::helpers::{ApiResult, to_500};
+use crate::models::audit::{AuditEvent, SecurityEventsQuery};
+
+const PAGE_MAX: i64 = 100;
+
+/// Historique de sécurité du compte connecté (connexions, mots de passe, profil), du plus récent au plus ancien.
+#[utoipa::path(
+    get,
+    path = "/api/v1/me/security-events",
+    tag = "me",
+    params(SecurityEventsQuery),
+    security(("bearer" = []), ("cookie" = [])),
+    responses(
+        (status = 200, description = "Événements visant le compte", body = Vec<AuditEvent>),
+        (status = 401, description = "Non authentifié (un token d'accès personnel ne suffit pas)", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn security_events(
+    CurrentUser(user_id): CurrentUser,
+    State(pool): State<PgPool>,
+    Query(params): Query<SecurityEventsQuery>,
+) -> ApiResult<Json<Vec<AuditEvent>>> {
+    let limit = params.limit.unwrap_or(50).clamp(1, PAGE_MAX);
+    let offset = params.offset.unwrap_or(0).max(0);
+    let events = sqlx::query_as::<_, AuditEvent>(
+        r#"
+        SELECT id, actor_id, action, target_id, ip, user_agent, outcome, metadata, created_at
+        FROM audit_events
+        WHERE target_id = $1
+        ORDER BY created_at DESC, id DESC
+        LIMIT $2 OFFSET $3
+        "#,
+    )
+    .bind(user_id)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(&pool)
+    .await
+    .map_err(to_500)?;
+    Ok(Json(events))
+}
@@ -1,21 +1,44 @@
 use axum::{
     Json,
-    extract::State,
+    extract::{Path, State},
     http::{HeaderMap, HeaderValue, StatusCode, header},
-    response::IntoResponse,
+    response::{IntoResponse, Response},
 };
 use serde_json::json;
 use sqlx::PgPool;
+use uuid::Uuid;
 
+use crate::account_deletion;
+use crate::audit::{self, Event};
 use crate::auth::{
-    CurrentUser, generate_access_token, generate_refresh_token, generate_reset_token,
-    hash_password, verify_password, verify_refresh, verify_reset,
+    CurrentSession, CurrentUser, generate_email_verification_token, generate_unlock_token,
+    hash_password, verify_access, verify_email_token, verify_password, verify_refresh,
+    verify_unlock_token,
 };
-use crate::helpers::{bad_request, internal_server_error, not_found, ok, unauthorized};
+use crate::clock::SharedClock;
+use crate::config::AppConfig;
+use crate::csrf;
+use crate::daily::streak_for;
+use crate::helpers::{
+    ClientInfo, bad_request, conflict, forbidden, internal_server_error, locked, not_found, ok,
+    too_many_requests, unauthorized,
+};
+use crate::login_guard::{self, LoginBlock};
+use crate::mailer::{Lang, MailQueue, templates};
+use crate::mfa;
 use crate::models::auth::{
-    ChangePasswordPayload, ConfirmPasswordResetPayload, RequestPasswordResetPayload,
+    Accepted, ChangePasswordPayload, ConfirmPasswordResetPayload, MfaChallenge,
+    RequestPasswordResetPayload, ResetRequested, SessionInfo, UnlockAccountPayload,
+    VerifyEmailPayload,
+};
+use crate::models::user::{LoginUser, MeInfo};
+use crate::password_reset;
+use crate::rng::SharedRng;
+use crate::sessions::{
+    SessionError, SessionTokens, list_active, revoke, revoke_all, revoke_all_except, revoke_owned,
+    rotate, start_session,
 };
-use crate::models::user::LoginUser;
+use crate::validation::{self, ValidationReport};
 
 fn get_bearer(headers: &HeaderMap) -> Option<String> {
     let v = headers.get(header::AUTHORIZATION)?.to_str().ok()?;
@@ -33,14 +56,74 @@ fn get_cookie(headers: &HeaderMap, name: &str) -> Option<String> {
     None
 }
 
+// Refus avant vérification du mot de passe (backoff ou verrouillage), avec Retry-After
+pub(crate) fn blocked_response(block: LoginBlock) -> Response {
+    let (retry_after, (status, msg)) = match block {
+        LoginBlock::Throttled { retry_after } => (
+            retry_after,
+            too_many_requests("Trop de tentatives, réessaie plus tard"),
+        ),
+        LoginBlock::Locked { retry_after } => {
+            (retry_after, locked("Compte temporairement verrouillé"))
+        }
+    };
+    (
+        status,
+        [(header::RETRY_AFTER, retry_after.to_string())],
+        msg,
+    )
+        .into_response()
+}
+
+// id, username, hash, email, email vérifié, 2FA active, compte suspendu
+type LoginRow = (Uuid, String, String, Option<String>, bool, bool, bool);
+
+/// Connexion par nom d'utilisateur (ou email) et mot de passe.
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/login",
+    tag = "auth",
+    request_body = LoginUser,
+    responses(
+        (status = 200, description = "Session ouverte", body = String, content_type = "text/plain", headers(("set-cookie" = String, description = "Cookies auth, refresh et csrf"))),
+        (status = 202, description = "Second facteur requis", body = MfaChallenge),
+        (status = 401, description = "Identifiants invalides", body = String, content_type = "text/plain"),
+        (status = 403, description = "Compte suspendu ou email non vérifié", body = String, content_type = "text/plain"),
+        (status = 423, description = "Compte verrouillé (Retry-After)", body = String, content_type = "text/plain"),
+        (status = 429, description = "Trop de tentatives (Retry-After)", body = String, content_type = "text/plain"),
+    )
+)]
 pub async fn login_user(
     State(pool): State<PgPool>,
+    State(config): State<&'static AppConfig>,
+    State(mail): State<MailQueue>,
+    State(clock): State<SharedClock>,
+    lang: Lang,
+    client: ClientInfo,
     Json(payload): Json<LoginUser>,
 ) -> impl IntoResponse {
-    let row = match sqlx::query_as::<_, (uuid::Uuid, String)>(
-        r#"SELECT id, password FROM users WHERE username = $1 OR email = $1"#,
+    let login = payload.username.as_str();
+    if login_guard::login_too_long(login) {
+        return unauthorized("Identifiants invalides").into_response();
+    }
+    match login_guard::check_ip(&pool, &*clock, &config.login, client.ip.as_deref()).await {
+        Ok(Some(block)) => {
+            let _ =
+                login_guard::log_attempt(&pool, &*clock, None, login, &client, "throttled").await;
+            return blocked_response(block);
+        }
+        Ok(None) => {}
+        Err(e) => return internal_server_error(e.to_string()).into_response(),
+    }
+
+    let row = match sqlx::query_as::<_, LoginRow>(
+        r#"
+        SELECT id, username, password, email, email_verified_at IS NOT NULL,
+               totp_enabled_at IS NOT NULL, disabled_at IS NOT NULL
+        FROM users WHERE LOWER(username) = LOWER($1) OR LOWER(email) = LOWER($1)
+        "#,
     )
-    .bind(&payload.username)
+    .bind(login)
     .fetch_optional(&pool)
     .await
     {
@@ -48,97 +131,336 @@ pub async fn login_user(
         Err(e) => return internal_server_error(e.to_string()).into_response(),
     };
 
-    let Some((user_id, password_hash)) = row else {
-        return unauthorized("Identifiants invalides").into_response();
+    let Some((user_id, username, password_hash, email, email_verified, mfa_enabled, disabled)) =
+        row
+    else {
+        // Même backoff, même verrouillage et même coût qu'un compte existant:
+        // ni le statut ni le temps de réponse ne révèlent l'existence du compte
+        match login_guard::check_unknown(&pool, &*clock, &config.login, login).await {
+            Ok(Some(block)) => {
+                let _ =
+                    login_guard::log_attempt(&pool, &*clock, None, login, &client, block.reason())
+                        .await;
+                return blocked_response(block);
+            }
+            Ok(None) => {}
+            Err(e) => return internal_server_error(e.to_string()).into_response(),
+        }
+        login_guard::dummy_verify(&payload.password);
+        return match login_guard::record_unknown_failure(
+            &pool,
+            &*clock,
+            &config.login,
+            login,
+            &client,
+        )
+        .await
+        {
+            Ok(Some(_)) => blocked_response(LoginBlock::Locked {
+                retry_after: config.login.lockout_secs,
+            }),
+            Ok(None) => unauthorized("Identifiants invalides").into_response(),
+            Err(e) => internal_server_error(e.to_string()).into_response(),
+        };
     };
 
+    match login_guard::check_account(&pool, &*clock, &config.login, user_id).await {
+        Ok(Some(block)) => {
+            let _ = login_guard::log_attempt(
+                &pool,
+                &*clock,
+                Some(user_id),
+                login,
+                &client,
+                block.reason(),
+            )
+            .await;
+            return blocked_response(block);
+        }
+        Ok(None) => {}
+        Err(e) => return internal_server_error(e.to_string()).into_response(),
+    }
+
     if !verify_password(&password_hash, &payload.password) {
-        return unauthorized("Identifiants invalides").into_response();
+        return match login_guard::record_failure(
+            &pool,
+            &*clock,
+            &config.login,
+            user_id,
+            login,
+            &client,
+            "invalid_password",
+        )
+        .await
+        {
+            Ok(Some(until)) => notify_lock(
+                config,
+                &mail,
+                lang,
+                user_id,
+                email.as_deref(),
+                &username,
+                until,
+            ),
+            Ok(None) => unauthorized("Identifiants invalides").into_response(),
+            Err(e) => internal_server_error(e.to_string()).into_response(),
+        };
     }
 
-    // Génère les tokens
-    let access = match generate_access_token(user_id) {
-        Ok(t) => t,
+    // Suspension: signalée seulement à qui connaît le mot de passe
+    if disabled {
+        let _ = login_guard::log_attempt(&pool, &*clock, Some(user_id), login, &client, "disabled")
+            .await;
+        return forbidden("Compte suspendu").into_response();
+    }
+
+    if config.auth.require_verified_email_for_login && !email_verified {
+        return forbidden("Email non vérifié").into_response();
+    }
+
+    // 2FA: pas de cookies avant le code. Le compteur d'échecs n'est pas remis à
+    // zéro, sinon le mot de passe suffirait à relancer les essais de codes.
+    if mfa_enabled {
+        if let Err(e) = login_guard::log_attempt(
+            &pool,
+            &*clock,
+            Some(user_id),
+            login,
+            &client,
+            "mfa_required",
+        )
+        .await
+        {
+            return internal_server_error(e.to_string()).into_response();
+        }
+        return match mfa::issue_pending_login(&pool, config, user_id).await {
+            Ok(mfa_token) => (
+                StatusCode::ACCEPTED,
+                Json(MfaChallenge {
+                    mfa_required: true,
+                    mfa_token,
+                }),
+            )
+                .into_response(),
+            Err(e) => internal_server_error(e.to_string()).into_response(),
+        };
+    }
+
+    if let Err(e) = login_guard::record_success(&pool, &*clock, user_id, login, &client).await {
+        return internal_server_error(e.to_string()).into_response();
+    }
+    let reactivated = match account_deletion::reactivate_on_login(&pool, &client, user_id).await {
+        Ok(r) => r,
         Err(e) => return internal_server_error(e.to_string()).into_response(),
     };
-    let refresh = match generate_refresh_token(user_id) {
+
+    let tokens = match start_session(&pool, &*clock, config, user_id, &client).await {
         Ok(t) => t,
         Err(e) => return internal_server_error(e.to_string()).into_response(),
     };
 
-    let access_max = std::env::var("JWT_EXP_SECONDS")
-        .ok()
-        .unwrap_or_else(|| "900".into());
-    let refresh_max = std::env::var("JWT_REFRESH_EXP_SECONDS")
-        .ok()
-        .unwrap_or_else(|| "2592000".into());
+    session_response(config, login_message(reactivated), &tokens)
+}
+
+pub(crate) fn login_message(reactivated: bool) -> &'static str {
+    if reactivated {
+        "Connexion réussie. Suppression du compte annulée."
+    } else {
+        "Connexion réussie."
+    }
+}
+
+// Le compte vient d'être verrouillé: lien de déverrouillage par email, puis 423
+pub(crate) fn notify_lock(
+    config: &AppConfig,
+    mail: &MailQueue,
+    lang: Lang,
+    user_id: Uuid,
+    email: Option<&str>,
+    username: &str,
+    until: time::OffsetDateTime,
+) -> Response {
+    if let Some(email) = email
+        && let Ok(token) = generate_unlock_token(config, user_id, until.unix_timestamp())
+    {
+        let link = templates::app_link(config, &format!("/unlock-account?token={token}"));
+        mail.enqueue(templates::account_locked(
+            lang,
+            email,
+            username,
+            &link,
+            config.login.lockout_secs / 60,
+        ));
+    }
+    blocked_response(LoginBlock::Locked {
+        retry_after: config.login.lockout_secs,
+    })
+}
+
+/// Lève un verrouillage de compte via le lien reçu par email.
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/unlock-account",
+    tag = "auth",
+    request_body = UnlockAccountPayload,
+    responses(
+        (status = 200, description = "Compte déverrouillé", body = String, content_type = "text/plain"),
+        (status = 400, description = "Token invalide ou expiré", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn unlock_account(
+    State(pool): State<PgPool>,
+    State(config): State<&'static AppConfig>,
+    Json(payload): Json<UnlockAccountPayload>,
+) -> impl IntoResponse {
+    let claims = match verify_unlock_token(config, &payload.token) {
+        Ok(c) => c,
+        Err(_) => return bad_request("Token invalide ou expiré").into_response(),
+    };
+
+    let res = sqlx::query(
+        r#"
+        UPDATE users SET failed_login_count = 0, last_failed_login_at = NULL, locked_until = NULL
+        WHERE id = $1 AND locked_until IS NOT NULL
+          AND FLOOR(EXTRACT(EPOCH FROM locked_until))::BIGINT = $2
+        "#,
+    )
+    .bind(claims.sub)
+    .bind(claims.lock)
+    .execute(&pool)
+    .await;
+    match res {
+        Ok(r) if r.rows_affected() > 0 => ok("Compte déverrouillé").into_response(),
+        Ok(_) => bad_request("Token invalide ou expiré").into_response(),
+        Err(e) => internal_server_error(e.to_string()).into_response(),
+    }
+}
+
+pub(crate) fn secure_flag(config: &AppConfig) -> &'static str {
+    if config.server.production {
+        "; Secure"
+    } else {
+        ""
+    }
+}
 
-    let prod = std::env::var("PRODUCTION_MODE")
-        .ok()
-        .is_some_and(|v| v == "true");
-    let secure_flag = if prod { "; Secure" } else { "" };
+// Cookies "auth", "refresh" et "csrf" d'une session
+pub(crate) fn session_cookies(config: &AppConfig, tokens: &SessionTokens) -> [HeaderValue; 3] {
+    let access_max = config.auth.access_ttl_secs;
+    let secure_flag = secure_flag(config);
 
-    let access_cookie =
-        format!("auth={access}; Path=/; Max-Age={access_max}; HttpOnly; SameSite=Lax{secure_flag}");
+    let access_cookie = format!(
+        "auth={}; Path=/; Max-Age={access_max}; HttpOnly; SameSite=Lax{secure_flag}",
+        tokens.access
+    );
     let refresh_cookie = format!(
-        "refresh={refresh}; Path=/; Max-Age={refresh_max}; HttpOnly; SameSite=Strict{secure_flag}"
+        "refresh={}; Path=/; Max-Age={}; HttpOnly; SameSite=Strict{secure_flag}",
+        tokens.refresh, tokens.refresh_max_age
     );
-
-    let mut res = "Connexion réussie.".to_string().into_response();
-    res.headers_mut().append(
-        header::SET_COOKIE,
-        HeaderValue::from_str(&access_cookie).unwrap(),
+    // Lisible par le frontend, qui le renvoie dans l'en-tête X-CSRF-Token
+    let csrf_cookie = format!(
+        "{}={}; Path=/; Max-Age={}; SameSite=Strict{secure_flag}",
+        csrf::COOKIE,
+        csrf::issue(config, tokens.session_id),
+        tokens.refresh_max_age
     );
-    res.headers_mut().append(
-        header::SET_COOKIE,
+    [
+        HeaderValue::from_str(&access_cookie).unwrap(),
         HeaderValue::from_str(&refresh_cookie).unwrap(),
-    );
+        HeaderValue::from_str(&csrf_cookie).unwrap(),
+    ]
+}
+
+// Pose les cookies "auth" et "refresh" d'une session
+pub(crate) fn session_response(config: &AppConfig, msg: &str, tokens: &SessionTokens) -> Response {
+    let mut res = msg.to_string().into_response();
+    for cookie in session_cookies(config, tokens) {
+        res.headers_mut().append(header::SET_COOKIE, cookie);
+    }
     *res.status_mut() = StatusCode::OK;
     res
 }
 
-pub async fn refresh_token(headers: HeaderMap) -> impl IntoResponse {
+/// Rotation du refresh token (cookie `refresh` ou en-tête Bearer).
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/refresh-token",
+    tag = "auth",
+    responses(
+        (status = 200, description = "Nouveaux tokens", body = String, content_type = "text/plain", headers(("set-cookie" = String, description = "Cookies auth, refresh et csrf"))),
+        (status = 401, description = "Refresh token absent, invalide ou réutilisé", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn refresh_token(
+    State(pool): State<PgPool>,
+    State(config): State<&'static AppConfig>,
+    State(clock): State<SharedClock>,
+    client: ClientInfo,
+    headers: HeaderMap,
+) -> impl IntoResponse {
     let token = get_bearer(&headers).or_else(|| get_cookie(&headers, "refresh"));
     let Some(token) = token else {
         return unauthorized("Refresh token requis").into_response();
     };
 
-    let claims = match verify_refresh(&token) {
+    let claims = match verify_refresh(config, &token) {
         Ok(c) => c,
         Err(_) => return unauthorized("Refresh token invalide").into_response(),
     };
 
-    let access = match generate_access_token(claims.sub) {
-        Ok(t) => t,
-        Err(e) => return internal_server_error(e.to_string()).into_response(),
-    };
+    match rotate(&pool, &*clock, config, &claims, &client).await {
+        Ok(tokens) => session_response(config, "Token régénéré.", &tokens),
+        Err(SessionError::Reused) => {
+            unauthorized("Refresh token réutilisé, session révoquée").into_response()
+        }
+        Err(SessionError::Invalid) => unauthorized("Session expirée ou révoquée").into_response(),
+        Err(e) => internal_server_error(e.to_string()).into_response(),
+    }
+}
 
-    let access_max = std::env::var("JWT_EXP_SECONDS")
-        .ok()
-        .unwrap_or_else(|| "900".into());
-    let prod = std::env::var("PRODUCTION_MODE")
-        .ok()
-        .is_some_and(|v| v == "true");
-    let secure_flag = if prod { "; Secure" } else { "" };
+/// Révoque la session courante et efface les cookies.
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/logout",
+    tag = "auth",
+    responses(
+        (status = 200, description = "Déconnecté", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn logout_user(
+    State(pool): State<PgPool>,
+    State(config): State<&'static AppConfig>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    // Révoque la session liée au refresh token (ou à défaut à l'access token)
+    let session_id = get_cookie(&headers, "refresh")
+        .and_then(|t| verify_refresh(config, &t).ok())
+        .or_else(|| {
+            get_cookie(&headers, "auth")
+                .or_else(|| get_bearer(&headers))
+                .and_then(|t| verify_access(config, &t).ok())
+        })
+        .and_then(|c| c.sid);
+    if let Some(sid) = session_id
+        && let Err(e) = revoke(&pool, sid, "logout").await
+    {
+        return internal_server_error(e.to_string()).into_response();
+    }
 
-    let access_cookie =
-        format!("auth={access}; Path=/; Max-Age={access_max}; HttpOnly; SameSite=Lax{secure_flag}");
+    clear_session_cookies(config, "Déconnecté.")
+}
 
-    let mut res = "Token régénéré.".to_string().into_response();
-    res.headers_mut().append(
-        header::SET_COOKIE,
-        HeaderValue::from_str(&access_cookie).unwrap(),
-    );
+fn clear_session_cookies(config: &AppConfig, msg: &str) -> Response {
+    let mut res = msg.to_string().into_response();
+    append_clear_cookies(config, &mut res);
     *res.status_mut() = StatusCode::OK;
     res
 }
-pub async fn logout_user() -> impl IntoResponse {
-    let prod = std::env::var("PRODUCTION_MODE")
-        .ok()
-        .is_some_and(|v| v == "true");
-    let secure_flag = if prod { "; Secure" } else { "" };
 
-    let mut res = "Déconnecté.".to_string().into_response();
+/// Ajoute à `res` l'effacement des cookies auth, refresh et csrf.
+pub(crate) fn append_clear_cookies(config: &AppConfig, res: &mut Response) {
+    let secure_flag = secure_flag(config);
+
     res.headers_mut().append(
         header::SET_COOKIE,
         HeaderValue::from_str(&format!(
@@ -153,56 +475,186 @@ pub async fn logout_user() -> impl IntoResponse {
         ))
         .unwrap(),
     );
-    *res.status_mut() = StatusCode::OK;
-    res
+    res.headers_mut().append(
+        header::SET_COOKIE,
+        HeaderValue::from_str(&format!(
+            "{}=; Path=/; Max-Age=0; SameSite=Strict{secure_flag}",
+            csrf::COOKIE
+        ))
+        .unwrap(),
+    );
+}
+
+/// Révoque toutes les sessions du compte.
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/logout-all",
+    tag = "auth",
+    security(("bearer" = []), ("cookie" = [])),
+    responses(
+        (status = 200, description = "Déconnecté de tous les appareils", body = String, content_type = "text/plain"),
+        (status = 401, description = "Non authentifié", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn logout_all(
+    State(pool): State<PgPool>,
+    State(config): State<&'static AppConfig>,
+    CurrentUser(user_id): CurrentUser,
+) -> impl IntoResponse {
+    if let Err(e) = revoke_all(&pool, user_id, "logout_all").await {
+        return internal_server_error(e.to_string()).into_response();
+    }
+    clear_session_cookies(config, "Déconnecté de tous les appareils.")
 }
 
+/// Sessions actives du compte.
+#[utoipa::path(
+    get,
+    path = "/api/v1/auth/sessions",
+    tag = "auth",
+    security(("bearer" = []), ("cookie" = [])),
+    responses(
+        (status = 200, description = "Sessions actives, la courante marquée `current`", body = Vec<SessionInfo>),
+        (status = 401, description = "Non authentifié", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn list_sessions(
+    State(pool): State<PgPool>,
+    State(clock): State<SharedClock>,
+    CurrentUser(user_id): CurrentUser,
+    CurrentSession(current): CurrentSession,
+) -> impl IntoResponse {
+    match list_active(&pool, &*clock, user_id, current).await {
+        Ok(sessions) => (StatusCode::OK, Json(sessions)).into_response(),
+        Err(e) => internal_server_error(e.to_string()).into_response(),
+    }
+}
+
+/// Révoque une session du compte.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/auth/sessions/{id}",
+    tag = "auth",
+    params(("id" = Uuid, Path, description = "Identifiant de la session")),
+    security(("bearer" = []), ("cookie" = [])),
+    responses(
+        (status = 200, description = "Session révoquée", body = String, content_type = "text/plain"),
+        (status = 401, description = "Non authentifié", body = String, content_type = "text/plain"),
+        (status = 404, description = "Session introuvable", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn revoke_session(
+    State(pool): State<PgPool>,
+    CurrentUser(user_id): CurrentUser,
+    Path(session_id): Path<Uuid>,
+) -> impl IntoResponse {
+    match revoke_owned(&pool, user_id, session_id, "revoked_by_user").await {
+        Ok(true) => ok("Session révoquée").into_response(),
+        Ok(false) => not_found("Session introuvable").into_response(),
+        Err(e) => internal_server_error(e.to_string()).into_response(),
+    }
+}
+
+/// Profil de l'utilisateur connecté.
+#[utoipa::path(
+    get,
+    path = "/api/v1/auth/me",
+    tag = "auth",
+    security(("bearer" = ["profile:read"]), ("cookie" = [])),
+    responses(
+        (status = 200, description = "Profil et série de défis quotidiens", body = MeInfo),
+        (status = 401, description = "Non authentifié", body = String, content_type = "text/plain"),
+    )
+)]
 pub async fn me(
     State(pool): State<PgPool>,
+    State(clock): State<SharedClock>,
     CurrentUser(user_id): CurrentUser,
 ) -> impl IntoResponse {
-    let row = sqlx::query_as::<_, (uuid::Uuid, String, Option<String>)>(
-        r#"SELECT id, username, email FROM users WHERE id = $1"#,
+    let row = sqlx::query_as::<_, (uuid::Uuid, String, Option<String>, String, bool, bool)>(
+        r#"
+        SELECT id, username, email, role, email_verified_at IS NOT NULL,
+               totp_enabled_at IS NOT NULL
+        FROM users WHERE id = $1
+        "#,
     )
     .bind(user_id)
     .fetch_optional(&pool)
     .await;
     match row {
-        Ok(Some((id, username, email))) => (
-            StatusCode::OK,
-            Json(json!({
-                "id": id,
-                "username": username,
-                "email": email
-            })),
-        )
-            .into_response(),
+        Ok(Some((id, username, email, role, email_verified, two_factor_enabled))) => {
+            let streak = match streak_for(&pool, &*clock, id).await {
+                Ok(s) => s,
+                Err(e) => return internal_server_error(e.to_string()).into_response(),
+            };
+            let me = MeInfo {
+                id,
+                username,
+                email,
+                role,
+                email_verified,
+                two_factor_enabled,
+                streak,
+            };
+            (StatusCode::OK, Json(me)).into_response()
+        }
         Ok(None) => not_found("Utilisateur introuvable").into_response(),
         Err(e) => internal_server_error(e.to_string()).into_response(),
     }
 }
 
+/// Changement de mot de passe (mot de passe actuel requis).
+#[utoipa::path(
+    put,
+    path = "/api/v1/auth/change-password",
+    tag = "auth",
+    security(("bearer" = []), ("cookie" = [])),
+    request_body = ChangePasswordPayload,
+    responses(
+        (status = 200, description = "Mot de passe mis à jour", body = String, content_type = "text/plain"),
+        (status = 401, description = "Non authentifié ou mot de passe actuel incorrect", body = String, content_type = "text/plain"),
+        (status = 422, description = "Nouveau mot de passe refusé", body = ValidationReport),
+    )
+)]
 pub async fn change_password(
     State(pool): State<PgPool>,
+    State(config): State<&'static AppConfig>,
+    State(mail): State<MailQueue>,
+    (lang, client): (Lang, ClientInfo),
     CurrentUser(user_id): CurrentUser,
+    CurrentSession(current): CurrentSession,
     Json(payload): Json<ChangePasswordPayload>,
 ) -> impl IntoResponse {
-    let row = match sqlx::query_scalar::<_, String>(r#"SELECT password FROM users WHERE id = $1"#)
-        .bind(user_id)
-        .fetch_optional(&pool)
-        .await
+    let row = match sqlx::query_as::<_, (String, String, Option<String>)>(
+        r#"SELECT password, username, email FROM users WHERE id = $1"#,
+    )
+    .bind(user_id)
+    .fetch_optional(&pool)
+    .await
     {
         Ok(r) => r,
         Err(e) => return internal_server_error(e.to_string()).into_response(),
     };
 
-    let Some(current_hash) = row else {
+    let Some((current_hash, username, email)) = row else {
         return not_found("Utilisateur introuvable").into_response();
     };
 
     if !verify_password(&current_hash, &payload.current_password) {
+        let event = Event::own(audit::PASSWORD_CHANGED, user_id)
+            .failed()
+            .meta(json!({ "reason": "invalid_current_password" }));
+        audit::log(&pool, &client, event).await;
         return unauthorized("Mot de passe actuel incorrect").into_response();
     }
+    if let Err(errors) = validation::password(
+        &config.password_policy,
+        &payload.new_password,
+        &username,
+        email.as_deref(),
+    ) {
+        return errors.into_response();
+    }
 
     let new_hash = match hash_password(&payload.new_password) {
         Ok(h) => h,
@@ -217,65 +669,341 @@ pub async fn change_password(
     {
         return internal_server_error(e.to_string()).into_response();
     }
+    // Un lien de reset demandé avant le changement ne doit plus servir
+    if let Err(e) = password_reset::revoke_all(&pool, user_id).await {
+        return internal_server_error(e.to_string()).into_response();
+    }
+
+    if payload.logout_other_sessions
+        && let Err(e) = revoke_all_except(&pool, user_id, current, "password_changed").await
+    {
+        return internal_server_error(e.to_string()).into_response();
+    }
+
+    let event = Event::own(audit::PASSWORD_CHANGED, user_id).meta(json!({
+        "via": "change_password",
+        "logout_other_sessions": payload.logout_other_sessions,
+    }));
+    audit::log(&pool, &client, event).await;
+
+    if let Some(email) = email {
+        mail.enqueue(templates::password_changed(lang, &email, &username));
+    }
 
     ok("Mot de passe mis à jour").into_response()
 }
 
+/// Envoie un lien de réinitialisation (réponse identique que le compte existe ou non).
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/request-password-reset",
+    tag = "auth",
+    request_body = RequestPasswordResetPayload,
+    responses(
+        (status = 202, description = "Demande prise en compte", body = ResetRequested),
+        (status = 429, description = "Trop de demandes (Retry-After)", body = String, content_type = "text/plain"),
+    )
+)]
 pub async fn request_password_reset(
     State(pool): State<PgPool>,
+    State(config): State<&'static AppConfig>,
+    State(rng): State<SharedRng>,
+    State(mail): State<MailQueue>,
+    State(clock): State<SharedClock>,
+    (lang, client): (Lang, ClientInfo),
     Json(payload): Json<RequestPasswordResetPayload>,
 ) -> impl IntoResponse {
-    let prod = std::env::var("PRODUCTION_MODE")
-        .ok()
-        .is_some_and(|v| v == "true");
+    let prod = config.server.production;
+    let ip = client.ip.as_deref();
+
+    match password_reset::ip_retry_after(&pool, &*clock, &config.password_reset, ip).await {
+        Ok(Some(retry_after)) => {
+            let (status, msg) = too_many_requests("Trop de demandes, réessaie plus tard");
+            return (
+                status,
+                [(header::RETRY_AFTER, retry_after.to_string())],
+                msg,
+            )
+                .into_response();
+        }
+        Ok(None) => {}
+        Err(e) => return internal_server_error(e.to_string()).into_response(),
+    }
 
-    let user = (sqlx::query_scalar::<_, uuid::Uuid>(
-        r#"SELECT id FROM users WHERE email = $1 OR username = $1"#,
+    let user = (sqlx::query_as::<_, (Uuid, String, Option<String>, bool)>(
+        r#"
+        SELECT id, username, email, email_verified_at IS NOT NULL
+        FROM users WHERE LOWER(email) = LOWER($1) OR LOWER(username) = LOWER($1)
+        "#,
     )
     .bind(&payload.email_or_username)
     .fetch_optional(&pool)
     .await)
         .unwrap_or_default();
 
-    if let Some(u) = user
-        && let Ok(token) = generate_reset_token(u)
-    {
-        // En prod: envoi par mail plutot.
+    // Email non vérifié: pas de reset si la configuration l'exige (réponse identique)
+    let require_verified = config.auth.require_verified_email_for_reset;
+    let user = user.filter(|(_, _, _, verified)| *verified || !require_verified);
+    // Compte déjà sollicité trop souvent: plus d'envoi, mais réponse identique
+    let limited = match &user {
+        Some((u, ..)) => {
+            match password_reset::account_limited(&pool, &*clock, &config.password_reset, *u).await
+            {
+                Ok(limited) => limited,
+                Err(e) => return internal_server_error(e.to_string()).into_response(),
+            }
+        }
+        None => false,
+    };
+    let user_id = user.as_ref().map(|(u, ..)| *u);
+    let user = user.filter(|_| !limited);
+
+    if let Err(e) = password_reset::log_request(&pool, &*clock, ip, user_id, user.is_some()).await {
+        return internal_server_error(e.to_string()).into_response();
+    }
+    let event = match user_id {
+        Some(u) => Event::new(audit::PASSWORD_RESET_REQUESTED)
+            .target(u)
+            .meta(json!({ "sent": user.is_some() })),
+        None => Event::new(audit::PASSWORD_RESET_REQUESTED)
+            .failed()
+            .meta(json!({ "login": payload.email_or_username, "reason": "unknown_account" })),
+    };
+    audit::log(&pool, &client, event).await;
+
+    if let Some((u, username, email, _)) = user {
+        let token =
+            match password_reset::issue(&pool, &*clock, &*rng, u, config.auth.reset_ttl_secs).await
+            {
+                Ok(t) => t,
+                Err(e) => return internal_server_error(e.to_string()).into_response(),
+            };
+        if let Some(email) = email {
+            let link = templates::app_link(config, &format!("/reset-password?token={token}"));
+            mail.enqueue(templates::password_reset(
+                lang,
+                &email,
+                &username,
+                &link,
+                config.auth.reset_ttl_secs / 60,
+            ));
+        }
+        // Hors prod: token aussi renvoyé dans la réponse pour faciliter les tests
         if !prod {
-            return (StatusCode::ACCEPTED, Json(json!({ "reset_token": token }))).into_response();
+            let body = ResetRequested::Token { reset_token: token };
+            return (StatusCode::ACCEPTED, Json(body)).into_response();
         }
-        // TODO: mettre ne place envoi du mail
     }
 
-    (StatusCode::ACCEPTED, Json(json!({ "status": "ok" }))).into_response()
+    let body = ResetRequested::Accepted(Accepted::OK);
+    (StatusCode::ACCEPTED, Json(body)).into_response()
 }
 
+// Utilisateur mis à jour, ou réponse d'erreur (token invalide, mot de passe refusé)
+type ResetOutcome = Result<(Uuid, String, Option<String>), Response>;
+
+/// Nouveau mot de passe à partir du token reçu par email.
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/confirm-password-reset",
+    tag = "auth",
+    request_body = ConfirmPasswordResetPayload,
+    responses(
+        (status = 200, description = "Mot de passe réinitialisé", body = String, content_type = "text/plain"),
+        (status = 400, description = "Token invalide ou expiré", body = String, content_type = "text/plain"),
+        (status = 422, description = "Nouveau mot de passe refusé", body = ValidationReport),
+    )
+)]
 pub async fn confirm_password_reset(
     State(pool): State<PgPool>,
+    State(config): State<&'static AppConfig>,
+    State(mail): State<MailQueue>,
+    State(clock): State<SharedClock>,
+    lang: Lang,
+    client: ClientInfo,
     Json(payload): Json<ConfirmPasswordResetPayload>,
 ) -> impl IntoResponse {
-    let claims = match verify_reset(&payload.token) {
+    // Le token n'est consommé que si le mot de passe est valide et effectivement mis à jour
+    let result: Result<ResetOutcome, sqlx::Error> = async {
+        let mut tx = pool.begin().await?;
+        let Some(user_id) = password_reset::consume(&mut tx, &*clock, &payload.token).await? else {
+            return Ok(Err(bad_request("Token invalide ou expiré").into_response()));
+        };
+        let (username, email) = sqlx::query_as::<_, (String, Option<String>)>(
+            r#"SELECT username, email FROM users WHERE id = $1"#,
+        )
+        .bind(user_id)
+        .fetch_one(&mut *tx)
+        .await?;
+        if let Err(errors) = validation::password(
+            &config.password_policy,
+            &payload.new_password,
+            &username,
+            email.as_deref(),
+        ) {
+            return Ok(Err(errors.into_response()));
+        }
+        let new_hash = match hash_password(&payload.new_password) {
+            Ok(h) => h,
+            Err(e) => return Ok(Err(internal_server_error(e.to_string()).into_response())),
+        };
+        sqlx::query(
+            r#"
+            UPDATE users
+            SET password = $1, failed_login_count = 0, last_failed_login_at = NULL, locked_until = NULL
+            WHERE id = $2
+            "#,
+        )
+        .bind(new_hash)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(Ok((user_id, username, email)))
+    }
+    .await;
+    let (user_id, username, email) = match result {
+        Ok(Ok(row)) => row,
+        Ok(Err(res)) => {
+            let event = Event::new(audit::PASSWORD_RESET_COMPLETED)
+                .failed()
+                .meta(json!({ "status": res.status().as_u16() }));
+            audit::log(&pool, &client, event).await;
+            return res;
+        }
+        Err(e) => return internal_server_error(e.to_string()).into_response(),
+    };
+    let event = Event::own(audit::PASSWORD_RESET_COMPLETED, user_id)
+        .meta(json!({ "logout_other_sessions": payload.logout_other_sessions }));
+    audit::log(&pool, &client, event).await;
+
+    if payload.logout_other_sessions
+        && let Err(e) = revoke_all(&pool, user_id, "password_reset").await
+    {
+        return internal_server_error(e.to_string()).into_response();
+    }
+
+    if let Some(email) = email {
+        mail.enqueue(templates::password_changed(lang, &email, &username));
+    }
+
+    ok("Mot de passe réinitialisé").into_response()
+}
+
+/// Envoie le lien de vérification pour `email` (inscription, changement d'email, renvoi).
+pub(crate) fn send_email_verification(
+    config: &AppConfig,
+    mail: &MailQueue,
+    lang: Lang,
+    user_id: Uuid,
+    username: &str,
+    email: &str,
+) -> Result<(), jsonwebtoken::errors::Error> {
+    let token = generate_email_verification_token(config, user_id, email)?;
+    let link = templates::app_link(config, &format!("/verify-email?token={token}"));
+    mail.enqueue(templates::verify_email(
+        lang,
+        email,
+        username,
+        &link,
+        config.auth.email_verify_ttl_secs / 3600,
+    ));
+    Ok(())
+}
+
+/// Confirme l'adresse email avec le token reçu.
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/verify-email",
+    tag = "auth",
+    request_body = VerifyEmailPayload,
+    responses(
+        (status = 200, description = "Email vérifié", body = String, content_type = "text/plain"),
+        (status = 400, description = "Token invalide, expiré ou obsolète", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn verify_email(
+    State(pool): State<PgPool>,
+    State(config): State<&'static AppConfig>,
+    Json(payload): Json<VerifyEmailPayload>,
+) -> impl IntoResponse {
+    let claims = match verify_email_token(config, &payload.token) {
         Ok(c) => c,
         Err(_) => return bad_request("Token invalide ou expiré").into_response(),
     };
 
-    if payload.new_password.len() < 8 {
-        return bad_request("Mot de passe trop court").into_response();
+    // Le token n'est valable que pour l'adresse actuelle du compte
+    let res = sqlx::query(
+        r#"
+        UPDATE users SET email_verified_at = COALESCE(email_verified_at, NOW())
+        WHERE id = $1 AND email = $2
+        "#,
+    )
+    .bind(claims.sub)
+    .bind(&claims.email)
+    .execute(&pool)
+    .await;
+    match res {
+        Ok(r) if r.rows_affected() > 0 => ok("Email vérifié").into_response(),
+        Ok(_) => bad_request("Token obsolète: l'adresse email a changé").into_response(),
+        Err(e) => internal_server_error(e.to_string()).into_response(),
     }
+}
 
-    let new_hash = match hash_password(&payload.new_password) {
-        Ok(h) => h,
+/// Renvoie le lien de vérification de l'adresse email.
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/resend-verification",
+    tag = "auth",
+    security(("bearer" = []), ("cookie" = [])),
+    responses(
+        (status = 202, description = "Lien renvoyé", body = Accepted),
+        (status = 400, description = "Aucune adresse email", body = String, content_type = "text/plain"),
+        (status = 401, description = "Non authentifié", body = String, content_type = "text/plain"),
+        (status = 409, description = "Email déjà vérifié", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn resend_verification(
+    State(pool): State<PgPool>,
+    State(config): State<&'static AppConfig>,
+    State(mail): State<MailQueue>,
+    lang: Lang,
+    CurrentUser(user_id): CurrentUser,
+) -> impl IntoResponse {
+    let row = sqlx::query_as::<_, (String, Option<String>, bool)>(
+        r#"SELECT username, email, email_verified_at IS NOT NULL FROM users WHERE id = $1"#,
+    )
+    .bind(user_id)
+    .fetch_optional(&pool)
+    .await;
+    let (username, email, verified) = match row {
+        Ok(Some(r)) => r,
+        Ok(None) => return not_found("Utilisateur introuvable").into_response(),
         Err(e) => return internal_server_error(e.to_string()).into_response(),
     };
 
-    if let Err(e) = sqlx::query(r#"UPDATE users SET password = $1 WHERE id = $2"#)
-        .bind(new_hash)
-        .bind(claims.sub)
-        .execute(&pool)
-        .await
-    {
+    let Some(email) = email else {
+        return bad_request("Aucune adresse email sur le compte").into_response();
+    };
+    if verified {
+        return conflict("Email déjà vérifié").into_response();
+    }
+    if let Err(e) = send_email_verification(config, &mail, lang, user_id, &username, &email) {
         return internal_server_error(e.to_string()).into_response();
     }
+    (StatusCode::ACCEPTED, Json(Accepted::OK)).into_response()
+}
 
-    ok("Mot de passe réinitialisé").into_response()
+/// Clés publiques de vérification des access tokens (RS256/EdDSA), pour les autres services.
+#[utoipa::path(
+    get,
+    path = "/.well-known/jwks.json",
+    tag = "auth",
+    responses((status = 200, description = "JWK Set (RFC 7517)", body = Object)),
+)]
+pub async fn jwks(State(config): State<&'static AppConfig>) -> impl IntoResponse {
+    (
+        [(header::CACHE_CONTROL, "public, max-age=300")],
+        Json(config.keys.loaded().access.jwks()),
+    )
 }
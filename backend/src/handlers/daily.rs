@@ -0,0 +1,30 @@
+use axum::{Json, extract::State};
+use sqlx::PgPool;
+
+use crate::auth::CurrentUser;
+use crate::clock::SharedClock;
+use crate::daily::daily_status;
+use crate::helpers::{ApiResult, to_500};
+use crate::models::daily::DailyStatus;
+
+/// Défi du jour (selon le fuseau du compte), progression et série.
+#[utoipa::path(
+    get,
+    path = "/api/v1/me/daily",
+    tag = "me",
+    security(("bearer" = ["collection:read"]), ("cookie" = [])),
+    responses(
+        (status = 200, description = "Défi du jour", body = DailyStatus),
+        (status = 401, description = "Non authentifié", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn get_daily(
+    CurrentUser(user_id): CurrentUser,
+    State(pool): State<PgPool>,
+    State(clock): State<SharedClock>,
+) -> ApiResult<Json<DailyStatus>> {
+    let status = daily_status(&pool, &*clock, user_id)
+        .await
+        .map_err(to_500)?;
+    Ok(Json(status))
+}
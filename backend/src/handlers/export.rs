@@ -0,0 +1,112 @@
+use axum::{
+    extract::State,
+    http::header,
+    response::{IntoResponse, Response},
+};
+use serde_json::{Value, json};
+use sqlx::PgPool;
+use time::format_description::well_known::Rfc3339;
+
+use crate::audit::{self, Event};
+use crate::auth::CurrentUser;
+use crate::clock::SharedClock;
+use crate::helpers::{ApiResult, ClientInfo, to_500};
+
+/// Version du format de l'export, à incrémenter si sa structure change.
+const EXPORT_FORMAT: i32 = 1;
+
+// Un seul aller-retour: chaque section est agrégée en JSON par Postgres.
+// Ni hash de mot de passe, ni secret TOTP, ni hash de token.
+const EXPORT_SQL: &str = r#"
+    SELECT jsonb_build_object(
+        'profile', (
+            SELECT jsonb_build_object(
+                'id', u.id, 'username', u.username, 'email', u.email,
+                'email_verified_at', u.email_verified_at, 'role', u.role,
+                'timezone', u.timezone, 'two_factor_enabled', u.totp_enabled_at IS NOT NULL,
+                'created_at', u.created_at, 'deleted_at', u.deleted_at,
+                'purge_after', u.purge_after)
+            FROM users u WHERE u.id = $1),
+        'collection', (
+            SELECT COALESCE(jsonb_agg(jsonb_build_object(
+                'pokemon_id', p.id, 'name', p.name, 'nickname', up.nickname,
+                'discovered_at', up.discovered_at) ORDER BY up.discovered_at), '[]')
+            FROM user_pokemon up JOIN pokemon p ON p.id = up.pokemon_id
+            WHERE up.user_id = $1),
+        'daily', jsonb_build_object(
+            'completions', (
+                SELECT COALESCE(jsonb_agg(to_jsonb(c) - 'user_id' ORDER BY c.day), '[]')
+                FROM daily_challenge_completions c WHERE c.user_id = $1),
+            'rewards', (
+                SELECT COALESCE(jsonb_agg(to_jsonb(r) - 'user_id' - 'id' ORDER BY r.granted_at), '[]')
+                FROM reward_grants r WHERE r.user_id = $1),
+            'streak', (
+                SELECT to_jsonb(s) - 'user_id' FROM user_streaks s WHERE s.user_id = $1)),
+        'sessions', (
+            SELECT COALESCE(jsonb_agg(to_jsonb(s) - 'user_id' - 'current_jti'
+                                      ORDER BY s.created_at), '[]')
+            FROM sessions s WHERE s.user_id = $1),
+        'api_tokens', (
+            SELECT COALESCE(jsonb_agg(to_jsonb(t) - 'user_id' - 'token_hash'
+                                      ORDER BY t.created_at), '[]')
+            FROM api_tokens t WHERE t.user_id = $1),
+        'linked_identities', (
+            SELECT COALESCE(jsonb_agg(to_jsonb(i) - 'user_id' ORDER BY i.created_at), '[]')
+            FROM user_identities i WHERE i.user_id = $1),
+        'login_attempts', (
+            SELECT COALESCE(jsonb_agg(to_jsonb(a) - 'user_id' - 'id' ORDER BY a.created_at), '[]')
+            FROM login_attempts a WHERE a.user_id = $1),
+        'audit_events', (
+            SELECT COALESCE(jsonb_agg(to_jsonb(e) ORDER BY e.created_at, e.id), '[]')
+            FROM audit_events e WHERE e.target_id = $1 OR e.actor_id = $1)
+    )
+"#;
+
+/// Export de toutes les données personnelles du compte connecté (fichier JSON téléchargeable).
+#[utoipa::path(
+    get,
+    path = "/api/v1/me/export",
+    tag = "me",
+    security(("bearer" = []), ("cookie" = [])),
+    responses(
+        (status = 200, description = "`{ format_version, exported_at, profile, collection, daily, sessions, api_tokens, linked_identities, login_attempts, audit_events }`", body = Object, headers(
+            ("Content-Disposition" = String, description = "attachment; filename=\"pokerncp-export-<nom>.json\""),
+        )),
+        (status = 401, description = "Non authentifié (un token d'accès personnel ne suffit pas)", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn export_data(
+    CurrentUser(user_id): CurrentUser,
+    State(pool): State<PgPool>,
+    State(clock): State<SharedClock>,
+    client: ClientInfo,
+) -> ApiResult<Response> {
+    let mut data: Value = sqlx::query_scalar(EXPORT_SQL)
+        .bind(user_id)
+        .fetch_one(&pool)
+        .await
+        .map_err(to_500)?;
+    let exported_at = clock.now().format(&Rfc3339).map_err(to_500)?;
+    let username = data["profile"]["username"]
+        .as_str()
+        .unwrap_or("compte")
+        .to_string();
+    data["format_version"] = json!(EXPORT_FORMAT);
+    data["exported_at"] = json!(exported_at);
+
+    audit::log(&pool, &client, Event::own(audit::DATA_EXPORTED, user_id)).await;
+
+    let body = serde_json::to_vec_pretty(&data).map_err(to_500)?;
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/json".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"pokerncp-export-{username}.json\""),
+            ),
+            (header::CACHE_CONTROL, "no-store".to_string()),
+        ],
+        body,
+    )
+        .into_response())
+}
@@ -0,0 +1,85 @@
+use axum::{
+    Json,
+    extract::State,
+    http::{StatusCode, header},
+    response::IntoResponse,
+};
+use serde_json::json;
+use sqlx::PgPool;
+use std::time::Duration;
+
+use crate::config::AppConfig;
+use crate::models::health::{Readiness, VersionInfo};
+use crate::{health, metrics};
+
+fn readiness_timeout(config: &AppConfig) -> Duration {
+    Duration::from_millis(config.health.readiness_timeout_ms)
+}
+
+/// Vivacité: le processus répond, sans toucher à la base.
+#[utoipa::path(
+    get,
+    path = "/healthz",
+    tag = "sondes",
+    responses((status = 200, description = "`{\"status\":\"ok\"}`", body = Object))
+)]
+pub async fn healthz() -> impl IntoResponse {
+    (
+        [(header::CACHE_CONTROL, "no-store")],
+        Json(json!({ "status": "ok" })),
+    )
+}
+
+/// Disponibilité: 200 si base, migrations et seed sont en ordre, 503 sinon (détail dans le corps).
+#[utoipa::path(
+    get,
+    path = "/readyz",
+    tag = "sondes",
+    responses(
+        (status = 200, description = "Prêt à servir du trafic", body = Readiness),
+        (status = 503, description = "Au moins une vérification a échoué", body = Readiness),
+    )
+)]
+pub async fn readyz(
+    State(pool): State<PgPool>,
+    State(config): State<&'static AppConfig>,
+) -> impl IntoResponse {
+    let report = health::readiness(&pool, readiness_timeout(config)).await;
+    let status = if report.ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, [(header::CACHE_CONTROL, "no-store")], Json(report))
+}
+
+/// Version du binaire et dernière migration appliquée.
+#[utoipa::path(
+    get,
+    path = "/version",
+    tag = "sondes",
+    responses((status = 200, description = "Version du binaire", body = VersionInfo))
+)]
+pub async fn version(
+    State(pool): State<PgPool>,
+    State(config): State<&'static AppConfig>,
+) -> impl IntoResponse {
+    Json(health::version(&pool, readiness_timeout(config)).await)
+}
+
+/// Métriques Prometheus (format texte 0.0.4).
+pub async fn metrics(
+    State(pool): State<PgPool>,
+    State(config): State<&'static AppConfig>,
+) -> impl IntoResponse {
+    (
+        [
+            (
+                header::CONTENT_TYPE,
+                "text/plain; version=0.0.4; charset=utf-8",
+            ),
+            (header::CACHE_CONTROL, "no-store"),
+        ],
+        metrics::render(&pool, config).await,
+    )
+}
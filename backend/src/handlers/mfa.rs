@@ -0,0 +1,299 @@
+use axum::{
+    Json,
+    extract::State,
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use sqlx::PgPool;
+
+use crate::account_deletion;
+use crate::auth::{CurrentUser, get_cookie, verify_mfa_pending_token};
+use crate::clock::SharedClock;
+use crate::config::AppConfig;
+use crate::handlers::auth::{
+    blocked_response, login_message, notify_lock, secure_flag, session_response,
+};
+use crate::helpers::{
+    ClientInfo, bad_request, conflict, forbidden, internal_server_error, not_found, ok,
+    unauthorized,
+};
+use crate::login_guard;
+use crate::mailer::{Lang, MailQueue};
+use crate::mfa;
+use crate::models::auth::{MfaCodePayload, MfaLoginPayload, RecoveryCodes, TotpSetup};
+use crate::rng::SharedRng;
+use crate::sessions::start_session;
+
+const PENDING_COOKIE: &str = "mfa_pending";
+
+// Retour OIDC d'un compte 2FA: le token "mfa pending" attend le code dans ce
+// cookie (Path=/api: /api/v1 comme l'alias /api), pas dans l'URL de l'application
+pub(crate) fn mfa_pending_cookie(config: &AppConfig, value: &str, max_age: i64) -> HeaderValue {
+    HeaderValue::from_str(&format!(
+        "{PENDING_COOKIE}={value}; Path=/api; Max-Age={max_age}; HttpOnly; SameSite=Lax{}",
+        secure_flag(config)
+    ))
+    .unwrap()
+}
+
+/// Démarre l'activation: nouveau secret et URI otpauth:// à scanner.
+/// La 2FA n'est active qu'après `/2fa/verify`.
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/2fa/setup",
+    tag = "auth",
+    security(("bearer" = []), ("cookie" = [])),
+    responses(
+        (status = 200, description = "Secret à confirmer par /2fa/verify", body = TotpSetup),
+        (status = 401, description = "Non authentifié", body = String, content_type = "text/plain"),
+        (status = 409, description = "Double authentification déjà activée", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn setup_two_factor(
+    State(pool): State<PgPool>,
+    State(config): State<&'static AppConfig>,
+    CurrentUser(user_id): CurrentUser,
+) -> impl IntoResponse {
+    let row = sqlx::query_as::<_, (String, bool)>(
+        r#"SELECT username, totp_enabled_at IS NOT NULL FROM users WHERE id = $1"#,
+    )
+    .bind(user_id)
+    .fetch_optional(&pool)
+    .await;
+    let (username, enabled) = match row {
+        Ok(Some(r)) => r,
+        Ok(None) => return not_found("Utilisateur introuvable").into_response(),
+        Err(e) => return internal_server_error(e.to_string()).into_response(),
+    };
+    if enabled {
+        return conflict("Double authentification déjà activée").into_response();
+    }
+
+    let secret = match mfa::begin_setup(&pool, user_id).await {
+        Ok(s) => s,
+        Err(e) => return internal_server_error(e.to_string()).into_response(),
+    };
+    match mfa::otpauth_uri(&secret, &config.auth.mfa_issuer, &username) {
+        Ok(uri) => (
+            StatusCode::OK,
+            Json(TotpSetup {
+                secret,
+                otpauth_uri: uri,
+            }),
+        )
+            .into_response(),
+        Err(e) => internal_server_error(e.to_string()).into_response(),
+    }
+}
+
+/// Confirme l'activation avec un premier code et renvoie les codes de récupération.
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/2fa/verify",
+    tag = "auth",
+    security(("bearer" = []), ("cookie" = [])),
+    request_body = MfaCodePayload,
+    responses(
+        (status = 200, description = "Double authentification activée", body = RecoveryCodes),
+        (status = 400, description = "Code invalide", body = String, content_type = "text/plain"),
+        (status = 401, description = "Non authentifié", body = String, content_type = "text/plain"),
+        (status = 409, description = "Double authentification déjà activée", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn verify_two_factor(
+    State(pool): State<PgPool>,
+    State(clock): State<SharedClock>,
+    State(rng): State<SharedRng>,
+    CurrentUser(user_id): CurrentUser,
+    Json(payload): Json<MfaCodePayload>,
+) -> impl IntoResponse {
+    match mfa::is_enabled(&pool, user_id).await {
+        Ok(true) => return conflict("Double authentification déjà activée").into_response(),
+        Ok(false) => {}
+        Err(e) => return internal_server_error(e.to_string()).into_response(),
+    }
+
+    match mfa::verify_totp(&pool, &*clock, user_id, payload.code.trim()).await {
+        Ok(true) => {}
+        Ok(false) => return bad_request("Code invalide").into_response(),
+        Err(e) => return internal_server_error(e.to_string()).into_response(),
+    }
+
+    match mfa::enable(&pool, &*rng, user_id).await {
+        Ok(codes) => (
+            StatusCode::OK,
+            Json(RecoveryCodes {
+                message: "Double authentification activée",
+                recovery_codes: codes,
+            }),
+        )
+            .into_response(),
+        Err(e) => internal_server_error(e.to_string()).into_response(),
+    }
+}
+
+/// Désactive la 2FA (code TOTP ou code de récupération requis).
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/2fa/disable",
+    tag = "auth",
+    security(("bearer" = []), ("cookie" = [])),
+    request_body = MfaCodePayload,
+    responses(
+        (status = 200, description = "Double authentification désactivée", body = String, content_type = "text/plain"),
+        (status = 400, description = "Double authentification non activée", body = String, content_type = "text/plain"),
+        (status = 401, description = "Non authentifié ou code invalide", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn disable_two_factor(
+    State(pool): State<PgPool>,
+    State(clock): State<SharedClock>,
+    CurrentUser(user_id): CurrentUser,
+    Json(payload): Json<MfaCodePayload>,
+) -> impl IntoResponse {
+    match mfa::is_enabled(&pool, user_id).await {
+        Ok(true) => {}
+        Ok(false) => return bad_request("Double authentification non activée").into_response(),
+        Err(e) => return internal_server_error(e.to_string()).into_response(),
+    }
+
+    match mfa::verify_second_factor(&pool, &*clock, user_id, &payload.code).await {
+        Ok(true) => {}
+        Ok(false) => return unauthorized("Code invalide").into_response(),
+        Err(e) => return internal_server_error(e.to_string()).into_response(),
+    }
+
+    match mfa::disable(&pool, user_id).await {
+        Ok(()) => ok("Double authentification désactivée").into_response(),
+        Err(e) => internal_server_error(e.to_string()).into_response(),
+    }
+}
+
+/// Seconde étape du login: token "mfa pending" (corps ou cookie `mfa_pending`)
+/// + code TOTP ou code de récupération. Le token ne sert qu'une fois; les
+/// mauvais codes comptent comme des échecs de connexion (backoff, verrouillage).
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/2fa/login",
+    tag = "auth",
+    request_body = MfaLoginPayload,
+    responses(
+        (status = 200, description = "Session ouverte", body = String, content_type = "text/plain", headers(("set-cookie" = String, description = "Cookies auth, refresh et csrf"))),
+        (status = 401, description = "Code invalide, session de connexion expirée ou déjà utilisée", body = String, content_type = "text/plain"),
+        (status = 403, description = "Compte suspendu", body = String, content_type = "text/plain"),
+        (status = 423, description = "Compte verrouillé (Retry-After)", body = String, content_type = "text/plain"),
+        (status = 429, description = "Trop de tentatives (Retry-After)", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn login_two_factor(
+    State(pool): State<PgPool>,
+    State(config): State<&'static AppConfig>,
+    State(clock): State<SharedClock>,
+    State(mail): State<MailQueue>,
+    (lang, client): (Lang, ClientInfo),
+    headers: HeaderMap,
+    Json(payload): Json<MfaLoginPayload>,
+) -> Response {
+    let token = payload
+        .mfa_token
+        .or_else(|| get_cookie(&headers, PENDING_COOKIE))
+        .unwrap_or_default();
+    let claims = match verify_mfa_pending_token(config, &token) {
+        Ok(c) => c,
+        Err(_) => return unauthorized("Session de connexion expirée").into_response(),
+    };
+    match mfa::pending_login_open(&pool, &claims).await {
+        Ok(true) => {}
+        Ok(false) => return unauthorized("Session de connexion expirée").into_response(),
+        Err(e) => return internal_server_error(e.to_string()).into_response(),
+    }
+    let user_id = claims.sub;
+
+    let row = sqlx::query_as::<_, (String, Option<String>, bool, bool)>(
+        r#"
+        SELECT username, email, totp_enabled_at IS NOT NULL, disabled_at IS NOT NULL
+        FROM users WHERE id = $1
+        "#,
+    )
+    .bind(user_id)
+    .fetch_optional(&pool)
+    .await;
+    let (username, email) = match row {
+        Ok(Some((_, _, true, true))) => return forbidden("Compte suspendu").into_response(),
+        Ok(Some((username, email, true, false))) => (username, email),
+        // Compte supprimé ou 2FA désactivée entre-temps: on recommence le login
+        Ok(_) => return unauthorized("Session de connexion expirée").into_response(),
+        Err(e) => return internal_server_error(e.to_string()).into_response(),
+    };
+
+    match login_guard::check_account(&pool, &*clock, &config.login, user_id).await {
+        Ok(Some(block)) => {
+            let _ = login_guard::log_attempt(
+                &pool,
+                &*clock,
+                Some(user_id),
+                &username,
+                &client,
+                "throttled",
+            )
+            .await;
+            return blocked_response(block);
+        }
+        Ok(None) => {}
+        Err(e) => return internal_server_error(e.to_string()).into_response(),
+    }
+
+    match mfa::verify_second_factor(&pool, &*clock, user_id, &payload.code).await {
+        Ok(true) => {}
+        Ok(false) => {
+            return match login_guard::record_failure(
+                &pool,
+                &*clock,
+                &config.login,
+                user_id,
+                &username,
+                &client,
+                "invalid_mfa_code",
+            )
+            .await
+            {
+                Ok(Some(until)) => notify_lock(
+                    config,
+                    &mail,
+                    lang,
+                    user_id,
+                    email.as_deref(),
+                    &username,
+                    until,
+                ),
+                Ok(None) => unauthorized("Code invalide").into_response(),
+                Err(e) => internal_server_error(e.to_string()).into_response(),
+            };
+        }
+        Err(e) => return internal_server_error(e.to_string()).into_response(),
+    }
+    // Deux requêtes concurrentes avec le même token: une seule ouvre la session
+    match mfa::consume_pending_login(&pool, &claims).await {
+        Ok(true) => {}
+        Ok(false) => return unauthorized("Session de connexion expirée").into_response(),
+        Err(e) => return internal_server_error(e.to_string()).into_response(),
+    }
+
+    if let Err(e) = login_guard::record_success(&pool, &*clock, user_id, &username, &client).await {
+        return internal_server_error(e.to_string()).into_response();
+    }
+    let reactivated = match account_deletion::reactivate_on_login(&pool, &client, user_id).await {
+        Ok(r) => r,
+        Err(e) => return internal_server_error(e.to_string()).into_response(),
+    };
+
+    let tokens = match start_session(&pool, &*clock, config, user_id, &client).await {
+        Ok(t) => t,
+        Err(e) => return internal_server_error(e.to_string()).into_response(),
+    };
+
+    let mut res = session_response(config, login_message(reactivated), &tokens);
+    res.headers_mut()
+        .append(header::SET_COOKIE, mfa_pending_cookie(config, "", 0));
+    res
+}
@@ -1,3 +1,11 @@
+pub mod admin;
+pub mod api_tokens;
+pub mod audit;
 pub mod auth;
+pub mod daily;
+pub mod export;
+pub mod health;
+pub mod mfa;
+pub mod oidc;
 pub mod pokemon;
 pub mod user;
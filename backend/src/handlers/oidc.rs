@@ -0,0 +1,431 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::account_deletion;
+use crate::auth::{
+    CurrentUser, OIDC_FLOW_TTL_SECS, generate_oidc_flow_token, get_cookie, verify_oidc_flow_token,
+};
+use crate::config::AppConfig;
+use crate::handlers::auth::{
+    blocked_response, secure_flag, send_email_verification, session_cookies,
+};
+use crate::handlers::mfa::mfa_pending_cookie;
+use crate::helpers::{
+    ApiResult, ClientInfo, bad_request, conflict, forbidden, internal_server_error, not_found, ok,
+    to_500, unauthorized,
+};
+use crate::login_guard;
+use crate::mailer::{Lang, templates};
+use crate::mfa;
+use crate::models::auth::{
+    IdentityInfo, OidcCallbackParams, OidcFlowClaims, OidcStartParams, ProviderInfo,
+};
+use crate::oidc::{self, IdTokenClaims, OidcError, OidcProvider, OidcProviders, Resolved};
+use crate::sessions::start_session;
+use crate::state::AppState;
+
+const FLOW_COOKIE: &str = "oidc_flow";
+
+// Path=/api: le flux démarre sous /api/v1 ou sous l'alias /api
+fn flow_cookie(config: &AppConfig, value: &str, max_age: i64) -> HeaderValue {
+    HeaderValue::from_str(&format!(
+        "{FLOW_COOKIE}={value}; Path=/api; Max-Age={max_age}; HttpOnly; SameSite=Lax{}",
+        secure_flag(config)
+    ))
+    .unwrap()
+}
+
+fn redirect(location: &str) -> Response {
+    (
+        StatusCode::FOUND,
+        [(header::LOCATION, location.to_string())],
+    )
+        .into_response()
+}
+
+// Seuls les chemins du frontend sont acceptés (pas de redirection ouverte)
+fn safe_redirect(path: Option<&str>) -> String {
+    match path {
+        Some(p) if p.starts_with('/') && !p.starts_with("//") && !p.contains('\\') => p.to_string(),
+        _ => "/".to_string(),
+    }
+}
+
+fn oidc_error(e: OidcError) -> Response {
+    match e {
+        OidcError::Provider(_) => (StatusCode::BAD_GATEWAY, e.to_string()).into_response(),
+        OidcError::InvalidIdToken(_) => unauthorized(e.to_string()).into_response(),
+        OidcError::IdentityInUse => conflict(e.to_string()).into_response(),
+        OidcError::Db(_) | OidcError::Internal(_) => {
+            internal_server_error(e.to_string()).into_response()
+        }
+    }
+}
+
+/// Fournisseurs configurés, pour les boutons "Se connecter avec …".
+#[utoipa::path(
+    get,
+    path = "/api/v1/auth/oidc/providers",
+    tag = "auth",
+    responses((status = 200, description = "Fournisseurs configurés", body = Vec<ProviderInfo>)),
+)]
+pub async fn list_providers(State(providers): State<OidcProviders>) -> Json<Vec<ProviderInfo>> {
+    Json(
+        providers
+            .list()
+            .iter()
+            .map(|p| ProviderInfo {
+                name: p.name.clone(),
+                display_name: p.display_name.clone(),
+            })
+            .collect(),
+    )
+}
+
+/// Redirige vers le fournisseur pour se connecter (ou créer un compte).
+#[utoipa::path(
+    get,
+    path = "/api/v1/auth/oidc/{provider}/start",
+    tag = "auth",
+    params(("provider" = String, Path), OidcStartParams),
+    responses(
+        (status = 302, description = "Redirection vers le fournisseur (cookie oidc_flow)"),
+        (status = 404, description = "Fournisseur inconnu", body = String, content_type = "text/plain"),
+        (status = 502, description = "Fournisseur injoignable", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn start_login(
+    State(config): State<&'static AppConfig>,
+    State(providers): State<OidcProviders>,
+    Path(name): Path<String>,
+    Query(params): Query<OidcStartParams>,
+) -> Response {
+    let provider = providers.get(&name);
+    begin(config, provider, params.redirect_to.as_deref(), None).await
+}
+
+/// Redirige vers le fournisseur pour rattacher une identité au compte connecté.
+#[utoipa::path(
+    get,
+    path = "/api/v1/auth/oidc/{provider}/link",
+    tag = "auth",
+    params(("provider" = String, Path), OidcStartParams),
+    security(("bearer" = []), ("cookie" = [])),
+    responses(
+        (status = 302, description = "Redirection vers le fournisseur (cookie oidc_flow)"),
+        (status = 401, description = "Non authentifié", body = String, content_type = "text/plain"),
+        (status = 404, description = "Fournisseur inconnu", body = String, content_type = "text/plain"),
+        (status = 502, description = "Fournisseur injoignable", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn start_link(
+    State(config): State<&'static AppConfig>,
+    State(providers): State<OidcProviders>,
+    CurrentUser(user_id): CurrentUser,
+    Path(name): Path<String>,
+    Query(params): Query<OidcStartParams>,
+) -> Response {
+    let provider = providers.get(&name);
+    begin(
+        config,
+        provider,
+        params.redirect_to.as_deref(),
+        Some(user_id),
+    )
+    .await
+}
+
+async fn begin(
+    config: &AppConfig,
+    provider: Option<&OidcProvider>,
+    redirect_to: Option<&str>,
+    link_user: Option<Uuid>,
+) -> Response {
+    let Some(provider) = provider else {
+        return not_found("Fournisseur inconnu").into_response();
+    };
+    let discovery = match oidc::discover(provider).await {
+        Ok(d) => d,
+        Err(e) => return oidc_error(e),
+    };
+
+    let flow = OidcFlowClaims {
+        provider: provider.name.clone(),
+        state: oidc::random_token(),
+        nonce: oidc::random_token(),
+        code_verifier: oidc::random_token(),
+        redirect_to: safe_redirect(redirect_to),
+        link_user,
+        ..Default::default()
+    };
+    let url = match oidc::authorization_url(
+        config,
+        provider,
+        &discovery,
+        &flow.state,
+        &flow.nonce,
+        &flow.code_verifier,
+    ) {
+        Ok(u) => u,
+        Err(e) => return oidc_error(e),
+    };
+    let token = match generate_oidc_flow_token(config, flow) {
+        Ok(t) => t,
+        Err(e) => return internal_server_error(e.to_string()).into_response(),
+    };
+
+    let mut res = redirect(&url);
+    res.headers_mut().append(
+        header::SET_COOKIE,
+        flow_cookie(config, &token, OIDC_FLOW_TTL_SECS),
+    );
+    res
+}
+
+/// Retour du fournisseur: échange du code, validation de l'ID token, puis session.
+#[utoipa::path(
+    get,
+    path = "/api/v1/auth/oidc/{provider}/callback",
+    tag = "auth",
+    params(("provider" = String, Path), OidcCallbackParams),
+    responses(
+        (status = 302, description = "Retour vers le frontend (session ouverte ou identité rattachée)"),
+        (status = 400, description = "Flux expiré, state invalide ou code manquant", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn callback(
+    State(state): State<AppState>,
+    lang: Lang,
+    client: ClientInfo,
+    Path(name): Path<String>,
+    Query(params): Query<OidcCallbackParams>,
+    headers: HeaderMap,
+) -> Response {
+    let mut res = complete(&state, lang, &client, &name, params, &headers).await;
+    // Le cookie de flux est à usage unique, quelle que soit l'issue
+    res.headers_mut()
+        .append(header::SET_COOKIE, flow_cookie(state.config, "", 0));
+    res
+}
+
+async fn id_token_claims(
+    config: &AppConfig,
+    provider: &OidcProvider,
+    code: &str,
+    flow: &OidcFlowClaims,
+) -> Result<IdTokenClaims, OidcError> {
+    let discovery = oidc::discover(provider).await?;
+    let id_token =
+        oidc::exchange_code(config, provider, &discovery, code, &flow.code_verifier).await?;
+    oidc::validate_id_token(provider, &discovery, &id_token, &flow.nonce).await
+}
+
+async fn complete(
+    state: &AppState,
+    lang: Lang,
+    client: &ClientInfo,
+    name: &str,
+    params: OidcCallbackParams,
+    headers: &HeaderMap,
+) -> Response {
+    let AppState {
+        pool,
+        config,
+        mail,
+        clock,
+        ..
+    } = state;
+    let flow = match get_cookie(headers, FLOW_COOKIE)
+        .and_then(|t| verify_oidc_flow_token(config, &t).ok())
+    {
+        Some(f) if f.provider == name => f,
+        _ => return bad_request("Connexion expirée, recommence").into_response(),
+    };
+    if params.state.as_deref() != Some(flow.state.as_str()) {
+        return bad_request("Paramètre state invalide").into_response();
+    }
+    if let Some(error) = params.error {
+        let detail = params.error_description.unwrap_or(error);
+        return unauthorized(format!("Connexion refusée par le fournisseur: {detail}"))
+            .into_response();
+    }
+    let Some(code) = params.code else {
+        return bad_request("Code d'autorisation manquant").into_response();
+    };
+    let Some(provider) = state.oidc.get(name) else {
+        return not_found("Fournisseur inconnu").into_response();
+    };
+
+    let claims = match id_token_claims(config, provider, &code, &flow).await {
+        Ok(c) => c,
+        Err(e) => return oidc_error(e),
+    };
+    let resolved = match oidc::resolve_user(pool, &provider.name, &claims, flow.link_user).await {
+        Ok(r) => r,
+        Err(e) => return oidc_error(e),
+    };
+    let user_id = resolved.user_id();
+
+    // Rattachement depuis un compte déjà connecté: pas de nouvelle session
+    if flow.link_user.is_some() {
+        return redirect(&templates::app_link(config, &flow.redirect_to));
+    }
+
+    if let Resolved::Created {
+        username,
+        email: Some(email),
+        ..
+    } = &resolved
+    {
+        mail.enqueue(templates::welcome(lang, email, username));
+        if !claims.email_verified
+            && let Err(e) = send_email_verification(config, mail, lang, user_id, username, email)
+        {
+            return internal_server_error(e.to_string()).into_response();
+        }
+    }
+
+    let row = sqlx::query_as::<_, (String, bool, bool, bool)>(
+        r#"
+        SELECT username, email_verified_at IS NOT NULL, totp_enabled_at IS NOT NULL,
+               disabled_at IS NOT NULL
+        FROM users WHERE id = $1
+        "#,
+    )
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await;
+    let (username, email_verified, mfa_enabled) = match row {
+        Ok(Some((_, _, _, true))) => return forbidden("Compte suspendu").into_response(),
+        Ok(Some((username, verified, mfa, false))) => (username, verified, mfa),
+        Ok(None) => return unauthorized("Utilisateur introuvable").into_response(),
+        Err(e) => return internal_server_error(e.to_string()).into_response(),
+    };
+
+    match login_guard::check_account(pool, &**clock, &config.login, user_id).await {
+        Ok(Some(block)) => return blocked_response(block),
+        Ok(None) => {}
+        Err(e) => return internal_server_error(e.to_string()).into_response(),
+    }
+
+    if config.auth.require_verified_email_for_login && !email_verified {
+        return forbidden("Email non vérifié").into_response();
+    }
+
+    // 2FA: le fournisseur remplace le mot de passe, pas le second facteur
+    if mfa_enabled {
+        if let Err(e) = login_guard::log_attempt(
+            pool,
+            &**clock,
+            Some(user_id),
+            &username,
+            client,
+            "mfa_required",
+        )
+        .await
+        {
+            return internal_server_error(e.to_string()).into_response();
+        }
+        // Token remis en cookie HttpOnly: jamais dans l'URL (historique, logs, Referer)
+        return match mfa::issue_pending_login(pool, config, user_id).await {
+            Ok(token) => {
+                let mut res = redirect(&templates::app_link(config, "/login?mfa=pending"));
+                res.headers_mut().append(
+                    header::SET_COOKIE,
+                    mfa_pending_cookie(config, &token, config.auth.mfa_pending_ttl_secs),
+                );
+                res
+            }
+            Err(e) => internal_server_error(e.to_string()).into_response(),
+        };
+    }
+
+    if let Err(e) = login_guard::record_success(pool, &**clock, user_id, &username, client).await {
+        return internal_server_error(e.to_string()).into_response();
+    }
+    if let Err(e) = account_deletion::reactivate_on_login(pool, client, user_id).await {
+        return internal_server_error(e.to_string()).into_response();
+    }
+    let tokens = match start_session(pool, &**clock, config, user_id, client).await {
+        Ok(t) => t,
+        Err(e) => return internal_server_error(e.to_string()).into_response(),
+    };
+
+    let mut res = redirect(&templates::app_link(config, &flow.redirect_to));
+    for cookie in session_cookies(config, &tokens) {
+        res.headers_mut().append(header::SET_COOKIE, cookie);
+    }
+    res
+}
+
+/// Identités externes rattachées au compte.
+#[utoipa::path(
+    get,
+    path = "/api/v1/auth/identities",
+    tag = "auth",
+    security(("bearer" = []), ("cookie" = [])),
+    responses(
+        (status = 200, description = "Identités rattachées", body = Vec<IdentityInfo>),
+        (status = 401, description = "Non authentifié", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn list_identities(
+    CurrentUser(user_id): CurrentUser,
+    State(pool): State<PgPool>,
+) -> ApiResult<Json<Vec<IdentityInfo>>> {
+    let identities = oidc::list_identities(&pool, user_id)
+        .await
+        .map_err(to_500)?;
+    Ok(Json(identities))
+}
+
+/// Retire une identité externe du compte.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/auth/identities/{id}",
+    tag = "auth",
+    params(("id" = Uuid, Path, description = "Identifiant de l'identité")),
+    security(("bearer" = []), ("cookie" = [])),
+    responses(
+        (status = 200, description = "Identité retirée", body = String, content_type = "text/plain"),
+        (status = 401, description = "Non authentifié", body = String, content_type = "text/plain"),
+        (status = 404, description = "Identité introuvable", body = String, content_type = "text/plain"),
+        (status = 409, description = "Dernier moyen de connexion du compte", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn unlink_identity(
+    CurrentUser(user_id): CurrentUser,
+    State(pool): State<PgPool>,
+    Path(identity_id): Path<Uuid>,
+) -> ApiResult<(StatusCode, String)> {
+    // Dernière identité d'un compte sans email: plus aucun moyen de s'y reconnecter
+    let (identities, has_email): (i64, bool) = sqlx::query_as(
+        r#"
+        SELECT (SELECT COUNT(*) FROM user_identities WHERE user_id = u.id), u.email IS NOT NULL
+        FROM users u WHERE u.id = $1
+        "#,
+    )
+    .bind(user_id)
+    .fetch_one(&pool)
+    .await
+    .map_err(to_500)?;
+    if identities <= 1 && !has_email {
+        return Err(conflict(
+            "Ajoute un email avant de retirer ta dernière identité",
+        ));
+    }
+
+    if !oidc::unlink(&pool, user_id, identity_id)
+        .await
+        .map_err(to_500)?
+    {
+        return Err(not_found("Identité introuvable"));
+    }
+    ok("Identité retirée")
+}
@@ -2,45 +2,127 @@ use axum::{
     Json,
     extract::Query,
     extract::{Path, State},
+    http::HeaderMap,
+    response::Response,
 };
 use sqlx::PgPool;
 
 use crate::auth::CurrentUser;
+use crate::catalog::{self, SharedCatalog};
+use crate::clock::SharedClock;
+use crate::daily::record_catch;
 use crate::helpers::{ApiResult, created, not_found, to_500};
-use crate::models::pokemon::{CatchByNamePayload, PokemonDetail, PokemonWithCaught, SearchParams};
+use crate::metrics;
+use crate::models::pokemon::{
+    CatchByNamePayload, PokemonDetail, PokemonPage, PokemonPageQuery, PokemonWithCaught,
+    SearchParams,
+};
+
+const PAGE_MAX: i64 = 100;
 
+/// Catalogue complet, avec l'état de capture de l'utilisateur (paginé en v2).
+#[utoipa::path(
+    get,
+    path = "/api/v1/pokemons",
+    tag = "pokemons",
+    params(("If-None-Match" = Option<String>, Header, description = "ETag d'une réponse précédente")),
+    security(("bearer" = ["pokedex:read"]), ("cookie" = [])),
+    responses(
+        (status = 200, description = "Catalogue trié par identifiant", body = Vec<PokemonWithCaught>, headers(
+            ("ETag" = String, description = "Version du catalogue et de la collection"),
+            ("Last-Modified" = String, description = "Dernière modification de l'un ou de l'autre"),
+        )),
+        (status = 304, description = "Représentation du client toujours à jour"),
+        (status = 401, description = "Non authentifié", body = String, content_type = "text/plain"),
+    )
+)]
 pub async fn list_all(
     CurrentUser(user_id): CurrentUser,
     State(pool): State<PgPool>,
-) -> ApiResult<Json<Vec<PokemonWithCaught>>> {
-    let rows = sqlx::query_as::<_, PokemonWithCaught>(
-        r#"
-        SELECT
-            p.id          AS id,
-            p.name        AS name,
-            p.type1       AS type1,
-            p.type2       AS type2,
-            p.dex_no      AS dex_no,
-            p.image_url   AS image_url,
-            EXISTS (
-                SELECT 1 FROM user_pokemon up
-                WHERE up.user_id = $1 AND up.pokemon_id = p.id
-            )            AS caught
-        FROM pokemon p
-        ORDER BY p.id
-        "#,
+    State(catalog): State<SharedCatalog>,
+    headers: HeaderMap,
+) -> ApiResult<Response> {
+    let collection = catalog::collection(&pool, user_id).await.map_err(to_500)?;
+    if collection.validators.is_fresh(&headers) {
+        return Ok(collection.validators.not_modified());
+    }
+    let snapshot = catalog
+        .at_least(&pool, collection.catalog_version)
+        .await
+        .map_err(to_500)?;
+
+    let rows = snapshot.list(&collection.caught, 0, snapshot.len());
+    Ok(collection.validators.respond(Json(rows)))
+}
+
+/// Catalogue paginé (v2 de `GET /pokemons`), avec le nombre total de Pokémon.
+#[utoipa::path(
+    get,
+    path = "/api/v2/pokemons",
+    tag = "pokemons",
+    params(
+        PokemonPageQuery,
+        ("If-None-Match" = Option<String>, Header, description = "ETag d'une réponse précédente"),
+    ),
+    security(("bearer" = ["pokedex:read"]), ("cookie" = [])),
+    responses(
+        (status = 200, description = "Page du catalogue trié par identifiant", body = PokemonPage, headers(
+            ("ETag" = String, description = "Version du catalogue et de la collection"),
+            ("Last-Modified" = String, description = "Dernière modification de l'un ou de l'autre"),
+        )),
+        (status = 304, description = "Représentation du client toujours à jour"),
+        (status = 400, description = "Paramètres invalides", body = String, content_type = "text/plain"),
+        (status = 401, description = "Non authentifié", body = String, content_type = "text/plain"),
     )
-    .bind(user_id)
-    .fetch_all(&pool)
-    .await
-    .map_err(to_500)?;
+)]
+pub async fn list_page(
+    CurrentUser(user_id): CurrentUser,
+    State(pool): State<PgPool>,
+    State(catalog): State<SharedCatalog>,
+    headers: HeaderMap,
+    Query(params): Query<PokemonPageQuery>,
+) -> ApiResult<Response> {
+    let limit = params.limit.unwrap_or(50).clamp(1, PAGE_MAX);
+    let offset = params.offset.unwrap_or(0).max(0);
+
+    // La page dépend aussi de limit/offset, mais ils font partie de l'URL
+    let collection = catalog::collection(&pool, user_id).await.map_err(to_500)?;
+    if collection.validators.is_fresh(&headers) {
+        return Ok(collection.validators.not_modified());
+    }
+    let snapshot = catalog
+        .at_least(&pool, collection.catalog_version)
+        .await
+        .map_err(to_500)?;
 
-    Ok(Json(rows))
+    let total = snapshot.len() as i64;
+    let pokemons = snapshot.list(
+        &collection.caught,
+        usize::try_from(offset).unwrap_or(usize::MAX),
+        limit as usize,
+    );
+    Ok(collection
+        .validators
+        .respond(Json(PokemonPage { total, pokemons })))
 }
 
+/// Marque un Pokémon comme capturé (sans effet s'il l'est déjà).
+#[utoipa::path(
+    post,
+    path = "/api/v1/pokemons/catch",
+    tag = "pokemons",
+    security(("bearer" = ["collection:write"]), ("cookie" = [])),
+    request_body = CatchByNamePayload,
+    responses(
+        (status = 201, description = "Capture enregistrée", body = String, content_type = "text/plain"),
+        (status = 401, description = "Non authentifié", body = String, content_type = "text/plain"),
+        (status = 404, description = "Pokémon introuvable", body = String, content_type = "text/plain"),
+    )
+)]
 pub async fn catch(
     CurrentUser(user_id): CurrentUser,
     State(pool): State<PgPool>,
+    State(clock): State<SharedClock>,
     Json(payload): Json<CatchByNamePayload>,
 ) -> ApiResult<(axum::http::StatusCode, String)> {
     let pokemon_id = sqlx::query_scalar::<_, i32>(r#"SELECT id FROM pokemon WHERE name = $1"#)
@@ -50,96 +132,100 @@ pub async fn catch(
         .map_err(to_500)?
         .ok_or_else(|| not_found("Pokémon introuvable."))?;
 
-    let _ = sqlx::query(
+    let inserted = sqlx::query(
         r#"
-        INSERT INTO user_pokemon (user_id, pokemon_id, nickname)
-        VALUES ($1, $2, $3)
+        INSERT INTO user_pokemon (user_id, pokemon_id, nickname, discovered_at)
+        VALUES ($1, $2, $3, $4)
         ON CONFLICT (user_id, pokemon_id) DO NOTHING
         "#,
     )
     .bind(user_id)
     .bind(pokemon_id)
     .bind(payload.nickname)
+    .bind(clock.now())
     .execute(&pool)
     .await
-    .map_err(to_500)?;
+    .map_err(to_500)?
+    .rows_affected();
+
+    if inserted > 0 {
+        metrics::record_catch();
+    }
+    // Seule une nouvelle capture fait progresser le défi du jour
+    if inserted > 0
+        && let Err(e) = record_catch(&pool, &*clock, user_id).await
+    {
+        eprintln!("⚠️ Suivi du défi quotidien impossible: {e}");
+    }
 
     created("Pokémon marqué comme capturé.")
 }
 
+/// Recherche par début de nom.
+#[utoipa::path(
+    get,
+    path = "/api/v1/pokemons/search",
+    tag = "pokemons",
+    params(SearchParams),
+    security(("bearer" = ["pokedex:read"]), ("cookie" = [])),
+    responses(
+        (status = 200, description = "Au plus 10 Pokémon, triés par nom", body = Vec<PokemonWithCaught>),
+        (status = 401, description = "Non authentifié", body = String, content_type = "text/plain"),
+    )
+)]
 pub async fn search_pokemons(
     CurrentUser(user_id): CurrentUser,
     State(pool): State<PgPool>,
+    State(catalog): State<SharedCatalog>,
     Query(params): Query<SearchParams>,
 ) -> ApiResult<Json<Vec<PokemonWithCaught>>> {
-    let q = format!("{}%", params.q);
-    let rows = sqlx::query_as::<_, PokemonWithCaught>(
-        r#"
-        SELECT
-            p.id          AS id,
-            p.name        AS name,
-            p.type1       AS type1,
-            p.type2       AS type2,
-            p.dex_no      AS dex_no,
-            p.image_url   AS image_url,
-            EXISTS (
-                SELECT 1 FROM user_pokemon up
-                WHERE up.user_id = $1 AND up.pokemon_id = p.id
-            )            AS caught
-        FROM pokemon p
-        WHERE p.name ILIKE $2
-        ORDER BY p.name
-        LIMIT 10
-        "#,
-    )
-    .bind(user_id)
-    .bind(q)
-    .fetch_all(&pool)
-    .await
-    .map_err(to_500)?;
-    Ok(Json(rows))
+    let collection = catalog::collection(&pool, user_id).await.map_err(to_500)?;
+    let snapshot = catalog
+        .at_least(&pool, collection.catalog_version)
+        .await
+        .map_err(to_500)?;
+    Ok(Json(snapshot.search(&params.q, &collection.caught, 10)))
 }
 
+/// Fiche détaillée d'un Pokémon.
+#[utoipa::path(
+    get,
+    path = "/api/v1/pokemons/{pokemon_id}",
+    tag = "pokemons",
+    params(
+        ("pokemon_id" = i32, Path, description = "Identifiant du Pokémon"),
+        ("If-None-Match" = Option<String>, Header, description = "ETag d'une réponse précédente"),
+    ),
+    security(("bearer" = ["pokedex:read"]), ("cookie" = [])),
+    responses(
+        (status = 200, description = "Fiche du Pokémon", body = PokemonDetail, headers(
+            ("ETag" = String, description = "Version du catalogue et de la collection"),
+            ("Last-Modified" = String, description = "Dernière modification de l'un ou de l'autre"),
+        )),
+        (status = 304, description = "Représentation du client toujours à jour"),
+        (status = 401, description = "Non authentifié", body = String, content_type = "text/plain"),
+        (status = 404, description = "Pokémon introuvable", body = String, content_type = "text/plain"),
+    )
+)]
 pub async fn get_pokemon_by_id(
     CurrentUser(user_id): CurrentUser,
     State(pool): State<PgPool>,
+    State(catalog): State<SharedCatalog>,
     Path(pokemon_id): Path<i32>,
-) -> ApiResult<Json<PokemonDetail>> {
-    let row = sqlx::query_as::<_, PokemonDetail>(
-        r#"
-        SELECT
-            p.id                 AS id,
-            p.name               AS name,
-            p.type1              AS type1,
-            p.type2              AS type2,
-            p.dex_no             AS dex_no,
-            p.image_url          AS image_url,
-            p.height_m           AS height_m,
-            p.weight_kg          AS weight_kg,
-            p.description        AS description,
-            p.base_hp            AS base_hp,
-            p.base_attack        AS base_attack,
-            p.base_defense       AS base_defense,
-            p.base_sp_attack     AS base_sp_attack,
-            p.base_sp_defense    AS base_sp_defense,
-            p.base_speed         AS base_speed,
-            EXISTS (
-                SELECT 1 FROM user_pokemon up
-                WHERE up.user_id = $1 AND up.pokemon_id = p.id
-            )                   AS caught
-        FROM pokemon p
-        WHERE p.id = $2
-        "#,
-    )
-    .bind(user_id)
-    .bind(pokemon_id)
-    .fetch_optional(&pool)
-    .await
-    .map_err(to_500)?;
+    headers: HeaderMap,
+) -> ApiResult<Response> {
+    let collection = catalog::collection(&pool, user_id).await.map_err(to_500)?;
+    if collection.validators.is_fresh(&headers) {
+        return Ok(collection.validators.not_modified());
+    }
+    let snapshot = catalog
+        .at_least(&pool, collection.catalog_version)
+        .await
+        .map_err(to_500)?;
 
-    let Some(row) = row else {
+    let Some(row) = snapshot.detail(pokemon_id, &collection.caught) else {
         return Err(not_found("Pokémon introuvable."));
     };
 
-    Ok(Json(row))
+    Ok(collection.validators.respond(Json(row)))
 }
@@ -2,19 +2,66 @@ use axum::{
     Json,
     extract::{Path, State},
     http::StatusCode,
+    response::{IntoResponse, Response},
 };
+use serde_json::json;
 use sqlx::PgPool;
+use time::format_description::well_known::Rfc3339;
 use uuid::Uuid;
 
-use crate::auth::{CurrentUser, hash_password};
+use crate::account_deletion;
+use crate::audit::{self, Event};
+use crate::auth::{CurrentSession, CurrentUser, hash_password};
+use crate::clock::SharedClock;
+use crate::config::AppConfig;
+use crate::daily::is_valid_timezone;
+use crate::handlers::auth::{append_clear_cookies, send_email_verification};
 use crate::helpers::{
-    ApiResult, conflict, created, internal_server_error, not_found, ok, to_500, unauthorized,
+    ApiResult, ClientInfo, bad_request, conflict, created, internal_server_error, not_found, ok,
+    to_500, unauthorized,
 };
-use crate::models::user::{CreateUser, UpdateUser};
+use crate::mailer::{Lang, MailQueue, templates};
+use crate::models::user::{CreateUser, DeletionScheduled, UpdateUser};
+use crate::password_reset;
+use crate::sessions::revoke_all_except;
+use crate::state::AppState;
+use crate::validation::{self, ValidationReport};
 
+/// Inscription (email facultatif: un lien de vérification y est envoyé).
+#[utoipa::path(
+    post,
+    path = "/api/v1/users",
+    tag = "users",
+    request_body = CreateUser,
+    responses(
+        (status = 201, description = "Compte créé", body = String, content_type = "text/plain"),
+        (status = 409, description = "Nom d'utilisateur ou email déjà utilisé", body = ValidationReport),
+        (status = 422, description = "Données invalides", body = ValidationReport),
+    )
+)]
 pub async fn create_user(
     State(pool): State<PgPool>,
+    State(config): State<&'static AppConfig>,
+    State(mail): State<MailQueue>,
+    lang: Lang,
+    client: ClientInfo,
     Json(payload): Json<CreateUser>,
+) -> Response {
+    if let Err(res) = validation::signup(&pool, &config.password_policy, &payload).await {
+        return res;
+    }
+    insert_user(&pool, config, &mail, lang, &client, payload)
+        .await
+        .into_response()
+}
+
+async fn insert_user(
+    pool: &PgPool,
+    config: &AppConfig,
+    mail: &MailQueue,
+    lang: Lang,
+    client: &ClientInfo,
+    payload: CreateUser,
 ) -> ApiResult<(StatusCode, String)> {
     let hashed =
         hash_password(&payload.password).map_err(|_| internal_server_error("Hash failed"))?;
@@ -29,11 +76,11 @@ pub async fn create_user(
     .bind(&payload.username)
     .bind(&payload.email)
     .bind(&hashed)
-    .fetch_one(&pool)
+    .fetch_one(pool)
     .await;
 
-    match res {
-        Ok(_) => {}
+    let user_id = match res {
+        Ok(id) => id,
         Err(e) => {
             if let sqlx::Error::Database(db) = &e
                 && db.code().as_deref() == Some("23505")
@@ -42,63 +89,229 @@ pub async fn create_user(
             }
             return Err(to_500(e));
         }
+    };
+    let event = Event::own(audit::ACCOUNT_CREATED, user_id)
+        .meta(json!({ "username": payload.username, "email": payload.email }));
+    audit::log(pool, client, event).await;
+
+    if let Some(ref email) = payload.email {
+        mail.enqueue(templates::welcome(lang, email, &payload.username));
+        send_email_verification(config, mail, lang, user_id, &payload.username, email)
+            .map_err(|_| internal_server_error("Token generation failed"))?;
     }
 
     created("User created.")
 }
 
+/// Mise à jour de son propre profil.
+#[utoipa::path(
+    patch,
+    path = "/api/v1/users/{id}",
+    tag = "users",
+    params(("id" = Uuid, Path, description = "Identifiant de l'utilisateur connecté")),
+    security(("bearer" = []), ("cookie" = [])),
+    request_body = UpdateUser,
+    responses(
+        (status = 200, description = "Profil mis à jour", body = String, content_type = "text/plain"),
+        (status = 400, description = "Fuseau horaire inconnu", body = String, content_type = "text/plain"),
+        (status = 401, description = "Non authentifié ou autre compte", body = String, content_type = "text/plain"),
+        (status = 409, description = "Nom d'utilisateur ou email déjà utilisé", body = ValidationReport),
+        (status = 422, description = "Données invalides", body = ValidationReport),
+    )
+)]
 pub async fn update_user(
     CurrentUser(current_user): CurrentUser,
-    State(pool): State<PgPool>,
+    CurrentSession(current_session): CurrentSession,
+    State(state): State<AppState>,
+    (lang, client): (Lang, ClientInfo),
     Path(user_id): Path<Uuid>,
     Json(payload): Json<UpdateUser>,
-) -> ApiResult<(StatusCode, String)> {
+) -> Response {
     if current_user != user_id {
-        return Err(unauthorized("ACCESS DENIED"));
+        return unauthorized("ACCESS DENIED").into_response();
     }
+    let policy = &state.config.password_policy;
+    if let Err(res) = validation::profile_update(&state.pool, policy, user_id, &payload).await {
+        return res;
+    }
+    apply_update(&state, lang, &client, user_id, current_session, payload)
+        .await
+        .into_response()
+}
+
+async fn apply_update(
+    state: &AppState,
+    lang: Lang,
+    client: &ClientInfo,
+    user_id: Uuid,
+    current_session: Option<Uuid>,
+    payload: UpdateUser,
+) -> ApiResult<(StatusCode, String)> {
+    let AppState {
+        pool, config, mail, ..
+    } = state;
     if let Some(ref password) = payload.password {
         let hashed = hash_password(password).map_err(|_| internal_server_error("Hash failed."))?;
-        sqlx::query("UPDATE users SET password = $1 WHERE id = $2")
-            .bind(&hashed)
-            .bind(user_id)
-            .execute(&pool)
+        let (username, email) = sqlx::query_as::<_, (String, Option<String>)>(
+            "UPDATE users SET password = $1 WHERE id = $2 RETURNING username, email",
+        )
+        .bind(&hashed)
+        .bind(user_id)
+        .fetch_one(pool)
+        .await
+        .map_err(to_500)?;
+        password_reset::revoke_all(pool, user_id)
             .await
             .map_err(to_500)?;
-    }
-    if let Some(ref username) = payload.username {
-        sqlx::query("UPDATE users SET username = $1 WHERE id = $2")
-            .bind(username)
-            .bind(user_id)
-            .execute(&pool)
+        revoke_all_except(pool, user_id, current_session, "password_changed")
             .await
             .map_err(to_500)?;
+        let event = Event::own(audit::PASSWORD_CHANGED, user_id).meta(json!({ "via": "profile" }));
+        audit::log(pool, client, event).await;
+        if let Some(email) = email {
+            mail.enqueue(templates::password_changed(lang, &email, &username));
+        }
+    }
+    if let Some(ref username) = payload.username {
+        let res = sqlx::query_scalar::<_, String>(
+            r#"
+            UPDATE users u SET username = $1
+            FROM (SELECT id, username FROM users WHERE id = $2) old
+            WHERE u.id = old.id AND u.username <> $1
+            RETURNING old.username
+            "#,
+        )
+        .bind(username)
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await;
+        // Course avec une autre inscription malgré la vérification préalable
+        if let Err(sqlx::Error::Database(db)) = &res
+            && db.code().as_deref() == Some("23505")
+        {
+            return Err(conflict("Nom d'utilisateur déjà pris"));
+        }
+        if let Some(previous) = res.map_err(to_500)? {
+            let event = Event::own(audit::USERNAME_CHANGED, user_id)
+                .meta(json!({ "from": previous, "to": username }));
+            audit::log(pool, client, event).await;
+        }
     }
     if let Some(ref email) = payload.email {
-        sqlx::query("UPDATE users SET email = $1 WHERE id = $2")
-            .bind(email)
+        // La nouvelle adresse doit être revérifiée; l'ancienne est prévenue du changement
+        let changed = sqlx::query_as::<_, (String, Option<String>)>(
+            r#"
+            UPDATE users u SET email = $1, email_verified_at = NULL
+            FROM (SELECT id, email FROM users WHERE id = $2) old
+            WHERE u.id = old.id AND u.email IS DISTINCT FROM $1
+            RETURNING u.username, old.email
+            "#,
+        )
+        .bind(email)
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(to_500)?;
+        if let Some((username, old_email)) = changed {
+            let event = Event::own(audit::EMAIL_CHANGED, user_id)
+                .meta(json!({ "from": old_email, "to": email }));
+            audit::log(pool, client, event).await;
+            send_email_verification(config, mail, lang, user_id, &username, email)
+                .map_err(|_| internal_server_error("Token generation failed"))?;
+            if let Some(old_email) = old_email {
+                mail.enqueue(templates::email_changed(lang, &old_email, &username, email));
+            }
+        }
+    }
+    if let Some(ref timezone) = payload.timezone {
+        if !is_valid_timezone(pool, timezone).await.map_err(to_500)? {
+            return Err(bad_request("Invalid timezone."));
+        }
+        sqlx::query("UPDATE users SET timezone = $1 WHERE id = $2")
+            .bind(timezone)
             .bind(user_id)
-            .execute(&pool)
+            .execute(pool)
             .await
             .map_err(to_500)?;
     }
     ok("User updated.")
 }
 
+/// Suppression de son propre compte: désactivation immédiate, purge après le délai de grâce
+/// (ACCOUNT_DELETION_GRACE_DAYS), ou suppression définitive si ce délai vaut 0.
+/// Suppression de son propre compte, différée de ACCOUNT_DELETION_GRACE_DAYS.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/users/{id}",
+    tag = "users",
+    params(("id" = Uuid, Path, description = "Identifiant de l'utilisateur connecté")),
+    security(("bearer" = []), ("cookie" = [])),
+    responses(
+        (status = 200, description = "Suppression programmée (texte si immédiate, délai de grâce à 0)", body = DeletionScheduled),
+        (status = 401, description = "Non authentifié ou autre compte", body = String, content_type = "text/plain"),
+        (status = 404, description = "Compte introuvable", body = String, content_type = "text/plain"),
+    )
+)]
 pub async fn delete_user(
     CurrentUser(current_user): CurrentUser,
     State(pool): State<PgPool>,
+    State(config): State<&'static AppConfig>,
+    State(mail): State<MailQueue>,
+    State(clock): State<SharedClock>,
+    (lang, client): (Lang, ClientInfo),
     Path(user_id): Path<Uuid>,
-) -> ApiResult<(StatusCode, String)> {
+) -> ApiResult<Response> {
     if current_user != user_id {
         return Err(unauthorized("ACCESS DENIED"));
     }
-    let res = sqlx::query("DELETE FROM users WHERE id = $1")
-        .bind(user_id)
-        .execute(&pool)
+    let grace_days = config.accounts.deletion_grace_days;
+    if grace_days == 0 {
+        let deleted =
+            sqlx::query_scalar::<_, String>("DELETE FROM users WHERE id = $1 RETURNING username")
+                .bind(user_id)
+                .fetch_optional(&pool)
+                .await
+                .map_err(to_500)?;
+        let Some(username) = deleted else {
+            return Err(not_found("User not found"));
+        };
+        // Conservé après la suppression (pas de clé étrangère sur audit_events)
+        let event =
+            Event::own(audit::ACCOUNT_DELETED, user_id).meta(json!({ "username": username }));
+        audit::log(&pool, &client, event).await;
+        let mut res = "User deleted.".into_response();
+        append_clear_cookies(config, &mut res);
+        return Ok(res);
+    }
+
+    // Déjà en cours de suppression: le compte n'existe plus pour l'utilisateur
+    let Some(scheduled) = account_deletion::schedule(&pool, &*clock, user_id, grace_days)
         .await
-        .map_err(to_500)?;
-    if res.rows_affected() == 0 {
+        .map_err(to_500)?
+    else {
         return Err(not_found("User not found"));
+    };
+    let event = Event::own(audit::ACCOUNT_DELETED, user_id).meta(json!({
+        "username": scheduled.username,
+        "grace_days": grace_days,
+        "purge_after": scheduled.purge_after.format(&Rfc3339).map_err(to_500)?,
+    }));
+    audit::log(&pool, &client, event).await;
+    if let Some(email) = &scheduled.email {
+        let date = scheduled.purge_after.date().to_string();
+        mail.enqueue(templates::account_deletion_scheduled(
+            lang,
+            email,
+            &scheduled.username,
+            &date,
+        ));
     }
-    ok("User deleted.")
+
+    let mut res = Json(DeletionScheduled {
+        message: "Compte désactivé. Reconnecte-toi avant la date de purge pour annuler la suppression.",
+        purge_after: scheduled.purge_after.format(&Rfc3339).map_err(to_500)?,
+    })
+    .into_response();
+    append_clear_cookies(config, &mut res);
+    Ok(res)
 }
@@ -0,0 +1,172 @@
+//! Sondes pour docker-compose et les orchestrateurs: vivacité (/healthz),
+//! disponibilité (/readyz: base, migrations, seed) et version (/version).
+
+use sqlx::PgPool;
+use std::collections::HashSet;
+use std::future::Future;
+use std::time::{Duration, Instant};
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+
+use crate::db::MIGRATOR;
+use crate::models::health::{
+    DatabaseCheck, MigrationVersion, MigrationsCheck, Readiness, SeedCheck, VersionInfo,
+};
+
+/// Hash git et date du build, fournis par build.rs.
+pub const GIT_HASH: &str = env!("BUILD_GIT_HASH");
+const BUILD_UNIX_TIME: &str = env!("BUILD_UNIX_TIME");
+
+async fn within<T>(
+    limit: Duration,
+    query: impl Future<Output = Result<T, sqlx::Error>>,
+) -> Result<T, String> {
+    match tokio::time::timeout(limit, query).await {
+        Ok(Ok(value)) => Ok(value),
+        Ok(Err(e)) => Err(e.to_string()),
+        Err(_) => Err(format!("pas de réponse en {} ms", limit.as_millis())),
+    }
+}
+
+/// Versions des migrations embarquées (hors scripts de retour arrière).
+fn expected_migrations() -> Vec<i64> {
+    MIGRATOR
+        .iter()
+        .filter(|m| !m.migration_type.is_down_migration())
+        .map(|m| m.version)
+        .collect()
+}
+
+async fn check_migrations(pool: &PgPool, limit: Duration) -> MigrationsCheck {
+    let expected = expected_migrations();
+    let rows = within(
+        limit,
+        sqlx::query_as::<_, (i64, bool)>("SELECT version, success FROM _sqlx_migrations")
+            .fetch_all(pool),
+    )
+    .await;
+    let rows = match rows {
+        Ok(rows) => rows,
+        Err(e) => {
+            return MigrationsCheck {
+                expected: expected.len(),
+                pending: expected,
+                error: Some(e),
+                ..MigrationsCheck::default()
+            };
+        }
+    };
+
+    let applied: HashSet<i64> = rows.iter().filter(|(_, ok)| *ok).map(|(v, _)| *v).collect();
+    let failed: Vec<i64> = rows.iter().filter(|(_, ok)| !ok).map(|(v, _)| *v).collect();
+    let pending: Vec<i64> = expected
+        .iter()
+        .copied()
+        .filter(|v| !applied.contains(v))
+        .collect();
+    MigrationsCheck {
+        ok: pending.is_empty() && failed.is_empty(),
+        applied: applied.len(),
+        expected: expected.len(),
+        pending,
+        failed,
+        error: None,
+    }
+}
+
+async fn check_seed(pool: &PgPool, limit: Duration) -> SeedCheck {
+    match within(
+        limit,
+        sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM pokemon").fetch_one(pool),
+    )
+    .await
+    {
+        Ok(pokemon) => SeedCheck {
+            ok: pokemon > 0,
+            pokemon,
+            error: (pokemon == 0).then(|| "catalogue vide (seed absent)".to_string()),
+        },
+        Err(e) => SeedCheck {
+            error: Some(e),
+            ..SeedCheck::default()
+        },
+    }
+}
+
+/// Prêt à servir du trafic: base joignable dans le délai, migrations appliquées, catalogue présent.
+pub async fn readiness(pool: &PgPool, limit: Duration) -> Readiness {
+    let started = Instant::now();
+    let database = match within(limit, sqlx::query("SELECT 1").execute(pool)).await {
+        Ok(_) => DatabaseCheck {
+            ok: true,
+            latency_ms: Some(started.elapsed().as_millis() as u64),
+            error: None,
+        },
+        Err(e) => DatabaseCheck {
+            error: Some(e),
+            ..DatabaseCheck::default()
+        },
+    };
+
+    // Base injoignable: inutile d'attendre deux délais de plus
+    let (migrations, seed) = if database.ok {
+        (
+            check_migrations(pool, limit).await,
+            check_seed(pool, limit).await,
+        )
+    } else {
+        let skipped = || Some("base de données indisponible".to_string());
+        (
+            MigrationsCheck {
+                expected: expected_migrations().len(),
+                error: skipped(),
+                ..MigrationsCheck::default()
+            },
+            SeedCheck {
+                error: skipped(),
+                ..SeedCheck::default()
+            },
+        )
+    };
+
+    Readiness {
+        ready: database.ok && migrations.ok && seed.ok,
+        database,
+        migrations,
+        seed,
+    }
+}
+
+/// Version du binaire et dernière migration appliquée.
+pub async fn version(pool: &PgPool, limit: Duration) -> VersionInfo {
+    let built_at = BUILD_UNIX_TIME
+        .parse::<i64>()
+        .ok()
+        .and_then(|t| OffsetDateTime::from_unix_timestamp(t).ok())
+        .and_then(|t| t.format(&Rfc3339).ok())
+        .unwrap_or_default();
+    VersionInfo {
+        name: env!("CARGO_PKG_NAME"),
+        version: env!("CARGO_PKG_VERSION"),
+        git_hash: GIT_HASH,
+        built_at,
+        migration: latest_migration(pool, limit).await,
+    }
+}
+
+/// Dernière migration appliquée avec succès (`None` si la base ne répond pas).
+pub async fn latest_migration(pool: &PgPool, limit: Duration) -> Option<MigrationVersion> {
+    within(
+        limit,
+        sqlx::query_as::<_, MigrationVersion>(
+            r#"
+            SELECT version, description FROM _sqlx_migrations
+            WHERE success ORDER BY version DESC LIMIT 1
+            "#,
+        )
+        .fetch_optional(pool),
+    )
+    .await
+    .ok()
+    .flatten()
+}
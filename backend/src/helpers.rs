@@ -1,6 +1,12 @@
-use axum::http::StatusCode;
+use axum::extract::{ConnectInfo, FromRef, FromRequestParts};
+use axum::http::{StatusCode, header, request::Parts};
+use std::convert::Infallible;
+use std::net::SocketAddr;
 use uuid::Uuid;
 
+use crate::config::AppConfig;
+use crate::rate_limit::client_ip;
+
 pub type ApiResult<T> = Result<T, (StatusCode, String)>;
 
 // a modif pour enlever mais por l'instant ca fonctionne avec ca
@@ -71,12 +77,30 @@ pub fn unauthorized(msg: impl Into<String>) -> (StatusCode, String) {
     (StatusCode::UNAUTHORIZED, strip_emoji_prefix(msg))
 }
 
+pub fn forbidden(msg: impl Into<String>) -> (StatusCode, String) {
+    let msg = msg.into();
+    log_warn(&msg);
+    (StatusCode::FORBIDDEN, strip_emoji_prefix(msg))
+}
+
 pub fn conflict(msg: impl Into<String>) -> (StatusCode, String) {
     let msg = msg.into();
     log_warn(&msg);
     (StatusCode::CONFLICT, strip_emoji_prefix(msg))
 }
 
+pub fn locked(msg: impl Into<String>) -> (StatusCode, String) {
+    let msg = msg.into();
+    log_warn(&msg);
+    (StatusCode::LOCKED, strip_emoji_prefix(msg))
+}
+
+pub fn too_many_requests(msg: impl Into<String>) -> (StatusCode, String) {
+    let msg = msg.into();
+    log_warn(&msg);
+    (StatusCode::TOO_MANY_REQUESTS, strip_emoji_prefix(msg))
+}
+
 pub fn internal_server_error(msg: impl Into<String>) -> (StatusCode, String) {
     let msg = msg.into();
     log_error(&msg);
@@ -98,3 +122,33 @@ pub async fn clear_user_mw(
     set_current_user(None);
     next.run(req).await
 }
+
+/// Informations sur le client (IP, user-agent) pour les sessions et les logs.
+/// L'IP tient compte de X-Forwarded-For derrière un proxy de confiance (TRUSTED_PROXIES).
+#[derive(Clone, Debug, Default)]
+pub struct ClientInfo {
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+impl<S> FromRequestParts<S> for ClientInfo
+where
+    &'static AppConfig: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let trusted = <&'static AppConfig>::from_ref(state).server.trusted_nets();
+        let ip = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| client_ip(addr.ip(), &parts.headers, &trusted).to_string());
+        let user_agent = parts
+            .headers
+            .get(header::USER_AGENT)
+            .and_then(|v| v.to_str().ok())
+            .map(|s| s.to_string());
+        Ok(ClientInfo { ip, user_agent })
+    }
+}
@@ -0,0 +1,116 @@
+//! Cache HTTP des lectures du catalogue (ETag / Last-Modified).
+//!
+//! Une réponse du catalogue ne dépend que de la version du catalogue (table
+//! `catalog_version`, incrémentée par trigger au seed ou à une édition admin) et
+//! de la version de la collection de l'utilisateur (`users.collection_version`,
+//! incrémentée à chaque capture). Les deux compteurs sont lus avec la collection
+//! (`catalog::collection`): si le client a déjà la représentation courante
+//! (`If-None-Match`, à défaut `If-Modified-Since`), il reçoit un 304 sans corps.
+
+use axum::http::{HeaderMap, HeaderValue, StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use sha2::{Digest, Sha256};
+use std::sync::LazyLock;
+use time::format_description::BorrowedFormatItem;
+use time::{OffsetDateTime, PrimitiveDateTime};
+use uuid::Uuid;
+
+/// Réponse propre à l'utilisateur: le navigateur peut la garder mais doit la
+/// revalider à chaque usage; aucun cache partagé ne la stocke.
+pub const CACHE_CONTROL: &str = "private, no-cache";
+
+// Même URL, réponse différente selon l'utilisateur authentifié
+const VARY: &str = "authorization, cookie";
+
+// Date HTTP (RFC 9110, IMF-fixdate), toujours en GMT
+static HTTP_DATE: LazyLock<Vec<BorrowedFormatItem<'static>>> = LazyLock::new(|| {
+    time::format_description::parse(
+        "[weekday repr:short], [day] [month repr:short] [year] [hour]:[minute]:[second] GMT",
+    )
+    .expect("format de date HTTP")
+});
+
+/// Formate une date au format HTTP (`Sun, 06 Nov 1994 08:49:37 GMT`).
+pub fn http_date(at: OffsetDateTime) -> String {
+    at.to_offset(time::UtcOffset::UTC)
+        .format(&HTTP_DATE)
+        .unwrap_or_default()
+}
+
+/// Lit une date HTTP; `None` si elle n'est pas au format IMF-fixdate.
+pub fn parse_http_date(value: &str) -> Option<OffsetDateTime> {
+    PrimitiveDateTime::parse(value.trim(), &HTTP_DATE)
+        .ok()
+        .map(PrimitiveDateTime::assume_utc)
+}
+
+/// Validateurs d'une lecture du catalogue pour un utilisateur.
+#[derive(Debug, Clone)]
+pub struct Validators {
+    etag: String,
+    last_modified: OffsetDateTime,
+}
+
+impl Validators {
+    /// ETag faible: la même donnée peut être servie compressée ou non. L'utilisateur
+    /// en fait partie pour qu'un autre compte sur le même navigateur n'obtienne
+    /// jamais de 304 sur la représentation du précédent.
+    pub fn new(
+        user_id: Uuid,
+        catalog_version: i64,
+        collection_version: i64,
+        last_modified: OffsetDateTime,
+    ) -> Self {
+        let digest =
+            Sha256::digest(format!("{user_id}:{catalog_version}:{collection_version}").as_bytes());
+        let hash = format!("{digest:x}");
+        Self {
+            etag: format!("W/\"{}\"", &hash[..32]),
+            last_modified: last_modified.replace_nanosecond(0).unwrap_or(last_modified),
+        }
+    }
+
+    /// Vrai si le client a déjà la représentation courante. `If-None-Match`
+    /// prime sur `If-Modified-Since` (RFC 9110 §13.2.2); comparaison faible.
+    pub fn is_fresh(&self, headers: &HeaderMap) -> bool {
+        if let Some(value) = headers.get(header::IF_NONE_MATCH) {
+            let Ok(value) = value.to_str() else {
+                return false;
+            };
+            return value.split(',').map(str::trim).any(|tag| {
+                tag == "*" || tag.trim_start_matches("W/") == self.etag.trim_start_matches("W/")
+            });
+        }
+        headers
+            .get(header::IF_MODIFIED_SINCE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(parse_http_date)
+            .is_some_and(|since| self.last_modified <= since)
+    }
+
+    /// 304 sans corps, avec les mêmes validateurs qu'une réponse 200.
+    pub fn not_modified(&self) -> Response {
+        self.apply(StatusCode::NOT_MODIFIED.into_response())
+    }
+
+    /// Réponse complète avec `ETag`, `Last-Modified`, `Cache-Control` et `Vary`.
+    pub fn respond(&self, body: impl IntoResponse) -> Response {
+        self.apply(body.into_response())
+    }
+
+    fn apply(&self, mut res: Response) -> Response {
+        let headers = res.headers_mut();
+        if let Ok(etag) = HeaderValue::from_str(&self.etag) {
+            headers.insert(header::ETAG, etag);
+        }
+        if let Ok(date) = HeaderValue::from_str(&http_date(self.last_modified)) {
+            headers.insert(header::LAST_MODIFIED, date);
+        }
+        headers.insert(
+            header::CACHE_CONTROL,
+            HeaderValue::from_static(CACHE_CONTROL),
+        );
+        headers.insert(header::VARY, HeaderValue::from_static(VARY));
+        res
+    }
+}
@@ -0,0 +1,446 @@
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use ed25519_dalek::SigningKey;
+use ed25519_dalek::pkcs8::DecodePrivateKey;
+use jsonwebtoken::errors::{Error as JwtError, ErrorKind};
+use jsonwebtoken::jwk::{AlgorithmParameters, Jwk, JwkSet};
+use jsonwebtoken::{
+    Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, decode_header, encode,
+};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+/// Clés de signature: algorithme et fichiers de clés (section `[jwt]` ou
+/// JWT_*), secrets lus dans l'environnement seulement (`AppConfig::apply_env`).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KeyConfig {
+    /// JWT_ALGORITHM: HS256 (défaut), RS256 ou EdDSA
+    pub algorithm: String,
+    /// JWT_SECRET: clé HS256 des access tokens et racine des clés dérivées
+    #[serde(skip)]
+    pub secret: Option<String>,
+    /// JWT_PREVIOUS_SECRETS: anciennes clés HS256 encore acceptées
+    #[serde(skip)]
+    pub previous_secrets: Vec<String>,
+    /// JWT_REFRESH_SECRET: clé des refresh tokens (défaut: dérivée de JWT_SECRET)
+    #[serde(skip)]
+    pub refresh_secret: Option<String>,
+    /// JWT_PREVIOUS_REFRESH_SECRETS: anciennes clés des refresh tokens
+    #[serde(skip)]
+    pub previous_refresh_secrets: Vec<String>,
+    /// JWT_PRIVATE_KEY_FILE: clé privée PEM (RS256 ou EdDSA)
+    pub private_key_file: Option<PathBuf>,
+    /// JWT_PREVIOUS_JWKS_FILE: clés publiques retirées mais encore acceptées
+    pub previous_jwks_file: Option<PathBuf>,
+    /// Secrets dédiés définis explicitement (EMAIL_VERIFY_SECRET, …)
+    #[serde(skip)]
+    pub scoped_secrets: Vec<(String, String)>,
+    /// Recopié de `server.production`
+    #[serde(skip)]
+    pub production: bool,
+    /// Clés chargées au premier usage (`KeyConfig::loaded`)
+    #[serde(skip)]
+    pub loaded: LoadedKeys,
+}
+
+impl Default for KeyConfig {
+    fn default() -> Self {
+        KeyConfig {
+            algorithm: "HS256".into(),
+            secret: None,
+            previous_secrets: Vec::new(),
+            refresh_secret: None,
+            previous_refresh_secrets: Vec::new(),
+            private_key_file: None,
+            previous_jwks_file: None,
+            scoped_secrets: Vec::new(),
+            production: false,
+            loaded: LoadedKeys::default(),
+        }
+    }
+}
+
+/// Algorithmes de signature des access tokens.
+pub const ALGORITHMS: [&str; 3] = ["HS256", "RS256", "EdDSA"];
+
+/// Secrets dédiés dont la valeur par défaut est dérivée de JWT_SECRET (voir `auth::scoped_secret`).
+pub const SCOPED_SECRET_VARS: [&str; 5] = [
+    "EMAIL_VERIFY_SECRET",
+    "UNLOCK_SECRET",
+    "MFA_SECRET",
+    "OIDC_STATE_SECRET",
+    "CSRF_SECRET",
+];
+
+impl KeyConfig {
+    /// Valeur explicite d'un secret dédié (`None`: dérivé de JWT_SECRET).
+    pub fn scoped_secret(&self, var: &str) -> Option<&str> {
+        self.scoped_secrets
+            .iter()
+            .find(|(name, _)| name == var)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Clés de cette configuration, chargées au premier appel (`init` les a
+    /// contrôlées au démarrage du serveur).
+    pub fn loaded(&self) -> &Keys {
+        self.loaded
+            .0
+            .get_or_init(|| Keys::load(self).unwrap_or_else(|e| panic!("Clés JWT: {e}")))
+    }
+}
+
+/// Clés d'une configuration: access tokens, refresh tokens et racine des clés dérivées.
+pub struct Keys {
+    pub access: KeySet,
+    pub refresh: KeySet,
+    /// JWT_SECRET: racine des clés HMAC dérivées (refresh, tokens à usage unique)
+    pub root: String,
+}
+
+impl Keys {
+    pub fn load(config: &KeyConfig) -> Result<Keys, KeyError> {
+        let root = config
+            .secret
+            .clone()
+            .ok_or_else(|| KeyError("JWT_SECRET must be set".into()))?;
+        let refresh = config
+            .refresh_secret
+            .clone()
+            .unwrap_or_else(|| format!("{root}:refresh"));
+        Ok(Keys {
+            access: KeySet::load(config)?,
+            refresh: KeySet::hmac(&refresh, &config.previous_refresh_secrets),
+            root,
+        })
+    }
+}
+
+/// Cache des clés chargées. Une copie de la configuration repart d'un cache
+/// vide: ses secrets ont pu être modifiés.
+#[derive(Default)]
+pub struct LoadedKeys(OnceLock<Keys>);
+
+impl Clone for LoadedKeys {
+    fn clone(&self) -> Self {
+        LoadedKeys::default()
+    }
+}
+
+impl std::fmt::Debug for LoadedKeys {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = if self.0.get().is_some() {
+            "chargées"
+        } else {
+            "non chargées"
+        };
+        write!(f, "LoadedKeys({state})")
+    }
+}
+
+#[derive(Debug)]
+pub struct KeyError(pub String);
+
+impl std::fmt::Display for KeyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+struct VerifyKey {
+    kid: String,
+    alg: Algorithm,
+    key: DecodingKey,
+}
+
+/// Clés d'un type de token: une clé de signature (annoncée par `kid`) et toutes
+/// les clés encore acceptées, pour une rotation sans déconnexion.
+pub struct KeySet {
+    alg: Algorithm,
+    kid: String,
+    encoding: EncodingKey,
+    verify: Vec<VerifyKey>,
+    // Clés publiques publiées par le JWKS (jamais de clé HMAC)
+    public: Vec<Jwk>,
+}
+
+// Empreinte RFC 7638: SHA-256 des membres requis, en JSON compact trié
+fn thumbprint(canonical: String) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(canonical.as_bytes()))
+}
+
+fn hmac_kid(secret: &str) -> String {
+    let k = URL_SAFE_NO_PAD.encode(secret.as_bytes());
+    thumbprint(format!(r#"{{"k":"{k}","kty":"oct"}}"#))
+}
+
+fn hmac_key(secret: &str) -> VerifyKey {
+    VerifyKey {
+        kid: hmac_kid(secret),
+        alg: Algorithm::HS256,
+        key: DecodingKey::from_secret(secret.as_bytes()),
+    }
+}
+
+fn public_jwk(value: serde_json::Value) -> Result<Jwk, KeyError> {
+    serde_json::from_value(value).map_err(|e| KeyError(format!("JWK invalide: {e}")))
+}
+
+fn rsa_key(pem: &str) -> Result<(EncodingKey, Jwk), KeyError> {
+    let invalid = |e: JwtError| KeyError(format!("clé RSA invalide: {e}"));
+    let encoding = EncodingKey::from_rsa_pem(pem.as_bytes()).map_err(invalid)?;
+    let derived = Jwk::from_encoding_key(&encoding, Algorithm::RS256).map_err(invalid)?;
+    let AlgorithmParameters::RSA(params) = derived.algorithm else {
+        return Err(KeyError("clé RSA invalide".into()));
+    };
+    let modulus = URL_SAFE_NO_PAD
+        .decode(&params.n)
+        .map_err(|e| KeyError(format!("clé RSA invalide: {e}")))?;
+    let bits = modulus.iter().skip_while(|b| **b == 0).count() * 8;
+    if bits < 2048 {
+        return Err(KeyError(format!(
+            "clé RSA trop courte ({bits} bits, 2048 minimum)"
+        )));
+    }
+    let (n, e) = (params.n, params.e);
+    let kid = thumbprint(format!(r#"{{"e":"{e}","kty":"RSA","n":"{n}"}}"#));
+    let jwk = public_jwk(serde_json::json!({
+        "kty": "RSA", "n": n, "e": e, "kid": kid, "alg": "RS256", "use": "sig"
+    }))?;
+    Ok((encoding, jwk))
+}
+
+fn ed25519_key(pem: &str) -> Result<(EncodingKey, Jwk), KeyError> {
+    let encoding = EncodingKey::from_ed_pem(pem.as_bytes())
+        .map_err(|e| KeyError(format!("clé Ed25519 invalide: {e}")))?;
+    let signing = SigningKey::from_pkcs8_pem(pem)
+        .map_err(|e| KeyError(format!("clé Ed25519 invalide: {e}")))?;
+    let x = URL_SAFE_NO_PAD.encode(signing.verifying_key().as_bytes());
+    let kid = thumbprint(format!(r#"{{"crv":"Ed25519","kty":"OKP","x":"{x}"}}"#));
+    let jwk = public_jwk(serde_json::json!({
+        "kty": "OKP", "crv": "Ed25519", "x": x, "kid": kid, "alg": "EdDSA", "use": "sig"
+    }))?;
+    Ok((encoding, jwk))
+}
+
+fn read_file(path: &PathBuf) -> Result<String, KeyError> {
+    std::fs::read_to_string(path).map_err(|e| KeyError(format!("{}: {e}", path.display())))
+}
+
+impl KeySet {
+    /// Clés HMAC: `secret` signe, `previous` restent acceptées.
+    pub fn hmac(secret: &str, previous: &[String]) -> KeySet {
+        KeySet {
+            alg: Algorithm::HS256,
+            kid: hmac_kid(secret),
+            encoding: EncodingKey::from_secret(secret.as_bytes()),
+            verify: std::iter::once(secret)
+                .chain(previous.iter().map(String::as_str))
+                .map(hmac_key)
+                .collect(),
+            public: Vec::new(),
+        }
+    }
+
+    /// Clés des access tokens selon `config`.
+    pub fn load(config: &KeyConfig) -> Result<KeySet, KeyError> {
+        let mut set = match config.algorithm.as_str() {
+            "HS256" => {
+                let secret = config
+                    .secret
+                    .as_deref()
+                    .ok_or_else(|| KeyError("JWT_SECRET must be set".into()))?;
+                KeySet::hmac(secret, &config.previous_secrets)
+            }
+            alg @ ("RS256" | "EdDSA") => {
+                let path = config
+                    .private_key_file
+                    .as_ref()
+                    .ok_or_else(|| KeyError(format!("JWT_PRIVATE_KEY_FILE requis pour {alg}")))?;
+                let pem = read_file(path)?;
+                let (encoding, jwk) = if alg == "RS256" {
+                    rsa_key(&pem)?
+                } else {
+                    ed25519_key(&pem)?
+                };
+                let alg = if alg == "RS256" {
+                    Algorithm::RS256
+                } else {
+                    Algorithm::EdDSA
+                };
+                let kid = jwk.common.key_id.clone().unwrap_or_default();
+                KeySet {
+                    alg,
+                    verify: std::iter::once(VerifyKey {
+                        kid: kid.clone(),
+                        alg,
+                        key: DecodingKey::from_jwk(&jwk)
+                            .map_err(|e| KeyError(format!("JWK invalide: {e}")))?,
+                    })
+                    // Anciennes clés HMAC: migration HS256 -> asymétrique sans déconnexion
+                    .chain(config.previous_secrets.iter().map(|s| hmac_key(s)))
+                    .collect(),
+                    kid,
+                    encoding,
+                    public: vec![jwk],
+                }
+            }
+            other => {
+                return Err(KeyError(format!(
+                    "JWT_ALGORITHM inconnu: {other} (HS256, RS256 ou EdDSA)"
+                )));
+            }
+        };
+
+        if let Some(path) = &config.previous_jwks_file {
+            let previous: JwkSet = serde_json::from_str(&read_file(path)?)
+                .map_err(|e| KeyError(format!("{}: {e}", path.display())))?;
+            for jwk in previous.keys {
+                set.accept_public(jwk)?;
+            }
+        }
+        Ok(set)
+    }
+
+    // Clé publique retirée: encore acceptée et publiée jusqu'à l'expiration des tokens signés
+    fn accept_public(&mut self, jwk: Jwk) -> Result<(), KeyError> {
+        if matches!(jwk.algorithm, AlgorithmParameters::OctetKey(_)) {
+            return Err(KeyError(
+                "JWT_PREVIOUS_JWKS_FILE ne doit contenir que des clés publiques".into(),
+            ));
+        }
+        let kid = jwk
+            .common
+            .key_id
+            .clone()
+            .ok_or_else(|| KeyError("clé sans kid dans JWT_PREVIOUS_JWKS_FILE".into()))?;
+        if self.verify.iter().any(|k| k.kid == kid) {
+            return Ok(());
+        }
+        let alg = jwk
+            .common
+            .key_algorithm
+            .and_then(|a| a.to_string().parse::<Algorithm>().ok())
+            .ok_or_else(|| KeyError(format!("clé {kid}: alg manquant ou non supporté")))?;
+        let key = DecodingKey::from_jwk(&jwk).map_err(|e| KeyError(format!("clé {kid}: {e}")))?;
+        self.verify.push(VerifyKey { kid, alg, key });
+        self.public.push(jwk);
+        Ok(())
+    }
+
+    pub fn kid(&self) -> &str {
+        &self.kid
+    }
+
+    pub fn algorithm(&self) -> Algorithm {
+        self.alg
+    }
+
+    /// Clés publiques à publier (vide en HS256).
+    pub fn jwks(&self) -> JwkSet {
+        JwkSet {
+            keys: self.public.clone(),
+        }
+    }
+
+    pub fn sign<T: Serialize>(&self, claims: &T) -> Result<String, JwtError> {
+        let mut header = Header::new(self.alg);
+        header.kid = Some(self.kid.clone());
+        encode(&header, claims, &self.encoding)
+    }
+
+    /// Vérifie avec la clé désignée par `kid`; sans `kid` (tokens antérieurs à la
+    /// rotation), avec la première clé de même algorithme.
+    pub fn verify<T: DeserializeOwned + Clone>(&self, token: &str) -> Result<T, JwtError> {
+        let header = decode_header(token)?;
+        let key = match &header.kid {
+            Some(kid) => self.verify.iter().find(|k| &k.kid == kid),
+            None => self.verify.iter().find(|k| k.alg == header.alg),
+        }
+        .ok_or_else(|| JwtError::from(ErrorKind::InvalidToken))?;
+        // L'algorithme vient de la clé, jamais de l'en-tête du token
+        let data = decode::<T>(token, &key.key, &Validation::new(key.alg))?;
+        Ok(data.claims)
+    }
+}
+
+const MIN_SECRET_LEN: usize = 32;
+
+fn is_weak(secret: &str) -> bool {
+    let lower = secret.to_lowercase();
+    let distinct = lower
+        .chars()
+        .collect::<std::collections::HashSet<_>>()
+        .len();
+    secret.len() < MIN_SECRET_LEN
+        || distinct < 8
+        || ["change_me", "changeme", "secret", "password", "example"]
+            .iter()
+            .any(|w| lower.contains(w))
+}
+
+/// Secrets faibles ou partagés entre usages.
+pub fn secret_problems(config: &KeyConfig) -> Vec<String> {
+    let mut named: Vec<(&str, &str)> = Vec::new();
+    if let Some(s) = &config.secret {
+        named.push(("JWT_SECRET", s));
+    }
+    if let Some(s) = &config.refresh_secret {
+        named.push(("JWT_REFRESH_SECRET", s));
+    }
+    named.extend(
+        config
+            .scoped_secrets
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str())),
+    );
+
+    let mut problems = Vec::new();
+    if config.refresh_secret.is_none() {
+        problems.push("JWT_REFRESH_SECRET absent (dérivé de JWT_SECRET)".to_string());
+    }
+    for (name, value) in &named {
+        if is_weak(value) {
+            problems.push(format!(
+                "{name} trop faible ({MIN_SECRET_LEN} caractères aléatoires minimum)"
+            ));
+        }
+    }
+    for (i, (a, va)) in named.iter().enumerate() {
+        for (b, vb) in &named[i + 1..] {
+            if va == vb {
+                problems.push(format!("{a} et {b} sont identiques"));
+            }
+        }
+    }
+    problems
+}
+
+/// Contrôle de démarrage: en production, secrets faibles ou partagés refusés;
+/// ailleurs, renvoyés comme avertissements. Les clés doivent se charger dans tous les cas.
+pub fn check(config: &KeyConfig) -> Result<Vec<String>, KeyError> {
+    if config.secret.is_none() {
+        return Err(KeyError(
+            "JWT_SECRET must be set (racine des clés dérivées)".into(),
+        ));
+    }
+    let problems = secret_problems(config);
+    if config.production && !problems.is_empty() {
+        return Err(KeyError(problems.join("; ")));
+    }
+    KeySet::load(config)?;
+    Ok(problems)
+}
+
+/// À appeler au démarrage du serveur, avant d'accepter des connexions.
+pub fn init(config: &KeyConfig) -> Result<(), KeyError> {
+    for warning in check(config)? {
+        eprintln!("⚠️ {warning}");
+    }
+    config.loaded();
+    Ok(())
+}
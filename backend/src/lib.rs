@@ -1,7 +1,32 @@
+pub mod account_deletion;
+pub mod api_tokens;
 pub mod app;
+pub mod audit;
 pub mod auth;
+pub mod catalog;
+pub mod clock;
+pub mod config;
+pub mod csrf;
+pub mod daily;
 pub mod db;
 pub mod handlers;
+pub mod health;
 pub mod helpers;
+pub mod http_cache;
+pub mod keys;
+pub mod login_guard;
+pub mod mailer;
+pub mod metrics;
+pub mod mfa;
 pub mod models;
+pub mod oidc;
+pub mod openapi;
+pub mod password_reset;
+pub mod rate_limit;
+pub mod rng;
+pub mod roles;
 pub mod routes;
+pub mod sessions;
+pub mod state;
+pub mod validation;
+pub mod versioning;
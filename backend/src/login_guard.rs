@@ -0,0 +1,353 @@
+use serde_json::json;
+use sqlx::PgPool;
+use std::sync::LazyLock;
+use std::time::Duration;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::audit::{self, Event};
+use crate::auth::{hash_password, verify_password};
+use crate::clock::Clock;
+use crate::config::LoginConfig;
+use crate::helpers::ClientInfo;
+use crate::metrics;
+
+const BACKOFF_MAX_SECS: i64 = 60;
+
+/// Taille des colonnes `login` (VARCHAR(255)) du journal et des compteurs.
+pub const MAX_LOGIN_CHARS: usize = 255;
+
+/// Identifiant qu'aucun compte ne peut porter: refusé avant toute écriture
+/// (il dépasserait les colonnes `login`).
+pub fn login_too_long(login: &str) -> bool {
+    login.chars().count() > MAX_LOGIN_CHARS
+}
+
+/// Délai imposé après `failures` échecs: 1s, 2s, 4s… à partir du seuil, plafonné à 60s.
+fn backoff_secs(failures: i64, after: i64) -> i64 {
+    if failures < after {
+        return 0;
+    }
+    (1_i64 << (failures - after).min(6)).min(BACKOFF_MAX_SECS)
+}
+
+fn remaining(delay: i64, elapsed: f64) -> Option<i64> {
+    let left = delay as f64 - elapsed;
+    (left > 0.0).then(|| left.ceil() as i64)
+}
+
+/// Raison pour laquelle une tentative est refusée avant même de vérifier le mot de passe.
+#[derive(Debug, PartialEq, Eq)]
+pub enum LoginBlock {
+    /// Trop d'échecs récents: réessayer après `retry_after` secondes
+    Throttled { retry_after: i64 },
+    /// Compte verrouillé jusqu'à expiration ou déverrouillage par email
+    Locked { retry_after: i64 },
+}
+
+impl LoginBlock {
+    /// Raison journalisée dans `login_attempts`.
+    pub fn reason(&self) -> &'static str {
+        match self {
+            LoginBlock::Locked { .. } => "locked",
+            LoginBlock::Throttled { .. } => "throttled",
+        }
+    }
+}
+
+// Hash calculé une fois: un identifiant inconnu coûte une vérification Argon2
+// comme un compte existant (pas de fuite d'existence par le temps de réponse).
+static DUMMY_HASH: LazyLock<String> =
+    LazyLock::new(|| hash_password("pokercnp-dummy-password").unwrap_or_default());
+
+pub fn dummy_verify(password: &str) {
+    let _ = verify_password(&DUMMY_HASH, password);
+}
+
+/// Backoff exponentiel par IP, d'après les échecs de la fenêtre LOGIN_IP_WINDOW_SECONDS.
+pub async fn check_ip(
+    pool: &PgPool,
+    clock: &dyn Clock,
+    login: &LoginConfig,
+    ip: Option<&str>,
+) -> Result<Option<LoginBlock>, sqlx::Error> {
+    let Some(ip) = ip else {
+        return Ok(None);
+    };
+    let (failures, since_last) = sqlx::query_as::<_, (i64, Option<f64>)>(
+        r#"
+        SELECT COUNT(*), EXTRACT(EPOCH FROM $3 - MAX(created_at))::FLOAT8
+        FROM login_attempts
+        WHERE ip = $1 AND reason IN ('invalid_password', 'invalid_mfa_code', 'unknown_account')
+          AND created_at > $3 - make_interval(secs => $2)
+        "#,
+    )
+    .bind(ip)
+    .bind(login.ip_window_secs as f64)
+    .bind(clock.now())
+    .fetch_one(pool)
+    .await?;
+
+    let delay = backoff_secs(failures, login.ip_backoff_after);
+    Ok(remaining(delay, since_last.unwrap_or(f64::MAX))
+        .map(|retry_after| LoginBlock::Throttled { retry_after }))
+}
+
+/// Verrouillage en cours ou backoff exponentiel sur les échecs consécutifs du compte.
+pub async fn check_account(
+    pool: &PgPool,
+    clock: &dyn Clock,
+    config: &LoginConfig,
+    user_id: Uuid,
+) -> Result<Option<LoginBlock>, sqlx::Error> {
+    let state = sqlx::query_as::<_, FailureState>(
+        r#"
+        SELECT failed_login_count,
+               EXTRACT(EPOCH FROM locked_until - $2)::FLOAT8,
+               EXTRACT(EPOCH FROM $2 - last_failed_login_at)::FLOAT8
+        FROM users WHERE id = $1
+        "#,
+    )
+    .bind(user_id)
+    .bind(clock.now())
+    .fetch_one(pool)
+    .await?;
+    Ok(block_for(state, config.backoff_after))
+}
+
+/// Même règle pour un identifiant sans compte, suivi par chaîne soumise:
+/// 429 puis 423 arrivent après le même nombre d'échecs qu'un compte réel.
+pub async fn check_unknown(
+    pool: &PgPool,
+    clock: &dyn Clock,
+    config: &LoginConfig,
+    login: &str,
+) -> Result<Option<LoginBlock>, sqlx::Error> {
+    let state = sqlx::query_as::<_, FailureState>(
+        r#"
+        SELECT failed_login_count,
+               EXTRACT(EPOCH FROM locked_until - $2)::FLOAT8,
+               EXTRACT(EPOCH FROM $2 - last_failed_login_at)::FLOAT8
+        FROM unknown_login_failures WHERE login = LOWER($1)
+        "#,
+    )
+    .bind(login)
+    .bind(clock.now())
+    .fetch_optional(pool)
+    .await?;
+    Ok(state.and_then(|state| block_for(state, config.backoff_after)))
+}
+
+/// (échecs consécutifs, secondes de verrouillage restantes, secondes depuis le dernier échec)
+type FailureState = (i32, Option<f64>, Option<f64>);
+
+fn block_for(
+    (failures, locked_left, since_last): FailureState,
+    backoff_after: i64,
+) -> Option<LoginBlock> {
+    if let Some(left) = locked_left {
+        if left > 0.0 {
+            return Some(LoginBlock::Locked {
+                retry_after: left.ceil() as i64,
+            });
+        }
+        // Verrou expiré: le compteur repartira de zéro au prochain échec
+        return None;
+    }
+
+    let delay = backoff_secs(failures as i64, backoff_after);
+    remaining(delay, since_last.unwrap_or(f64::MAX))
+        .map(|retry_after| LoginBlock::Throttled { retry_after })
+}
+
+/// Journalise une tentative (table `login_attempts` et journal d'audit).
+pub async fn log_attempt(
+    pool: &PgPool,
+    clock: &dyn Clock,
+    user_id: Option<Uuid>,
+    login: &str,
+    client: &ClientInfo,
+    reason: &str,
+) -> Result<(), sqlx::Error> {
+    metrics::record_login(reason);
+    sqlx::query(
+        r#"
+        INSERT INTO login_attempts (user_id, login, ip, user_agent, success, reason, created_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        "#,
+    )
+    .bind(user_id)
+    .bind(login)
+    .bind(&client.ip)
+    .bind(&client.user_agent)
+    .bind(reason == "success")
+    .bind(reason)
+    .bind(clock.now())
+    .execute(pool)
+    .await?;
+
+    let event = match reason {
+        "success" => Event::new(audit::LOGIN),
+        "mfa_required" => Event::new(audit::LOGIN_MFA_REQUIRED),
+        _ => Event::new(audit::LOGIN).failed(),
+    };
+    // Échec: l'auteur n'est pas authentifié, seul le compte visé est connu
+    let event = Event {
+        actor: user_id.filter(|_| reason == "success"),
+        target: user_id,
+        ..event.meta(json!({ "login": login, "reason": reason }))
+    };
+    audit::log(pool, client, event).await;
+    Ok(())
+}
+
+/// Enregistre un échec (`reason`: mauvais mot de passe ou mauvais code 2FA);
+/// renvoie la date de fin de verrouillage si cet échec vient de verrouiller le compte.
+pub async fn record_failure(
+    pool: &PgPool,
+    clock: &dyn Clock,
+    config: &LoginConfig,
+    user_id: Uuid,
+    login: &str,
+    client: &ClientInfo,
+    reason: &str,
+) -> Result<Option<OffsetDateTime>, sqlx::Error> {
+    log_attempt(pool, clock, Some(user_id), login, client, reason).await?;
+
+    let (failures, locked_until) = sqlx::query_as::<_, (i32, Option<OffsetDateTime>)>(
+        r#"
+        WITH next AS (
+            SELECT id,
+                   CASE WHEN locked_until IS NOT NULL AND locked_until <= $4 THEN 1
+                        ELSE failed_login_count + 1 END AS failures
+            FROM users WHERE id = $1
+            FOR UPDATE
+        )
+        UPDATE users u SET
+            failed_login_count = next.failures,
+            last_failed_login_at = $4,
+            locked_until = CASE WHEN next.failures >= $2
+                                THEN $4 + make_interval(secs => $3) END
+        FROM next WHERE u.id = next.id
+        RETURNING u.failed_login_count, u.locked_until
+        "#,
+    )
+    .bind(user_id)
+    .bind(config.max_failures as i32)
+    .bind(config.lockout_secs as f64)
+    .bind(clock.now())
+    .fetch_one(pool)
+    .await?;
+
+    Ok(locked_until.filter(|_| failures as i64 == config.max_failures))
+}
+
+/// Échec sur un identifiant sans compte: compteur tenu comme celui d'un compte,
+/// renvoie la date de fin de verrouillage si cet échec vient de le poser.
+pub async fn record_unknown_failure(
+    pool: &PgPool,
+    clock: &dyn Clock,
+    config: &LoginConfig,
+    login: &str,
+    client: &ClientInfo,
+) -> Result<Option<OffsetDateTime>, sqlx::Error> {
+    log_attempt(pool, clock, None, login, client, "unknown_account").await?;
+
+    sqlx::query(
+        "INSERT INTO unknown_login_failures (login) VALUES (LOWER($1)) ON CONFLICT DO NOTHING",
+    )
+    .bind(login)
+    .execute(pool)
+    .await?;
+    // Même calcul que `record_failure` sur la table des comptes
+    let (failures, locked_until) = sqlx::query_as::<_, (i32, Option<OffsetDateTime>)>(
+        r#"
+        WITH next AS (
+            SELECT login,
+                   CASE WHEN locked_until IS NOT NULL AND locked_until <= $4 THEN 1
+                        ELSE failed_login_count + 1 END AS failures
+            FROM unknown_login_failures WHERE login = LOWER($1)
+            FOR UPDATE
+        )
+        UPDATE unknown_login_failures f SET
+            failed_login_count = next.failures,
+            last_failed_login_at = $4,
+            locked_until = CASE WHEN next.failures >= $2
+                                THEN $4 + make_interval(secs => $3) END
+        FROM next WHERE f.login = next.login
+        RETURNING f.failed_login_count, f.locked_until
+        "#,
+    )
+    .bind(login)
+    .bind(config.max_failures as i32)
+    .bind(config.lockout_secs as f64)
+    .bind(clock.now())
+    .fetch_one(pool)
+    .await?;
+
+    Ok(locked_until.filter(|_| failures as i64 == config.max_failures))
+}
+
+/// Connexion réussie: remise à zéro du compteur d'échecs.
+pub async fn record_success(
+    pool: &PgPool,
+    clock: &dyn Clock,
+    user_id: Uuid,
+    login: &str,
+    client: &ClientInfo,
+) -> Result<(), sqlx::Error> {
+    log_attempt(pool, clock, Some(user_id), login, client, "success").await?;
+    reset(pool, user_id).await
+}
+
+/// Lève le verrouillage et remet le compteur à zéro (déverrouillage, reset du mot de passe).
+pub async fn reset(pool: &PgPool, user_id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE users SET failed_login_count = 0, last_failed_login_at = NULL, locked_until = NULL
+        WHERE id = $1
+        "#,
+    )
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Supprime les tentatives et les compteurs d'identifiants inconnus plus
+/// anciens que LOGIN_ATTEMPTS_RETENTION_DAYS.
+pub async fn prune(pool: &PgPool, older_than_days: i64) -> Result<u64, sqlx::Error> {
+    let attempts = sqlx::query(
+        "DELETE FROM login_attempts WHERE created_at < NOW() - make_interval(days => $1)",
+    )
+    .bind(older_than_days as i32)
+    .execute(pool)
+    .await?
+    .rows_affected();
+    sqlx::query(
+        r#"
+        DELETE FROM unknown_login_failures
+        WHERE last_failed_login_at < NOW() - make_interval(days => $1)
+          AND (locked_until IS NULL OR locked_until <= NOW())
+        "#,
+    )
+    .bind(older_than_days as i32)
+    .execute(pool)
+    .await?;
+    Ok(attempts)
+}
+
+/// Purge quotidienne du journal des tentatives de connexion (LOGIN_ATTEMPTS_RETENTION_DAYS).
+pub fn spawn_retention(pool: PgPool, days: i64) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(24 * 3600));
+        loop {
+            interval.tick().await;
+            match prune(&pool, days).await {
+                Ok(0) => {}
+                Ok(n) => println!("🧹 {n} tentatives de connexion de plus de {days} jours purgées"),
+                Err(e) => eprintln!("❌ Purge des tentatives de connexion: {e}"),
+            }
+        }
+    });
+}
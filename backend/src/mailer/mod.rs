@@ -0,0 +1,48 @@
+pub mod outbox;
+pub mod queue;
+pub mod smtp;
+pub mod templates;
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use crate::config::{MailConfig, MailTransport};
+
+pub use queue::MailQueue;
+pub use templates::Lang;
+
+/// Email prêt à être envoyé (texte brut).
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct Email {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+#[derive(Debug)]
+pub struct MailError(pub String);
+
+impl std::fmt::Display for MailError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+pub type MailFuture<'a> = Pin<Box<dyn Future<Output = Result<(), MailError>> + Send + 'a>>;
+
+/// Transport d'emails (SMTP en prod, outbox fichier/stdout en dev et tests).
+pub trait Mailer: Send + Sync {
+    fn send<'a>(&'a self, email: &'a Email) -> MailFuture<'a>;
+}
+
+/// Construit le transport configuré (MAIL_TRANSPORT: "smtp" ou "outbox", défaut "outbox").
+pub fn from_config(config: &MailConfig) -> Arc<dyn Mailer> {
+    match config.transport {
+        MailTransport::Smtp => match smtp::SmtpMailer::from_config(&config.smtp) {
+            Ok(m) => Arc::new(m),
+            Err(e) => panic!("Configuration SMTP invalide: {e}"),
+        },
+        MailTransport::Outbox => Arc::new(outbox::OutboxMailer::from_config(config)),
+    }
+}
@@ -0,0 +1,50 @@
+use std::path::PathBuf;
+
+use super::{Email, MailError, MailFuture, Mailer};
+use crate::config::MailConfig;
+
+/// Outbox de dev/test: chaque email est écrit en JSON dans MAIL_OUTBOX_DIR
+/// (défaut "tmp/outbox"), ou affiché sur stdout si MAIL_OUTBOX_DIR="-".
+pub struct OutboxMailer {
+    dir: Option<PathBuf>,
+}
+
+impl OutboxMailer {
+    pub fn new(dir: Option<PathBuf>) -> Self {
+        OutboxMailer { dir }
+    }
+
+    pub fn from_config(config: &MailConfig) -> Self {
+        match config.outbox_dir.as_str() {
+            "-" => OutboxMailer::new(None),
+            dir => OutboxMailer::new(Some(PathBuf::from(dir))),
+        }
+    }
+}
+
+impl Mailer for OutboxMailer {
+    fn send<'a>(&'a self, email: &'a Email) -> MailFuture<'a> {
+        Box::pin(async move {
+            let Some(dir) = &self.dir else {
+                println!(
+                    "📧 À: {}\n   Sujet: {}\n{}",
+                    email.to, email.subject, email.body
+                );
+                return Ok(());
+            };
+
+            tokio::fs::create_dir_all(dir)
+                .await
+                .map_err(|e| MailError(format!("outbox: {e}")))?;
+            let name = format!(
+                "{}-{}.json",
+                time::OffsetDateTime::now_utc().unix_timestamp_nanos(),
+                uuid::Uuid::new_v4().simple()
+            );
+            let json = serde_json::to_vec_pretty(email).map_err(|e| MailError(e.to_string()))?;
+            tokio::fs::write(dir.join(name), json)
+                .await
+                .map_err(|e| MailError(format!("outbox: {e}")))
+        })
+    }
+}
@@ -0,0 +1,66 @@
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+use super::{Email, Mailer};
+
+/// File d'envoi en arrière-plan: les handlers n'attendent pas le serveur SMTP,
+/// les échecs sont retentés avec backoff exponentiel (MAIL_MAX_ATTEMPTS, défaut 5).
+/// Le worker démarre au premier email: une file remplacée avant usage
+/// (`AppState::with_mailer`) n'en lance aucun.
+#[derive(Clone)]
+pub struct MailQueue {
+    mailer: Arc<dyn Mailer>,
+    max_attempts: u32,
+    tx: Arc<OnceLock<mpsc::UnboundedSender<Email>>>,
+}
+
+impl MailQueue {
+    pub fn new(mailer: Arc<dyn Mailer>, max_attempts: u32) -> Self {
+        MailQueue {
+            mailer,
+            max_attempts: max_attempts.max(1),
+            tx: Arc::default(),
+        }
+    }
+
+    // Worker d'envoi (appelé dans un runtime Tokio, depuis un handler)
+    fn start(&self) -> mpsc::UnboundedSender<Email> {
+        let (tx, mut rx) = mpsc::unbounded_channel::<Email>();
+        let mailer = self.mailer.clone();
+        let max_attempts = self.max_attempts;
+
+        tokio::spawn(async move {
+            while let Some(email) = rx.recv().await {
+                let mailer = mailer.clone();
+                tokio::spawn(async move { deliver(&*mailer, &email, max_attempts).await });
+            }
+        });
+
+        tx
+    }
+
+    pub fn enqueue(&self, email: Email) {
+        if self.tx.get_or_init(|| self.start()).send(email).is_err() {
+            eprintln!("❌ File d'envoi des emails arrêtée, email perdu");
+        }
+    }
+}
+
+async fn deliver(mailer: &dyn Mailer, email: &Email, max_attempts: u32) {
+    let mut backoff = 1u64;
+    for attempt in 1..=max_attempts {
+        match mailer.send(email).await {
+            Ok(()) => return,
+            Err(e) if attempt < max_attempts => {
+                eprintln!(
+                    "⏳ Envoi email à {} tentative {attempt}/{max_attempts} échouée: {e} (re-tentative dans {backoff}s)",
+                    email.to
+                );
+                tokio::time::sleep(Duration::from_secs(backoff)).await;
+                backoff = (backoff * 2).min(60);
+            }
+            Err(e) => eprintln!("❌ Envoi email à {} abandonné: {e}", email.to),
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+
+use super::{Email, MailError, MailFuture, Mailer};
+use crate::config::{SmtpConfig, SmtpTls};
+
+/// Envoi SMTP (section `[mail.smtp]` ou SMTP_HOST, SMTP_PORT, SMTP_USERNAME,
+/// SMTP_PASSWORD, SMTP_FROM et SMTP_TLS: "starttls" par défaut, "tls" ou "none").
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpMailer {
+    pub fn from_config(config: &SmtpConfig) -> Result<Self, MailError> {
+        let host = config
+            .host
+            .as_deref()
+            .ok_or_else(|| MailError("SMTP_HOST manquant".into()))?;
+        let from = config
+            .from
+            .as_deref()
+            .ok_or_else(|| MailError("SMTP_FROM manquant".into()))?
+            .parse::<Mailbox>()
+            .map_err(|e| MailError(format!("SMTP_FROM invalide: {e}")))?;
+
+        let mut builder = match config.tls {
+            SmtpTls::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host),
+            SmtpTls::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(host)
+                .map_err(|e| MailError(e.to_string()))?,
+            SmtpTls::Starttls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)
+                .map_err(|e| MailError(e.to_string()))?,
+        };
+        if let Some(port) = config.port {
+            builder = builder.port(port);
+        }
+        if let (Some(user), Some(pass)) = (&config.username, &config.password) {
+            builder = builder.credentials(Credentials::new(user.clone(), pass.clone()));
+        }
+
+        Ok(SmtpMailer {
+            transport: builder.build(),
+            from,
+        })
+    }
+}
+
+impl Mailer for SmtpMailer {
+    fn send<'a>(&'a self, email: &'a Email) -> MailFuture<'a> {
+        Box::pin(async move {
+            let to = email
+                .to
+                .parse::<Mailbox>()
+                .map_err(|e| MailError(format!("destinataire invalide: {e}")))?;
+            let message = Message::builder()
+                .from(self.from.clone())
+                .to(to)
+                .subject(&email.subject)
+                .body(email.body.clone())
+                .map_err(|e| MailError(e.to_string()))?;
+            self.transport
+                .send(message)
+                .await
+                .map(|_| ())
+                .map_err(|e| MailError(e.to_string()))
+        })
+    }
+}
@@ -0,0 +1,246 @@
+use axum::extract::FromRequestParts;
+use axum::http::{header, request::Parts};
+use std::convert::Infallible;
+
+use super::Email;
+use crate::config::AppConfig;
+
+/// Langue des emails, déduite de l'en-tête Accept-Language (français par défaut).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Lang {
+    #[default]
+    Fr,
+    En,
+}
+
+impl Lang {
+    pub fn from_accept_language(value: &str) -> Lang {
+        let first = value
+            .split(',')
+            .next()
+            .unwrap_or("")
+            .trim()
+            .to_ascii_lowercase();
+        if first.starts_with("en") {
+            Lang::En
+        } else {
+            Lang::Fr
+        }
+    }
+}
+
+impl<S> FromRequestParts<S> for Lang
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(parts
+            .headers
+            .get(header::ACCEPT_LANGUAGE)
+            .and_then(|v| v.to_str().ok())
+            .map(Lang::from_accept_language)
+            .unwrap_or_default())
+    }
+}
+
+/// Lien vers le frontend (APP_BASE_URL, sinon FRONTEND_ORIGIN).
+pub fn app_link(config: &AppConfig, path: &str) -> String {
+    let base = config.server.app_base_url();
+    format!("{}{}", base.trim_end_matches('/'), path)
+}
+
+pub fn password_reset(lang: Lang, to: &str, username: &str, link: &str, ttl_minutes: i64) -> Email {
+    let (subject, body) = match lang {
+        Lang::Fr => (
+            "Réinitialisation de ton mot de passe PokeRNCP".to_string(),
+            format!(
+                "Bonjour {username},\n\n\
+                 Une réinitialisation du mot de passe a été demandée pour ton compte.\n\
+                 Pour choisir un nouveau mot de passe, ouvre ce lien (valable {ttl_minutes} minutes):\n\n\
+                 {link}\n\n\
+                 Si tu n'es pas à l'origine de cette demande, ignore cet email."
+            ),
+        ),
+        Lang::En => (
+            "Reset your PokeRNCP password".to_string(),
+            format!(
+                "Hi {username},\n\n\
+                 A password reset was requested for your account.\n\
+                 To choose a new password, open this link (valid for {ttl_minutes} minutes):\n\n\
+                 {link}\n\n\
+                 If you did not request this, you can ignore this email."
+            ),
+        ),
+    };
+    Email {
+        to: to.to_string(),
+        subject,
+        body,
+    }
+}
+
+pub fn welcome(lang: Lang, to: &str, username: &str) -> Email {
+    let (subject, body) = match lang {
+        Lang::Fr => (
+            "Bienvenue sur PokeRNCP".to_string(),
+            format!(
+                "Bonjour {username},\n\n\
+                 Ton compte PokeRNCP est créé. Bonne chasse aux Pokémon !"
+            ),
+        ),
+        Lang::En => (
+            "Welcome to PokeRNCP".to_string(),
+            format!(
+                "Hi {username},\n\n\
+                 Your PokeRNCP account is ready. Happy Pokémon hunting!"
+            ),
+        ),
+    };
+    Email {
+        to: to.to_string(),
+        subject,
+        body,
+    }
+}
+
+pub fn password_changed(lang: Lang, to: &str, username: &str) -> Email {
+    let (subject, body) = match lang {
+        Lang::Fr => (
+            "Ton mot de passe PokeRNCP a été modifié".to_string(),
+            format!(
+                "Bonjour {username},\n\n\
+                 Le mot de passe de ton compte vient d'être modifié.\n\
+                 Si tu n'es pas à l'origine de ce changement, réinitialise ton mot de passe immédiatement."
+            ),
+        ),
+        Lang::En => (
+            "Your PokeRNCP password was changed".to_string(),
+            format!(
+                "Hi {username},\n\n\
+                 The password of your account was just changed.\n\
+                 If you did not make this change, reset your password immediately."
+            ),
+        ),
+    };
+    Email {
+        to: to.to_string(),
+        subject,
+        body,
+    }
+}
+
+pub fn verify_email(lang: Lang, to: &str, username: &str, link: &str, ttl_hours: i64) -> Email {
+    let (subject, body) = match lang {
+        Lang::Fr => (
+            "Confirme ton adresse email PokeRNCP".to_string(),
+            format!(
+                "Bonjour {username},\n\n\
+                 Pour confirmer cette adresse email, ouvre ce lien (valable {ttl_hours} heures):\n\n\
+                 {link}\n\n\
+                 Si tu n'as pas de compte PokeRNCP, ignore cet email."
+            ),
+        ),
+        Lang::En => (
+            "Confirm your PokeRNCP email address".to_string(),
+            format!(
+                "Hi {username},\n\n\
+                 To confirm this email address, open this link (valid for {ttl_hours} hours):\n\n\
+                 {link}\n\n\
+                 If you don't have a PokeRNCP account, you can ignore this email."
+            ),
+        ),
+    };
+    Email {
+        to: to.to_string(),
+        subject,
+        body,
+    }
+}
+
+/// Prévient l'ancienne adresse qu'un changement d'email a eu lieu.
+pub fn email_changed(lang: Lang, to: &str, username: &str, new_email: &str) -> Email {
+    let (subject, body) = match lang {
+        Lang::Fr => (
+            "L'adresse email de ton compte PokeRNCP a été modifiée".to_string(),
+            format!(
+                "Bonjour {username},\n\n\
+                 L'adresse email de ton compte a été remplacée par {new_email}.\n\
+                 Si tu n'es pas à l'origine de ce changement, réinitialise ton mot de passe immédiatement."
+            ),
+        ),
+        Lang::En => (
+            "Your PokeRNCP email address was changed".to_string(),
+            format!(
+                "Hi {username},\n\n\
+                 The email address of your account was changed to {new_email}.\n\
+                 If you did not make this change, reset your password immediately."
+            ),
+        ),
+    };
+    Email {
+        to: to.to_string(),
+        subject,
+        body,
+    }
+}
+
+/// Compte verrouillé après trop d'échecs de connexion, avec lien de déverrouillage.
+pub fn account_locked(lang: Lang, to: &str, username: &str, link: &str, minutes: i64) -> Email {
+    let (subject, body) = match lang {
+        Lang::Fr => (
+            "Ton compte PokeRNCP est temporairement verrouillé".to_string(),
+            format!(
+                "Bonjour {username},\n\n\
+                 Trop de tentatives de connexion ont échoué: ton compte est verrouillé pendant {minutes} minutes.\n\
+                 Si c'était toi, tu peux le déverrouiller dès maintenant avec ce lien:\n\n\
+                 {link}\n\n\
+                 Sinon, change ton mot de passe: quelqu'un essaie peut-être d'accéder à ton compte."
+            ),
+        ),
+        Lang::En => (
+            "Your PokeRNCP account is temporarily locked".to_string(),
+            format!(
+                "Hi {username},\n\n\
+                 Too many failed sign-in attempts: your account is locked for {minutes} minutes.\n\
+                 If it was you, you can unlock it right away with this link:\n\n\
+                 {link}\n\n\
+                 Otherwise, change your password: someone may be trying to access your account."
+            ),
+        ),
+    };
+    Email {
+        to: to.to_string(),
+        subject,
+        body,
+    }
+}
+
+pub fn account_deletion_scheduled(lang: Lang, to: &str, username: &str, purge_date: &str) -> Email {
+    let (subject, body) = match lang {
+        Lang::Fr => (
+            "Suppression de ton compte PokeRNCP".to_string(),
+            format!(
+                "Bonjour {username},\n\n\
+                 Ton compte a été désactivé à ta demande. Il sera supprimé définitivement le {purge_date}, \
+                 avec ta collection et tout ton historique.\n\
+                 Tu as changé d'avis ? Reconnecte-toi avant cette date pour annuler la suppression."
+            ),
+        ),
+        Lang::En => (
+            "Your PokeRNCP account deletion".to_string(),
+            format!(
+                "Hi {username},\n\n\
+                 Your account has been deactivated at your request. It will be permanently deleted on {purge_date}, \
+                 along with your collection and all your history.\n\
+                 Changed your mind? Sign in before that date to cancel the deletion."
+            ),
+        ),
+    };
+    Email {
+        to: to.to_string(),
+        subject,
+        body,
+    }
+}
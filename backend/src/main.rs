@@ -1,27 +1,126 @@
 use axum::Router;
 use axum::http::{HeaderValue, Method};
 use dotenvy::dotenv;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::Duration;
 use tokio::net::TcpListener;
 use tower_http::cors::CorsLayer;
 
-use pokedex_rncp_backend::{app, db::init_db, helpers};
+use pokedex_rncp_backend::config::{self, AppConfig};
+use pokedex_rncp_backend::state::AppState;
+use pokedex_rncp_backend::{
+    account_deletion, app, audit, db::init_db, helpers, keys, login_guard, password_reset,
+};
+
+// Arguments: --config <fichier> (sinon CONFIG_FILE), --print-config et --healthcheck
+struct Args {
+    config: Option<PathBuf>,
+    print_config: bool,
+    healthcheck: bool,
+}
+
+fn parse_args() -> Args {
+    let mut args = Args {
+        config: None,
+        print_config: false,
+        healthcheck: false,
+    };
+    let mut it = std::env::args().skip(1);
+    while let Some(arg) = it.next() {
+        match arg.as_str() {
+            "--config" => match it.next() {
+                Some(path) => args.config = Some(PathBuf::from(path)),
+                None => {
+                    eprintln!("❌ --config attend un chemin de fichier");
+                    std::process::exit(2);
+                }
+            },
+            "--print-config" => args.print_config = true,
+            "--healthcheck" => args.healthcheck = true,
+            other => {
+                eprintln!(
+                    "❌ Argument inconnu: {other} (attendus: --config <fichier>, --print-config, --healthcheck)"
+                );
+                std::process::exit(2);
+            }
+        }
+    }
+    args
+}
+
+// HEALTHCHECK Docker: interroge /readyz du serveur local (l'image n'a ni curl ni wget)
+async fn healthcheck(config: &AppConfig) -> i32 {
+    let port = config
+        .server
+        .bind_addr()
+        .and_then(|addr| addr.rsplit_once(':'))
+        .map_or("8080", |(_, port)| port);
+    let url = format!("http://127.0.0.1:{port}/readyz");
+    let client = match reqwest::Client::builder()
+        .timeout(Duration::from_secs(5))
+        .build()
+    {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("❌ {e}");
+            return 1;
+        }
+    };
+    match client.get(&url).send().await {
+        Ok(res) if res.status().is_success() => 0,
+        Ok(res) => {
+            eprintln!("❌ {url}: {}", res.status());
+            1
+        }
+        Err(e) => {
+            eprintln!("❌ {url}: {e}");
+            1
+        }
+    }
+}
 
 #[tokio::main]
 async fn main() {
     dotenv().ok();
-    let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
-    let db_pool = init_db(&url).await;
-
-    let mut addr = std::env::var("BACKEND_URL").expect("BACKEND_URL must be set.");
-    if let Some(s) = addr
-        .strip_prefix("http://")
-        .or_else(|| addr.strip_prefix("https://"))
-    {
-        addr = s.to_string();
+    let args = parse_args();
+    let loaded = match AppConfig::load(args.config.as_deref()) {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            eprintln!("❌ Configuration invalide:\n{e}");
+            std::process::exit(1);
+        }
+    };
+    if args.print_config {
+        print!("{}", loaded.redacted_toml());
+        return;
+    }
+    if args.healthcheck {
+        std::process::exit(healthcheck(&loaded).await);
+    }
+    let config = config::install(loaded);
+    if let Err(e) = config.check_server() {
+        eprintln!("❌ Configuration incomplète:\n{e}");
+        std::process::exit(1);
+    }
+    // Clés JWT: refus de démarrer en production avec des secrets faibles ou partagés
+    if let Err(e) = keys::init(&config.keys) {
+        eprintln!("❌ Configuration JWT: {e}");
+        std::process::exit(1);
     }
+    let url = config.database.url.as_deref().unwrap_or_default();
+    let db_pool = init_db(url, &config.database).await;
+    // Rétention du journal d'audit (AUDIT_RETENTION_DAYS)
+    audit::spawn_retention(db_pool.clone(), config.audit.retention_days);
+    // Purge des comptes supprimés après le délai de grâce (ACCOUNT_DELETION_GRACE_DAYS)
+    account_deletion::spawn_purge(db_pool.clone());
+    // Rétention des tentatives de connexion (LOGIN_ATTEMPTS_RETENTION_DAYS)
+    login_guard::spawn_retention(db_pool.clone(), config.login.attempts_retention_days);
+    // Demandes de reset sorties de la fenêtre RESET_RATE_WINDOW_SECONDS
+    password_reset::spawn_prune(db_pool.clone(), config.password_reset.rate_window_secs);
 
-    let origin =
-        std::env::var("FRONTEND_ORIGIN").unwrap_or_else(|_| "http://localhost:3000".into());
+    let addr = config.server.bind_addr().unwrap_or_default();
+    let origin = &config.server.frontend_origin;
     let cors = CorsLayer::new()
         .allow_origin(origin.parse::<HeaderValue>().unwrap())
         .allow_methods([
@@ -34,16 +133,43 @@ async fn main() {
         .allow_headers([
             axum::http::header::CONTENT_TYPE,
             axum::http::header::AUTHORIZATION,
+            axum::http::HeaderName::from_static("x-csrf-token"),
         ])
         .allow_credentials(true);
 
-    let app: Router<_> = app::build_routes().with_state(db_pool).layer(cors);
+    let state = AppState::new(db_pool, config);
+    // Catalogue en mémoire, chargé après le seed (rechargé ensuite à chaque changement de version)
+    match state.catalog.load(&state.pool).await {
+        Ok(catalog) => println!("📚 Catalogue en mémoire: {} Pokémon", catalog.len()),
+        Err(e) => {
+            eprintln!("⚠️ Chargement du catalogue impossible (réessayé à la première requête): {e}")
+        }
+    }
+    // Écouteur dédié aux métriques, hors du port public
+    if config.metrics.enabled
+        && let Some(metrics_addr) = config.metrics.bind.as_deref()
+    {
+        let listener = TcpListener::bind(metrics_addr).await.unwrap();
+        let metrics_app = app::metrics_routes().with_state(state.clone());
+        println!("📈 Métriques sur http://{metrics_addr}/metrics");
+        tokio::spawn(async move {
+            if let Err(err) = axum::serve(listener, metrics_app).await {
+                eprintln!("Erreur serveur des métriques: {err}");
+            }
+        });
+    }
+
+    let app: Router<_> = app::build_routes(config).with_state(state).layer(cors);
 
-    let listener = TcpListener::bind(&addr).await.unwrap();
+    let listener = TcpListener::bind(addr).await.unwrap();
 
     println!("🚀 Serveur démarré sur {addr} (Ctrl+C pour arrêter)");
 
-    let server = axum::serve(listener, app).with_graceful_shutdown(helpers::shutdown());
+    let server = axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(helpers::shutdown());
     if let Err(err) = server.into_future().await {
         eprintln!("Erreur serveur: {err}");
     }
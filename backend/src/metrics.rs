@@ -0,0 +1,216 @@
+//! Métriques Prometheus exposées par `GET /metrics` (format texte).
+//!
+//! Compteurs et histogrammes sont alimentés au fil des requêtes; les jauges
+//! (pool, migrations, seed) sont relevées au moment de la collecte.
+
+use axum::extract::{MatchedPath, Request};
+use axum::middleware::Next;
+use axum::response::Response;
+use prometheus::{
+    Encoder, Gauge, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec,
+    Opts, Registry, TextEncoder,
+};
+use sqlx::PgPool;
+use std::sync::LazyLock;
+use std::time::{Duration, Instant};
+
+use crate::config::AppConfig;
+use crate::health;
+
+struct Metrics {
+    registry: Registry,
+    http_requests: IntCounterVec,
+    http_duration: HistogramVec,
+    login_attempts: IntCounterVec,
+    catches: IntCounter,
+    pool_connections: IntGaugeVec,
+    pool_max: IntGauge,
+    pool_acquire: Gauge,
+    ready: IntGauge,
+    migrations: IntGaugeVec,
+    migration_version: IntGauge,
+    seed_pokemon: IntGauge,
+}
+
+fn register<M: prometheus::core::Collector + Clone + 'static>(registry: &Registry, metric: M) -> M {
+    registry
+        .register(Box::new(metric.clone()))
+        .expect("métrique enregistrée deux fois");
+    metric
+}
+
+static METRICS: LazyLock<Metrics> = LazyLock::new(|| {
+    let registry = Registry::new_custom(Some("pokedex".into()), None).expect("registre");
+    let build_info = register(
+        &registry,
+        IntGaugeVec::new(
+            Opts::new("build_info", "Version du binaire (valeur toujours 1)"),
+            &["version", "git_hash"],
+        )
+        .unwrap(),
+    );
+    build_info
+        .with_label_values(&[env!("CARGO_PKG_VERSION"), health::GIT_HASH])
+        .set(1);
+
+    Metrics {
+        http_requests: register(
+            &registry,
+            IntCounterVec::new(
+                Opts::new("http_requests_total", "Requêtes HTTP par route et statut"),
+                &["method", "route", "status"],
+            )
+            .unwrap(),
+        ),
+        http_duration: register(
+            &registry,
+            HistogramVec::new(
+                HistogramOpts::new(
+                    "http_request_duration_seconds",
+                    "Durée de traitement des requêtes HTTP",
+                ),
+                &["method", "route", "status"],
+            )
+            .unwrap(),
+        ),
+        login_attempts: register(
+            &registry,
+            IntCounterVec::new(
+                Opts::new(
+                    "login_attempts_total",
+                    "Tentatives de connexion (result: success, failure, pending)",
+                ),
+                &["result", "reason"],
+            )
+            .unwrap(),
+        ),
+        catches: register(
+            &registry,
+            IntCounter::new("catches_total", "Nouvelles captures de Pokémon").unwrap(),
+        ),
+        pool_connections: register(
+            &registry,
+            IntGaugeVec::new(
+                Opts::new("db_pool_connections", "Connexions du pool par état"),
+                &["state"],
+            )
+            .unwrap(),
+        ),
+        pool_max: register(
+            &registry,
+            IntGauge::new("db_pool_max_connections", "Taille maximale du pool").unwrap(),
+        ),
+        pool_acquire: register(
+            &registry,
+            Gauge::new(
+                "db_pool_acquire_seconds",
+                "Attente pour obtenir une connexion, mesurée à la collecte (-1: échec)",
+            )
+            .unwrap(),
+        ),
+        ready: register(
+            &registry,
+            IntGauge::new("ready", "1 si /readyz répond 200").unwrap(),
+        ),
+        migrations: register(
+            &registry,
+            IntGaugeVec::new(
+                Opts::new("migrations", "Migrations embarquées par état"),
+                &["state"],
+            )
+            .unwrap(),
+        ),
+        migration_version: register(
+            &registry,
+            IntGauge::new("migration_version", "Dernière migration appliquée").unwrap(),
+        ),
+        seed_pokemon: register(
+            &registry,
+            IntGauge::new("seed_pokemon", "Pokémon présents dans le catalogue").unwrap(),
+        ),
+        registry,
+    }
+});
+
+/// Tentative de connexion, avec la raison enregistrée dans `login_attempts`.
+pub fn record_login(reason: &str) {
+    let result = match reason {
+        "success" => "success",
+        // Mot de passe correct, second facteur attendu
+        "mfa_required" => "pending",
+        _ => "failure",
+    };
+    METRICS
+        .login_attempts
+        .with_label_values(&[result, reason])
+        .inc();
+}
+
+pub fn record_catch() {
+    METRICS.catches.inc();
+}
+
+/// Middleware (à poser en `route_layer`): compte et chronomètre chaque requête
+/// par modèle de route (`/api/pokemons/{id}`), jamais par chemin brut.
+pub async fn track(req: Request, next: Next) -> Response {
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|p| p.as_str().to_owned())
+        .unwrap_or_else(|| "inconnue".into());
+    let method = req.method().as_str().to_owned();
+    let started = Instant::now();
+
+    let res = next.run(req).await;
+
+    let status = res.status().as_u16().to_string();
+    let labels = [method.as_str(), route.as_str(), status.as_str()];
+    METRICS.http_requests.with_label_values(&labels).inc();
+    METRICS
+        .http_duration
+        .with_label_values(&labels)
+        .observe(started.elapsed().as_secs_f64());
+    res
+}
+
+/// Relève les jauges puis encode toutes les métriques au format texte Prometheus.
+pub async fn render(pool: &PgPool, config: &AppConfig) -> String {
+    let m = &*METRICS;
+    let limit = Duration::from_millis(config.health.readiness_timeout_ms);
+
+    let idle = pool.num_idle() as i64;
+    m.pool_connections.with_label_values(&["idle"]).set(idle);
+    m.pool_connections
+        .with_label_values(&["in_use"])
+        .set(pool.size() as i64 - idle);
+    m.pool_max.set(config.database.max_connections.into());
+    let started = Instant::now();
+    let acquired = tokio::time::timeout(limit, pool.acquire()).await;
+    m.pool_acquire.set(match acquired {
+        Ok(Ok(_)) => started.elapsed().as_secs_f64(),
+        _ => -1.0,
+    });
+
+    let report = health::readiness(pool, limit).await;
+    m.ready.set(report.ready.into());
+    let migrations = &report.migrations;
+    m.migrations
+        .with_label_values(&["applied"])
+        .set(migrations.applied as i64);
+    m.migrations
+        .with_label_values(&["pending"])
+        .set(migrations.pending.len() as i64);
+    m.migrations
+        .with_label_values(&["failed"])
+        .set(migrations.failed.len() as i64);
+    m.seed_pokemon.set(report.seed.pokemon);
+    let latest = health::latest_migration(pool, limit).await;
+    m.migration_version
+        .set(latest.map_or(0, |migration| migration.version));
+
+    let mut buf = Vec::new();
+    if let Err(e) = TextEncoder::new().encode(&m.registry.gather(), &mut buf) {
+        eprintln!("⚠️ Encodage des métriques impossible: {e}");
+    }
+    String::from_utf8(buf).unwrap_or_default()
+}
@@ -0,0 +1,328 @@
+use sqlx::PgPool;
+use totp_rs::{Algorithm, Secret, TOTP};
+use uuid::Uuid;
+
+use crate::auth::{generate_mfa_pending_token, hash_password, verify_password};
+use crate::clock::Clock;
+use crate::config::AppConfig;
+use crate::models::auth::MfaPendingClaims;
+use crate::rng::Rng;
+
+const DIGITS: usize = 6;
+const STEP_SECS: u64 = 30;
+/// Pas de temps tolérés de part et d'autre (décalage d'horloge du téléphone)
+const SKEW: i64 = 1;
+const RECOVERY_CODES: usize = 10;
+
+#[derive(Debug)]
+pub enum MfaError {
+    Db(sqlx::Error),
+    /// Secret stocké illisible (ne devrait pas arriver)
+    Secret(String),
+    Hash(String),
+    Token(String),
+}
+
+impl std::fmt::Display for MfaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MfaError::Db(e) => write!(f, "{e}"),
+            MfaError::Secret(e) => write!(f, "Secret TOTP invalide: {e}"),
+            MfaError::Hash(e) => write!(f, "{e}"),
+            MfaError::Token(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl From<sqlx::Error> for MfaError {
+    fn from(e: sqlx::Error) -> Self {
+        MfaError::Db(e)
+    }
+}
+
+// `issuer` ne sert qu'au libellé de l'URI otpauth, pas au calcul des codes
+fn totp(secret_b32: &str, issuer: Option<&str>, account: &str) -> Result<TOTP, MfaError> {
+    let bytes = Secret::Encoded(secret_b32.to_string())
+        .to_bytes()
+        .map_err(|e| MfaError::Secret(format!("{e:?}")))?;
+    // ':' est réservé dans le libellé otpauth://issuer:compte
+    TOTP::new(
+        Algorithm::SHA1,
+        DIGITS,
+        SKEW as u8,
+        STEP_SECS,
+        bytes,
+        issuer.map(str::to_string),
+        account.replace(':', "_"),
+    )
+    .map_err(|e| MfaError::Secret(e.to_string()))
+}
+
+/// Nouveau secret aléatoire (160 bits), encodé en base32.
+pub fn new_secret() -> String {
+    match Secret::generate_secret().to_encoded() {
+        Secret::Encoded(s) => s,
+        Secret::Raw(_) => unreachable!("to_encoded renvoie toujours Secret::Encoded"),
+    }
+}
+
+/// URI à afficher en QR code dans l'application d'authentification (`issuer`: MFA_ISSUER).
+pub fn otpauth_uri(secret_b32: &str, issuer: &str, account: &str) -> Result<String, MfaError> {
+    Ok(totp(secret_b32, Some(issuer), account)?.get_url())
+}
+
+/// Code attendu pour `unix_time` (utile aux tests et au debug).
+pub fn code_at(secret_b32: &str, unix_time: u64) -> Result<String, MfaError> {
+    Ok(totp(secret_b32, None, "")?.generate(unix_time))
+}
+
+/// Pas de temps auquel correspond `code`, en tolérant ±SKEW pas.
+fn matching_step(secret_b32: &str, code: &str, unix_time: u64) -> Result<Option<i64>, MfaError> {
+    let totp = totp(secret_b32, None, "")?;
+    let current = (unix_time / STEP_SECS) as i64;
+    Ok((current - SKEW..=current + SKEW)
+        .filter(|step| *step >= 0)
+        .find(|step| totp.generate(*step as u64 * STEP_SECS) == code))
+}
+
+fn looks_like_totp(code: &str) -> bool {
+    code.len() == DIGITS && code.chars().all(|c| c.is_ascii_digit())
+}
+
+pub async fn is_enabled(pool: &PgPool, user_id: Uuid) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar(r#"SELECT totp_enabled_at IS NOT NULL FROM users WHERE id = $1"#)
+        .bind(user_id)
+        .fetch_one(pool)
+        .await
+}
+
+/// Démarre (ou recommence) l'activation: un nouveau secret est stocké, inactif
+/// tant qu'un premier code n'a pas été vérifié.
+pub async fn begin_setup(pool: &PgPool, user_id: Uuid) -> Result<String, sqlx::Error> {
+    let secret = new_secret();
+    sqlx::query(
+        r#"
+        UPDATE users SET totp_secret = $2, totp_last_step = NULL
+        WHERE id = $1 AND totp_enabled_at IS NULL
+        "#,
+    )
+    .bind(user_id)
+    .bind(&secret)
+    .execute(pool)
+    .await?;
+    Ok(secret)
+}
+
+/// Vérifie un code TOTP; chaque pas de temps n'est accepté qu'une fois (anti-rejeu).
+pub async fn verify_totp(
+    pool: &PgPool,
+    clock: &dyn Clock,
+    user_id: Uuid,
+    code: &str,
+) -> Result<bool, MfaError> {
+    if !looks_like_totp(code) {
+        return Ok(false);
+    }
+    let secret: Option<String> =
+        sqlx::query_scalar(r#"SELECT totp_secret FROM users WHERE id = $1"#)
+            .bind(user_id)
+            .fetch_optional(pool)
+            .await?
+            .flatten();
+    let Some(secret) = secret else {
+        return Ok(false);
+    };
+    let Some(step) = matching_step(&secret, code, clock.unix_now().max(0) as u64)? else {
+        return Ok(false);
+    };
+
+    let res = sqlx::query(
+        r#"
+        UPDATE users SET totp_last_step = $2
+        WHERE id = $1 AND (totp_last_step IS NULL OR totp_last_step < $2)
+        "#,
+    )
+    .bind(user_id)
+    .bind(step)
+    .execute(pool)
+    .await?;
+    Ok(res.rows_affected() > 0)
+}
+
+/// Active la 2FA après vérification du premier code et renvoie les codes de récupération (en clair, une seule fois).
+pub async fn enable(pool: &PgPool, rng: &dyn Rng, user_id: Uuid) -> Result<Vec<String>, MfaError> {
+    let codes = (0..RECOVERY_CODES)
+        .map(|_| new_recovery_code(rng))
+        .collect::<Vec<_>>();
+    let hashes = codes
+        .iter()
+        .map(|c| hash_password(c).map_err(|e| MfaError::Hash(e.to_string())))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut tx = pool.begin().await?;
+    sqlx::query(r#"UPDATE users SET totp_enabled_at = NOW() WHERE id = $1"#)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query(r#"DELETE FROM mfa_recovery_codes WHERE user_id = $1"#)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+    for hash in hashes {
+        sqlx::query(r#"INSERT INTO mfa_recovery_codes (user_id, code_hash) VALUES ($1, $2)"#)
+            .bind(user_id)
+            .bind(hash)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await?;
+    Ok(codes)
+}
+
+pub async fn disable(pool: &PgPool, user_id: Uuid) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    sqlx::query(
+        r#"
+        UPDATE users SET totp_secret = NULL, totp_enabled_at = NULL, totp_last_step = NULL
+        WHERE id = $1
+        "#,
+    )
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query(r#"DELETE FROM mfa_recovery_codes WHERE user_id = $1"#)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await
+}
+
+// 10 caractères hexadécimaux (40 bits) présentés en deux blocs: "a1b2c-3d4e5"
+fn new_recovery_code(rng: &dyn Rng) -> String {
+    let hex = rng.hex(5);
+    format!("{}-{}", &hex[..5], &hex[5..])
+}
+
+fn normalize_recovery_code(code: &str) -> String {
+    let compact = code
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_lowercase();
+    if compact.len() != 10 {
+        return compact;
+    }
+    format!("{}-{}", &compact[..5], &compact[5..])
+}
+
+/// Consomme un code de récupération s'il correspond à l'un des codes encore inutilisés.
+pub async fn use_recovery_code(
+    pool: &PgPool,
+    user_id: Uuid,
+    code: &str,
+) -> Result<bool, sqlx::Error> {
+    let code = normalize_recovery_code(code);
+    let candidates = sqlx::query_as::<_, (Uuid, String)>(
+        r#"SELECT id, code_hash FROM mfa_recovery_codes WHERE user_id = $1 AND used_at IS NULL"#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    let Some((id, _)) = candidates
+        .into_iter()
+        .find(|(_, hash)| verify_password(hash, &code))
+    else {
+        return Ok(false);
+    };
+    let res = sqlx::query(
+        r#"UPDATE mfa_recovery_codes SET used_at = NOW() WHERE id = $1 AND used_at IS NULL"#,
+    )
+    .bind(id)
+    .execute(pool)
+    .await?;
+    Ok(res.rows_affected() > 0)
+}
+
+/// Second facteur: code TOTP (6 chiffres) ou, à défaut, code de récupération.
+pub async fn verify_second_factor(
+    pool: &PgPool,
+    clock: &dyn Clock,
+    user_id: Uuid,
+    code: &str,
+) -> Result<bool, MfaError> {
+    let code = code.trim();
+    if looks_like_totp(code) {
+        return verify_totp(pool, clock, user_id, code).await;
+    }
+    Ok(use_recovery_code(pool, user_id, code).await?)
+}
+
+pub async fn remaining_recovery_codes(pool: &PgPool, user_id: Uuid) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"SELECT COUNT(*) FROM mfa_recovery_codes WHERE user_id = $1 AND used_at IS NULL"#,
+    )
+    .bind(user_id)
+    .fetch_one(pool)
+    .await
+}
+
+/// Login en attente du second facteur: jti enregistré puis token signé.
+/// Les attentes expirées sont purgées au passage.
+pub async fn issue_pending_login(
+    pool: &PgPool,
+    config: &AppConfig,
+    user_id: Uuid,
+) -> Result<String, MfaError> {
+    sqlx::query(r#"DELETE FROM mfa_pending_logins WHERE expires_at <= NOW()"#)
+        .execute(pool)
+        .await?;
+    let jti = Uuid::new_v4();
+    sqlx::query(
+        r#"
+        INSERT INTO mfa_pending_logins (jti, user_id, expires_at)
+        VALUES ($1, $2, NOW() + make_interval(secs => $3))
+        "#,
+    )
+    .bind(jti)
+    .bind(user_id)
+    .bind(config.auth.mfa_pending_ttl_secs as f64)
+    .execute(pool)
+    .await?;
+    generate_mfa_pending_token(config, user_id, jti).map_err(|e| MfaError::Token(e.to_string()))
+}
+
+/// Le token n'a pas encore servi: un mauvais code laisse réessayer avec le même.
+pub async fn pending_login_open(
+    pool: &PgPool,
+    claims: &MfaPendingClaims,
+) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM mfa_pending_logins
+            WHERE jti = $1 AND user_id = $2 AND expires_at > NOW()
+        )
+        "#,
+    )
+    .bind(claims.jti)
+    .bind(claims.sub)
+    .fetch_one(pool)
+    .await
+}
+
+/// Consomme le token après un code valide. `false`: déjà utilisé (requête
+/// concurrente) ou expiré entre-temps.
+pub async fn consume_pending_login(
+    pool: &PgPool,
+    claims: &MfaPendingClaims,
+) -> Result<bool, sqlx::Error> {
+    let res = sqlx::query(
+        r#"DELETE FROM mfa_pending_logins WHERE jti = $1 AND user_id = $2 AND expires_at > NOW()"#,
+    )
+    .bind(claims.jti)
+    .bind(claims.sub)
+    .execute(pool)
+    .await?;
+    Ok(res.rows_affected() > 0)
+}
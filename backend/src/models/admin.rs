@@ -0,0 +1,92 @@
+use serde::{Deserialize, Deserializer, Serialize};
+use time::OffsetDateTime;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct AdminUserQuery {
+    /// Recherche partielle sur le nom d'utilisateur ou l'email
+    pub q: Option<String>,
+    /// user, moderator ou admin
+    pub role: Option<String>,
+    /// 50 par défaut, 100 au plus
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow, ToSchema)]
+pub struct AdminUser {
+    pub id: Uuid,
+    pub username: String,
+    pub email: Option<String>,
+    pub role: String,
+    pub email_verified: bool,
+    pub two_factor_enabled: bool,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub locked_until: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub disabled_at: Option<OffsetDateTime>,
+    pub disabled_reason: Option<String>,
+    /// Suppression demandée par l'utilisateur, purge définitive à `purge_after`
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub deleted_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub purge_after: Option<OffsetDateTime>,
+    pub catches: i64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AdminUserPage {
+    pub total: i64,
+    pub users: Vec<AdminUser>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SuspendUserPayload {
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SetRolePayload {
+    pub role: String,
+}
+
+// Champ présent (même à null) -> Some: distingue "absent" de "effacer"
+fn present<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+/// Champs modifiables d'un Pokémon; les champs absents sont conservés.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdatePokemonPayload {
+    pub description: Option<String>,
+    pub image_url: Option<String>,
+    pub type1: Option<String>,
+    /// `null` retire le second type
+    #[serde(default, deserialize_with = "present")]
+    #[schema(value_type = Option<String>, nullable)]
+    pub type2: Option<Option<String>>,
+    pub height_m: Option<f64>,
+    pub weight_kg: Option<f64>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow, ToSchema)]
+pub struct SystemStats {
+    pub users: i64,
+    pub users_verified: i64,
+    pub users_two_factor: i64,
+    pub users_locked: i64,
+    pub users_disabled: i64,
+    pub signups_7d: i64,
+    pub active_sessions: i64,
+    pub api_tokens: i64,
+    pub logins_24h: i64,
+    pub failed_logins_24h: i64,
+    pub pokemon: i64,
+    pub catches: i64,
+    pub catches_24h: i64,
+}
@@ -1,34 +1,233 @@
 use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
 
-#[derive(Deserialize)]
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Deserialize, ToSchema)]
 pub struct ChangePasswordPayload {
     pub current_password: String,
     pub new_password: String,
+    /// Déconnecte les autres appareils (la session courante est conservée)
+    #[serde(default = "default_true")]
+    pub logout_other_sessions: bool,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct RequestPasswordResetPayload {
     pub email_or_username: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct ConfirmPasswordResetPayload {
     pub token: String,
     pub new_password: String,
+    /// Déconnecte toutes les sessions ouvertes
+    #[serde(default = "default_true")]
+    pub logout_other_sessions: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EmailVerifyClaims {
+    pub sub: Uuid,
+    pub email: String,
+    pub iat: i64,
+    pub exp: i64,
+    pub scope: String,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct VerifyEmailPayload {
+    pub token: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UnlockClaims {
+    pub sub: Uuid,
+    /// Fin du verrou (timestamp unix) que ce token permet de lever
+    pub lock: i64,
+    pub iat: i64,
+    pub exp: i64,
+    pub scope: String,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct UnlockAccountPayload {
+    pub token: String,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct ResetClaims {
+pub struct MfaPendingClaims {
     pub sub: Uuid,
+    /// Consommé au premier code valide (table mfa_pending_logins)
+    pub jti: Uuid,
     pub iat: i64,
     pub exp: i64,
     pub scope: String,
 }
 
+/// Code TOTP à 6 chiffres ou code de récupération.
+#[derive(Deserialize, ToSchema)]
+pub struct MfaCodePayload {
+    pub code: String,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct MfaLoginPayload {
+    /// Absent: token du cookie `mfa_pending` (retour OIDC)
+    #[serde(default)]
+    pub mfa_token: Option<String>,
+    pub code: String,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Claims {
     pub sub: Uuid,
     pub iat: i64,
     pub exp: i64,
+    /// Session serveur à laquelle le token est rattaché
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<Uuid>,
+    /// Identifiant unique du refresh token (rotation)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jti: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow, ToSchema)]
+pub struct SessionInfo {
+    pub id: Uuid,
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub last_used_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub expires_at: OffsetDateTime,
+    /// Session de la requête courante
+    pub current: bool,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct CreateApiTokenPayload {
+    pub name: String,
+    pub scopes: Vec<String>,
+    /// Durée de validité en jours (sans expiration si absent)
+    pub expires_in_days: Option<i64>,
+}
+
+/// Token d'accès personnel tel que listé (jamais la valeur du token).
+#[derive(Debug, Serialize, sqlx::FromRow, ToSchema)]
+pub struct ApiTokenInfo {
+    pub id: Uuid,
+    pub name: String,
+    pub prefix: String,
+    pub scopes: Vec<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub last_used_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub expires_at: Option<OffsetDateTime>,
+}
+
+/// Token tout juste créé: seule réponse contenant sa valeur.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CreatedApiToken {
+    #[serde(flatten)]
+    pub info: ApiTokenInfo,
+    /// Valeur à présenter en Bearer (pkr_…)
+    pub token: String,
+}
+
+/// Contenu du cookie de login OIDC (voir `auth::generate_oidc_flow_token`).
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct OidcFlowClaims {
+    pub provider: String,
+    pub state: String,
+    pub nonce: String,
+    pub code_verifier: String,
+    /// Chemin du frontend où revenir après connexion
+    pub redirect_to: String,
+    /// Rattachement à un compte déjà connecté plutôt que connexion
+    pub link_user: Option<Uuid>,
+    pub iat: i64,
+    pub exp: i64,
+    pub scope: String,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct OidcStartParams {
+    /// Chemin du frontend où revenir (défaut: "/")
+    pub redirect_to: Option<String>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct OidcCallbackParams {
+    pub code: Option<String>,
+    pub state: Option<String>,
+    pub error: Option<String>,
+    pub error_description: Option<String>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow, ToSchema)]
+pub struct IdentityInfo {
+    pub id: Uuid,
+    pub provider: String,
+    pub email: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub last_login_at: Option<OffsetDateTime>,
+}
+
+/// Mot de passe correct, second facteur attendu (`POST /api/auth/2fa/login`).
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MfaChallenge {
+    pub mfa_required: bool,
+    pub mfa_token: String,
+}
+
+/// Secret TOTP à enregistrer dans l'application d'authentification.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TotpSetup {
+    pub secret: String,
+    pub otpauth_uri: String,
+}
+
+/// Codes de récupération, affichés une seule fois.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RecoveryCodes {
+    pub message: &'static str,
+    pub recovery_codes: Vec<String>,
+}
+
+/// Demande prise en compte (réponse identique que le compte existe ou non).
+#[derive(Debug, Serialize, ToSchema)]
+pub struct Accepted {
+    pub status: &'static str,
+}
+
+impl Accepted {
+    pub const OK: Accepted = Accepted { status: "ok" };
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(untagged)]
+pub enum ResetRequested {
+    /// Hors production uniquement: token renvoyé pour faciliter les tests
+    Token {
+        reset_token: String,
+    },
+    Accepted(Accepted),
+}
+
+/// Fournisseur OIDC proposé sur l'écran de connexion.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ProviderInfo {
+    pub name: String,
+    pub display_name: String,
 }
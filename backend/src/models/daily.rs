@@ -0,0 +1,89 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// Statistique de base ciblée par un défi "stat ≥ seuil".
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Stat {
+    Hp,
+    Attack,
+    Defense,
+    SpAttack,
+    SpDefense,
+    Speed,
+}
+
+impl Stat {
+    pub const ALL: [Stat; 6] = [
+        Stat::Hp,
+        Stat::Attack,
+        Stat::Defense,
+        Stat::SpAttack,
+        Stat::SpDefense,
+        Stat::Speed,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Stat::Hp => "HP",
+            Stat::Attack => "Attack",
+            Stat::Defense => "Defense",
+            Stat::SpAttack => "Sp. Attack",
+            Stat::SpDefense => "Sp. Defense",
+            Stat::Speed => "Speed",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ChallengeKind {
+    /// Attraper un Pokémon ayant ce type (type1 ou type2)
+    CatchType { type_name: String },
+    /// Attraper un Pokémon dont la stat de base est ≥ min
+    CatchStatAtLeast { stat: Stat, min: i32 },
+    /// Attraper un Pokémon de cette génération (d'après dex_no)
+    CatchGeneration { generation: i32 },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, ToSchema)]
+pub struct DailyChallenge {
+    pub key: String,
+    pub title: String,
+    #[serde(flatten)]
+    pub kind: ChallengeKind,
+    pub target: i32,
+    pub reward_points: i32,
+}
+
+/// Champs d'un Pokémon utiles pour évaluer un défi.
+#[derive(Clone, Debug, sqlx::FromRow)]
+pub struct ChallengePokemon {
+    pub type1: String,
+    pub type2: Option<String>,
+    pub dex_no: Option<i32>,
+    pub base_hp: Option<i32>,
+    pub base_attack: Option<i32>,
+    pub base_defense: Option<i32>,
+    pub base_sp_attack: Option<i32>,
+    pub base_sp_defense: Option<i32>,
+    pub base_speed: Option<i32>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, ToSchema)]
+pub struct Streak {
+    pub current: i32,
+    pub best: i32,
+    pub last_completed_on: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DailyStatus {
+    pub day: String,
+    pub timezone: String,
+    pub challenge: DailyChallenge,
+    pub progress: i32,
+    pub completed: bool,
+    pub streak: Streak,
+    pub reward_points_total: i64,
+}
@@ -0,0 +1,59 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// Réponse de `GET /readyz`: chaque vérification est détaillée, même en cas de succès.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct Readiness {
+    pub ready: bool,
+    pub database: DatabaseCheck,
+    pub migrations: MigrationsCheck,
+    pub seed: SeedCheck,
+}
+
+#[derive(Debug, Default, Serialize, ToSchema)]
+pub struct DatabaseCheck {
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, ToSchema)]
+pub struct MigrationsCheck {
+    pub ok: bool,
+    pub applied: usize,
+    pub expected: usize,
+    /// Versions embarquées dans le binaire mais pas encore appliquées
+    pub pending: Vec<i64>,
+    /// Versions dont l'application a échoué
+    pub failed: Vec<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, ToSchema)]
+pub struct SeedCheck {
+    pub ok: bool,
+    pub pokemon: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Réponse de `GET /version`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct VersionInfo {
+    pub name: &'static str,
+    pub version: &'static str,
+    pub git_hash: &'static str,
+    /// Date du build (RFC 3339)
+    pub built_at: String,
+    /// Dernière migration appliquée (`null` si la base est injoignable)
+    pub migration: Option<MigrationVersion>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow, ToSchema)]
+pub struct MigrationVersion {
+    pub version: i64,
+    pub description: String,
+}
@@ -1,3 +1,7 @@
+pub mod admin;
+pub mod audit;
 pub mod auth;
+pub mod daily;
+pub mod health;
 pub mod pokemon;
 pub mod user;
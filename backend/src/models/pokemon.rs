@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 
-#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
 pub struct PokemonWithCaught {
     pub id: i32,
     pub name: String,
@@ -11,18 +12,33 @@ pub struct PokemonWithCaught {
     pub caught: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct CatchByNamePayload {
     pub name: String,
     pub nickname: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Pagination du catalogue (v2).
+#[derive(Debug, Default, Serialize, Deserialize, IntoParams)]
+pub struct PokemonPageQuery {
+    /// 50 par défaut, 100 au plus
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PokemonPage {
+    pub total: i64,
+    pub pokemons: Vec<PokemonWithCaught>,
+}
+
+#[derive(Debug, Serialize, Deserialize, IntoParams)]
 pub struct SearchParams {
+    /// Début du nom (10 résultats au plus)
     pub q: String,
 }
 
-#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
 pub struct PokemonDetail {
     pub id: i32,
     pub name: String,
@@ -1,7 +1,10 @@
 use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
+use utoipa::ToSchema;
 use uuid::Uuid;
 
+use crate::models::daily::Streak;
+
 #[derive(Debug, Serialize, sqlx::FromRow)]
 pub struct User {
     pub id: Uuid,
@@ -14,22 +17,44 @@ pub struct User {
     pub created_at: OffsetDateTime,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateUser {
     pub username: String,
     pub email: Option<String>,
     pub password: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct LoginUser {
     pub username: String,
     pub password: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdateUser {
     pub username: Option<String>,
     pub email: Option<String>,
     pub password: Option<String>,
+    /// Fuseau IANA (ex: "Europe/Paris"), utilisé pour les défis quotidiens
+    pub timezone: Option<String>,
+}
+
+/// Profil renvoyé par `GET /api/auth/me`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MeInfo {
+    pub id: Uuid,
+    pub username: String,
+    pub email: Option<String>,
+    pub role: String,
+    pub email_verified: bool,
+    pub two_factor_enabled: bool,
+    pub streak: Streak,
+}
+
+/// Suppression différée: le compte reste récupérable jusqu'à `purge_after`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DeletionScheduled {
+    pub message: &'static str,
+    /// Date de purge (RFC 3339)
+    pub purge_after: String,
 }
@@ -0,0 +1,613 @@
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{Algorithm, DecodingKey, Validation, decode, decode_header};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+use crate::auth::hash_password;
+use crate::config::{AppConfig, OidcConfig, OidcProviderConfig};
+use crate::mailer::templates;
+
+/// Fournisseur OpenID Connect ("Se connecter avec …").
+#[derive(Clone, Debug, PartialEq)]
+pub struct OidcProvider {
+    /// Identifiant dans les URLs (/api/v1/auth/oidc/{name}/start) et en base
+    pub name: String,
+    pub display_name: String,
+    /// Émetteur: la découverte se fait sur {issuer}/.well-known/openid-configuration
+    pub issuer: String,
+    pub client_id: String,
+    /// Absent pour un client public (PKCE seul)
+    pub client_secret: Option<String>,
+    pub scopes: String,
+}
+
+const DEFAULT_SCOPES: &str = "openid email profile";
+
+impl OidcProvider {
+    pub fn new(name: &str, issuer: &str, client_id: &str, client_secret: Option<&str>) -> Self {
+        OidcProvider {
+            name: name.to_string(),
+            display_name: name.to_string(),
+            issuer: issuer.trim_end_matches('/').to_string(),
+            client_id: client_id.to_string(),
+            client_secret: client_secret.map(str::to_string),
+            scopes: DEFAULT_SCOPES.to_string(),
+        }
+    }
+
+    /// Fournisseur déclaré dans `[[oidc.providers]]` ou par OIDC_<NAME>_*.
+    fn from_config(config: &OidcProviderConfig) -> OidcProvider {
+        let mut provider = OidcProvider::new(
+            &config.name,
+            &config.issuer,
+            &config.client_id,
+            config.client_secret.as_deref(),
+        );
+        if let Some(scopes) = &config.scopes {
+            provider.scopes = scopes.clone();
+        }
+        if let Some(display_name) = &config.display_name {
+            provider.display_name = display_name.clone();
+        }
+        provider
+    }
+
+    /// URI de retour à déclarer chez le fournisseur. Préfixe `/api` par défaut:
+    /// les URI enregistrées avant `/api/v1` restent valides.
+    pub fn redirect_uri(&self, config: &AppConfig) -> String {
+        let oidc = &config.oidc;
+        let base = match &oidc.redirect_base_url {
+            Some(url) => url.clone(),
+            None => templates::app_link(config, ""),
+        };
+        format!(
+            "{}{}/auth/oidc/{}/callback",
+            base.trim_end_matches('/'),
+            oidc.callback_prefix,
+            self.name
+        )
+    }
+}
+
+/// Fournisseurs disponibles, partagés par l'état de l'application.
+#[derive(Clone, Debug, Default)]
+pub struct OidcProviders(Arc<Vec<OidcProvider>>);
+
+impl OidcProviders {
+    /// Fournisseurs déclarés dans la configuration (OIDC_PROVIDERS).
+    pub fn from_config(config: &OidcConfig) -> Self {
+        OidcProviders(Arc::new(
+            config
+                .providers
+                .iter()
+                .map(OidcProvider::from_config)
+                .collect(),
+        ))
+    }
+
+    /// Ajoute (ou remplace) un fournisseur.
+    pub fn with(&self, provider: OidcProvider) -> Self {
+        let mut providers: Vec<OidcProvider> = self
+            .0
+            .iter()
+            .filter(|p| p.name != provider.name)
+            .cloned()
+            .collect();
+        providers.push(provider);
+        OidcProviders(Arc::new(providers))
+    }
+
+    pub fn list(&self) -> &[OidcProvider] {
+        &self.0
+    }
+
+    pub fn get(&self, name: &str) -> Option<&OidcProvider> {
+        self.0.iter().find(|p| p.name == name)
+    }
+}
+
+#[derive(Debug)]
+pub enum OidcError {
+    /// Fournisseur injoignable ou réponse inattendue
+    Provider(String),
+    /// ID token refusé (signature, émetteur, audience, nonce, expiration)
+    InvalidIdToken(String),
+    /// Identité externe déjà rattachée à un autre compte
+    IdentityInUse,
+    Db(sqlx::Error),
+    Internal(String),
+}
+
+impl std::fmt::Display for OidcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OidcError::Provider(e) => write!(f, "Fournisseur OIDC: {e}"),
+            OidcError::InvalidIdToken(e) => write!(f, "ID token invalide: {e}"),
+            OidcError::IdentityInUse => write!(f, "Identité déjà rattachée à un autre compte"),
+            OidcError::Db(e) => write!(f, "{e}"),
+            OidcError::Internal(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl From<sqlx::Error> for OidcError {
+    fn from(e: sqlx::Error) -> Self {
+        OidcError::Db(e)
+    }
+}
+
+// Appels rares (un login OIDC): pas de connexions gardées ouvertes entre deux
+static HTTP: LazyLock<reqwest::Client> = LazyLock::new(|| {
+    reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .pool_max_idle_per_host(0)
+        .build()
+        .expect("client HTTP OIDC")
+});
+
+/// Métadonnées utiles du document de découverte.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Discovery {
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    pub jwks_uri: String,
+}
+
+const CACHE_TTL: Duration = Duration::from_secs(3600);
+
+static DISCOVERY: LazyLock<Mutex<HashMap<String, (Discovery, Instant)>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+static JWKS: LazyLock<Mutex<HashMap<String, (JwkSet, Instant)>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+async fn get_json<T: DeserializeOwned>(url: &str) -> Result<T, OidcError> {
+    let res = HTTP
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| OidcError::Provider(format!("{url}: {e}")))?;
+    if !res.status().is_success() {
+        return Err(OidcError::Provider(format!("{url}: HTTP {}", res.status())));
+    }
+    res.json()
+        .await
+        .map_err(|e| OidcError::Provider(format!("{url}: {e}")))
+}
+
+/// Document de découverte du fournisseur (mis en cache une heure).
+pub async fn discover(provider: &OidcProvider) -> Result<Discovery, OidcError> {
+    let issuer = provider.issuer.as_str();
+    let cached = DISCOVERY.lock().unwrap().get(issuer).cloned();
+    if let Some((discovery, at)) = cached
+        && at.elapsed() < CACHE_TTL
+    {
+        return Ok(discovery);
+    }
+
+    let discovery: Discovery =
+        get_json(&format!("{issuer}/.well-known/openid-configuration")).await?;
+    if discovery.issuer.trim_end_matches('/') != issuer {
+        return Err(OidcError::Provider(format!(
+            "issuer annoncé ({}) différent de la configuration ({issuer})",
+            discovery.issuer
+        )));
+    }
+    DISCOVERY
+        .lock()
+        .unwrap()
+        .insert(issuer.to_string(), (discovery.clone(), Instant::now()));
+    Ok(discovery)
+}
+
+async fn jwks(uri: &str, refresh: bool) -> Result<JwkSet, OidcError> {
+    let cached = JWKS.lock().unwrap().get(uri).cloned();
+    if !refresh
+        && let Some((set, at)) = cached
+        && at.elapsed() < CACHE_TTL
+    {
+        return Ok(set);
+    }
+    let set: JwkSet = get_json(uri).await?;
+    JWKS.lock()
+        .unwrap()
+        .insert(uri.to_string(), (set.clone(), Instant::now()));
+    Ok(set)
+}
+
+// Clé publique du `kid`; le JWKS est rechargé une fois si la clé est inconnue (rotation).
+async fn jwk_key(discovery: &Discovery, kid: Option<&str>) -> Result<DecodingKey, OidcError> {
+    for refresh in [false, true] {
+        let set = jwks(&discovery.jwks_uri, refresh).await?;
+        let jwk = match kid {
+            Some(kid) => set.find(kid),
+            None => set.keys.first(),
+        };
+        if let Some(jwk) = jwk {
+            return DecodingKey::from_jwk(jwk)
+                .map_err(|e| OidcError::InvalidIdToken(format!("JWK inutilisable: {e}")));
+        }
+    }
+    Err(OidcError::InvalidIdToken(
+        "clé de signature inconnue".into(),
+    ))
+}
+
+/// Valeur aléatoire (state, nonce, code_verifier PKCE): 64 caractères hexadécimaux.
+pub fn random_token() -> String {
+    format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+/// code_challenge PKCE (méthode S256).
+pub fn pkce_challenge(verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()))
+}
+
+pub fn authorization_url(
+    config: &AppConfig,
+    provider: &OidcProvider,
+    discovery: &Discovery,
+    state: &str,
+    nonce: &str,
+    code_verifier: &str,
+) -> Result<String, OidcError> {
+    let redirect_uri = provider.redirect_uri(config);
+    let challenge = pkce_challenge(code_verifier);
+    reqwest::Url::parse_with_params(
+        &discovery.authorization_endpoint,
+        &[
+            ("response_type", "code"),
+            ("client_id", provider.client_id.as_str()),
+            ("redirect_uri", redirect_uri.as_str()),
+            ("scope", provider.scopes.as_str()),
+            ("state", state),
+            ("nonce", nonce),
+            ("code_challenge", challenge.as_str()),
+            ("code_challenge_method", "S256"),
+        ],
+    )
+    .map(String::from)
+    .map_err(|e| OidcError::Provider(format!("authorization_endpoint invalide: {e}")))
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    id_token: Option<String>,
+}
+
+/// Échange le code d'autorisation contre l'ID token (client_secret_post + PKCE).
+pub async fn exchange_code(
+    config: &AppConfig,
+    provider: &OidcProvider,
+    discovery: &Discovery,
+    code: &str,
+    code_verifier: &str,
+) -> Result<String, OidcError> {
+    let redirect_uri = provider.redirect_uri(config);
+    let mut form = vec![
+        ("grant_type", "authorization_code"),
+        ("code", code),
+        ("redirect_uri", redirect_uri.as_str()),
+        ("client_id", provider.client_id.as_str()),
+        ("code_verifier", code_verifier),
+    ];
+    if let Some(secret) = provider.client_secret.as_deref() {
+        form.push(("client_secret", secret));
+    }
+
+    let res = HTTP
+        .post(&discovery.token_endpoint)
+        .form(&form)
+        .send()
+        .await
+        .map_err(|e| OidcError::Provider(format!("token_endpoint: {e}")))?;
+    let status = res.status();
+    if !status.is_success() {
+        let body = res.text().await.unwrap_or_default();
+        return Err(OidcError::Provider(format!(
+            "échange du code refusé (HTTP {status}): {body}"
+        )));
+    }
+    res.json::<TokenResponse>()
+        .await
+        .map_err(|e| OidcError::Provider(format!("token_endpoint: {e}")))?
+        .id_token
+        .ok_or_else(|| OidcError::Provider("id_token absent de la réponse".into()))
+}
+
+/// Claims de l'ID token utilisés pour identifier ou créer le compte.
+#[derive(Clone, Debug, Deserialize)]
+pub struct IdTokenClaims {
+    pub sub: String,
+    pub email: Option<String>,
+    #[serde(default, deserialize_with = "bool_or_string")]
+    pub email_verified: bool,
+    pub preferred_username: Option<String>,
+    pub name: Option<String>,
+    pub nonce: Option<String>,
+}
+
+// Certains fournisseurs envoient "true"/"false" en chaîne
+fn bool_or_string<'de, D: Deserializer<'de>>(d: D) -> Result<bool, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Flag {
+        Bool(bool),
+        Str(String),
+    }
+    Ok(match Option::<Flag>::deserialize(d)? {
+        Some(Flag::Bool(b)) => b,
+        Some(Flag::Str(s)) => s.eq_ignore_ascii_case("true"),
+        None => false,
+    })
+}
+
+/// Vérifie signature (JWKS, ou client_secret pour HS*), émetteur, audience,
+/// expiration et nonce de l'ID token.
+pub async fn validate_id_token(
+    provider: &OidcProvider,
+    discovery: &Discovery,
+    id_token: &str,
+    nonce: &str,
+) -> Result<IdTokenClaims, OidcError> {
+    let header = decode_header(id_token).map_err(|e| OidcError::InvalidIdToken(e.to_string()))?;
+    let key = match header.alg {
+        // Signature symétrique avec le client_secret (OIDC Core §10.1)
+        Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512 => {
+            let secret = provider.client_secret.as_deref().ok_or_else(|| {
+                OidcError::InvalidIdToken("signature HS* sans client_secret".into())
+            })?;
+            DecodingKey::from_secret(secret.as_bytes())
+        }
+        _ => jwk_key(discovery, header.kid.as_deref()).await?,
+    };
+
+    let mut validation = Validation::new(header.alg);
+    validation.set_issuer(&[discovery.issuer.as_str()]);
+    validation.set_audience(&[provider.client_id.as_str()]);
+    validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+    validation.leeway = 60;
+    let claims = decode::<IdTokenClaims>(id_token, &key, &validation)
+        .map_err(|e| OidcError::InvalidIdToken(e.to_string()))?
+        .claims;
+
+    if claims.nonce.as_deref() != Some(nonce) {
+        return Err(OidcError::InvalidIdToken("nonce invalide".into()));
+    }
+    Ok(claims)
+}
+
+/// Compte retenu pour une identité externe.
+#[derive(Debug, PartialEq)]
+pub enum Resolved {
+    /// Identité déjà connue
+    Existing(Uuid),
+    /// Identité rattachée à un compte existant (compte connecté ou même email vérifié)
+    Linked(Uuid),
+    /// Premier login: compte créé
+    Created {
+        user_id: Uuid,
+        username: String,
+        email: Option<String>,
+    },
+}
+
+impl Resolved {
+    pub fn user_id(&self) -> Uuid {
+        match self {
+            Resolved::Existing(id) | Resolved::Linked(id) => *id,
+            Resolved::Created { user_id, .. } => *user_id,
+        }
+    }
+}
+
+/// Retrouve, rattache ou crée le compte correspondant à l'identité `claims`.
+/// `link_user`: compte connecté qui demande explicitement le rattachement.
+pub async fn resolve_user(
+    pool: &PgPool,
+    provider: &str,
+    claims: &IdTokenClaims,
+    link_user: Option<Uuid>,
+) -> Result<Resolved, OidcError> {
+    let known: Option<Uuid> = sqlx::query_scalar(
+        r#"
+        UPDATE user_identities SET last_login_at = NOW(), email = COALESCE($3, email)
+        WHERE provider = $1 AND subject = $2
+        RETURNING user_id
+        "#,
+    )
+    .bind(provider)
+    .bind(&claims.sub)
+    .bind(&claims.email)
+    .fetch_optional(pool)
+    .await?;
+    if let Some(user_id) = known {
+        return match link_user {
+            Some(current) if current != user_id => Err(OidcError::IdentityInUse),
+            _ => Ok(Resolved::Existing(user_id)),
+        };
+    }
+
+    // Email vérifié des deux côtés: même personne, on rattache plutôt que dupliquer
+    let by_email = match (&claims.email, claims.email_verified, link_user) {
+        (_, _, Some(_)) => None,
+        (Some(email), true, None) => {
+            sqlx::query_scalar::<_, Uuid>(
+                r#"
+                SELECT id FROM users
+                WHERE LOWER(email) = LOWER($1) AND email_verified_at IS NOT NULL
+                "#,
+            )
+            .bind(email)
+            .fetch_optional(pool)
+            .await?
+        }
+        _ => None,
+    };
+    if let Some(user_id) = link_user.or(by_email) {
+        insert_identity(pool, user_id, provider, claims).await?;
+        return Ok(Resolved::Linked(user_id));
+    }
+
+    create_user(pool, provider, claims).await
+}
+
+async fn insert_identity<'e, E>(
+    executor: E,
+    user_id: Uuid,
+    provider: &str,
+    claims: &IdTokenClaims,
+) -> Result<(), OidcError>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let res = sqlx::query(
+        r#"
+        INSERT INTO user_identities (user_id, provider, subject, email, last_login_at)
+        VALUES ($1, $2, $3, $4, NOW())
+        "#,
+    )
+    .bind(user_id)
+    .bind(provider)
+    .bind(&claims.sub)
+    .bind(&claims.email)
+    .execute(executor)
+    .await;
+    match res {
+        Ok(_) => Ok(()),
+        // Course entre deux callbacks pour la même identité
+        Err(sqlx::Error::Database(db)) if db.code().as_deref() == Some("23505") => {
+            Err(OidcError::IdentityInUse)
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+async fn create_user(
+    pool: &PgPool,
+    provider: &str,
+    claims: &IdTokenClaims,
+) -> Result<Resolved, OidcError> {
+    // Pas de mot de passe: hash d'un secret jetable (un reset par email reste possible)
+    let unusable =
+        hash_password(&random_token()).map_err(|e| OidcError::Internal(e.to_string()))?;
+
+    // Email déjà pris par un autre compte (non vérifié): le nouveau compte n'en a pas
+    let email = match &claims.email {
+        Some(email) => {
+            let taken: bool = sqlx::query_scalar(
+                r#"SELECT EXISTS (SELECT 1 FROM users WHERE LOWER(email) = LOWER($1))"#,
+            )
+            .bind(email)
+            .fetch_one(pool)
+            .await?;
+            (!taken).then(|| email.clone())
+        }
+        None => None,
+    };
+    let verified = email.is_some() && claims.email_verified;
+
+    let mut tx = pool.begin().await?;
+    let mut created = None;
+    for username in username_candidates(&username_base(claims)) {
+        let id: Option<Uuid> = sqlx::query_scalar(
+            r#"
+            INSERT INTO users (username, email, password, email_verified_at)
+            VALUES ($1, $2, $3, CASE WHEN $4 THEN NOW() END)
+            ON CONFLICT DO NOTHING
+            RETURNING id
+            "#,
+        )
+        .bind(&username)
+        .bind(&email)
+        .bind(&unusable)
+        .bind(verified)
+        .fetch_optional(&mut *tx)
+        .await?;
+        if let Some(id) = id {
+            created = Some((id, username));
+            break;
+        }
+    }
+    let Some((user_id, username)) = created else {
+        return Err(OidcError::Internal(
+            "aucun nom d'utilisateur disponible".into(),
+        ));
+    };
+    insert_identity(&mut *tx, user_id, provider, claims).await?;
+    tx.commit().await?;
+
+    Ok(Resolved::Created {
+        user_id,
+        username,
+        email,
+    })
+}
+
+const USERNAME_BASE_MAX: usize = 40;
+
+/// Nom d'utilisateur proposé: preferred_username, sinon nom, sinon début de l'email,
+/// réduit à [a-z0-9_].
+pub fn username_base(claims: &IdTokenClaims) -> String {
+    let raw = claims
+        .preferred_username
+        .as_deref()
+        .or(claims.name.as_deref())
+        .or(claims.email.as_deref().and_then(|e| e.split('@').next()))
+        .unwrap_or_default();
+
+    let mut base = String::new();
+    for c in raw.chars() {
+        if c.is_ascii_alphanumeric() {
+            base.push(c.to_ascii_lowercase());
+        } else if !base.is_empty() && !base.ends_with('_') {
+            base.push('_');
+        }
+    }
+    let mut base = base.trim_end_matches('_').to_string();
+    base.truncate(USERNAME_BASE_MAX);
+    if base.len() < 3 {
+        return "dresseur".to_string();
+    }
+    base
+}
+
+/// `base`, puis `base_2` … `base_9`, puis des suffixes aléatoires.
+pub fn username_candidates(base: &str) -> impl Iterator<Item = String> + '_ {
+    std::iter::once(base.to_string())
+        .chain((2..=9).map(move |n| format!("{base}_{n}")))
+        .chain((0..3).map(move |_| format!("{base}_{}", &Uuid::new_v4().simple().to_string()[..8])))
+}
+
+pub async fn list_identities(
+    pool: &PgPool,
+    user_id: Uuid,
+) -> Result<Vec<crate::models::auth::IdentityInfo>, sqlx::Error> {
+    sqlx::query_as(
+        r#"
+        SELECT id, provider, email, created_at, last_login_at
+        FROM user_identities WHERE user_id = $1
+        ORDER BY created_at
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn unlink(pool: &PgPool, user_id: Uuid, identity_id: Uuid) -> Result<bool, sqlx::Error> {
+    let res = sqlx::query(r#"DELETE FROM user_identities WHERE id = $1 AND user_id = $2"#)
+        .bind(identity_id)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+    Ok(res.rows_affected() > 0)
+}
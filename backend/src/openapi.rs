@@ -0,0 +1,131 @@
+//! Spécification OpenAPI 3.1 générée à partir des annotations `#[utoipa::path]`
+//! des handlers et des `ToSchema` des modèles: elle ne peut pas diverger du code.
+//!
+//! Servie sur `/api/openapi.json`, avec Swagger UI (embarqué) sur `/api/docs`.
+//! Copie de référence: `backend/openapi.json`, comparée par `tests/openapi.rs`,
+//! qui vérifie aussi que chaque route de `src/routes/` y figure.
+
+use utoipa::openapi::security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+use utoipa_swagger_ui::SwaggerUi;
+
+use crate::handlers::{
+    admin, api_tokens, audit, auth, daily, export, health, mfa, oidc, pokemon, user,
+};
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "PokeRNCP API",
+        description = "Pokédex: catalogue, captures, comptes et authentification.\n\n\
+            Routes servies sous /api/v1; /api/v2 ne sert que les endpoints décrits sous ce \
+            préfixe. L'alias /api/… est obsolète: en-têtes Deprecation et Sunset."
+    ),
+    paths(
+        pokemon::list_all,
+        pokemon::list_page,
+        pokemon::search_pokemons,
+        pokemon::catch,
+        pokemon::get_pokemon_by_id,
+        user::create_user,
+        user::update_user,
+        user::delete_user,
+        auth::login_user,
+        mfa::login_two_factor,
+        auth::refresh_token,
+        auth::logout_user,
+        auth::logout_all,
+        auth::me,
+        auth::change_password,
+        auth::request_password_reset,
+        auth::confirm_password_reset,
+        auth::unlock_account,
+        auth::verify_email,
+        auth::resend_verification,
+        auth::list_sessions,
+        auth::revoke_session,
+        mfa::setup_two_factor,
+        mfa::verify_two_factor,
+        mfa::disable_two_factor,
+        oidc::list_providers,
+        oidc::start_login,
+        oidc::start_link,
+        oidc::callback,
+        oidc::list_identities,
+        oidc::unlink_identity,
+        daily::get_daily,
+        api_tokens::list_tokens,
+        api_tokens::create_token,
+        api_tokens::revoke_token,
+        audit::security_events,
+        export::export_data,
+        admin::list_users,
+        admin::get_user,
+        admin::lock_user,
+        admin::unlock_user,
+        admin::force_password_reset,
+        admin::set_role,
+        admin::delete_user,
+        admin::update_pokemon,
+        admin::stats,
+        admin::list_audit_events,
+        admin::prune_audit_events,
+        auth::jwks,
+        health::healthz,
+        health::readyz,
+        health::version,
+    ),
+    modifiers(&SecuritySchemes),
+    tags(
+        (name = "pokemons", description = "Catalogue et captures"),
+        (name = "users", description = "Inscription et profil"),
+        (name = "auth", description = "Sessions, mots de passe, 2FA et OpenID Connect"),
+        (name = "me", description = "Défi du jour, tokens d'accès personnels, historique de sécurité et export"),
+        (name = "admin", description = "Modération des comptes et du catalogue, statistiques et journal d'audit"),
+        (name = "sondes", description = "Vivacité, disponibilité et version"),
+    )
+)]
+pub struct ApiDoc;
+
+/// Access token (JWT) ou token d'accès personnel en Bearer; sinon cookie de session.
+struct SecuritySchemes;
+
+impl Modify for SecuritySchemes {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        // Pas de licence déclarée dans Cargo.toml
+        openapi.info.license = None;
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .description(Some(
+                        "Access token JWT, ou token d'accès personnel (pkr_…) limité à ses scopes",
+                    ))
+                    .build(),
+            ),
+        );
+        components.add_security_scheme(
+            "cookie",
+            SecurityScheme::ApiKey(ApiKey::Cookie(ApiKeyValue::with_description(
+                "auth",
+                "Session du navigateur; les mutations exigent aussi l'en-tête X-CSRF-Token",
+            ))),
+        );
+    }
+}
+
+/// Spécification au format JSON indenté (contenu de `backend/openapi.json`).
+pub fn to_json() -> String {
+    ApiDoc::openapi()
+        .to_pretty_json()
+        .expect("sérialisation de la spécification OpenAPI")
+}
+
+/// `/api/openapi.json` et Swagger UI sur `/api/docs`.
+pub fn docs_routes<S: Clone + Send + Sync + 'static>() -> axum::Router<S> {
+    SwaggerUi::new("/api/docs")
+        .url("/api/openapi.json", ApiDoc::openapi())
+        .into()
+}
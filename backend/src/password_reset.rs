@@ -0,0 +1,194 @@
+use sha2::{Digest, Sha256};
+use sqlx::{PgPool, Postgres, Transaction};
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::clock::Clock;
+use crate::config::PasswordResetConfig;
+use crate::rng::Rng;
+
+/// Seul ce hash est stocké: une fuite de la table ne permet pas de réinitialiser un mot de passe.
+pub fn hash_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+/// Émet un nouveau token de reset valable `ttl_secs` (RESET_TTL_SECONDS); les
+/// tokens encore valides du compte sont révoqués.
+pub async fn issue(
+    pool: &PgPool,
+    clock: &dyn Clock,
+    rng: &dyn Rng,
+    user_id: Uuid,
+    ttl_secs: i64,
+) -> Result<String, sqlx::Error> {
+    let token = rng.hex(32);
+
+    let mut tx = pool.begin().await?;
+    revoke_outstanding(&mut tx, user_id).await?;
+    sqlx::query(
+        r#"
+        INSERT INTO password_reset_tokens (user_id, token_hash, expires_at)
+        VALUES ($1, $2, $4 + make_interval(secs => $3))
+        "#,
+    )
+    .bind(user_id)
+    .bind(hash_token(&token))
+    .bind(ttl_secs as f64)
+    .bind(clock.now())
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    Ok(token)
+}
+
+/// Consomme le token dans la transaction du changement de mot de passe:
+/// `None` s'il est inconnu, expiré, révoqué ou déjà utilisé.
+pub async fn consume(
+    tx: &mut Transaction<'_, Postgres>,
+    clock: &dyn Clock,
+    token: &str,
+) -> Result<Option<Uuid>, sqlx::Error> {
+    let user_id: Option<Uuid> = sqlx::query_scalar(
+        r#"
+        UPDATE password_reset_tokens SET used_at = $2
+        WHERE token_hash = $1
+          AND used_at IS NULL AND revoked_at IS NULL AND expires_at > $2
+        RETURNING user_id
+        "#,
+    )
+    .bind(hash_token(token))
+    .bind(clock.now())
+    .fetch_optional(&mut **tx)
+    .await?;
+
+    if let Some(user_id) = user_id {
+        revoke_outstanding(tx, user_id).await?;
+    }
+    Ok(user_id)
+}
+
+async fn revoke_outstanding(
+    tx: &mut Transaction<'_, Postgres>,
+    user_id: Uuid,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE password_reset_tokens SET revoked_at = NOW()
+        WHERE user_id = $1 AND used_at IS NULL AND revoked_at IS NULL
+        "#,
+    )
+    .bind(user_id)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+/// Invalide les tokens en attente (ex: le mot de passe vient d'être changé).
+pub async fn revoke_all(pool: &PgPool, user_id: Uuid) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    revoke_outstanding(&mut tx, user_id).await?;
+    tx.commit().await
+}
+
+/// Nombre de secondes avant de pouvoir refaire une demande depuis cette IP,
+/// `None` si la limite n'est pas atteinte.
+pub async fn ip_retry_after(
+    pool: &PgPool,
+    clock: &dyn Clock,
+    config: &PasswordResetConfig,
+    ip: Option<&str>,
+) -> Result<Option<i64>, sqlx::Error> {
+    let Some(ip) = ip else {
+        return Ok(None);
+    };
+    let (count, retry_after) = sqlx::query_as::<_, (i64, Option<f64>)>(
+        r#"
+        SELECT COUNT(*),
+               EXTRACT(EPOCH FROM MIN(created_at) + make_interval(secs => $2) - $3)::FLOAT8
+        FROM password_reset_requests
+        WHERE ip = $1 AND created_at > $3 - make_interval(secs => $2)
+        "#,
+    )
+    .bind(ip)
+    .bind(config.rate_window_secs as f64)
+    .bind(clock.now())
+    .fetch_one(pool)
+    .await?;
+
+    if count < config.max_per_ip {
+        return Ok(None);
+    }
+    Ok(Some(retry_after.unwrap_or(0.0).ceil().max(1.0) as i64))
+}
+
+/// Le compte a-t-il déjà reçu le nombre maximal de liens sur la fenêtre ?
+pub async fn account_limited(
+    pool: &PgPool,
+    clock: &dyn Clock,
+    config: &PasswordResetConfig,
+    user_id: Uuid,
+) -> Result<bool, sqlx::Error> {
+    let count: i64 = sqlx::query_scalar(
+        r#"
+        SELECT COUNT(*) FROM password_reset_requests
+        WHERE user_id = $1 AND sent AND created_at > $3 - make_interval(secs => $2)
+        "#,
+    )
+    .bind(user_id)
+    .bind(config.rate_window_secs as f64)
+    .bind(clock.now())
+    .fetch_one(pool)
+    .await?;
+    Ok(count >= config.max_per_account)
+}
+
+/// Journalise une demande; toutes comptent pour l'IP, seules celles ayant
+/// donné lieu à un lien (`sent`) comptent pour le compte.
+pub async fn log_request(
+    pool: &PgPool,
+    clock: &dyn Clock,
+    ip: Option<&str>,
+    user_id: Option<Uuid>,
+    sent: bool,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO password_reset_requests (ip, user_id, sent, created_at)
+        VALUES ($1, $2, $3, $4)
+        "#,
+    )
+    .bind(ip)
+    .bind(user_id)
+    .bind(sent)
+    .bind(clock.now())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Supprime les demandes sorties de la fenêtre de limitation: elles ne comptent
+/// plus pour aucune limite, et la table ne grossit pas au gré des appels anonymes.
+pub async fn prune_requests(pool: &PgPool, window_secs: i64) -> Result<u64, sqlx::Error> {
+    let deleted = sqlx::query(
+        "DELETE FROM password_reset_requests WHERE created_at < NOW() - make_interval(secs => $1)",
+    )
+    .bind(window_secs as f64)
+    .execute(pool)
+    .await?
+    .rows_affected();
+    Ok(deleted)
+}
+
+/// Purge horaire des demandes sorties de la fenêtre RESET_RATE_WINDOW_SECONDS.
+pub fn spawn_prune(pool: PgPool, window_secs: i64) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(3600));
+        loop {
+            interval.tick().await;
+            if let Err(e) = prune_requests(&pool, window_secs).await {
+                eprintln!("❌ Purge des demandes de réinitialisation: {e}");
+            }
+        }
+    });
+}
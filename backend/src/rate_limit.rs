@@ -0,0 +1,291 @@
+use axum::extract::{ConnectInfo, Request, State};
+use axum::http::{HeaderMap, HeaderName, HeaderValue, header};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use crate::auth::{get_bearer, get_cookie, verify_access};
+use crate::config::{AppConfig, RateLimitConfig};
+use crate::helpers::too_many_requests;
+
+/// Politique de limitation: `limit` requêtes par fenêtre de `window_secs`
+/// (seau de jetons de capacité `limit`, rechargé en continu).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Policy {
+    pub name: &'static str,
+    pub limit: u32,
+    pub window_secs: u32,
+}
+
+impl Policy {
+    /// Connexion: limite stricte (brute-force)
+    pub const LOGIN: Policy = Policy::new("login", 10, 60);
+    /// Demandes de reset du mot de passe
+    pub const PASSWORD_RESET: Policy = Policy::new("password_reset", 5, 300);
+    /// Inscription
+    pub const SIGNUP: Policy = Policy::new("signup", 10, 3600);
+    /// Catalogue Pokémon (navigation, recherche): large
+    pub const POKEMONS: Policy = Policy::new("pokemons", 300, 60);
+    /// Tout le reste de l'API
+    pub const DEFAULT: Policy = Policy::new("default", 120, 60);
+
+    pub const fn new(name: &'static str, limit: u32, window_secs: u32) -> Policy {
+        Policy {
+            name,
+            limit,
+            window_secs,
+        }
+    }
+
+    /// Toutes les politiques, surchargeables par `rate_limit.policies` (RATE_LIMIT_<NAME>)
+    pub const ALL: [Policy; 5] = [
+        Policy::LOGIN,
+        Policy::PASSWORD_RESET,
+        Policy::SIGNUP,
+        Policy::POKEMONS,
+        Policy::DEFAULT,
+    ];
+
+    /// Applique la surcharge configurée ("limite/fenêtre", ex: "10/60"), validée au chargement.
+    pub fn configured(self, config: &RateLimitConfig) -> Policy {
+        match config
+            .policies
+            .get(self.name)
+            .and_then(|value| parse_override(value))
+        {
+            Some((limit, window_secs)) => Policy {
+                limit,
+                window_secs,
+                ..self
+            },
+            None => self,
+        }
+    }
+
+    fn refill_per_sec(&self) -> f64 {
+        self.limit as f64 / self.window_secs as f64
+    }
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Résultat d'un passage au limiteur, de quoi remplir les en-têtes RateLimit-*.
+#[derive(Debug)]
+pub struct Decision {
+    pub allowed: bool,
+    pub limit: u32,
+    pub remaining: u32,
+    /// Secondes avant qu'un jeton soit disponible (refus) ou que le seau soit plein
+    pub reset_secs: u64,
+}
+
+const MAX_BUCKETS: usize = 50_000;
+
+/// "limite/fenêtre_en_secondes", deux entiers strictement positifs.
+pub fn parse_override(value: &str) -> Option<(u32, u32)> {
+    let (limit, window) = value.split_once('/')?;
+    let limit = limit.trim().parse().ok().filter(|l| *l > 0)?;
+    let window = window.trim().parse().ok().filter(|w| *w > 0)?;
+    Some((limit, window))
+}
+
+/// Limiteur en mémoire (un par politique et par instance de l'application),
+/// à poser avec `from_fn_with_state(RateLimiter::new(config, policy), rate_limit)`.
+#[derive(Clone)]
+pub struct RateLimiter {
+    policy: Policy,
+    buckets: Arc<Mutex<HashMap<String, Bucket>>>,
+    config: &'static AppConfig,
+}
+
+impl RateLimiter {
+    /// `policy` surchargée par `rate_limit.policies` de `config`.
+    pub fn new(config: &'static AppConfig, policy: Policy) -> Self {
+        RateLimiter {
+            policy: policy.configured(&config.rate_limit),
+            buckets: Arc::new(Mutex::new(HashMap::new())),
+            config,
+        }
+    }
+
+    pub fn policy(&self) -> Policy {
+        self.policy
+    }
+
+    /// Consomme un jeton pour `key` si possible.
+    pub fn check(&self, key: &str) -> Decision {
+        let policy = self.policy;
+        let capacity = policy.limit as f64;
+        let rate = policy.refill_per_sec();
+        let now = Instant::now();
+
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.len() >= MAX_BUCKETS {
+            // Les seaux redevenus pleins n'apportent plus rien: on les oublie
+            buckets.retain(|_, b| {
+                b.tokens + now.duration_since(b.updated).as_secs_f64() * rate < capacity
+            });
+        }
+        let bucket = buckets.entry(key.to_string()).or_insert(Bucket {
+            tokens: capacity,
+            updated: now,
+        });
+        bucket.tokens =
+            (bucket.tokens + now.duration_since(bucket.updated).as_secs_f64() * rate).min(capacity);
+        bucket.updated = now;
+
+        let allowed = bucket.tokens >= 1.0;
+        if allowed {
+            bucket.tokens -= 1.0;
+        }
+        let reset_secs = if allowed {
+            ((capacity - bucket.tokens) / rate).ceil() as u64
+        } else {
+            ((1.0 - bucket.tokens) / rate).ceil().max(1.0) as u64
+        };
+        Decision {
+            allowed,
+            limit: policy.limit,
+            remaining: bucket.tokens.floor() as u32,
+            reset_secs,
+        }
+    }
+}
+
+pub async fn rate_limit(State(limiter): State<RateLimiter>, req: Request, next: Next) -> Response {
+    let config = limiter.config;
+    if !config.rate_limit.enabled {
+        return next.run(req).await;
+    }
+
+    let key = request_key(config, &req);
+    let decision = limiter.check(&key);
+    let policy = limiter.policy();
+
+    let mut res = if decision.allowed {
+        next.run(req).await
+    } else {
+        let mut res = too_many_requests("Trop de requêtes, réessaie plus tard").into_response();
+        res.headers_mut()
+            .insert(header::RETRY_AFTER, HeaderValue::from(decision.reset_secs));
+        res
+    };
+
+    // En-têtes RateLimit-* (draft IETF): quand plusieurs politiques s'appliquent,
+    // celle de la route (couche la plus interne, qui répond en premier) l'emporte.
+    let headers = res.headers_mut();
+    if !headers.contains_key("ratelimit-limit") {
+        headers.insert(
+            HeaderName::from_static("ratelimit-policy"),
+            HeaderValue::from_str(&format!("{};w={}", policy.limit, policy.window_secs)).unwrap(),
+        );
+        headers.insert(
+            HeaderName::from_static("ratelimit-limit"),
+            HeaderValue::from(decision.limit),
+        );
+        headers.insert(
+            HeaderName::from_static("ratelimit-remaining"),
+            HeaderValue::from(decision.remaining),
+        );
+        headers.insert(
+            HeaderName::from_static("ratelimit-reset"),
+            HeaderValue::from(decision.reset_secs),
+        );
+    }
+    res
+}
+
+/// Clé de limitation: l'utilisateur si un access token valide est présent, l'IP sinon.
+fn request_key(config: &AppConfig, req: &Request) -> String {
+    let headers = req.headers();
+    if let Some(token) = get_cookie(headers, "auth").or_else(|| get_bearer(headers))
+        && let Ok(claims) = verify_access(config, &token)
+    {
+        return format!("user:{}", claims.sub);
+    }
+    let peer = req
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip());
+    match peer {
+        Some(peer) => format!(
+            "ip:{}",
+            client_ip(peer, headers, &config.server.trusted_nets())
+        ),
+        None => "ip:unknown".to_string(),
+    }
+}
+
+/// Réseau de confiance: une IP ("10.0.0.1") ou un bloc CIDR ("10.0.0.0/8").
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TrustedNet {
+    addr: IpAddr,
+    prefix: u8,
+}
+
+impl TrustedNet {
+    pub fn parse(s: &str) -> Option<TrustedNet> {
+        let (addr, prefix) = match s.split_once('/') {
+            Some((a, p)) => (
+                a.trim().parse::<IpAddr>().ok()?,
+                Some(p.trim().parse().ok()?),
+            ),
+            None => (s.trim().parse::<IpAddr>().ok()?, None),
+        };
+        let max = if addr.is_ipv4() { 32 } else { 128 };
+        let prefix = prefix.unwrap_or(max);
+        (prefix <= max).then_some(TrustedNet { addr, prefix })
+    }
+
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        match (self.addr, ip) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                let mask = u32::MAX.checked_shl(32 - self.prefix as u32).unwrap_or(0);
+                u32::from(net) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                let mask = u128::MAX.checked_shl(128 - self.prefix as u32).unwrap_or(0);
+                u128::from(net) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+/// IP du client: X-Forwarded-For n'est lu que si la connexion vient d'un proxy
+/// de confiance; on remonte alors la chaîne depuis la droite jusqu'à la
+/// première adresse qui n'est pas un proxy de confiance.
+pub fn client_ip(peer: IpAddr, headers: &HeaderMap, trusted: &[TrustedNet]) -> IpAddr {
+    let is_trusted = |ip: IpAddr| trusted.iter().any(|net| net.contains(ip));
+    if !is_trusted(peer) {
+        return peer;
+    }
+    let forwarded = headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .map(|s| s.trim().parse::<IpAddr>())
+        .collect::<Vec<_>>();
+
+    let mut client = peer;
+    for hop in forwarded.into_iter().rev() {
+        match hop {
+            Ok(ip) => {
+                client = ip;
+                if !is_trusted(ip) {
+                    break;
+                }
+            }
+            // Entrée illisible: on s'arrête au dernier saut fiable
+            Err(_) => break,
+        }
+    }
+    client
+}
@@ -0,0 +1,26 @@
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use std::sync::Arc;
+
+/// Aléa des secrets émis par l'application (tokens de reset, tokens d'API,
+/// codes de récupération). Les tests peuvent injecter une source déterministe.
+pub trait Rng: Send + Sync {
+    fn fill(&self, buf: &mut [u8]);
+
+    /// `bytes` octets aléatoires en hexadécimal (2 caractères par octet).
+    fn hex(&self, bytes: usize) -> String {
+        let mut buf = vec![0u8; bytes];
+        self.fill(&mut buf);
+        buf.iter().map(|b| format!("{b:02x}")).collect()
+    }
+}
+
+pub type SharedRng = Arc<dyn Rng>;
+
+/// Générateur du système d'exploitation.
+pub struct SystemRng;
+
+impl Rng for SystemRng {
+    fn fill(&self, buf: &mut [u8]) {
+        OsRng.fill_bytes(buf);
+    }
+}
@@ -0,0 +1,98 @@
+use axum::extract::{FromRef, FromRequestParts};
+use axum::http::{StatusCode, request::Parts};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::marker::PhantomData;
+use uuid::Uuid;
+
+use crate::auth::CurrentUser;
+use crate::clock::SharedClock;
+use crate::config::AppConfig;
+
+/// Rôle d'un compte, du moins au plus privilégié (l'ordre sert aux comparaisons).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    User,
+    Moderator,
+    Admin,
+}
+
+impl Role {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Role::User => "user",
+            Role::Moderator => "moderator",
+            Role::Admin => "admin",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Role> {
+        match s {
+            "user" => Some(Role::User),
+            "moderator" => Some(Role::Moderator),
+            "admin" => Some(Role::Admin),
+            _ => None,
+        }
+    }
+}
+
+/// Rôle minimal exigé par `RequireRole<R>`.
+pub trait MinRole {
+    const ROLE: Role;
+}
+
+pub struct Moderator;
+pub struct Admin;
+
+impl MinRole for Moderator {
+    const ROLE: Role = Role::Moderator;
+}
+
+impl MinRole for Admin {
+    const ROLE: Role = Role::Admin;
+}
+
+/// Utilisateur authentifié ayant au moins le rôle `R` (les rôles supérieurs passent):
+/// `RequireRole::<Admin>` refuse un modérateur, `RequireRole::<Moderator>` accepte un admin.
+pub struct RequireRole<R: MinRole> {
+    pub user_id: Uuid,
+    pub role: Role,
+    _role: PhantomData<R>,
+}
+
+pub async fn role_of(pool: &PgPool, user_id: Uuid) -> Result<Option<Role>, sqlx::Error> {
+    let role: Option<String> = sqlx::query_scalar(r#"SELECT role FROM users WHERE id = $1"#)
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?;
+    Ok(role.as_deref().and_then(Role::parse))
+}
+
+impl<S, R> FromRequestParts<S> for RequireRole<R>
+where
+    PgPool: FromRef<S>,
+    &'static AppConfig: FromRef<S>,
+    SharedClock: FromRef<S>,
+    S: Send + Sync,
+    R: MinRole,
+{
+    type Rejection = (StatusCode, String);
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let CurrentUser(user_id) = CurrentUser::from_request_parts(parts, state).await?;
+        // Lu en base à chaque requête: un changement de rôle s'applique immédiatement
+        let role = role_of(&PgPool::from_ref(state), user_id)
+            .await
+            .map_err(crate::helpers::to_500)?
+            .ok_or((StatusCode::UNAUTHORIZED, "Utilisateur introuvable".into()))?;
+        if role < R::ROLE {
+            return Err((StatusCode::FORBIDDEN, "Droits insuffisants".into()));
+        }
+        Ok(RequireRole {
+            user_id,
+            role,
+            _role: PhantomData,
+        })
+    }
+}
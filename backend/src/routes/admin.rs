@@ -0,0 +1,28 @@
+use axum::Router;
+use axum::routing::{get, patch, post};
+
+use crate::handlers::admin::{
+    delete_user, force_password_reset, get_user, list_audit_events, list_users, lock_user,
+    prune_audit_events, set_role, stats, unlock_user, update_pokemon,
+};
+use crate::state::AppState;
+
+/// Routes d'administration: le rôle requis est porté par chaque handler (`RequireRole`).
+pub fn admin_routes() -> Router<AppState> {
+    Router::new()
+        .route("/users", get(list_users))
+        .route("/users/{id}", get(get_user).delete(delete_user))
+        .route("/users/{id}/lock", post(lock_user))
+        .route("/users/{id}/unlock", post(unlock_user))
+        .route(
+            "/users/{id}/force-password-reset",
+            post(force_password_reset),
+        )
+        .route("/users/{id}/role", patch(set_role))
+        .route("/pokemons/{id}", patch(update_pokemon))
+        .route("/stats", get(stats))
+        .route(
+            "/audit-events",
+            get(list_audit_events).delete(prune_audit_events),
+        )
+}
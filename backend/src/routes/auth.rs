@@ -1,20 +1,74 @@
+use crate::api_tokens::{RequiredScope, SCOPE_PROFILE_READ};
+use crate::config::AppConfig;
 use crate::handlers::auth::{
-    change_password, confirm_password_reset, login_user, logout_user, me, refresh_token,
-    request_password_reset,
+    change_password, confirm_password_reset, list_sessions, login_user, logout_all, logout_user,
+    me, refresh_token, request_password_reset, resend_verification, revoke_session, unlock_account,
+    verify_email,
 };
-use axum::Router;
-use axum::routing::{get, post, put};
-use sqlx::PgPool;
+use crate::handlers::mfa::{
+    disable_two_factor, login_two_factor, setup_two_factor, verify_two_factor,
+};
+use crate::handlers::oidc::{
+    callback, list_identities, list_providers, start_link, start_login, unlink_identity,
+};
+use crate::rate_limit::{Policy, RateLimiter, rate_limit};
+use crate::state::AppState;
+use axum::middleware::from_fn_with_state;
+use axum::routing::{delete, get, post, put};
+use axum::{Extension, Router};
 
-pub fn user_routes() -> Router<PgPool> {
+pub fn user_routes(config: &'static AppConfig) -> Router<AppState> {
     Router::new()
         //PUBLIC
-        .route("/login", post(login_user))
+        .route(
+            "/login",
+            post(login_user).layer(from_fn_with_state(
+                RateLimiter::new(config, Policy::LOGIN),
+                rate_limit,
+            )),
+        )
+        .route(
+            "/2fa/login",
+            post(login_two_factor).layer(from_fn_with_state(
+                RateLimiter::new(config, Policy::LOGIN),
+                rate_limit,
+            )),
+        )
+        .route("/oidc/providers", get(list_providers))
+        .route("/oidc/{provider}/start", get(start_login))
+        .route(
+            "/oidc/{provider}/callback",
+            get(callback).layer(from_fn_with_state(
+                RateLimiter::new(config, Policy::LOGIN),
+                rate_limit,
+            )),
+        )
         .route("/refresh-token", post(refresh_token))
         .route("/logout", post(logout_user))
         //PRIVE
-        .route("/request-password-reset", post(request_password_reset))
+        .route(
+            "/request-password-reset",
+            post(request_password_reset).layer(from_fn_with_state(
+                RateLimiter::new(config, Policy::PASSWORD_RESET),
+                rate_limit,
+            )),
+        )
         .route("/confirm-password-reset", post(confirm_password_reset))
-        .route("/me", get(me))
+        .route("/unlock-account", post(unlock_account))
+        .route("/verify-email", post(verify_email))
+        .route("/resend-verification", post(resend_verification))
+        .route(
+            "/me",
+            get(me).layer(Extension(RequiredScope(SCOPE_PROFILE_READ))),
+        )
         .route("/change-password", put(change_password))
+        .route("/2fa/setup", post(setup_two_factor))
+        .route("/2fa/verify", post(verify_two_factor))
+        .route("/2fa/disable", post(disable_two_factor))
+        .route("/oidc/{provider}/link", get(start_link))
+        .route("/identities", get(list_identities))
+        .route("/identities/{id}", delete(unlink_identity))
+        .route("/sessions", get(list_sessions))
+        .route("/sessions/{id}", delete(revoke_session))
+        .route("/logout-all", post(logout_all))
 }
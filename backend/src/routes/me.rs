@@ -0,0 +1,24 @@
+use axum::routing::{delete, get};
+use axum::{Extension, Router};
+
+use crate::api_tokens::{RequiredScope, SCOPE_COLLECTION_READ};
+use crate::handlers::api_tokens::{create_token, list_tokens, revoke_token};
+use crate::handlers::audit::security_events;
+use crate::handlers::daily::get_daily;
+use crate::handlers::export::export_data;
+use crate::state::AppState;
+
+pub fn me_routes() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/daily",
+            get(get_daily).layer(Extension(RequiredScope(SCOPE_COLLECTION_READ))),
+        )
+        // Tokens d'accès personnels (session uniquement: un token ne peut pas en créer)
+        .route("/tokens", get(list_tokens).post(create_token))
+        .route("/tokens/{id}", delete(revoke_token))
+        // Historique de sécurité (session uniquement)
+        .route("/security-events", get(security_events))
+        // Export des données personnelles (session uniquement)
+        .route("/export", get(export_data))
+}
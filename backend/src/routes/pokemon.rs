@@ -1,13 +1,41 @@
-use axum::Router;
+use axum::middleware::from_fn_with_state;
 use axum::routing::{get, post};
-use sqlx::PgPool;
+use axum::{Extension, Router};
 
-use crate::handlers::pokemon::{catch, get_pokemon_by_id, list_all, search_pokemons};
+use crate::api_tokens::{RequiredScope, SCOPE_COLLECTION_WRITE, SCOPE_POKEDEX_READ};
+use crate::config::AppConfig;
+use crate::handlers::pokemon::{catch, get_pokemon_by_id, list_all, list_page, search_pokemons};
+use crate::rate_limit::{Policy, RateLimiter, rate_limit};
+use crate::state::AppState;
 
-pub fn pokemon_routes() -> Router<PgPool> {
+/// `limiter`: politique POKEMONS, partagée avec `pokemon_v2_routes`.
+pub fn pokemon_routes(limiter: RateLimiter) -> Router<AppState> {
     Router::new()
-        .route("/", get(list_all))
-        .route("/search", get(search_pokemons))
-        .route("/catch", post(catch))
-        .route("/{pokemon_id}", get(get_pokemon_by_id))
+        .route("/", get(list_all).layer(pokedex_read()))
+        .route("/search", get(search_pokemons).layer(pokedex_read()))
+        .route(
+            "/catch",
+            post(catch).layer(Extension(RequiredScope(SCOPE_COLLECTION_WRITE))),
+        )
+        .route(
+            "/{pokemon_id}",
+            get(get_pokemon_by_id).layer(pokedex_read()),
+        )
+        .route_layer(from_fn_with_state(limiter, rate_limit))
+}
+
+/// Endpoints du catalogue qui ont une v2 (montés sous `/api/v2/pokemons`).
+pub fn pokemon_v2_routes(limiter: RateLimiter) -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_page).layer(pokedex_read()))
+        .route_layer(from_fn_with_state(limiter, rate_limit))
+}
+
+/// Politique de débit du catalogue, à partager entre les versions.
+pub fn pokemon_limiter(config: &'static AppConfig) -> RateLimiter {
+    RateLimiter::new(config, Policy::POKEMONS)
+}
+
+fn pokedex_read() -> Extension<RequiredScope> {
+    Extension(RequiredScope(SCOPE_POKEDEX_READ))
 }
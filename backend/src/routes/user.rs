@@ -1,11 +1,20 @@
+use crate::config::AppConfig;
 use crate::handlers::user::{create_user, delete_user, update_user};
+use crate::rate_limit::{Policy, RateLimiter, rate_limit};
+use crate::state::AppState;
 use axum::Router;
+use axum::middleware::from_fn_with_state;
 use axum::routing::{delete, patch, post};
-use sqlx::PgPool;
 
-pub fn user_routes() -> Router<PgPool> {
+pub fn user_routes(config: &'static AppConfig) -> Router<AppState> {
     Router::new()
-        .route("/", post(create_user))
+        .route(
+            "/",
+            post(create_user).layer(from_fn_with_state(
+                RateLimiter::new(config, Policy::SIGNUP),
+                rate_limit,
+            )),
+        )
         .route("/{id}", patch(update_user))
         .route("/{id}", delete(delete_user))
 }
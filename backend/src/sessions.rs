@@ -0,0 +1,314 @@
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::auth::{generate_refresh_token, generate_session_access_token};
+use crate::clock::Clock;
+use crate::config::AppConfig;
+use crate::helpers::ClientInfo;
+use crate::models::auth::{Claims, SessionInfo};
+
+/// Couple de tokens émis pour une session.
+#[derive(Debug)]
+pub struct SessionTokens {
+    pub session_id: Uuid,
+    pub access: String,
+    pub refresh: String,
+    /// Durée de vie restante du refresh token (secondes), pour le Max-Age du cookie
+    pub refresh_max_age: i64,
+}
+
+#[derive(Debug)]
+pub enum SessionError {
+    /// Session inconnue, expirée ou révoquée
+    Invalid,
+    /// Un refresh token déjà remplacé a été présenté: toute la session est révoquée
+    Reused,
+    Db(sqlx::Error),
+    Token(jsonwebtoken::errors::Error),
+}
+
+impl std::fmt::Display for SessionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SessionError::Invalid => write!(f, "Session invalide"),
+            SessionError::Reused => write!(f, "Refresh token réutilisé"),
+            SessionError::Db(e) => write!(f, "{e}"),
+            SessionError::Token(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl From<sqlx::Error> for SessionError {
+    fn from(e: sqlx::Error) -> Self {
+        SessionError::Db(e)
+    }
+}
+
+impl From<jsonwebtoken::errors::Error> for SessionError {
+    fn from(e: jsonwebtoken::errors::Error) -> Self {
+        SessionError::Token(e)
+    }
+}
+
+fn issue(
+    config: &AppConfig,
+    clock: &dyn Clock,
+    user_id: Uuid,
+    session_id: Uuid,
+    jti: Uuid,
+    expires_at: OffsetDateTime,
+) -> Result<SessionTokens, SessionError> {
+    let exp = expires_at.unix_timestamp();
+    Ok(SessionTokens {
+        session_id,
+        access: generate_session_access_token(config, user_id, session_id)?,
+        refresh: generate_refresh_token(config, user_id, session_id, jti, exp)?,
+        refresh_max_age: (exp - clock.unix_now()).max(0),
+    })
+}
+
+/// Ouvre une nouvelle session (login) et émet ses premiers tokens.
+pub async fn start_session(
+    pool: &PgPool,
+    clock: &dyn Clock,
+    config: &AppConfig,
+    user_id: Uuid,
+    client: &ClientInfo,
+) -> Result<SessionTokens, SessionError> {
+    let jti = Uuid::new_v4();
+    let (session_id, expires_at) = sqlx::query_as::<_, (Uuid, OffsetDateTime)>(
+        r#"
+        INSERT INTO sessions (user_id, current_jti, user_agent, ip, expires_at)
+        VALUES ($1, $2, $3, $4, $6 + make_interval(secs => $5))
+        RETURNING id, expires_at
+        "#,
+    )
+    .bind(user_id)
+    .bind(jti)
+    .bind(&client.user_agent)
+    .bind(&client.ip)
+    .bind(config.auth.refresh_ttl_secs as f64)
+    .bind(clock.now())
+    .fetch_one(pool)
+    .await?;
+
+    issue(config, clock, user_id, session_id, jti, expires_at)
+}
+
+/// Rotation: le refresh token présenté est échangé contre un nouveau couple.
+/// Présenter un ancien refresh token révoque la session entière.
+pub async fn rotate(
+    pool: &PgPool,
+    clock: &dyn Clock,
+    config: &AppConfig,
+    claims: &Claims,
+    client: &ClientInfo,
+) -> Result<SessionTokens, SessionError> {
+    let (Some(session_id), Some(jti)) = (claims.sid, claims.jti) else {
+        return Err(SessionError::Invalid);
+    };
+
+    let mut tx = pool.begin().await?;
+    let row = sqlx::query_as::<_, (Uuid, Uuid, OffsetDateTime, bool)>(
+        r#"
+        SELECT user_id, current_jti, expires_at,
+               revoked_at IS NULL AND expires_at > $2 AS active
+        FROM sessions WHERE id = $1
+        FOR UPDATE
+        "#,
+    )
+    .bind(session_id)
+    .bind(clock.now())
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let Some((user_id, current_jti, expires_at, active)) = row else {
+        return Err(SessionError::Invalid);
+    };
+    if !active || user_id != claims.sub {
+        return Err(SessionError::Invalid);
+    }
+    if current_jti != jti {
+        sqlx::query(
+            r#"UPDATE sessions SET revoked_at = NOW(), revoked_reason = 'reuse_detected' WHERE id = $1"#,
+        )
+        .bind(session_id)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        cache_forget(session_id);
+        return Err(SessionError::Reused);
+    }
+
+    let next_jti = Uuid::new_v4();
+    sqlx::query(
+        r#"
+        UPDATE sessions
+        SET current_jti = $2,
+            last_used_at = $5,
+            ip = COALESCE($3, ip),
+            user_agent = COALESCE($4, user_agent)
+        WHERE id = $1
+        "#,
+    )
+    .bind(session_id)
+    .bind(next_jti)
+    .bind(&client.ip)
+    .bind(&client.user_agent)
+    .bind(clock.now())
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    issue(config, clock, user_id, session_id, next_jti, expires_at)
+}
+
+// Cache d'échéance des sessions consulté par `CurrentUser` (`None`: révoquée ou
+// inconnue): une révocation faite par une autre instance est prise en compte au
+// plus tard après SESSION_CHECK_INTERVAL_SECONDS, l'expiration immédiatement.
+type CachedExpiry = (Option<OffsetDateTime>, Instant);
+static ACTIVE_CACHE: LazyLock<Mutex<HashMap<Uuid, CachedExpiry>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+const ACTIVE_CACHE_MAX: usize = 10_000;
+
+fn cache_set(session_id: Uuid, expires_at: Option<OffsetDateTime>, ttl: Duration) {
+    let mut cache = ACTIVE_CACHE.lock().unwrap();
+    if cache.len() >= ACTIVE_CACHE_MAX {
+        cache.retain(|_, (_, at)| at.elapsed() < ttl);
+        // Encore plein (plus de sessions actives que la taille sur la période):
+        // on repart de zéro, les prochaines requêtes relisent la base
+        if cache.len() >= ACTIVE_CACHE_MAX {
+            cache.clear();
+        }
+    }
+    cache.insert(session_id, (expires_at, Instant::now()));
+}
+
+// Révocation locale: la prochaine vérification relit la base, sans attendre l'intervalle
+fn cache_forget(session_id: Uuid) {
+    ACTIVE_CACHE.lock().unwrap().remove(&session_id);
+}
+
+/// La session est-elle encore valide (ni révoquée ni expirée) ? L'état en cache
+/// sert pendant `check_interval_secs` (SESSION_CHECK_INTERVAL_SECONDS).
+pub async fn is_active(
+    pool: &PgPool,
+    clock: &dyn Clock,
+    session_id: Uuid,
+    check_interval_secs: u64,
+) -> Result<bool, sqlx::Error> {
+    let ttl = Duration::from_secs(check_interval_secs);
+    let cached = ACTIVE_CACHE.lock().unwrap().get(&session_id).copied();
+    let expires_at = match cached {
+        Some((expires_at, at)) if at.elapsed() < ttl => expires_at,
+        _ => {
+            let expires_at = sqlx::query_scalar(
+                r#"SELECT expires_at FROM sessions WHERE id = $1 AND revoked_at IS NULL"#,
+            )
+            .bind(session_id)
+            .fetch_optional(pool)
+            .await?;
+            cache_set(session_id, expires_at, ttl);
+            expires_at
+        }
+    };
+    Ok(expires_at.is_some_and(|exp| exp > clock.now()))
+}
+
+/// Sessions actives d'un utilisateur, la plus récemment utilisée en premier.
+pub async fn list_active(
+    pool: &PgPool,
+    clock: &dyn Clock,
+    user_id: Uuid,
+    current: Option<Uuid>,
+) -> Result<Vec<SessionInfo>, sqlx::Error> {
+    sqlx::query_as::<_, SessionInfo>(
+        r#"
+        SELECT id, user_agent, ip, created_at, last_used_at, expires_at,
+               COALESCE(id = $2, FALSE) AS current
+        FROM sessions
+        WHERE user_id = $1 AND revoked_at IS NULL AND expires_at > $3
+        ORDER BY last_used_at DESC
+        "#,
+    )
+    .bind(user_id)
+    .bind(current)
+    .bind(clock.now())
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn revoke(pool: &PgPool, session_id: Uuid, reason: &str) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE sessions SET revoked_at = NOW(), revoked_reason = $2
+        WHERE id = $1 AND revoked_at IS NULL
+        "#,
+    )
+    .bind(session_id)
+    .bind(reason)
+    .execute(pool)
+    .await?;
+    cache_forget(session_id);
+    Ok(())
+}
+
+/// Révoque une session de l'utilisateur; `false` si elle ne lui appartient pas ou est déjà inactive.
+pub async fn revoke_owned(
+    pool: &PgPool,
+    user_id: Uuid,
+    session_id: Uuid,
+    reason: &str,
+) -> Result<bool, sqlx::Error> {
+    let res = sqlx::query(
+        r#"
+        UPDATE sessions SET revoked_at = NOW(), revoked_reason = $3
+        WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL
+        "#,
+    )
+    .bind(session_id)
+    .bind(user_id)
+    .bind(reason)
+    .execute(pool)
+    .await?;
+    if res.rows_affected() == 0 {
+        return Ok(false);
+    }
+    cache_forget(session_id);
+    Ok(true)
+}
+
+/// Révoque toutes les sessions actives d'un utilisateur (ex: déconnexion partout).
+pub async fn revoke_all(pool: &PgPool, user_id: Uuid, reason: &str) -> Result<(), sqlx::Error> {
+    revoke_all_except(pool, user_id, None, reason).await
+}
+
+/// Idem `revoke_all` en conservant éventuellement la session `keep` (ex: changement de mot de passe).
+pub async fn revoke_all_except(
+    pool: &PgPool,
+    user_id: Uuid,
+    keep: Option<Uuid>,
+    reason: &str,
+) -> Result<(), sqlx::Error> {
+    let revoked: Vec<Uuid> = sqlx::query_scalar(
+        r#"
+        UPDATE sessions SET revoked_at = NOW(), revoked_reason = $3
+        WHERE user_id = $1 AND revoked_at IS NULL
+          AND ($2::UUID IS NULL OR id <> $2)
+        RETURNING id
+        "#,
+    )
+    .bind(user_id)
+    .bind(keep)
+    .bind(reason)
+    .fetch_all(pool)
+    .await?;
+    for session_id in revoked {
+        cache_forget(session_id);
+    }
+    Ok(())
+}
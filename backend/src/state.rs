@@ -0,0 +1,102 @@
+use axum::extract::FromRef;
+use sqlx::PgPool;
+use std::sync::Arc;
+
+use crate::catalog::SharedCatalog;
+use crate::clock::{SharedClock, SystemClock};
+use crate::config::AppConfig;
+use crate::mailer::{self, MailQueue, Mailer};
+use crate::oidc::{OidcProvider, OidcProviders};
+use crate::rng::{SharedRng, SystemRng};
+
+/// État partagé par les routes. Les handlers extraient seulement ce dont ils
+/// ont besoin (`State<PgPool>`, `State<MailQueue>`, `State<SharedCatalog>`…)
+/// grâce à `FromRef`.
+#[derive(Clone)]
+pub struct AppState {
+    pub pool: PgPool,
+    pub config: &'static AppConfig,
+    pub mail: MailQueue,
+    pub clock: SharedClock,
+    pub rng: SharedRng,
+    pub catalog: SharedCatalog,
+    pub oidc: OidcProviders,
+}
+
+impl AppState {
+    /// Dépendances de production: transport d'emails configuré, horloge et aléa système.
+    pub fn new(pool: PgPool, config: &'static AppConfig) -> Self {
+        AppState {
+            pool,
+            config,
+            mail: MailQueue::new(mailer::from_config(&config.mail), config.mail.max_attempts),
+            clock: Arc::new(SystemClock),
+            rng: Arc::new(SystemRng),
+            catalog: SharedCatalog::default(),
+            oidc: OidcProviders::from_config(&config.oidc),
+        }
+    }
+
+    pub fn with_mailer(mut self, mailer: Arc<dyn Mailer>) -> Self {
+        self.mail = MailQueue::new(mailer, self.config.mail.max_attempts);
+        self
+    }
+
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn with_rng(mut self, rng: SharedRng) -> Self {
+        self.rng = rng;
+        self
+    }
+
+    /// Ajoute (ou remplace) un fournisseur OIDC en plus de ceux de la configuration.
+    pub fn with_oidc_provider(mut self, provider: OidcProvider) -> Self {
+        self.oidc = self.oidc.with(provider);
+        self
+    }
+}
+
+impl FromRef<AppState> for PgPool {
+    fn from_ref(state: &AppState) -> Self {
+        state.pool.clone()
+    }
+}
+
+impl FromRef<AppState> for &'static AppConfig {
+    fn from_ref(state: &AppState) -> Self {
+        state.config
+    }
+}
+
+impl FromRef<AppState> for MailQueue {
+    fn from_ref(state: &AppState) -> Self {
+        state.mail.clone()
+    }
+}
+
+impl FromRef<AppState> for SharedClock {
+    fn from_ref(state: &AppState) -> Self {
+        state.clock.clone()
+    }
+}
+
+impl FromRef<AppState> for SharedRng {
+    fn from_ref(state: &AppState) -> Self {
+        state.rng.clone()
+    }
+}
+
+impl FromRef<AppState> for SharedCatalog {
+    fn from_ref(state: &AppState) -> Self {
+        state.catalog.clone()
+    }
+}
+
+impl FromRef<AppState> for OidcProviders {
+    fn from_ref(state: &AppState) -> Self {
+        state.oidc.clone()
+    }
+}
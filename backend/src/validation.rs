@@ -0,0 +1,363 @@
+//! Règles de validation partagées: nom d'utilisateur, email, politique de mot de passe.
+//!
+//! Les erreurs sont regroupées par champ et renvoyées d'un bloc:
+//! `422 { "error": …, "fields": { "password": ["…"] } }`, ou 409 si seule
+//! l'unicité (insensible à la casse) est en cause.
+
+use axum::Json;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::collections::{BTreeMap, HashSet};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::helpers::to_500;
+use crate::models::user::{CreateUser, UpdateUser};
+
+/// Longueurs imposées par le schéma (VARCHAR(50) / VARCHAR(100)).
+pub const USERNAME_MIN_LEN: usize = 3;
+pub const USERNAME_MAX_LEN: usize = 50;
+pub const EMAIL_MAX_LEN: usize = 100;
+
+#[derive(Debug, Default)]
+pub struct ValidationErrors {
+    fields: BTreeMap<&'static str, Vec<String>>,
+    invalid: bool,
+}
+
+impl ValidationErrors {
+    pub fn add(&mut self, field: &'static str, msg: impl Into<String>) {
+        self.invalid = true;
+        self.fields.entry(field).or_default().push(msg.into());
+    }
+
+    /// Valeur déjà prise: 409 si c'est la seule erreur.
+    pub fn taken(&mut self, field: &'static str, msg: impl Into<String>) {
+        self.fields.entry(field).or_default().push(msg.into());
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    pub fn field(&self, name: &str) -> Option<&[String]> {
+        self.fields.get(name).map(Vec::as_slice)
+    }
+
+    pub fn into_result(self) -> Result<(), ValidationErrors> {
+        if self.is_empty() { Ok(()) } else { Err(self) }
+    }
+}
+
+/// Corps des réponses 422/409 de validation.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ValidationReport {
+    pub error: &'static str,
+    /// Messages par champ (ex: `{"password": ["…"]}`)
+    pub fields: BTreeMap<&'static str, Vec<String>>,
+}
+
+impl IntoResponse for ValidationErrors {
+    fn into_response(self) -> Response {
+        let (status, error) = if self.invalid {
+            (StatusCode::UNPROCESSABLE_ENTITY, "Données invalides")
+        } else {
+            (StatusCode::CONFLICT, "Déjà utilisé")
+        };
+        let report = ValidationReport {
+            error,
+            fields: self.fields,
+        };
+        (status, Json(report)).into_response()
+    }
+}
+
+/// Politique de mot de passe (variables PASSWORD_*, section `[password_policy]`).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PasswordPolicy {
+    pub min_length: usize,
+    pub max_length: usize,
+    /// Score zxcvbn minimal (0 à 4)
+    pub min_score: u8,
+    /// Liste hors ligne de mots de passe compromis, un par ligne
+    pub breached_list: Option<PathBuf>,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        PasswordPolicy {
+            min_length: 8,
+            max_length: 128,
+            min_score: 3,
+            breached_list: None,
+        }
+    }
+}
+
+impl PasswordPolicy {
+    /// `user_inputs`: nom d'utilisateur, email… (interdits tels quels, pénalisés par zxcvbn)
+    pub fn check(&self, password: &str, user_inputs: &[&str], errors: &mut ValidationErrors) {
+        let len = password.chars().count();
+        if len < self.min_length {
+            errors.add(
+                "password",
+                format!("Au moins {} caractères", self.min_length),
+            );
+        }
+        // Argon2 sur une entrée arbitrairement longue: coût inutile
+        if len > self.max_length {
+            errors.add(
+                "password",
+                format!("Au plus {} caractères", self.max_length),
+            );
+            return;
+        }
+        let inputs: Vec<&str> = user_inputs
+            .iter()
+            .copied()
+            .filter(|i| !i.is_empty())
+            .collect();
+        if inputs.iter().any(|i| i.eq_ignore_ascii_case(password)) {
+            errors.add(
+                "password",
+                "Ne doit pas être identique au nom d'utilisateur ou à l'email",
+            );
+        }
+        if u8::from(zxcvbn::zxcvbn(password, &inputs).score()) < self.min_score {
+            errors.add(
+                "password",
+                "Mot de passe trop facile à deviner (allonge-le ou mélange des mots)",
+            );
+        }
+        if let Some(path) = &self.breached_list
+            && breached_passwords(path).contains(password)
+        {
+            errors.add(
+                "password",
+                "Mot de passe présent dans une liste de fuites connues",
+            );
+        }
+    }
+}
+
+// Liste chargée une fois par processus; fichier illisible: liste vide (signalé au chargement)
+fn breached_passwords(path: &PathBuf) -> &'static HashSet<String> {
+    static LIST: OnceLock<HashSet<String>> = OnceLock::new();
+    LIST.get_or_init(|| match std::fs::read_to_string(path) {
+        Ok(data) => data
+            .lines()
+            .map(str::trim_end)
+            .filter(|l| !l.is_empty())
+            .map(str::to_string)
+            .collect(),
+        Err(e) => {
+            eprintln!(
+                "⚠️ PASSWORD_BREACHED_LIST_FILE illisible ({}): {e}",
+                path.display()
+            );
+            HashSet::new()
+        }
+    })
+}
+
+/// Lettres, chiffres, `_`, `.` et `-`; commence par une lettre ou un chiffre.
+pub fn check_username(username: &str, errors: &mut ValidationErrors) {
+    let len = username.chars().count();
+    if !(USERNAME_MIN_LEN..=USERNAME_MAX_LEN).contains(&len) {
+        errors.add(
+            "username",
+            format!("Entre {USERNAME_MIN_LEN} et {USERNAME_MAX_LEN} caractères"),
+        );
+    }
+    if !username
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'))
+    {
+        errors.add(
+            "username",
+            "Caractères autorisés: lettres, chiffres, « _ », « . » et « - »",
+        );
+    } else if username
+        .chars()
+        .next()
+        .is_some_and(|c| !c.is_ascii_alphanumeric())
+    {
+        errors.add("username", "Doit commencer par une lettre ou un chiffre");
+    }
+}
+
+/// Syntaxe volontairement simple: `local@domaine.tld`, sans espace.
+pub fn check_email(email: &str, errors: &mut ValidationErrors) {
+    if email.chars().count() > EMAIL_MAX_LEN {
+        errors.add("email", format!("Au plus {EMAIL_MAX_LEN} caractères"));
+        return;
+    }
+    if !is_valid_email(email) {
+        errors.add("email", "Adresse email invalide");
+    }
+}
+
+fn is_valid_email(email: &str) -> bool {
+    let Some((local, domain)) = email.split_once('@') else {
+        return false;
+    };
+    if local.is_empty()
+        || local.len() > 64
+        || local.starts_with('.')
+        || local.ends_with('.')
+        || local.contains("..")
+        || local
+            .chars()
+            .any(|c| c.is_whitespace() || c.is_control() || matches!(c, '@' | '"' | '<' | '>'))
+    {
+        return false;
+    }
+    let labels: Vec<&str> = domain.split('.').collect();
+    labels.len() >= 2
+        && labels.iter().all(|l| {
+            !l.is_empty()
+                && l.len() <= 63
+                && !l.starts_with('-')
+                && !l.ends_with('-')
+                && l.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+        && labels
+            .last()
+            .is_some_and(|tld| tld.len() >= 2 && tld.chars().all(|c| c.is_ascii_alphabetic()))
+}
+
+/// Unicité insensible à la casse (`except`: l'utilisateur lui-même lors d'une mise à jour).
+pub async fn check_unique(
+    pool: &PgPool,
+    username: Option<&str>,
+    email: Option<&str>,
+    except: Option<Uuid>,
+    errors: &mut ValidationErrors,
+) -> Result<(), sqlx::Error> {
+    let (username_taken, email_taken): (bool, bool) = sqlx::query_as(
+        r#"
+        SELECT
+            EXISTS (SELECT 1 FROM users
+                    WHERE $1::TEXT IS NOT NULL AND LOWER(username) = LOWER($1)
+                      AND id IS DISTINCT FROM $3),
+            EXISTS (SELECT 1 FROM users
+                    WHERE $2::TEXT IS NOT NULL AND LOWER(email) = LOWER($2)
+                      AND id IS DISTINCT FROM $3)
+        "#,
+    )
+    .bind(username)
+    .bind(email)
+    .bind(except)
+    .fetch_one(pool)
+    .await?;
+    if username_taken {
+        errors.taken("username", "Nom d'utilisateur déjà pris");
+    }
+    if email_taken {
+        errors.taken("email", "Email déjà utilisé");
+    }
+    Ok(())
+}
+
+/// Mot de passe seul (changement, reset).
+pub fn password(
+    policy: &PasswordPolicy,
+    password: &str,
+    username: &str,
+    email: Option<&str>,
+) -> Result<(), ValidationErrors> {
+    let mut errors = ValidationErrors::default();
+    policy.check(
+        password,
+        &[username, email.unwrap_or_default()],
+        &mut errors,
+    );
+    errors.into_result()
+}
+
+/// Inscription: format des champs puis unicité.
+pub async fn signup(
+    pool: &PgPool,
+    policy: &PasswordPolicy,
+    payload: &CreateUser,
+) -> Result<(), Response> {
+    let mut errors = ValidationErrors::default();
+    check_username(&payload.username, &mut errors);
+    if let Some(email) = &payload.email {
+        check_email(email, &mut errors);
+    }
+    policy.check(
+        &payload.password,
+        &[
+            &payload.username,
+            payload.email.as_deref().unwrap_or_default(),
+        ],
+        &mut errors,
+    );
+    if errors.is_empty() {
+        check_unique(
+            pool,
+            Some(&payload.username),
+            payload.email.as_deref(),
+            None,
+            &mut errors,
+        )
+        .await
+        .map_err(|e| to_500(e).into_response())?;
+    }
+    errors.into_result().map_err(IntoResponse::into_response)
+}
+
+/// Mise à jour du profil: seuls les champs présents sont vérifiés.
+pub async fn profile_update(
+    pool: &PgPool,
+    policy: &PasswordPolicy,
+    user_id: Uuid,
+    payload: &UpdateUser,
+) -> Result<(), Response> {
+    let mut errors = ValidationErrors::default();
+    if let Some(username) = &payload.username {
+        check_username(username, &mut errors);
+    }
+    if let Some(email) = &payload.email {
+        check_email(email, &mut errors);
+    }
+    if let Some(password) = &payload.password {
+        let (username, email) = sqlx::query_as::<_, (String, Option<String>)>(
+            "SELECT username, email FROM users WHERE id = $1",
+        )
+        .bind(user_id)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| to_500(e).into_response())?;
+        policy.check(
+            password,
+            &[
+                payload.username.as_deref().unwrap_or(&username),
+                payload
+                    .email
+                    .as_deref()
+                    .or(email.as_deref())
+                    .unwrap_or_default(),
+            ],
+            &mut errors,
+        );
+    }
+    if errors.is_empty() && (payload.username.is_some() || payload.email.is_some()) {
+        check_unique(
+            pool,
+            payload.username.as_deref(),
+            payload.email.as_deref(),
+            Some(user_id),
+            &mut errors,
+        )
+        .await
+        .map_err(|e| to_500(e).into_response())?;
+    }
+    errors.into_result().map_err(IntoResponse::into_response)
+}
@@ -0,0 +1,71 @@
+//! Versions de l'API.
+//!
+//! Toutes les routes sont servies sous `/api/v1`. `/api/v2` ne monte que les
+//! endpoints qui changent de forme, déclarés à part (`*_v2_routes`, ex:
+//! `GET /api/v2/pokemons` paginé); toute autre requête `/api/v2/…` reçoit 404.
+//! `/api/…`, alias historique de v1, répond avec les en-têtes `Deprecation`,
+//! `Sunset` et `Link` (successor-version) jusqu'à son retrait.
+
+use axum::extract::{Request, State};
+use axum::http::{HeaderName, HeaderValue, header};
+use axum::middleware::Next;
+use axum::response::Response;
+
+use crate::config::AppConfig;
+use crate::http_cache::http_date;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ApiVersion {
+    V1,
+    V2,
+}
+
+impl ApiVersion {
+    /// Préfixe de montage (`/api/v1`, `/api/v2`).
+    pub fn prefix(self) -> &'static str {
+        match self {
+            ApiVersion::V1 => "/api/v1",
+            ApiVersion::V2 => "/api/v2",
+        }
+    }
+}
+
+/// Date d'annonce de l'obsolescence de l'alias `/api` (2026-10-19, RFC 9745).
+pub const LEGACY_DEPRECATED_AT: i64 = 1_792_368_000;
+
+const DEPRECATION: HeaderName = HeaderName::from_static("deprecation");
+const SUNSET: HeaderName = HeaderName::from_static("sunset");
+
+// Date HTTP de API_LEGACY_SUNSET, validée au chargement de la configuration
+fn sunset_value(config: &AppConfig) -> Option<HeaderValue> {
+    let date = config.api.legacy_sunset_date()?;
+    HeaderValue::from_str(&http_date(date.midnight().assume_utc())).ok()
+}
+
+/// Middleware de l'alias `/api`: la réponse indique l'obsolescence, la date de
+/// retrait et l'URL équivalente sous `/api/v1`.
+pub async fn deprecated(
+    State(config): State<&'static AppConfig>,
+    req: Request,
+    next: Next,
+) -> Response {
+    // Chemin relatif au montage /api ("/" pour /api lui-même)
+    let successor = match req.uri().path() {
+        "/" => ApiVersion::V1.prefix().to_string(),
+        path => format!("{}{path}", ApiVersion::V1.prefix()),
+    };
+    let mut res = next.run(req).await;
+
+    let headers = res.headers_mut();
+    headers.insert(
+        DEPRECATION,
+        HeaderValue::from_str(&format!("@{LEGACY_DEPRECATED_AT}")).unwrap(),
+    );
+    if let Some(sunset) = sunset_value(config) {
+        headers.insert(SUNSET, sunset);
+    }
+    if let Ok(link) = HeaderValue::from_str(&format!("<{successor}>; rel=\"successor-version\"")) {
+        headers.append(header::LINK, link);
+    }
+    res
+}
@@ -0,0 +1,199 @@
+use pokedex_rncp_backend as backend;
+
+use backend::rng::SystemRng;
+use reqwest::StatusCode;
+use reqwest::header::{CONTENT_DISPOSITION, SET_COOKIE};
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+mod common;
+use common::{
+    FakeClock, connect_pool, create_test_user, delete_user, ensure_pokemon, login, start_server,
+};
+
+async fn audit_actions(target: Uuid) -> Vec<String> {
+    sqlx::query_scalar("SELECT action FROM audit_events WHERE target_id = $1 ORDER BY id")
+        .bind(target)
+        .fetch_all(&connect_pool().await)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn export_contient_les_donnees_personnelles_sans_secrets() {
+    let (uid, username, email, password) = create_test_user("export").await;
+    ensure_pokemon("Exportmon", "Normal").await;
+    let (base, handle) = start_server().await;
+    let client = reqwest::Client::new();
+    assert_eq!(
+        login(&client, &base, &username, &password).await.status(),
+        StatusCode::OK
+    );
+    let access = common::access_token(uid);
+
+    let res = client
+        .post(format!("{base}/api/pokemons/catch"))
+        .bearer_auth(&access)
+        .json(&json!({ "name": "Exportmon" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::CREATED);
+
+    let res = client
+        .get(format!("{base}/api/me/export"))
+        .bearer_auth(&access)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let disposition = res.headers()[CONTENT_DISPOSITION].to_str().unwrap();
+    assert!(disposition.starts_with("attachment;"));
+    assert!(disposition.contains(&username));
+    let raw = res.text().await.unwrap();
+    for secret in ["password", "totp_secret", "token_hash", "current_jti"] {
+        assert!(!raw.contains(&format!("\"{secret}\"")), "{secret}");
+    }
+
+    let data: Value = serde_json::from_str(&raw).unwrap();
+    assert_eq!(data["format_version"], 1);
+    assert_eq!(data["profile"]["username"], username.as_str());
+    assert_eq!(data["profile"]["email"], email.as_str());
+    assert_eq!(data["collection"][0]["name"], "Exportmon");
+    assert_eq!(data["sessions"].as_array().unwrap().len(), 1);
+    assert_eq!(data["login_attempts"][0]["reason"], "success");
+    assert!(
+        data["audit_events"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|e| e["action"] == "login")
+    );
+    // L'export lui-même est tracé
+    assert_eq!(
+        audit_actions(uid).await.last().unwrap(),
+        "account.data_exported"
+    );
+
+    handle.abort();
+    delete_user(&username).await;
+}
+
+#[tokio::test]
+async fn suppression_differee_annulee_par_un_login() {
+    let (uid, username, _email, password) = create_test_user("soft_delete").await;
+    let pool = connect_pool().await;
+    let (pat, _) = backend::api_tokens::create(
+        &pool,
+        &SystemRng,
+        uid,
+        "script",
+        &["collection:read".to_string()],
+        None,
+    )
+    .await
+    .unwrap();
+    let (base, handle) = start_server().await;
+    let client = reqwest::Client::new();
+    let access = common::access_token(uid);
+
+    let res = client
+        .delete(format!("{base}/api/users/{uid}"))
+        .bearer_auth(&access)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert!(
+        res.headers()
+            .get_all(SET_COOKIE)
+            .iter()
+            .any(|c| c.to_str().unwrap().starts_with("auth=;"))
+    );
+    let body: Value = res.json().await.unwrap();
+    let purge_after = time::OffsetDateTime::parse(
+        body["purge_after"].as_str().unwrap(),
+        &time::format_description::well_known::Rfc3339,
+    )
+    .unwrap();
+    let grace = purge_after - time::OffsetDateTime::now_utc();
+    assert!(grace > time::Duration::days(29) && grace <= time::Duration::days(30));
+
+    // Compte désactivé: tokens d'API refusés, ligne toujours présente
+    let res = client
+        .get(format!("{base}/api/me/daily"))
+        .bearer_auth(&pat)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    // Un login pendant le délai de grâce annule la suppression
+    let res = login(&client, &base, &username, &password).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let msg = res.text().await.unwrap();
+    assert!(msg.contains("annulée"), "{msg}");
+    let (deleted_at, purge): (Option<time::OffsetDateTime>, Option<time::OffsetDateTime>) =
+        sqlx::query_as("SELECT deleted_at, purge_after FROM users WHERE id = $1")
+            .bind(uid)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert!(deleted_at.is_none() && purge.is_none());
+    let res = client
+        .get(format!("{base}/api/me/daily"))
+        .bearer_auth(&pat)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let actions = audit_actions(uid).await;
+    assert!(actions.ends_with(&[
+        "account.deleted".to_string(),
+        "login".to_string(),
+        "account.reactivated".to_string(),
+    ]));
+
+    handle.abort();
+    delete_user(&username).await;
+}
+
+#[tokio::test]
+async fn purge_apres_le_delai_de_grace() {
+    let (uid, username, _email, _password) = create_test_user("purge").await;
+    let pool = connect_pool().await;
+    // Dans le passé: l'avance de l'horloge ne rend échu aucun autre compte programmé
+    let clock = FakeClock::at(time::OffsetDateTime::now_utc() - time::Duration::days(60));
+    let scheduled = backend::account_deletion::schedule(&pool, &*clock, uid, 30)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(scheduled.username, username);
+    // Déjà programmée: pas de seconde demande
+    assert!(
+        backend::account_deletion::schedule(&pool, &*clock, uid, 30)
+            .await
+            .unwrap()
+            .is_none()
+    );
+
+    // Pas encore échu
+    clock.advance(time::Duration::days(30) - time::Duration::minutes(1));
+    let purged = backend::account_deletion::purge_due(&pool, &*clock)
+        .await
+        .unwrap();
+    assert!(!purged.iter().any(|(id, _)| *id == uid));
+
+    clock.advance(time::Duration::minutes(1));
+    let purged = backend::account_deletion::purge_due(&pool, &*clock)
+        .await
+        .unwrap();
+    assert!(purged.contains(&(uid, username.clone())));
+    let remaining: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM users WHERE id = $1")
+        .bind(uid)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(remaining, 0);
+}
@@ -0,0 +1,348 @@
+use reqwest::StatusCode;
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+mod common;
+use common::{
+    connect_pool, create_test_user, delete_user, ensure_pokemon, login, outbox_mail, set_role,
+    start_server, token_from_mail,
+};
+
+#[tokio::test]
+async fn roles_hierarchiques() {
+    let (uid_user, user, _, _) = create_test_user("role_user").await;
+    let (uid_mod, moderator, _, _) = create_test_user("role_mod").await;
+    set_role(uid_mod, "moderator").await;
+    let (base, handle) = start_server().await;
+    let client = reqwest::Client::new();
+    let access_user = common::access_token(uid_user);
+    let access_mod = common::access_token(uid_mod);
+
+    let res = client
+        .get(format!("{}/api/admin/users", base))
+        .bearer_auth(&access_user)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+
+    // Modérateur: recherche autorisée
+    let res = client
+        .get(format!("{}/api/admin/users", base))
+        .query(&[("q", user.as_str())])
+        .bearer_auth(&access_mod)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let page: Value = res.json().await.unwrap();
+    assert_eq!(page["total"], 1);
+    assert_eq!(page["users"][0]["username"], user.as_str());
+    assert_eq!(page["users"][0]["role"], "user");
+
+    // …mais ni statistiques ni suppression (réservées aux admins)
+    let res = client
+        .get(format!("{}/api/admin/stats", base))
+        .bearer_auth(&access_mod)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+    let res = client
+        .delete(format!("{}/api/admin/users/{}", base, uid_user))
+        .bearer_auth(&access_mod)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+
+    // Promotion: effective immédiatement
+    set_role(uid_mod, "admin").await;
+    let res = client
+        .get(format!("{}/api/admin/stats", base))
+        .bearer_auth(&access_mod)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let stats: Value = res.json().await.unwrap();
+    assert!(stats["users"].as_i64().unwrap() >= 2);
+
+    let me: Value = client
+        .get(format!("{}/api/auth/me", base))
+        .bearer_auth(&access_mod)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(me["role"], "admin");
+
+    handle.abort();
+    delete_user(&user).await;
+    delete_user(&moderator).await;
+}
+
+#[tokio::test]
+async fn suspension_reset_force_et_suppression() {
+    let (uid_admin, admin, _, _) = create_test_user("role_admin").await;
+    set_role(uid_admin, "admin").await;
+    let (uid, username, email, password) = create_test_user("role_target").await;
+    let (base, handle) = start_server().await;
+    let client = reqwest::Client::new();
+    let access_admin = common::access_token(uid_admin);
+
+    let res = client
+        .post(format!("{}/api/admin/users/{}/lock", base, uid))
+        .bearer_auth(&access_admin)
+        .json(&json!({ "reason": "spam" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let target: Value = res.json().await.unwrap();
+    assert_eq!(target["disabled_reason"], "spam");
+    assert_eq!(
+        login(&client, &base, &username, &password).await.status(),
+        StatusCode::FORBIDDEN
+    );
+
+    let res = client
+        .post(format!("{}/api/admin/users/{}/unlock", base, uid))
+        .bearer_auth(&access_admin)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(
+        login(&client, &base, &username, &password).await.status(),
+        StatusCode::OK
+    );
+
+    // Reset forcé: l'ancien mot de passe ne fonctionne plus, un lien est envoyé
+    let res = client
+        .post(format!(
+            "{}/api/admin/users/{}/force-password-reset",
+            base, uid
+        ))
+        .bearer_auth(&access_admin)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::ACCEPTED);
+    assert_eq!(
+        login(&client, &base, &username, &password).await.status(),
+        StatusCode::UNAUTHORIZED
+    );
+    let mail = outbox_mail(&email, "Réinitialisation de ton mot de passe PokeRNCP")
+        .await
+        .expect("email de reset attendu");
+    let token = token_from_mail(&mail, "/reset-password");
+    let res = client
+        .post(format!("{}/api/auth/confirm-password-reset", base))
+        .json(&json!({ "token": token, "new_password": "NouveauPass123!" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(
+        login(&client, &base, &username, "NouveauPass123!")
+            .await
+            .status(),
+        StatusCode::OK
+    );
+
+    let res = client
+        .delete(format!("{}/api/admin/users/{}", base, uid))
+        .bearer_auth(&access_admin)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let res = client
+        .get(format!("{}/api/admin/users/{}", base, uid))
+        .bearer_auth(&access_admin)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+    handle.abort();
+    delete_user(&admin).await;
+}
+
+#[tokio::test]
+async fn changement_de_role_et_suspension_traces() {
+    let (uid_admin, admin, _, _) = create_test_user("role_audit_admin").await;
+    set_role(uid_admin, "admin").await;
+    let (uid, username, _, _) = create_test_user("role_audit_target").await;
+    let (base, handle) = start_server().await;
+    let client = reqwest::Client::new();
+    let access_admin = common::access_token(uid_admin);
+
+    let res = client
+        .patch(format!("{}/api/admin/users/{}/role", base, uid))
+        .bearer_auth(&access_admin)
+        .json(&json!({ "role": "moderator" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let target: Value = res.json().await.unwrap();
+    assert_eq!(target["role"], "moderator");
+
+    let res = client
+        .post(format!("{}/api/admin/users/{}/lock", base, uid))
+        .bearer_auth(&access_admin)
+        .json(&json!({ "reason": "  triche  " }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let page: Value = client
+        .get(format!("{}/api/admin/audit-events", base))
+        .query(&[("target", uid.to_string())])
+        .bearer_auth(&access_admin)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let events = page["events"].as_array().unwrap();
+    let event = |action: &str| {
+        events
+            .iter()
+            .find(|e| e["action"] == action)
+            .unwrap_or_else(|| panic!("événement {action} attendu"))
+    };
+    let role = event("role.changed");
+    assert_eq!(role["actor_id"], uid_admin.to_string());
+    assert_eq!(role["target_id"], uid.to_string());
+    assert_eq!(
+        role["metadata"],
+        json!({ "from": "user", "to": "moderator" })
+    );
+    let lock = event("account.locked");
+    assert_eq!(lock["actor_id"], uid_admin.to_string());
+    assert_eq!(lock["metadata"]["reason"], "triche");
+
+    handle.abort();
+    delete_user(&admin).await;
+    delete_user(&username).await;
+}
+
+#[tokio::test]
+async fn moderateur_ne_leve_pas_la_suspension_d_un_pair() {
+    let (uid_admin, admin, _, _) = create_test_user("unlock_admin").await;
+    set_role(uid_admin, "admin").await;
+    let (uid_mod, moderator, _, _) = create_test_user("unlock_mod").await;
+    set_role(uid_mod, "moderator").await;
+    let (uid_peer, peer, _, _) = create_test_user("unlock_peer").await;
+    set_role(uid_peer, "moderator").await;
+    let (base, handle) = start_server().await;
+    let client = reqwest::Client::new();
+    let access_admin = common::access_token(uid_admin);
+    let access_mod = common::access_token(uid_mod);
+
+    let res = client
+        .post(format!("{}/api/admin/users/{}/lock", base, uid_peer))
+        .bearer_auth(&access_admin)
+        .json(&json!({ "reason": "abus" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+
+    // Suspension d'un modérateur par un admin: un autre modérateur ne la lève pas
+    for target in [uid_peer, uid_admin] {
+        let res = client
+            .post(format!("{}/api/admin/users/{}/unlock", base, target))
+            .bearer_auth(&access_mod)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+    }
+    let res = client
+        .get(format!("{}/api/admin/users/{}", base, uid_peer))
+        .bearer_auth(&access_admin)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(
+        res.json::<Value>().await.unwrap()["disabled_reason"],
+        "abus"
+    );
+
+    let res = client
+        .post(format!("{}/api/admin/users/{}/unlock", base, uid_peer))
+        .bearer_auth(&access_admin)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+
+    handle.abort();
+    delete_user(&admin).await;
+    delete_user(&moderator).await;
+    delete_user(&peer).await;
+}
+
+#[tokio::test]
+async fn moderateur_corrige_une_fiche_pokemon() {
+    let (uid_mod, moderator, _, _) = create_test_user("role_pkmn").await;
+    set_role(uid_mod, "moderator").await;
+    let name = format!("Testmon{}", &Uuid::new_v4().simple().to_string()[..8]);
+    let pokemon_id = ensure_pokemon(&name, "Normal").await;
+    let (base, handle) = start_server().await;
+    let client = reqwest::Client::new();
+    let access = common::access_token(uid_mod);
+
+    let res = client
+        .patch(format!("{}/api/admin/pokemons/{}", base, pokemon_id))
+        .bearer_auth(&access)
+        .json(&json!({ "description": "Corrigée", "image_url": "https://img.example/t.png" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let detail: Value = res.json().await.unwrap();
+    assert_eq!(detail["description"], "Corrigée");
+    assert_eq!(detail["image_url"], "https://img.example/t.png");
+    assert_eq!(detail["type1"], "Normal");
+
+    let res = client
+        .patch(format!("{}/api/admin/pokemons/{}", base, pokemon_id))
+        .bearer_auth(&access)
+        .json(&json!({ "image_url": "javascript:alert(1)" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    // Seule la correction acceptée est tracée, avec les champs envoyés
+    let changes: Vec<Value> = sqlx::query_scalar(
+        "SELECT metadata FROM audit_events WHERE action = 'pokemon.updated' AND actor_id = $1",
+    )
+    .bind(uid_mod)
+    .fetch_all(&connect_pool().await)
+    .await
+    .unwrap();
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0]["pokemon_id"], pokemon_id);
+    assert_eq!(
+        changes[0]["changes"],
+        json!({ "description": "Corrigée", "image_url": "https://img.example/t.png" })
+    );
+
+    handle.abort();
+    sqlx::query("DELETE FROM pokemon WHERE id = $1")
+        .bind(pokemon_id)
+        .execute(&connect_pool().await)
+        .await
+        .unwrap();
+    delete_user(&moderator).await;
+}
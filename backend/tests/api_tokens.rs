@@ -0,0 +1,182 @@
+use reqwest::StatusCode;
+use serde_json::{Value, json};
+
+mod common;
+use common::{connect_pool, create_test_user, delete_user, start_server};
+
+async fn create_token(
+    client: &reqwest::Client,
+    base: &str,
+    access: &str,
+    body: Value,
+) -> reqwest::Response {
+    client
+        .post(format!("{}/api/me/tokens", base))
+        .bearer_auth(access)
+        .json(&body)
+        .send()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn token_personnel_limite_a_ses_scopes_et_revocable() {
+    let (uid, username, _email, _password) = create_test_user("pat").await;
+    let (base, handle) = start_server().await;
+    let client = reqwest::Client::new();
+    let access = common::access_token(uid);
+
+    let res = create_token(
+        &client,
+        &base,
+        &access,
+        json!({ "name": "script export", "scopes": ["pokedex:read"], "expires_in_days": 30 }),
+    )
+    .await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let created: Value = res.json().await.unwrap();
+    let token = created["token"].as_str().unwrap().to_string();
+    let token_id = created["id"].as_str().unwrap().to_string();
+    assert!(token.starts_with("pkr_"));
+    assert!(token.starts_with(created["prefix"].as_str().unwrap()));
+    assert!(created["expires_at"].is_string());
+
+    // Scope couvert
+    let res = client
+        .get(format!("{}/api/pokemons", base))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+
+    // Scope absent
+    let res = client
+        .post(format!("{}/api/pokemons/catch", base))
+        .bearer_auth(&token)
+        .json(&json!({ "name": "pikachu" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+
+    // Route sans scope déclaré: refusée à un token, même pour gérer les tokens
+    let res = client
+        .get(format!("{}/api/me/tokens", base))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+
+    // La liste ne contient jamais la valeur du token
+    let list: Vec<Value> = client
+        .get(format!("{}/api/me/tokens", base))
+        .bearer_auth(&access)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(list.len(), 1);
+    assert!(list[0].get("token").is_none());
+    assert_eq!(list[0]["scopes"], json!(["pokedex:read"]));
+    assert!(list[0]["last_used_at"].is_string());
+
+    let res = client
+        .delete(format!("{}/api/me/tokens/{}", base, token_id))
+        .bearer_auth(&access)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res = client
+        .get(format!("{}/api/pokemons", base))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    handle.abort();
+    delete_user(&username).await;
+}
+
+#[tokio::test]
+async fn validation_expiration_et_propriete() {
+    let (uid_a, user_a, _, _) = create_test_user("pat_a").await;
+    let (uid_b, user_b, _, _) = create_test_user("pat_b").await;
+    let (base, handle) = start_server().await;
+    let client = reqwest::Client::new();
+    let access_a = common::access_token(uid_a);
+    let access_b = common::access_token(uid_b);
+
+    for body in [
+        json!({ "name": "x", "scopes": ["admin"] }),
+        json!({ "name": "x", "scopes": [] }),
+        json!({ "name": " ", "scopes": ["pokedex:read"] }),
+        json!({ "name": "x", "scopes": ["pokedex:read"], "expires_in_days": 0 }),
+    ] {
+        let res = create_token(&client, &base, &access_a, body).await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
+
+    let created: Value = create_token(
+        &client,
+        &base,
+        &access_a,
+        json!({ "name": "ci", "scopes": ["profile:read"] }),
+    )
+    .await
+    .json()
+    .await
+    .unwrap();
+    let token = created["token"].as_str().unwrap();
+    assert!(created["expires_at"].is_null());
+
+    let me: Value = client
+        .get(format!("{}/api/auth/me", base))
+        .bearer_auth(token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(me["username"], user_a.as_str());
+
+    // Un autre utilisateur ne peut pas révoquer ce token
+    let res = client
+        .delete(format!(
+            "{}/api/me/tokens/{}",
+            base,
+            created["id"].as_str().unwrap()
+        ))
+        .bearer_auth(&access_b)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+    // Token expiré
+    sqlx::query(
+        "UPDATE api_tokens SET expires_at = NOW() - INTERVAL '1 minute' WHERE user_id = $1",
+    )
+    .bind(uid_a)
+    .execute(&connect_pool().await)
+    .await
+    .unwrap();
+    let res = client
+        .get(format!("{}/api/auth/me", base))
+        .bearer_auth(token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    handle.abort();
+    delete_user(&user_a).await;
+    delete_user(&user_b).await;
+}
@@ -0,0 +1,258 @@
+use reqwest::StatusCode;
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+mod common;
+use common::{connect_pool, create_test_user, delete_user, set_role, start_server};
+
+fn actions(events: &Value) -> Vec<(String, String)> {
+    events
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| {
+            (
+                e["action"].as_str().unwrap().to_string(),
+                e["outcome"].as_str().unwrap().to_string(),
+            )
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn connexions_et_mot_de_passe_dans_l_historique_de_securite() {
+    let (uid, username, _email, password) = create_test_user("audit_me").await;
+    let (base, handle) = start_server().await;
+    let client = reqwest::Client::new();
+
+    for pwd in ["mauvais-mot-de-passe", password.as_str()] {
+        client
+            .post(format!("{base}/api/auth/login"))
+            .header("User-Agent", "audit-test/1.0")
+            .json(&json!({ "username": username, "password": pwd }))
+            .send()
+            .await
+            .unwrap();
+    }
+    let access = common::access_token(uid);
+    let res = client
+        .put(format!("{base}/api/auth/change-password"))
+        .bearer_auth(&access)
+        .json(&json!({ "current_password": password, "new_password": "AuditPass-Kanto-42" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res = client
+        .get(format!("{base}/api/me/security-events"))
+        .bearer_auth(&access)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let events: Value = res.json().await.unwrap();
+    // Du plus récent au plus ancien
+    assert_eq!(
+        actions(&events),
+        vec![
+            ("password.changed".to_string(), "success".to_string()),
+            ("login".to_string(), "success".to_string()),
+            ("login".to_string(), "failure".to_string()),
+        ]
+    );
+    let failed_login = &events[2];
+    assert!(failed_login["actor_id"].is_null());
+    assert_eq!(failed_login["target_id"], uid.to_string());
+    assert_eq!(failed_login["user_agent"], "audit-test/1.0");
+    assert_eq!(failed_login["metadata"]["reason"], "invalid_password");
+    assert_eq!(events[1]["actor_id"], uid.to_string());
+
+    let res = client
+        .get(format!("{base}/api/me/security-events?limit=1&offset=1"))
+        .bearer_auth(&access)
+        .send()
+        .await
+        .unwrap();
+    let page: Value = res.json().await.unwrap();
+    assert_eq!(actions(&page), vec![("login".into(), "success".into())]);
+
+    handle.abort();
+    delete_user(&username).await;
+}
+
+#[tokio::test]
+async fn recherche_admin_et_suppression_de_compte_tracee() {
+    let (uid_admin, admin, _, _) = create_test_user("audit_admin").await;
+    set_role(uid_admin, "admin").await;
+    let (uid, username, _email, password) = create_test_user("audit_target").await;
+    let (base, handle) = start_server().await;
+    let client = reqwest::Client::new();
+    let access_admin = common::access_token(uid_admin);
+    let access = common::access_token(uid);
+
+    // Réservé aux admins
+    let res = client
+        .get(format!("{base}/api/admin/audit-events"))
+        .bearer_auth(&access)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+
+    client
+        .post(format!("{base}/api/auth/login"))
+        .json(&json!({ "username": username, "password": password }))
+        .send()
+        .await
+        .unwrap();
+    let res = client
+        .patch(format!("{base}/api/users/{uid}"))
+        .bearer_auth(&access)
+        .json(&json!({ "email": format!("{username}@kanto.example") }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res = client
+        .delete(format!("{base}/api/admin/users/{uid}"))
+        .bearer_auth(&access_admin)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+
+    // L'historique survit à la suppression du compte
+    let target = uid.to_string();
+    let res = client
+        .get(format!("{base}/api/admin/audit-events"))
+        .query(&[("target", target.as_str())])
+        .bearer_auth(&access_admin)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let page: Value = res.json().await.unwrap();
+    assert_eq!(page["total"], 3);
+    let events = &page["events"];
+    assert_eq!(events[0]["action"], "account.deleted");
+    assert_eq!(events[0]["actor_id"], uid_admin.to_string());
+    assert_eq!(events[0]["metadata"]["username"], username.as_str());
+    assert_eq!(events[1]["action"], "email.changed");
+    assert_eq!(
+        events[1]["metadata"]["to"],
+        format!("{username}@kanto.example")
+    );
+
+    // Préfixe d'action et issue
+    let since = "2000-01-01T00:00:00Z";
+    let res = client
+        .get(format!("{base}/api/admin/audit-events"))
+        .query(&[
+            ("target", target.as_str()),
+            ("action", "login"),
+            ("outcome", "success"),
+            ("since", since),
+        ])
+        .bearer_auth(&access_admin)
+        .send()
+        .await
+        .unwrap();
+    let page: Value = res.json().await.unwrap();
+    assert_eq!(page["total"], 1);
+    assert_eq!(page["events"][0]["action"], "login");
+
+    let res = client
+        .get(format!("{base}/api/admin/audit-events?outcome=peut-etre"))
+        .bearer_auth(&access_admin)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    handle.abort();
+    delete_user(&admin).await;
+}
+
+#[tokio::test]
+async fn journal_en_ajout_seul_et_purge_de_retention() {
+    let (uid_admin, admin, _, _) = create_test_user("audit_prune").await;
+    set_role(uid_admin, "admin").await;
+    let pool = connect_pool().await;
+    let target = Uuid::new_v4();
+
+    for age in ["40 years", "1 day"] {
+        sqlx::query(
+            "INSERT INTO audit_events (action, target_id, outcome, created_at)
+             VALUES ('login', $1, 'success', NOW() - $2::INTERVAL)",
+        )
+        .bind(target)
+        .bind(age)
+        .execute(&pool)
+        .await
+        .unwrap();
+    }
+
+    // Ni modification ni suppression directe
+    assert!(
+        sqlx::query("UPDATE audit_events SET outcome = 'failure' WHERE target_id = $1")
+            .bind(target)
+            .execute(&pool)
+            .await
+            .is_err()
+    );
+    assert!(
+        sqlx::query("DELETE FROM audit_events WHERE target_id = $1")
+            .bind(target)
+            .execute(&pool)
+            .await
+            .is_err()
+    );
+
+    let (base, handle) = start_server().await;
+    let client = reqwest::Client::new();
+    let access_admin = common::access_token(uid_admin);
+
+    let res = client
+        .delete(format!("{base}/api/admin/audit-events?older_than_days=0"))
+        .bearer_auth(&access_admin)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    let res = client
+        .delete(format!(
+            "{base}/api/admin/audit-events?older_than_days=10000"
+        ))
+        .bearer_auth(&access_admin)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let body: Value = res.json().await.unwrap();
+    assert!(body["deleted"].as_u64().unwrap() >= 1);
+
+    let remaining: Vec<String> = sqlx::query_scalar(
+        "SELECT (NOW() - created_at)::TEXT FROM audit_events WHERE target_id = $1",
+    )
+    .bind(target)
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+    assert_eq!(remaining.len(), 1);
+
+    // La purge elle-même est tracée
+    let pruned: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM audit_events WHERE action = 'audit.pruned' AND actor_id = $1",
+    )
+    .bind(uid_admin)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(pruned, 1);
+
+    handle.abort();
+    delete_user(&admin).await;
+}
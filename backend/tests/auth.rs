@@ -1,11 +1,15 @@
 use pokedex_rncp_backend as backend;
 
+use backend::clock::SystemClock;
 use reqwest::StatusCode;
 use serde_json::json;
 use sqlx::{Connection, Row};
 
 mod common;
-use common::{cookie_header, create_test_user, delete_user, start_server};
+use common::{
+    FakeClock, connect_pool, cookie_header, create_test_user, delete_user, set_cookie_value,
+    start_server, start_server_with,
+};
 
 #[tokio::test]
 async fn me_requiert_authentification() {
@@ -27,7 +31,7 @@ async fn me_ok_avec_bearer() {
     let (uid, username, _email, _pwd) = create_test_user("basco").await;
     let (base, handle) = start_server().await;
     let client = reqwest::Client::new();
-    let access = backend::auth::generate_access_token(uid).expect("access token");
+    let access = common::access_token(uid);
 
     let res = client
         .get(format!("{}/api/auth/me", base))
@@ -46,7 +50,17 @@ async fn refresh_token_requiert_bearer_et_definit_cookie() {
     let (uid, username, _email, _pwd) = create_test_user("basco_refresh").await;
     let (base, handle) = start_server().await;
     let client = reqwest::Client::new();
-    let refresh = backend::auth::generate_refresh_token(uid).expect("refresh token");
+    let pool = connect_pool().await;
+    let refresh = backend::sessions::start_session(
+        &pool,
+        &SystemClock,
+        common::test_config(),
+        uid,
+        &Default::default(),
+    )
+    .await
+    .expect("session")
+    .refresh;
 
     let res = client
         .post(format!("{}/api/auth/refresh-token", base))
@@ -98,7 +112,11 @@ async fn reset_mot_de_passe_retourne_token_et_confirmation_mise_a_jour() {
         .await
         .unwrap();
     assert_eq!(res.status(), StatusCode::ACCEPTED);
-    let token = backend::auth::generate_reset_token(uid).expect("reset token");
+    let body = res.json::<serde_json::Value>().await.unwrap();
+    let token = body["reset_token"]
+        .as_str()
+        .expect("reset token")
+        .to_string();
     let res = client
         .post(format!("{}/api/auth/confirm-password-reset", base))
         .json(&json!({ "token": token, "new_password": new_password }))
@@ -135,7 +153,7 @@ async fn me_ok_avec_cookie_auth() {
     let (uid, username, _email, _pwd) = create_test_user("me_cookie").await;
     let (base, handle) = start_server().await;
     let client = reqwest::Client::new();
-    let access = backend::auth::generate_access_token(uid).expect("access token");
+    let access = common::access_token(uid);
 
     let res = client
         .get(format!("{}/api/auth/me", base))
@@ -156,14 +174,25 @@ async fn refresh_token_ok_avec_cookie_refresh() {
     let (uid, username, _email, _pwd) = create_test_user("refresh_cookie").await;
     let (base, handle) = start_server().await;
     let client = reqwest::Client::new();
-    let refresh = backend::auth::generate_refresh_token(uid).expect("refresh token");
+    let pool = connect_pool().await;
+    let tokens = backend::sessions::start_session(
+        &pool,
+        &SystemClock,
+        common::test_config(),
+        uid,
+        &Default::default(),
+    )
+    .await
+    .expect("session");
+    let csrf = backend::csrf::issue(common::test_config(), tokens.session_id);
 
     let res = client
         .post(format!("{}/api/auth/refresh-token", base))
         .header(
             reqwest::header::COOKIE,
-            cookie_header(&[("refresh", &refresh)]),
+            cookie_header(&[("refresh", &tokens.refresh), ("csrf", &csrf)]),
         )
+        .header("X-CSRF-Token", &csrf)
         .send()
         .await
         .unwrap();
@@ -226,3 +255,292 @@ async fn tokens_invalides_donnent_401() {
 
     handle.abort();
 }
+
+#[tokio::test]
+async fn login_puis_rotation_du_refresh_et_detection_de_reutilisation() {
+    let (_uid, username, _email, password) = create_test_user("rotation").await;
+    let (base, handle) = start_server().await;
+    let client = reqwest::Client::new();
+
+    let res = client
+        .post(format!("{}/api/auth/login", base))
+        .json(&json!({ "username": username, "password": password }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let refresh1 = set_cookie_value(&res, "refresh").expect("refresh cookie");
+
+    // Rotation: un nouveau refresh token remplace le précédent
+    let res = client
+        .post(format!("{}/api/auth/refresh-token", base))
+        .bearer_auth(&refresh1)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let refresh2 = set_cookie_value(&res, "refresh").expect("rotated refresh cookie");
+    assert_ne!(refresh1, refresh2);
+
+    // Réutilisation de l'ancien token: refus et révocation de toute la session
+    let res = client
+        .post(format!("{}/api/auth/refresh-token", base))
+        .bearer_auth(&refresh1)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    let res = client
+        .post(format!("{}/api/auth/refresh-token", base))
+        .bearer_auth(&refresh2)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    handle.abort();
+    delete_user(&username).await;
+}
+
+#[tokio::test]
+async fn logout_revoque_la_session() {
+    let (uid, username, _email, _pwd) = create_test_user("logout_rev").await;
+    let pool = connect_pool().await;
+    let tokens = backend::sessions::start_session(
+        &pool,
+        &SystemClock,
+        common::test_config(),
+        uid,
+        &Default::default(),
+    )
+    .await
+    .unwrap();
+    let (base, handle) = start_server().await;
+    let client = reqwest::Client::new();
+    let csrf = backend::csrf::issue(common::test_config(), tokens.session_id);
+
+    let res = client
+        .post(format!("{}/api/auth/logout", base))
+        .header(
+            reqwest::header::COOKIE,
+            cookie_header(&[("refresh", &tokens.refresh), ("csrf", &csrf)]),
+        )
+        .header("X-CSRF-Token", &csrf)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res = client
+        .post(format!("{}/api/auth/refresh-token", base))
+        .bearer_auth(&tokens.refresh)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    handle.abort();
+    delete_user(&username).await;
+}
+
+#[tokio::test]
+async fn changement_mot_de_passe_revoque_les_autres_sessions() {
+    let (uid, username, _email, password) = create_test_user("pwd_rev").await;
+    let pool = connect_pool().await;
+    let current = backend::sessions::start_session(
+        &pool,
+        &SystemClock,
+        common::test_config(),
+        uid,
+        &Default::default(),
+    )
+    .await
+    .unwrap();
+    let other = backend::sessions::start_session(
+        &pool,
+        &SystemClock,
+        common::test_config(),
+        uid,
+        &Default::default(),
+    )
+    .await
+    .unwrap();
+    let (base, handle) = start_server().await;
+    let client = reqwest::Client::new();
+
+    let res = client
+        .put(format!("{}/api/auth/change-password", base))
+        .bearer_auth(&current.access)
+        .json(&json!({ "current_password": password, "new_password": "BrandNewPass123!" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res = client
+        .post(format!("{}/api/auth/refresh-token", base))
+        .bearer_auth(&other.refresh)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    let res = client
+        .post(format!("{}/api/auth/refresh-token", base))
+        .bearer_auth(&current.refresh)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+
+    handle.abort();
+    delete_user(&username).await;
+}
+
+#[tokio::test]
+async fn sessions_liste_revocation_et_logout_all() {
+    let (uid, username, _email, _pwd) = create_test_user("sessions_api").await;
+    let (other_uid, other_username, _e, _p) = create_test_user("sessions_other").await;
+    let pool = connect_pool().await;
+    let current = backend::sessions::start_session(
+        &pool,
+        &SystemClock,
+        common::test_config(),
+        uid,
+        &Default::default(),
+    )
+    .await
+    .unwrap();
+    let second = backend::sessions::start_session(
+        &pool,
+        &SystemClock,
+        common::test_config(),
+        uid,
+        &Default::default(),
+    )
+    .await
+    .unwrap();
+    let foreign = backend::sessions::start_session(
+        &pool,
+        &SystemClock,
+        common::test_config(),
+        other_uid,
+        &Default::default(),
+    )
+    .await
+    .unwrap();
+    let (base, handle) = start_server().await;
+    let client = reqwest::Client::new();
+
+    let res = client
+        .get(format!("{}/api/auth/sessions", base))
+        .bearer_auth(&current.access)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let list = res.json::<serde_json::Value>().await.unwrap();
+    let list = list.as_array().unwrap();
+    assert_eq!(list.len(), 2);
+    let flagged = list
+        .iter()
+        .find(|s| s["current"] == true)
+        .expect("current session flagged");
+    assert_eq!(flagged["id"], current.session_id.to_string());
+
+    // Impossible de révoquer la session d'un autre utilisateur
+    let res = client
+        .delete(format!("{}/api/auth/sessions/{}", base, foreign.session_id))
+        .bearer_auth(&current.access)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+    let res = client
+        .delete(format!("{}/api/auth/sessions/{}", base, second.session_id))
+        .bearer_auth(&current.access)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+
+    // L'access token de la session révoquée est refusé
+    let res = client
+        .get(format!("{}/api/auth/me", base))
+        .bearer_auth(&second.access)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    let res = client
+        .post(format!("{}/api/auth/logout-all", base))
+        .bearer_auth(&current.access)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res = client
+        .get(format!("{}/api/auth/me", base))
+        .bearer_auth(&current.access)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    let res = client
+        .get(format!("{}/api/auth/me", base))
+        .bearer_auth(&foreign.access)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+
+    handle.abort();
+    delete_user(&username).await;
+    delete_user(&other_username).await;
+}
+
+#[tokio::test]
+async fn session_expiree_selon_l_horloge_injectee() {
+    let (uid, username, _email, _pwd) = create_test_user("session_clock").await;
+    let clock = FakeClock::at(time::OffsetDateTime::now_utc());
+    let pool = connect_pool().await;
+    let tokens = backend::sessions::start_session(
+        &pool,
+        &*clock,
+        common::test_config(),
+        uid,
+        &Default::default(),
+    )
+    .await
+    .unwrap();
+    let (base, handle) = start_server_with(|state| state.with_clock(clock.clone())).await;
+    let client = reqwest::Client::new();
+    let me = || {
+        client
+            .get(format!("{}/api/auth/me", base))
+            .bearer_auth(&tokens.access)
+            .send()
+    };
+
+    assert_eq!(me().await.unwrap().status(), StatusCode::OK);
+
+    // Échéance atteinte même si l'état de la session est encore en cache
+    let ttl = common::test_config().auth.refresh_ttl_secs;
+    clock.advance(time::Duration::seconds(ttl + 1));
+    assert_eq!(me().await.unwrap().status(), StatusCode::UNAUTHORIZED);
+    let res = client
+        .post(format!("{}/api/auth/refresh-token", base))
+        .bearer_auth(&tokens.refresh)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    handle.abort();
+    delete_user(&username).await;
+}
@@ -0,0 +1,122 @@
+use reqwest::StatusCode;
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+mod common;
+use common::{connect_pool, create_test_user, delete_user, start_server};
+
+async fn get(client: &reqwest::Client, url: String, token: &str) -> reqwest::Response {
+    client.get(url).bearer_auth(token).send().await.unwrap()
+}
+
+#[tokio::test]
+async fn le_catalogue_en_memoire_suit_la_base() {
+    let (uid, username, _, _) = create_test_user("catalog").await;
+    let (uid_mod, moderator, _, _) = create_test_user("catalog_mod").await;
+    let pool = connect_pool().await;
+    sqlx::query("UPDATE users SET role = 'moderator' WHERE id = $1")
+        .bind(uid_mod)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let token = common::access_token(uid);
+    let token_mod = common::access_token(uid_mod);
+    let (base, handle) = start_server().await;
+    let client = reqwest::Client::new();
+
+    // Premier appel: catalogue chargé
+    let res = get(&client, format!("{base}/api/v1/pokemons"), &token).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let before = res.json::<Vec<Value>>().await.unwrap().len();
+
+    // Ajout direct en base: visible dès la requête suivante
+    let suffix = &Uuid::new_v4().simple().to_string()[..8];
+    let name = format!("Cachetest{suffix}");
+    let id: i32 = sqlx::query_scalar(
+        "INSERT INTO pokemon (name, type1, description) VALUES ($1, 'Normal', 'Avant') RETURNING id",
+    )
+    .bind(&name)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    let res = get(&client, format!("{base}/api/v1/pokemons"), &token).await;
+    assert_eq!(res.json::<Vec<Value>>().await.unwrap().len(), before + 1);
+    let res = get(&client, format!("{base}/api/v2/pokemons?limit=1"), &token).await;
+    assert_eq!(res.json::<Value>().await.unwrap()["total"], before + 1);
+
+    // Recherche par préfixe, sans tenir compte de la casse
+    let q = format!("cachetest{suffix}");
+    let res = get(
+        &client,
+        format!("{base}/api/v1/pokemons/search?q={q}"),
+        &token,
+    )
+    .await;
+    let found: Vec<Value> = res.json().await.unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0]["name"], name);
+    assert_eq!(found[0]["caught"], false);
+
+    // Capture: superposée au catalogue partagé, pour cet utilisateur seulement
+    let res = client
+        .post(format!("{base}/api/v1/pokemons/catch"))
+        .bearer_auth(&token)
+        .json(&json!({ "name": name }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let res = get(&client, format!("{base}/api/v1/pokemons/{id}"), &token).await;
+    assert_eq!(res.json::<Value>().await.unwrap()["caught"], true);
+    let res = get(&client, format!("{base}/api/v1/pokemons/{id}"), &token_mod).await;
+    assert_eq!(res.json::<Value>().await.unwrap()["caught"], false);
+
+    // Correction admin: la fiche servie est rechargée
+    let res = client
+        .patch(format!("{base}/api/v1/admin/pokemons/{id}"))
+        .bearer_auth(&token_mod)
+        .json(&json!({ "description": "Après" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let res = get(&client, format!("{base}/api/v1/pokemons/{id}"), &token).await;
+    let detail: Value = res.json().await.unwrap();
+    assert_eq!(detail["description"], "Après");
+    assert_eq!(detail["caught"], true);
+
+    // Second type: ajouté, conservé si absent, retiré par null
+    for (patch, expected) in [
+        (json!({ "type2": "Vol" }), json!("Vol")),
+        (json!({ "description": "Encore" }), json!("Vol")),
+        (json!({ "type2": null }), Value::Null),
+    ] {
+        let res = client
+            .patch(format!("{base}/api/v1/admin/pokemons/{id}"))
+            .bearer_auth(&token_mod)
+            .json(&patch)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.json::<Value>().await.unwrap()["type2"], expected);
+    }
+
+    // Pas de cascade depuis pokemon: captures supprimées d'abord
+    sqlx::query("DELETE FROM user_pokemon WHERE pokemon_id = $1")
+        .bind(id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::query("DELETE FROM pokemon WHERE id = $1")
+        .bind(id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let res = get(&client, format!("{base}/api/v1/pokemons/{id}"), &token).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+    delete_user(&username).await;
+    delete_user(&moderator).await;
+    handle.abort();
+}
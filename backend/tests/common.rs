@@ -1,39 +1,115 @@
 use axum::Router;
 use axum::http::{HeaderValue, Method};
+use pokedex_rncp_backend::clock::Clock;
+use pokedex_rncp_backend::config::AppConfig;
+use pokedex_rncp_backend::mailer::{Email, MailFuture, Mailer};
+use pokedex_rncp_backend::rng::Rng;
+use pokedex_rncp_backend::state::AppState;
 use sqlx::{Connection, PgPool};
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
 use std::time::Duration;
+use time::OffsetDateTime;
 use tokio::net::TcpListener;
 use tokio::sync::OnceCell;
 use uuid::Uuid;
 
 static POOL: OnceCell<PgPool> = OnceCell::const_new();
 
+static CONFIG: LazyLock<AppConfig> = LazyLock::new(|| {
+    let _ = dotenvy::dotenv();
+    let mut config =
+        AppConfig::load(None).unwrap_or_else(|e| panic!("Configuration invalide:\n{e}"));
+    // JWT_SECRET absent de l'environnement: secret propre aux tests
+    config
+        .keys
+        .secret
+        .get_or_insert_with(|| "test_access_secret".into());
+    config
+});
+
+/// Configuration des tests (environnement et .env, secret JWT par défaut),
+/// passée à l'état du serveur.
+#[allow(dead_code)]
+pub fn test_config() -> &'static AppConfig {
+    &CONFIG
+}
+
+/// Access token signé avec la configuration des tests.
+#[allow(dead_code)]
+pub fn access_token(user_id: Uuid) -> String {
+    pokedex_rncp_backend::auth::generate_access_token(test_config(), user_id)
+        .expect("generate_access_token failed")
+}
+
 pub async fn test_pool() -> &'static PgPool {
     let _ = dotenvy::dotenv();
 
     POOL.get_or_init(|| async {
         let url = std::env::var("TEST_DATABASE_URL").expect("Set TEST_DATABASE_URL for tests");
         eprintln!("[tests] Using TEST_DATABASE_URL={}", url);
-        pokedex_rncp_backend::db::init_db(&url).await
+        pokedex_rncp_backend::db::init_db(&url, &test_config().database).await
     })
     .await
 }
 
+static SCHEMA: OnceCell<()> = OnceCell::const_new();
+
+/// Migrations et seed, une fois par binaire de test: les helpers qui écrivent
+/// directement en base peuvent précéder `start_server` sur une base neuve.
+#[allow(dead_code)]
+pub async fn ensure_schema() {
+    SCHEMA
+        .get_or_init(|| async { connect_pool().await.close().await })
+        .await;
+}
+
+// Pool propre au test appelant: le pool partagé de `test_pool` reste lié au
+// runtime du premier test qui l'initialise.
 #[allow(dead_code)]
-pub async fn test_build_app() -> axum::Router<PgPool> {
+pub async fn connect_pool() -> PgPool {
+    let _ = dotenvy::dotenv();
+    let url = std::env::var("TEST_DATABASE_URL")
+        .or_else(|_| std::env::var("DATABASE_URL"))
+        .expect("Set TEST_DATABASE_URL for tests");
+    pokedex_rncp_backend::db::init_db(&url, &test_config().database).await
+}
+
+#[allow(dead_code)]
+pub async fn test_build_app() -> axum::Router {
     let pool = test_pool().await.clone();
-    pokedex_rncp_backend::app::build_routes().with_state(pool)
+    let config = test_config();
+    pokedex_rncp_backend::app::build_routes(config).with_state(AppState::new(pool, config))
 }
 
 #[allow(dead_code)]
 pub async fn start_server() -> (String, tokio::task::JoinHandle<()>) {
+    start_server_with(|state| state).await
+}
+
+/// Comme `start_server`, en remplaçant des dépendances de l'état (mailer, horloge, aléa):
+/// `start_server_with(|state| state.with_clock(clock.clone()))`.
+#[allow(dead_code)]
+pub async fn start_server_with(
+    configure: impl FnOnce(AppState) -> AppState,
+) -> (String, tokio::task::JoinHandle<()>) {
+    start_server_with_config(test_config(), configure).await
+}
+
+/// Comme `start_server_with`, avec une autre configuration que celle des tests
+/// (partir de `test_config().clone()` puis modifier les champs voulus).
+#[allow(dead_code)]
+pub async fn start_server_with_config(
+    config: &'static AppConfig,
+    configure: impl FnOnce(AppState) -> AppState,
+) -> (String, tokio::task::JoinHandle<()>) {
     let _ = dotenvy::dotenv();
     let url = std::env::var("TEST_DATABASE_URL")
         .or_else(|_| std::env::var("DATABASE_URL"))
         .expect("Set TEST_DATABASE_URL for tests");
-    let pool = pokedex_rncp_backend::db::init_db(&url).await;
-    let origin =
-        std::env::var("FRONTEND_ORIGIN").unwrap_or_else(|_| "http://localhost:3000".into());
+    let pool = pokedex_rncp_backend::db::init_db(&url, &config.database).await;
+    let origin = &config.server.frontend_origin;
     let cors = tower_http::cors::CorsLayer::new()
         .allow_origin(origin.parse::<HeaderValue>().unwrap())
         .allow_methods([
@@ -46,19 +122,24 @@ pub async fn start_server() -> (String, tokio::task::JoinHandle<()>) {
         .allow_headers([
             axum::http::header::CONTENT_TYPE,
             axum::http::header::AUTHORIZATION,
+            axum::http::HeaderName::from_static("x-csrf-token"),
         ])
         .allow_credentials(true);
 
-    let app: Router<_> = pokedex_rncp_backend::app::build_routes()
-        .with_state(pool)
+    let state = configure(AppState::new(pool, config));
+    let app: Router<_> = pokedex_rncp_backend::app::build_routes(config)
+        .with_state(state)
         .layer(cors);
     let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
     let addr = listener.local_addr().unwrap();
     let url = format!("http://{}", addr);
 
     let handle = tokio::spawn(async move {
-        let server = axum::serve(listener, app)
-            .with_graceful_shutdown(async { std::future::pending::<()>().await });
+        let server = axum::serve(
+            listener,
+            app.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .with_graceful_shutdown(async { std::future::pending::<()>().await });
         if let Err(e) = server.into_future().await {
             eprintln!("serve error: {e}");
         }
@@ -78,7 +159,7 @@ pub async fn start_server() -> (String, tokio::task::JoinHandle<()>) {
 
 #[allow(dead_code)]
 pub async fn create_test_user(base: &str) -> (Uuid, String, String, String) {
-    let _ = dotenvy::dotenv();
+    ensure_schema().await;
     let url = std::env::var("TEST_DATABASE_URL")
         .or_else(|_| std::env::var("DATABASE_URL"))
         .expect("Set TEST_DATABASE_URL for tests");
@@ -150,3 +231,181 @@ pub fn cookie_header(pairs: &[(&str, &str)]) -> String {
         .collect::<Vec<_>>()
         .join("; ")
 }
+
+// En-tête Set-Cookie complet (attributs compris) du cookie `name`
+#[allow(dead_code)]
+pub fn set_cookie_header(res: &reqwest::Response, name: &str) -> Option<String> {
+    res.headers()
+        .get_all(reqwest::header::SET_COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .find(|c| c.starts_with(&format!("{name}=")))
+        .map(|c| c.to_string())
+}
+
+// Valeur seule du cookie `name` posé par la réponse
+#[allow(dead_code)]
+pub fn set_cookie_value(res: &reqwest::Response, name: &str) -> Option<String> {
+    set_cookie_header(res, name)
+        .map(|c| c.split(';').next().unwrap_or_default()[name.len() + 1..].to_string())
+}
+
+#[allow(dead_code)]
+pub async fn set_role(user_id: Uuid, role: &str) {
+    sqlx::query("UPDATE users SET role = $2 WHERE id = $1")
+        .bind(user_id)
+        .bind(role)
+        .execute(&connect_pool().await)
+        .await
+        .unwrap();
+}
+
+#[allow(dead_code)]
+pub async fn login(
+    client: &reqwest::Client,
+    base: &str,
+    username: &str,
+    password: &str,
+) -> reqwest::Response {
+    client
+        .post(format!("{}/api/auth/login", base))
+        .json(&serde_json::json!({ "username": username, "password": password }))
+        .send()
+        .await
+        .unwrap()
+}
+
+// Emails écrits par l'outbox (mail.outbox_dir, défaut tmp/outbox) pour ce destinataire.
+// Attend jusqu'à ~5s que la file d'envoi les ait traités.
+#[allow(dead_code)]
+pub async fn outbox_for(to: &str) -> Vec<serde_json::Value> {
+    let dir = test_config().mail.outbox_dir.as_str();
+    for _ in 0..50 {
+        let mut found = Vec::new();
+        if let Ok(entries) = std::fs::read_dir(dir) {
+            for entry in entries.flatten() {
+                let Ok(data) = std::fs::read_to_string(entry.path()) else {
+                    continue;
+                };
+                if let Ok(mail) = serde_json::from_str::<serde_json::Value>(&data)
+                    && mail["to"] == to
+                {
+                    found.push(mail);
+                }
+            }
+        }
+        if !found.is_empty() {
+            return found;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    Vec::new()
+}
+
+/// Attend l'email destiné à `to` dont le sujet est `subject`.
+#[allow(dead_code)]
+pub async fn outbox_mail(to: &str, subject: &str) -> Option<serde_json::Value> {
+    for _ in 0..50 {
+        if let Some(mail) = outbox_for(to)
+            .await
+            .into_iter()
+            .find(|m| m["subject"] == subject)
+        {
+            return Some(mail);
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    None
+}
+
+/// Extrait la valeur du paramètre `token` du premier lien `path?token=` trouvé dans le corps d'un email.
+#[allow(dead_code)]
+pub fn token_from_mail(mail: &serde_json::Value, path: &str) -> String {
+    let body = mail["body"].as_str().unwrap_or_default();
+    let marker = format!("{path}?token=");
+    let start = body.find(&marker).expect("lien absent de l'email") + marker.len();
+    body[start..]
+        .chars()
+        .take_while(|c| !c.is_whitespace())
+        .collect()
+}
+
+/// Mailer en mémoire: les emails envoyés sont consultables sans passer par l'outbox.
+#[allow(dead_code)]
+#[derive(Clone, Default)]
+pub struct FakeMailer {
+    sent: Arc<Mutex<Vec<Email>>>,
+}
+
+#[allow(dead_code)]
+impl FakeMailer {
+    /// Attend (~5s max) les emails envoyés à `to` par la file d'envoi.
+    pub async fn sent_to(&self, to: &str) -> Vec<Email> {
+        for _ in 0..50 {
+            let found: Vec<Email> = self
+                .sent
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|mail| mail.to == to)
+                .cloned()
+                .collect();
+            if !found.is_empty() {
+                return found;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        Vec::new()
+    }
+}
+
+impl Mailer for FakeMailer {
+    fn send<'a>(&'a self, email: &'a Email) -> MailFuture<'a> {
+        Box::pin(async move {
+            self.sent.lock().unwrap().push(email.clone());
+            Ok(())
+        })
+    }
+}
+
+/// Horloge figée, avancée explicitement par le test.
+#[allow(dead_code)]
+pub struct FakeClock(Mutex<OffsetDateTime>);
+
+#[allow(dead_code)]
+impl FakeClock {
+    pub fn at(now: OffsetDateTime) -> Arc<FakeClock> {
+        Arc::new(FakeClock(Mutex::new(now)))
+    }
+
+    pub fn advance(&self, by: time::Duration) {
+        *self.0.lock().unwrap() += by;
+    }
+}
+
+impl Clock for FakeClock {
+    fn now(&self) -> OffsetDateTime {
+        *self.0.lock().unwrap()
+    }
+}
+
+/// Aléa déterministe: octets d'un compteur, pour prédire les tokens émis.
+/// Partir d'une valeur propre au test évite les collisions entre exécutions.
+#[allow(dead_code)]
+pub struct SeqRng(AtomicU64);
+
+#[allow(dead_code)]
+impl SeqRng {
+    pub fn starting_at(n: u64) -> SeqRng {
+        SeqRng(AtomicU64::new(n))
+    }
+}
+
+impl Rng for SeqRng {
+    fn fill(&self, buf: &mut [u8]) {
+        for chunk in buf.chunks_mut(8) {
+            let n = self.0.fetch_add(1, Ordering::SeqCst).to_be_bytes();
+            chunk.copy_from_slice(&n[..chunk.len()]);
+        }
+    }
+}
@@ -0,0 +1,210 @@
+use pokedex_rncp_backend as backend;
+
+use backend::clock::SystemClock;
+use reqwest::StatusCode;
+use reqwest::header::{COOKIE, ORIGIN, REFERER};
+use serde_json::json;
+
+mod common;
+use common::{
+    cookie_header, create_test_user, delete_user, set_cookie_header, set_cookie_value, start_server,
+};
+
+async fn catch(
+    client: &reqwest::Client,
+    base: &str,
+    cookies: &str,
+    extra: &[(reqwest::header::HeaderName, &str)],
+) -> StatusCode {
+    let mut req = client
+        .post(format!("{base}/api/pokemons/catch"))
+        .header(COOKIE, cookies)
+        .json(&json!({ "name": "Pikachu" }));
+    for (name, value) in extra {
+        req = req.header(name, *value);
+    }
+    req.send().await.unwrap().status()
+}
+
+#[tokio::test]
+async fn login_pose_le_cookie_csrf_et_mutations_par_cookie_protegees() {
+    let (_uid, username, _email, password) = create_test_user("csrf_login").await;
+    let (base, handle) = start_server().await;
+    let client = reqwest::Client::new();
+
+    let res = client
+        .post(format!("{base}/api/auth/login"))
+        .json(&json!({ "username": username, "password": password }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let csrf_cookie = set_cookie_header(&res, "csrf").expect("cookie csrf attendu");
+    // Lisible par le frontend, jamais envoyé cross-site
+    assert!(!csrf_cookie.contains("HttpOnly"));
+    assert!(csrf_cookie.contains("SameSite=Strict"));
+    let csrf = set_cookie_value(&res, "csrf").unwrap();
+    let auth = set_cookie_value(&res, "auth").unwrap();
+    let cookies = cookie_header(&[("auth", &auth), ("csrf", &csrf)]);
+
+    // Sans en-tête, ou avec un en-tête différent du cookie: refus
+    assert_eq!(
+        catch(&client, &base, &cookies, &[]).await,
+        StatusCode::FORBIDDEN
+    );
+    let other = backend::csrf::issue(common::test_config(), uuid::Uuid::new_v4());
+    assert_eq!(
+        catch(
+            &client,
+            &base,
+            &cookies,
+            &[("x-csrf-token".parse().unwrap(), &other)]
+        )
+        .await,
+        StatusCode::FORBIDDEN
+    );
+
+    // Jeton valide, avec ou sans Origin autorisée
+    let token = ("x-csrf-token".parse().unwrap(), csrf.as_str());
+    assert_eq!(
+        catch(&client, &base, &cookies, std::slice::from_ref(&token)).await,
+        StatusCode::CREATED
+    );
+    assert_eq!(
+        catch(
+            &client,
+            &base,
+            &cookies,
+            &[token.clone(), (ORIGIN, "http://localhost:3000")]
+        )
+        .await,
+        StatusCode::CREATED
+    );
+
+    // Origine ou Referer d'un autre site: refus, même avec le jeton
+    assert_eq!(
+        catch(
+            &client,
+            &base,
+            &cookies,
+            &[token.clone(), (ORIGIN, "https://evil.example")]
+        )
+        .await,
+        StatusCode::FORBIDDEN
+    );
+    assert_eq!(
+        catch(
+            &client,
+            &base,
+            &cookies,
+            &[token.clone(), (REFERER, "https://evil.example/page")]
+        )
+        .await,
+        StatusCode::FORBIDDEN
+    );
+
+    // La déconnexion efface le cookie csrf
+    let res = client
+        .post(format!("{base}/api/auth/logout"))
+        .header(COOKIE, &cookies)
+        .header("X-CSRF-Token", &csrf)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert!(
+        set_cookie_header(&res, "csrf")
+            .unwrap()
+            .contains("Max-Age=0")
+    );
+
+    handle.abort();
+    delete_user(&username).await;
+}
+
+#[tokio::test]
+async fn jeton_d_une_autre_session_refuse() {
+    let (uid, username, _email, _pwd) = create_test_user("csrf_sessions").await;
+    let pool = common::connect_pool().await;
+    let first = backend::sessions::start_session(
+        &pool,
+        &SystemClock,
+        common::test_config(),
+        uid,
+        &Default::default(),
+    )
+    .await
+    .unwrap();
+    let second = backend::sessions::start_session(
+        &pool,
+        &SystemClock,
+        common::test_config(),
+        uid,
+        &Default::default(),
+    )
+    .await
+    .unwrap();
+    let (base, handle) = start_server().await;
+    let client = reqwest::Client::new();
+
+    // Cookie et en-tête cohérents, mais émis pour une autre session
+    let stolen = backend::csrf::issue(common::test_config(), first.session_id);
+    let cookies = cookie_header(&[("auth", &second.access), ("csrf", &stolen)]);
+    assert_eq!(
+        catch(
+            &client,
+            &base,
+            &cookies,
+            &[("x-csrf-token".parse().unwrap(), &stolen)]
+        )
+        .await,
+        StatusCode::FORBIDDEN
+    );
+
+    let own = backend::csrf::issue(common::test_config(), second.session_id);
+    let cookies = cookie_header(&[("auth", &second.access), ("csrf", &own)]);
+    assert_eq!(
+        catch(
+            &client,
+            &base,
+            &cookies,
+            &[("x-csrf-token".parse().unwrap(), &own)]
+        )
+        .await,
+        StatusCode::CREATED
+    );
+
+    handle.abort();
+    delete_user(&username).await;
+}
+
+#[tokio::test]
+async fn bearer_et_requetes_anonymes_non_concernees() {
+    let (uid, username, _email, _pwd) = create_test_user("csrf_bearer").await;
+    let (base, handle) = start_server().await;
+    let client = reqwest::Client::new();
+    let access = common::access_token(uid);
+
+    let res = client
+        .post(format!("{base}/api/pokemons/catch"))
+        .bearer_auth(&access)
+        .header(ORIGIN, "https://evil.example")
+        .json(&json!({ "name": "Pikachu" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::CREATED);
+
+    // Sans cookie de session valide: pas de CSRF, l'authentification décide
+    let res = client
+        .post(format!("{base}/api/pokemons/catch"))
+        .header(COOKIE, "auth=invalide")
+        .json(&json!({ "name": "Pikachu" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    handle.abort();
+    delete_user(&username).await;
+}
@@ -0,0 +1,186 @@
+use pokedex_rncp_backend as backend;
+
+use backend::clock::SystemClock;
+use backend::models::daily::ChallengePokemon;
+use reqwest::StatusCode;
+use serde_json::json;
+
+mod common;
+use common::{FakeClock, connect_pool, create_test_user, delete_user, start_server};
+
+#[tokio::test]
+async fn daily_requiert_auth() {
+    let (base, handle) = start_server().await;
+    let res = reqwest::get(format!("{}/api/me/daily", base))
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    handle.abort();
+}
+
+#[tokio::test]
+async fn defi_deterministe_pour_une_date() {
+    let pool = &connect_pool().await;
+    let day = time::Date::from_calendar_date(2025, time::Month::November, 2).unwrap();
+    let a = backend::daily::challenge_for_day(pool, day).await.unwrap();
+    let b = backend::daily::challenge_for_day(pool, day).await.unwrap();
+    assert_eq!(a, b);
+    assert!((1..=3).contains(&a.target));
+    assert!(a.reward_points > 0);
+}
+
+#[tokio::test]
+async fn capture_valide_le_defi_et_demarre_la_serie() {
+    let (uid, username, _email, _pwd) = create_test_user("daily").await;
+    let (base, handle) = start_server().await;
+    let client = reqwest::Client::new();
+    let access = common::access_token(uid);
+
+    let res = client
+        .get(format!("{}/api/me/daily", base))
+        .bearer_auth(&access)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let status = res.json::<serde_json::Value>().await.unwrap();
+    assert_eq!(status["progress"], 0);
+    assert_eq!(status["completed"], false);
+    assert_eq!(status["streak"]["current"], 0);
+
+    // Capture assez de Pokémon correspondant au défi du jour
+    let pool = &connect_pool().await;
+    let (day, _tz) = backend::daily::user_today(pool, &SystemClock, uid)
+        .await
+        .unwrap();
+    let challenge = backend::daily::challenge_for_day(pool, day).await.unwrap();
+    let names: Vec<String> = sqlx::query_scalar("SELECT name FROM pokemon ORDER BY id")
+        .fetch_all(pool)
+        .await
+        .unwrap();
+    let rows = sqlx::query_as::<_, ChallengePokemon>(
+        r#"
+        SELECT type1, type2, dex_no, base_hp, base_attack, base_defense,
+               base_sp_attack, base_sp_defense, base_speed
+        FROM pokemon ORDER BY id
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .unwrap();
+    let matching = names
+        .iter()
+        .zip(rows.iter())
+        .filter(|(_, p)| challenge.matches(p))
+        .map(|(n, _)| n.clone())
+        .take(challenge.target as usize)
+        .collect::<Vec<_>>();
+    assert_eq!(matching.len(), challenge.target as usize);
+
+    for name in &matching {
+        let res = client
+            .post(format!("{}/api/pokemons/catch", base))
+            .bearer_auth(&access)
+            .json(&json!({ "name": name }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::CREATED);
+    }
+
+    let res = client
+        .get(format!("{}/api/me/daily", base))
+        .bearer_auth(&access)
+        .send()
+        .await
+        .unwrap();
+    let status = res.json::<serde_json::Value>().await.unwrap();
+    assert_eq!(status["completed"], true);
+    assert_eq!(status["progress"], challenge.target);
+    assert_eq!(status["streak"]["current"], 1);
+    assert_eq!(status["reward_points_total"], challenge.reward_points);
+
+    let res = client
+        .get(format!("{}/api/auth/me", base))
+        .bearer_auth(&access)
+        .send()
+        .await
+        .unwrap();
+    let me = res.json::<serde_json::Value>().await.unwrap();
+    assert_eq!(me["streak"]["current"], 1);
+    assert_eq!(me["streak"]["best"], 1);
+
+    handle.abort();
+    delete_user(&username).await;
+}
+
+#[tokio::test]
+async fn fuseau_horaire_modifiable_et_valide() {
+    let (uid, username, _email, _pwd) = create_test_user("daily_tz").await;
+    let (base, handle) = start_server().await;
+    let client = reqwest::Client::new();
+    let access = common::access_token(uid);
+
+    let res = client
+        .patch(format!("{}/api/users/{}", base, uid))
+        .bearer_auth(&access)
+        .json(&json!({ "timezone": "Not/AZone" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    let res = client
+        .patch(format!("{}/api/users/{}", base, uid))
+        .bearer_auth(&access)
+        .json(&json!({ "timezone": "Pacific/Kiritimati" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res = client
+        .get(format!("{}/api/me/daily", base))
+        .bearer_auth(&access)
+        .send()
+        .await
+        .unwrap();
+    let status = res.json::<serde_json::Value>().await.unwrap();
+    assert_eq!(status["timezone"], "Pacific/Kiritimati");
+
+    handle.abort();
+    delete_user(&username).await;
+}
+
+#[tokio::test]
+async fn jour_du_defi_selon_l_horloge_injectee() {
+    let (uid, username, _email, _pwd) = create_test_user("daily_clock").await;
+    let pool = &connect_pool().await;
+    sqlx::query("UPDATE users SET timezone = 'Pacific/Kiritimati' WHERE id = $1")
+        .bind(uid)
+        .execute(pool)
+        .await
+        .unwrap();
+
+    // 11h UTC le 1er mars: déjà le 2 mars à Kiritimati (UTC+14)
+    let clock = FakeClock::at(time::OffsetDateTime::from_unix_timestamp(1_772_362_800).unwrap());
+    let (day, tz) = backend::daily::user_today(pool, &*clock, uid)
+        .await
+        .unwrap();
+    assert_eq!(tz, "Pacific/Kiritimati");
+    assert_eq!(
+        day,
+        time::Date::from_calendar_date(2026, time::Month::March, 2).unwrap()
+    );
+
+    clock.advance(time::Duration::days(1));
+    let (day, _tz) = backend::daily::user_today(pool, &*clock, uid)
+        .await
+        .unwrap();
+    assert_eq!(
+        day,
+        time::Date::from_calendar_date(2026, time::Month::March, 3).unwrap()
+    );
+
+    delete_user(&username).await;
+}
@@ -0,0 +1,153 @@
+use reqwest::StatusCode;
+use serde_json::json;
+
+mod common;
+use common::{
+    connect_pool, create_test_user, delete_user, outbox_mail, start_server, token_from_mail,
+};
+
+const VERIFY_SUBJECT: &str = "Confirme ton adresse email PokeRNCP";
+
+async fn email_verified(client: &reqwest::Client, base: &str, access: &str) -> bool {
+    let me = client
+        .get(format!("{}/api/auth/me", base))
+        .bearer_auth(access)
+        .send()
+        .await
+        .unwrap()
+        .json::<serde_json::Value>()
+        .await
+        .unwrap();
+    me["email_verified"].as_bool().unwrap()
+}
+
+#[tokio::test]
+async fn inscription_puis_verification_email() {
+    let (base, handle) = start_server().await;
+    let client = reqwest::Client::new();
+    let username = format!("verify_{}", &uuid::Uuid::new_v4().simple().to_string()[..8]);
+    let email = format!("{}@example.com", username);
+
+    let res = client
+        .post(format!("{}/api/users", base))
+        .json(&json!({ "username": username, "email": email, "password": "SignupPass123!" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::CREATED);
+
+    let mail = outbox_mail(&email, VERIFY_SUBJECT)
+        .await
+        .expect("email de vérification attendu");
+    let token = token_from_mail(&mail, "/verify-email");
+
+    let uid: uuid::Uuid = sqlx::query_scalar("SELECT id FROM users WHERE username = $1")
+        .bind(&username)
+        .fetch_one(&connect_pool().await)
+        .await
+        .unwrap();
+    let access = common::access_token(uid);
+    assert!(!email_verified(&client, &base, &access).await);
+
+    let res = client
+        .post(format!("{}/api/auth/verify-email", base))
+        .json(&json!({ "token": token }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert!(email_verified(&client, &base, &access).await);
+
+    // Adresse déjà vérifiée: pas de renvoi possible
+    let res = client
+        .post(format!("{}/api/auth/resend-verification", base))
+        .bearer_auth(&access)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::CONFLICT);
+
+    handle.abort();
+    delete_user(&username).await;
+}
+
+#[tokio::test]
+async fn token_invalide_refuse() {
+    let (base, handle) = start_server().await;
+    let res = reqwest::Client::new()
+        .post(format!("{}/api/auth/verify-email", base))
+        .json(&json!({ "token": "pas-un-jwt" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    handle.abort();
+}
+
+#[tokio::test]
+async fn changement_email_reinitialise_la_verification() {
+    let (uid, username, old_email, _pwd) = create_test_user("verify_change").await;
+    let (base, handle) = start_server().await;
+    let client = reqwest::Client::new();
+    let access = common::access_token(uid);
+
+    // Vérifie l'adresse d'origine via un renvoi
+    let res = client
+        .post(format!("{}/api/auth/resend-verification", base))
+        .bearer_auth(&access)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::ACCEPTED);
+    let mail = outbox_mail(&old_email, VERIFY_SUBJECT).await.unwrap();
+    let old_token = token_from_mail(&mail, "/verify-email");
+    let res = client
+        .post(format!("{}/api/auth/verify-email", base))
+        .json(&json!({ "token": old_token }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert!(email_verified(&client, &base, &access).await);
+
+    let new_email = format!("new_{}", old_email);
+    let res = client
+        .patch(format!("{}/api/users/{}", base, uid))
+        .bearer_auth(&access)
+        .json(&json!({ "email": new_email }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert!(!email_verified(&client, &base, &access).await);
+
+    let notice = outbox_mail(
+        &old_email,
+        "L'adresse email de ton compte PokeRNCP a été modifiée",
+    )
+    .await
+    .expect("l'ancienne adresse doit être prévenue");
+    assert!(notice["body"].as_str().unwrap().contains(&new_email));
+
+    // Le token émis pour l'ancienne adresse ne vaut plus rien
+    let res = client
+        .post(format!("{}/api/auth/verify-email", base))
+        .json(&json!({ "token": old_token }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    let mail = outbox_mail(&new_email, VERIFY_SUBJECT).await.unwrap();
+    let res = client
+        .post(format!("{}/api/auth/verify-email", base))
+        .json(&json!({ "token": token_from_mail(&mail, "/verify-email") }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert!(email_verified(&client, &base, &access).await);
+
+    handle.abort();
+    delete_user(&username).await;
+}
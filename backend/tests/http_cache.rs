@@ -0,0 +1,178 @@
+use reqwest::StatusCode;
+use reqwest::header::{CACHE_CONTROL, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED, VARY};
+use serde_json::{Value, json};
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+mod common;
+use common::{connect_pool, create_test_user, delete_user, start_server};
+
+// Les deux tests comparent des ETag: aucune modification du catalogue ne doit
+// s'intercaler entre deux requêtes de l'autre
+static CATALOG: Mutex<()> = Mutex::const_new(());
+
+async fn get(
+    client: &reqwest::Client,
+    url: &str,
+    token: &str,
+    conditions: &[(reqwest::header::HeaderName, &str)],
+) -> reqwest::Response {
+    let mut req = client.get(url).bearer_auth(token);
+    for (name, value) in conditions {
+        req = req.header(name, *value);
+    }
+    req.send().await.unwrap()
+}
+
+fn header(res: &reqwest::Response, name: reqwest::header::HeaderName) -> String {
+    res.headers()[name].to_str().unwrap().to_string()
+}
+
+async fn catalog_version() -> i64 {
+    sqlx::query_scalar("SELECT version FROM catalog_version")
+        .fetch_one(&connect_pool().await)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn etag_suit_la_collection_de_l_utilisateur() {
+    let _catalog = CATALOG.lock().await;
+    let (uid, username, _, _) = create_test_user("etag").await;
+    let (uid_other, other, _, _) = create_test_user("etag_other").await;
+    let token = common::access_token(uid);
+    let token_other = common::access_token(uid_other);
+    let (base, handle) = start_server().await;
+    let client = reqwest::Client::new();
+    let url = format!("{base}/api/v1/pokemons");
+
+    let res = get(&client, &url, &token, &[]).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(header(&res, CACHE_CONTROL), "private, no-cache");
+    assert!(header(&res, VARY).contains("authorization"));
+    let etag = header(&res, ETAG);
+    let last_modified = header(&res, LAST_MODIFIED);
+    assert!(etag.starts_with("W/\""));
+    let list: Vec<Value> = res.json().await.unwrap();
+    let first = list.first().expect("catalogue vide").clone();
+
+    // Représentation à jour: 304 sans corps, avec les mêmes validateurs
+    let res = get(&client, &url, &token, &[(IF_NONE_MATCH, &etag)]).await;
+    assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(header(&res, ETAG), etag);
+    assert_eq!(header(&res, CACHE_CONTROL), "private, no-cache");
+    assert!(res.bytes().await.unwrap().is_empty());
+
+    let res = get(
+        &client,
+        &url,
+        &token,
+        &[(IF_MODIFIED_SINCE, &last_modified)],
+    )
+    .await;
+    assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+
+    // If-None-Match prime sur If-Modified-Since
+    let conditions = [
+        (IF_NONE_MATCH, "W/\"perime\""),
+        (IF_MODIFIED_SINCE, last_modified.as_str()),
+    ];
+    let res = get(&client, &url, &token, &conditions).await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    // Un autre compte n'obtient jamais la représentation du premier
+    let res = get(&client, &url, &token_other, &[(IF_NONE_MATCH, &etag)]).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_ne!(header(&res, ETAG), etag);
+
+    // Une capture change la collection, donc l'ETag
+    let res = client
+        .post(format!("{base}/api/v1/pokemons/catch"))
+        .bearer_auth(&token)
+        .json(&json!({ "name": first["name"] }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let res = get(&client, &url, &token, &[(IF_NONE_MATCH, &etag)]).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let etag = header(&res, ETAG);
+    let list: Vec<Value> = res.json().await.unwrap();
+    assert_eq!(list[0]["caught"], true);
+
+    // Fiche et page v2 partagent les mêmes validateurs
+    let detail = format!("{base}/api/v1/pokemons/{}", first["id"]);
+    let res = get(&client, &detail, &token, &[(IF_NONE_MATCH, &etag)]).await;
+    assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+    let page = format!("{base}/api/v2/pokemons?limit=2");
+    let res = get(&client, &page, &token, &[(IF_NONE_MATCH, &etag)]).await;
+    assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+    let res = get(&client, &page, &token, &[]).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(header(&res, ETAG), etag);
+
+    delete_user(&username).await;
+    delete_user(&other).await;
+    handle.abort();
+}
+
+#[tokio::test]
+async fn version_du_catalogue_suit_les_modifications_effectives() {
+    let _catalog = CATALOG.lock().await;
+    let (uid, username, _, _) = create_test_user("etag_catalog").await;
+    let token = common::access_token(uid);
+    let pool = connect_pool().await;
+    let (base, handle) = start_server().await;
+    let client = reqwest::Client::new();
+    let url = format!("{base}/api/v1/pokemons");
+
+    let name = format!("Cachemon{}", &Uuid::new_v4().simple().to_string()[..8]);
+    let before = catalog_version().await;
+    let id: i32 = sqlx::query_scalar(
+        "INSERT INTO pokemon (name, type1, description) VALUES ($1, 'Normal', 'Avant') RETURNING id",
+    )
+    .bind(&name)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(catalog_version().await, before + 1);
+
+    let res = get(&client, &url, &token, &[]).await;
+    let etag = header(&res, ETAG);
+
+    // Reseed à l'identique (ON CONFLICT DO UPDATE sans changement): rien ne bouge
+    sqlx::query(
+        r#"
+        INSERT INTO pokemon (name, type1, description) VALUES ($1, 'Normal', 'Avant')
+        ON CONFLICT (name) DO UPDATE SET type1 = EXCLUDED.type1, description = EXCLUDED.description
+        "#,
+    )
+    .bind(&name)
+    .execute(&pool)
+    .await
+    .unwrap();
+    assert_eq!(catalog_version().await, before + 1);
+    let res = get(&client, &url, &token, &[(IF_NONE_MATCH, &etag)]).await;
+    assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+
+    // Une vraie correction invalide les représentations en cache
+    sqlx::query("UPDATE pokemon SET description = 'Après' WHERE id = $1")
+        .bind(id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    assert_eq!(catalog_version().await, before + 2);
+    let res = get(&client, &url, &token, &[(IF_NONE_MATCH, &etag)]).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_ne!(header(&res, ETAG), etag);
+
+    sqlx::query("DELETE FROM pokemon WHERE id = $1")
+        .bind(id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    assert_eq!(catalog_version().await, before + 3);
+
+    delete_user(&username).await;
+    handle.abort();
+}
@@ -0,0 +1,211 @@
+use pokedex_rncp_backend as backend;
+
+use backend::keys::{KeyConfig, KeySet, check};
+use backend::models::auth::Claims;
+use jsonwebtoken::{EncodingKey, Header, decode_header};
+use reqwest::StatusCode;
+use serde_json::Value;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+mod common;
+use common::start_server;
+
+const STRONG: &str = "4f9c2a7e1b8d3f6a0c5e9b2d7a1f4c8e";
+const STRONG_REFRESH: &str = "b7e1d94c2f8a6b3e0d5c1a9f7e2b4d6c";
+
+fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+fn claims() -> Claims {
+    let now = time::OffsetDateTime::now_utc().unix_timestamp();
+    Claims {
+        sub: Uuid::new_v4(),
+        iat: now,
+        exp: now + 300,
+        sid: None,
+        jti: None,
+    }
+}
+
+fn asymmetric(algorithm: &str, key: &str, previous_jwks: Option<PathBuf>) -> KeyConfig {
+    KeyConfig {
+        algorithm: algorithm.into(),
+        secret: Some(STRONG.into()),
+        private_key_file: Some(fixture(key)),
+        previous_jwks_file: previous_jwks,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn rotation_rs256_vers_eddsa_sans_invalider_les_tokens() {
+    let rsa = KeySet::load(&asymmetric("RS256", "oidc_mock_rsa.pem", None)).unwrap();
+    let old_claims = claims();
+    let old_token = rsa.sign(&old_claims).unwrap();
+    assert_eq!(
+        decode_header(&old_token).unwrap().kid.as_deref(),
+        Some(rsa.kid())
+    );
+
+    // JWKS publié avant la rotation, conservé comme clés précédentes
+    let jwks_file = std::env::temp_dir().join(format!("jwks_{}.json", Uuid::new_v4().simple()));
+    std::fs::write(&jwks_file, serde_json::to_string(&rsa.jwks()).unwrap()).unwrap();
+    let ed = KeySet::load(&asymmetric(
+        "EdDSA",
+        "jwt_ed25519.pem",
+        Some(jwks_file.clone()),
+    ))
+    .unwrap();
+    std::fs::remove_file(&jwks_file).unwrap();
+
+    assert_ne!(ed.kid(), rsa.kid());
+    let verified: Claims = ed.verify(&old_token).unwrap();
+    assert_eq!(verified.sub, old_claims.sub);
+    let new_token = ed.sign(&claims()).unwrap();
+    assert!(ed.verify::<Claims>(&new_token).is_ok());
+    assert!(rsa.verify::<Claims>(&new_token).is_err());
+
+    // Les deux clés publiques sont publiées, sans partie privée
+    let jwks: Value = serde_json::to_value(ed.jwks()).unwrap();
+    let keys = jwks["keys"].as_array().unwrap();
+    assert_eq!(keys.len(), 2);
+    assert_eq!(keys[0]["kid"], ed.kid());
+    assert_eq!(keys[0]["kty"], "OKP");
+    assert_eq!(keys[1]["kid"], rsa.kid());
+    assert!(keys.iter().all(|k| k.get("d").is_none()));
+
+    // Clé retirée du JWKS: ses tokens sont refusés
+    let ed_only = KeySet::load(&asymmetric("EdDSA", "jwt_ed25519.pem", None)).unwrap();
+    assert!(ed_only.verify::<Claims>(&old_token).is_err());
+}
+
+#[test]
+fn secrets_precedents_et_tokens_sans_kid() {
+    let old = KeySet::hmac(STRONG, &[]);
+    let current = KeySet::hmac(STRONG_REFRESH, &[STRONG.to_string()]);
+    assert_ne!(old.kid(), current.kid());
+
+    let token = old.sign(&claims()).unwrap();
+    assert!(current.verify::<Claims>(&token).is_ok());
+
+    // Token émis avant l'ajout du kid
+    let legacy = jsonwebtoken::encode(
+        &Header::default(),
+        &claims(),
+        &EncodingKey::from_secret(STRONG.as_bytes()),
+    )
+    .unwrap();
+    assert!(old.verify::<Claims>(&legacy).is_ok());
+
+    // kid inconnu ou clé publique RSA utilisée comme secret HMAC: refusés
+    let header = Header {
+        kid: Some("inconnu".into()),
+        ..Header::default()
+    };
+    let unknown = jsonwebtoken::encode(
+        &header,
+        &claims(),
+        &EncodingKey::from_secret(STRONG.as_bytes()),
+    )
+    .unwrap();
+    assert!(old.verify::<Claims>(&unknown).is_err());
+
+    let rsa = KeySet::load(&asymmetric("RS256", "oidc_mock_rsa.pem", None)).unwrap();
+    let header = Header {
+        kid: Some(rsa.kid().to_string()),
+        ..Header::default()
+    };
+    let n = serde_json::to_value(rsa.jwks()).unwrap()["keys"][0]["n"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let confused =
+        jsonwebtoken::encode(&header, &claims(), &EncodingKey::from_secret(n.as_bytes())).unwrap();
+    assert!(rsa.verify::<Claims>(&confused).is_err());
+}
+
+#[test]
+fn production_refuse_secrets_faibles_ou_partages() {
+    let good = KeyConfig {
+        algorithm: "HS256".into(),
+        secret: Some(STRONG.into()),
+        refresh_secret: Some(STRONG_REFRESH.into()),
+        production: true,
+        ..Default::default()
+    };
+    assert_eq!(check(&good).unwrap(), Vec::<String>::new());
+
+    let weak = KeyConfig {
+        secret: Some("change_me_access_secret".into()),
+        ..good.clone()
+    };
+    assert!(check(&weak).is_err());
+
+    let shared = KeyConfig {
+        refresh_secret: Some(STRONG.into()),
+        ..good.clone()
+    };
+    let err = check(&shared).unwrap_err().to_string();
+    assert!(err.contains("JWT_SECRET et JWT_REFRESH_SECRET sont identiques"));
+
+    let shared_scoped = KeyConfig {
+        scoped_secrets: vec![("MFA_SECRET".into(), STRONG_REFRESH.into())],
+        ..good.clone()
+    };
+    assert!(check(&shared_scoped).is_err());
+
+    let derived_refresh = KeyConfig {
+        refresh_secret: None,
+        ..good.clone()
+    };
+    assert!(check(&derived_refresh).is_err());
+
+    // Hors production: simples avertissements
+    let dev = KeyConfig {
+        production: false,
+        ..weak
+    };
+    assert!(!check(&dev).unwrap().is_empty());
+
+    // Fichier de clé illisible: refusé dans tous les modes
+    let missing = KeyConfig {
+        algorithm: "RS256".into(),
+        private_key_file: Some(fixture("absent.pem")),
+        production: false,
+        ..good
+    };
+    assert!(check(&missing).is_err());
+}
+
+#[tokio::test]
+async fn jwks_publie_et_kid_dans_les_access_tokens() {
+    let (base, handle) = start_server().await;
+
+    let res = reqwest::get(format!("{}/.well-known/jwks.json", base))
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert!(
+        res.headers()["cache-control"]
+            .to_str()
+            .unwrap()
+            .contains("max-age")
+    );
+    let jwks: Value = res.json().await.unwrap();
+    // HS256 (configuration des tests): aucune clé publiée
+    assert_eq!(jwks["keys"], serde_json::json!([]));
+
+    let config = common::test_config();
+    let token = common::access_token(Uuid::new_v4());
+    assert_eq!(
+        decode_header(&token).unwrap().kid.as_deref(),
+        Some(config.keys.loaded().access.kid())
+    );
+    assert!(backend::auth::verify_refresh(config, &token).is_err());
+
+    handle.abort();
+}
@@ -0,0 +1,279 @@
+use pokedex_rncp_backend::login_guard;
+use reqwest::StatusCode;
+use serde_json::json;
+use std::time::Duration;
+
+mod common;
+use common::{
+    FakeClock, connect_pool, create_test_user, delete_user, login, outbox_mail, start_server,
+    start_server_with, token_from_mail,
+};
+
+fn retry_after(res: &reqwest::Response) -> u64 {
+    res.headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse().ok())
+        .expect("en-tête Retry-After attendu")
+}
+
+#[tokio::test]
+async fn backoff_puis_verrouillage_et_deverrouillage_par_email() {
+    let (_uid, username, email, password) = create_test_user("lockout").await;
+    let (base, handle) = start_server().await;
+    let client = reqwest::Client::new();
+
+    for _ in 0..3 {
+        let res = login(&client, &base, &username, "mauvais").await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    // Backoff: une nouvelle tentative immédiate est refusée sans vérifier le mot de passe
+    let res = login(&client, &base, &username, &password).await;
+    assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+    tokio::time::sleep(Duration::from_secs(retry_after(&res)) + Duration::from_millis(100)).await;
+
+    let res = login(&client, &base, &username, "mauvais").await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    let res = login(&client, &base, &username, &password).await;
+    assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+    tokio::time::sleep(Duration::from_secs(retry_after(&res)) + Duration::from_millis(100)).await;
+
+    // 5e échec: verrouillage
+    let res = login(&client, &base, &username, "mauvais").await;
+    assert_eq!(res.status(), StatusCode::LOCKED);
+    let res = login(&client, &base, &username, &password).await;
+    assert_eq!(res.status(), StatusCode::LOCKED);
+    assert!(retry_after(&res) > 60);
+
+    let mail = outbox_mail(&email, "Ton compte PokeRNCP est temporairement verrouillé")
+        .await
+        .expect("email de verrouillage attendu");
+    let token = token_from_mail(&mail, "/unlock-account");
+    let res = client
+        .post(format!("{}/api/auth/unlock-account", base))
+        .json(&json!({ "token": token }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res = login(&client, &base, &username, &password).await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    // Le lien ne sert qu'une fois
+    let res = client
+        .post(format!("{}/api/auth/unlock-account", base))
+        .json(&json!({ "token": token }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    handle.abort();
+    delete_user(&username).await;
+}
+
+#[tokio::test]
+async fn succes_remet_le_compteur_a_zero_et_tentatives_journalisees() {
+    let (uid, username, _email, password) = create_test_user("login_audit").await;
+    let (base, handle) = start_server().await;
+    let client = reqwest::Client::new();
+    let pool = connect_pool().await;
+
+    for _ in 0..2 {
+        let res = login(&client, &base, &username, "mauvais").await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    }
+    let failures: i32 = sqlx::query_scalar("SELECT failed_login_count FROM users WHERE id = $1")
+        .bind(uid)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(failures, 2);
+
+    let res = login(&client, &base, &username, &password).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let failures: i32 = sqlx::query_scalar("SELECT failed_login_count FROM users WHERE id = $1")
+        .bind(uid)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(failures, 0);
+
+    let reasons: Vec<String> =
+        sqlx::query_scalar("SELECT reason FROM login_attempts WHERE user_id = $1 ORDER BY id")
+            .bind(uid)
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+    assert_eq!(reasons, ["invalid_password", "invalid_password", "success"]);
+
+    handle.abort();
+    delete_user(&username).await;
+}
+
+#[tokio::test]
+async fn compte_inconnu_meme_reponse_et_journalise() {
+    let (base, handle) = start_server().await;
+    let pool = connect_pool().await;
+    let login_name = format!(
+        "inconnu_{}",
+        &uuid::Uuid::new_v4().simple().to_string()[..8]
+    );
+
+    let res = login(&reqwest::Client::new(), &base, &login_name, "whatever").await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(res.text().await.unwrap(), "Identifiants invalides");
+
+    let reason: String = sqlx::query_scalar("SELECT reason FROM login_attempts WHERE login = $1")
+        .bind(&login_name)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(reason, "unknown_account");
+
+    // Pas de compte à supprimer: on nettoie le journal pour ne pas peser sur le backoff par IP
+    forget_login(&pool, &login_name).await;
+    handle.abort();
+}
+
+async fn forget_login(pool: &sqlx::PgPool, login: &str) {
+    sqlx::query("DELETE FROM login_attempts WHERE LOWER(login) = LOWER($1)")
+        .bind(login)
+        .execute(pool)
+        .await
+        .unwrap();
+    sqlx::query("DELETE FROM unknown_login_failures WHERE login = LOWER($1)")
+        .bind(login)
+        .execute(pool)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn compte_inconnu_meme_backoff_et_verrouillage() {
+    let (base, handle) = start_server().await;
+    let pool = connect_pool().await;
+    let client = reqwest::Client::new();
+    let login_name = format!(
+        "fantome_{}",
+        &uuid::Uuid::new_v4().simple().to_string()[..8]
+    );
+
+    // Séquence du test de verrouillage d'un compte réel, statut pour statut
+    for _ in 0..3 {
+        let res = login(&client, &base, &login_name, "mauvais").await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    }
+    // Compté par identifiant, sans tenir compte de la casse
+    let res = login(&client, &base, &login_name.to_uppercase(), "mauvais").await;
+    assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+    tokio::time::sleep(Duration::from_secs(retry_after(&res)) + Duration::from_millis(100)).await;
+
+    let res = login(&client, &base, &login_name, "mauvais").await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    let res = login(&client, &base, &login_name, "mauvais").await;
+    assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+    tokio::time::sleep(Duration::from_secs(retry_after(&res)) + Duration::from_millis(100)).await;
+
+    let res = login(&client, &base, &login_name, "mauvais").await;
+    assert_eq!(res.status(), StatusCode::LOCKED);
+    let res = login(&client, &base, &login_name, "mauvais").await;
+    assert_eq!(res.status(), StatusCode::LOCKED);
+    assert!(retry_after(&res) > 60);
+
+    forget_login(&pool, &login_name).await;
+    handle.abort();
+}
+
+#[tokio::test]
+async fn identifiant_trop_long_refuse_comme_un_mauvais_login() {
+    let (base, handle) = start_server().await;
+    let client = reqwest::Client::new();
+    let login_name = "x".repeat(300);
+
+    // Plus long que les colonnes login: 401, pas d'erreur SQL
+    for _ in 0..2 {
+        let res = login(&client, &base, &login_name, "whatever").await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(res.text().await.unwrap(), "Identifiants invalides");
+    }
+
+    handle.abort();
+}
+
+#[tokio::test]
+async fn tentatives_anciennes_purgees() {
+    let pool = connect_pool().await;
+    let login_name = format!("ancien_{}", &uuid::Uuid::new_v4().simple().to_string()[..8]);
+    for days in [1, 40] {
+        sqlx::query(
+            r#"INSERT INTO login_attempts (login, success, reason, created_at)
+               VALUES ($1, false, 'unknown_account', NOW() - make_interval(days => $2))"#,
+        )
+        .bind(&login_name)
+        .bind(days)
+        .execute(&pool)
+        .await
+        .unwrap();
+    }
+    sqlx::query(
+        r#"INSERT INTO unknown_login_failures (login, failed_login_count, last_failed_login_at)
+           VALUES ($1, 2, NOW() - INTERVAL '40 days')"#,
+    )
+    .bind(&login_name)
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    assert!(login_guard::prune(&pool, 30).await.unwrap() >= 1);
+    let left: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM login_attempts WHERE login = $1")
+        .bind(&login_name)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(left, 1);
+    let counters: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM unknown_login_failures WHERE login = $1")
+            .bind(&login_name)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(counters, 0);
+
+    forget_login(&pool, &login_name).await;
+}
+
+#[tokio::test]
+async fn backoff_et_verrouillage_suivent_l_horloge_injectee() {
+    let (_uid, username, _email, password) = create_test_user("lockout_clock").await;
+    // Dans le passé: les tentatives datées par l'horloge ne pèsent pas sur les autres tests
+    let clock = FakeClock::at(time::OffsetDateTime::now_utc() - time::Duration::days(1));
+    let (base, handle) = start_server_with(|state| state.with_clock(clock.clone())).await;
+    let client = reqwest::Client::new();
+
+    for _ in 0..3 {
+        let res = login(&client, &base, &username, "mauvais").await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    }
+    for _ in 0..2 {
+        let res = login(&client, &base, &username, &password).await;
+        assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+        clock.advance(time::Duration::seconds(retry_after(&res) as i64));
+        let res = login(&client, &base, &username, "mauvais").await;
+        assert_ne!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    let res = login(&client, &base, &username, &password).await;
+    assert_eq!(res.status(), StatusCode::LOCKED);
+    let lockout = common::test_config().login.lockout_secs;
+    assert_eq!(retry_after(&res), lockout as u64);
+
+    clock.advance(time::Duration::seconds(lockout));
+    let res = login(&client, &base, &username, &password).await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    handle.abort();
+    delete_user(&username).await;
+}
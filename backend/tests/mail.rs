@@ -0,0 +1,142 @@
+use pokedex_rncp_backend as backend;
+
+use backend::mailer::{Email, MailError, MailFuture, MailQueue, Mailer};
+use reqwest::StatusCode;
+use serde_json::json;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+mod common;
+use common::{create_test_user, delete_user, outbox_for, outbox_mail, start_server};
+
+#[tokio::test]
+async fn reset_envoie_un_email_avec_le_lien() {
+    let (_uid, username, email, _pwd) = create_test_user("mail_reset").await;
+    let (base, handle) = start_server().await;
+    let client = reqwest::Client::new();
+
+    let res = client
+        .post(format!("{}/api/auth/request-password-reset", base))
+        .header(reqwest::header::ACCEPT_LANGUAGE, "en-US,en;q=0.9")
+        .json(&json!({ "email_or_username": username }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::ACCEPTED);
+    let body = res.json::<serde_json::Value>().await.unwrap();
+    let token = body["reset_token"].as_str().unwrap();
+
+    let mails = outbox_for(&email).await;
+    assert_eq!(mails.len(), 1, "un email de reset attendu");
+    assert_eq!(mails[0]["subject"], "Reset your PokeRNCP password");
+    assert!(
+        mails[0]["body"]
+            .as_str()
+            .unwrap()
+            .contains(&format!("/reset-password?token={token}"))
+    );
+
+    handle.abort();
+    delete_user(&username).await;
+}
+
+#[tokio::test]
+async fn inscription_envoie_un_email_de_bienvenue() {
+    let (base, handle) = start_server().await;
+    let client = reqwest::Client::new();
+    let username = format!(
+        "welcome_{}",
+        &uuid::Uuid::new_v4().simple().to_string()[..8]
+    );
+    let email = format!("{}@example.com", username);
+
+    let res = client
+        .post(format!("{}/api/users", base))
+        .json(&json!({ "username": username, "email": email, "password": "SignupPass123!" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::CREATED);
+
+    assert!(
+        outbox_mail(&email, "Bienvenue sur PokeRNCP")
+            .await
+            .is_some()
+    );
+
+    handle.abort();
+    delete_user(&username).await;
+}
+
+#[tokio::test]
+async fn changement_mot_de_passe_notifie_par_email() {
+    let (uid, username, email, password) = create_test_user("mail_pwd").await;
+    let (base, handle) = start_server().await;
+    let client = reqwest::Client::new();
+    let access = common::access_token(uid);
+
+    let res = client
+        .put(format!("{}/api/auth/change-password", base))
+        .bearer_auth(&access)
+        .json(&json!({ "current_password": password, "new_password": "ChangedPass123!" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let mails = outbox_for(&email).await;
+    assert_eq!(mails.len(), 1);
+    assert_eq!(
+        mails[0]["subject"],
+        "Ton mot de passe PokeRNCP a été modifié"
+    );
+
+    handle.abort();
+    delete_user(&username).await;
+}
+
+// Transport qui échoue un certain nombre de fois avant de réussir
+struct FlakyMailer {
+    failures: u32,
+    attempts: Arc<AtomicU32>,
+}
+
+impl Mailer for FlakyMailer {
+    fn send<'a>(&'a self, _email: &'a Email) -> MailFuture<'a> {
+        Box::pin(async move {
+            let n = self.attempts.fetch_add(1, Ordering::SeqCst) + 1;
+            if n <= self.failures {
+                Err(MailError("smtp indisponible".into()))
+            } else {
+                Ok(())
+            }
+        })
+    }
+}
+
+#[tokio::test]
+async fn file_envoi_retente_apres_echec() {
+    let attempts = Arc::new(AtomicU32::new(0));
+    let queue = MailQueue::new(
+        Arc::new(FlakyMailer {
+            failures: 1,
+            attempts: attempts.clone(),
+        }),
+        5,
+    );
+
+    queue.enqueue(Email {
+        to: "retry@example.com".into(),
+        subject: "test".into(),
+        body: "test".into(),
+    });
+
+    for _ in 0..40 {
+        if attempts.load(Ordering::SeqCst) >= 2 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    assert_eq!(attempts.load(Ordering::SeqCst), 2);
+}
@@ -0,0 +1,158 @@
+use pokedex_rncp_backend::config::{AppConfig, MetricsConfig};
+use reqwest::StatusCode;
+use reqwest::header::CONTENT_TYPE;
+use serde_json::json;
+
+mod common;
+use common::{connect_pool, create_test_user, delete_user, ensure_pokemon};
+
+// /metrics n'est servi sur le port principal que sur demande (METRICS_PUBLIC).
+async fn start_server() -> (String, tokio::task::JoinHandle<()>) {
+    let config = Box::leak(Box::new(AppConfig {
+        metrics: MetricsConfig {
+            public: true,
+            ..MetricsConfig::default()
+        },
+        ..common::test_config().clone()
+    }));
+    common::start_server_with_config(config, |state| state).await
+}
+
+async fn scrape(base: &str) -> String {
+    let res = reqwest::get(format!("{base}/metrics")).await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert!(
+        res.headers()[CONTENT_TYPE]
+            .to_str()
+            .unwrap()
+            .starts_with("text/plain; version=0.0.4")
+    );
+    res.text().await.unwrap()
+}
+
+// Valeur d'une série: nom exact et tous les labels demandés (0 si absente)
+fn sample(text: &str, name: &str, labels: &[(&str, &str)]) -> f64 {
+    text.lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter(|line| {
+            let series = line.rsplit_once(' ').map_or(*line, |(series, _)| series);
+            series.split('{').next() == Some(name)
+                && labels
+                    .iter()
+                    .all(|(k, v)| series.contains(&format!("{k}=\"{v}\"")))
+        })
+        .map(|line| line.rsplit_once(' ').unwrap().1.parse::<f64>().unwrap())
+        .sum()
+}
+
+#[tokio::test]
+async fn requetes_comptees_par_modele_de_route() {
+    let (base, handle) = start_server().await;
+    let id: i32 = sqlx::query_scalar("SELECT MIN(id) FROM pokemon")
+        .fetch_one(&connect_pool().await)
+        .await
+        .unwrap();
+
+    let route = [
+        ("method", "GET"),
+        ("route", "/api/pokemons/{pokemon_id}"),
+        ("status", "401"),
+    ];
+    let before = sample(&scrape(&base).await, "pokedex_http_requests_total", &route);
+    for _ in 0..2 {
+        let res = reqwest::get(format!("{base}/api/pokemons/{id}"))
+            .await
+            .unwrap();
+        // Sans jeton: refus, compté avec son statut
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    let text = scrape(&base).await;
+    assert_eq!(
+        sample(&text, "pokedex_http_requests_total", &route),
+        before + 2.0
+    );
+    assert!(sample(&text, "pokedex_http_request_duration_seconds_count", &route) >= 2.0);
+    // Jamais de chemin brut en label
+    assert!(!text.contains(&format!("route=\"/api/pokemons/{id}\"")));
+
+    handle.abort();
+}
+
+#[tokio::test]
+async fn connexions_et_captures_comptees() {
+    let (base, handle) = start_server().await;
+    let (uid, username, _email, password) = create_test_user("metrics").await;
+    ensure_pokemon("Countmon", "Normal").await;
+    let client = reqwest::Client::new();
+
+    let success = [("result", "success")];
+    let failure = [("result", "failure"), ("reason", "invalid_password")];
+    let text = scrape(&base).await;
+    let (ok_before, ko_before) = (
+        sample(&text, "pokedex_login_attempts_total", &success),
+        sample(&text, "pokedex_login_attempts_total", &failure),
+    );
+    let catches_before = sample(&text, "pokedex_catches_total", &[]);
+
+    for attempt in ["mauvais-mot-de-passe", password.as_str()] {
+        client
+            .post(format!("{base}/api/auth/login"))
+            .json(&json!({ "username": username, "password": attempt }))
+            .send()
+            .await
+            .unwrap();
+    }
+    let access = common::access_token(uid);
+    // La seconde capture du même Pokémon n'en est pas une nouvelle
+    for _ in 0..2 {
+        client
+            .post(format!("{base}/api/pokemons/catch"))
+            .bearer_auth(&access)
+            .json(&json!({ "name": "Countmon" }))
+            .send()
+            .await
+            .unwrap();
+    }
+
+    let text = scrape(&base).await;
+    assert_eq!(
+        sample(&text, "pokedex_login_attempts_total", &success),
+        ok_before + 1.0
+    );
+    assert_eq!(
+        sample(&text, "pokedex_login_attempts_total", &failure),
+        ko_before + 1.0
+    );
+    assert_eq!(
+        sample(&text, "pokedex_catches_total", &[]),
+        catches_before + 1.0
+    );
+
+    delete_user(&username).await;
+    handle.abort();
+}
+
+#[tokio::test]
+async fn jauges_du_pool_des_migrations_et_du_seed() {
+    let (base, handle) = start_server().await;
+    let text = scrape(&base).await;
+
+    assert_eq!(sample(&text, "pokedex_ready", &[]), 1.0);
+    assert_eq!(
+        sample(&text, "pokedex_migrations", &[("state", "pending")]),
+        0.0
+    );
+    assert!(sample(&text, "pokedex_migrations", &[("state", "applied")]) > 0.0);
+    assert!(sample(&text, "pokedex_migration_version", &[]) > 0.0);
+    assert!(sample(&text, "pokedex_seed_pokemon", &[]) > 0.0);
+    assert!(sample(&text, "pokedex_db_pool_max_connections", &[]) > 0.0);
+    assert!(sample(&text, "pokedex_db_pool_acquire_seconds", &[]) >= 0.0);
+    assert!(text.contains("pokedex_db_pool_connections{state=\"idle\"}"));
+    assert!(text.contains(&format!(
+        "pokedex_build_info{{git_hash=\"{}\"",
+        pokedex_rncp_backend::health::GIT_HASH
+    )));
+
+    handle.abort();
+}
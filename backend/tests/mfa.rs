@@ -0,0 +1,294 @@
+use pokedex_rncp_backend as backend;
+
+use reqwest::StatusCode;
+use serde_json::{Value, json};
+
+mod common;
+use common::{FakeClock, create_test_user, delete_user, start_server, start_server_with};
+
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+fn code(secret: &str, at: u64) -> String {
+    backend::mfa::code_at(secret, at).unwrap()
+}
+
+// Active la 2FA et renvoie (secret, code utilisé pour l'activation, codes de récupération)
+async fn enable_two_factor(
+    client: &reqwest::Client,
+    base: &str,
+    access: &str,
+    at: u64,
+) -> (String, String, Vec<String>) {
+    let setup = client
+        .post(format!("{}/api/auth/2fa/setup", base))
+        .bearer_auth(access)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(setup.status(), StatusCode::OK);
+    let setup: Value = setup.json().await.unwrap();
+    let secret = setup["secret"].as_str().unwrap().to_string();
+    let uri = setup["otpauth_uri"].as_str().unwrap();
+    assert!(uri.starts_with("otpauth://totp/"));
+    assert!(uri.contains(&format!("secret={secret}")));
+
+    let first_code = code(&secret, at);
+    let res = client
+        .post(format!("{}/api/auth/2fa/verify", base))
+        .bearer_auth(access)
+        .json(&json!({ "code": first_code }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let body: Value = res.json().await.unwrap();
+    let codes = body["recovery_codes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| c.as_str().unwrap().to_string())
+        .collect::<Vec<_>>();
+    (secret, first_code, codes)
+}
+
+// Première étape: mot de passe correct, 202 sans cookies et token "mfa pending"
+async fn login_first_step(
+    client: &reqwest::Client,
+    base: &str,
+    username: &str,
+    password: &str,
+) -> String {
+    let res = client
+        .post(format!("{}/api/auth/login", base))
+        .json(&json!({ "username": username, "password": password }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::ACCEPTED);
+    assert!(res.headers().get(reqwest::header::SET_COOKIE).is_none());
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["mfa_required"], true);
+    body["mfa_token"].as_str().unwrap().to_string()
+}
+
+async fn login_second_step(
+    client: &reqwest::Client,
+    base: &str,
+    mfa_token: &str,
+    code: &str,
+) -> reqwest::Response {
+    client
+        .post(format!("{}/api/auth/2fa/login", base))
+        .json(&json!({ "mfa_token": mfa_token, "code": code }))
+        .send()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn activation_puis_login_en_deux_etapes() {
+    let (uid, username, _email, password) = create_test_user("mfa_login").await;
+    let (base, handle) = start_server().await;
+    let client = reqwest::Client::new();
+    let access = common::access_token(uid);
+
+    let (secret, first_code, codes) = enable_two_factor(&client, &base, &access, now()).await;
+    assert_eq!(codes.len(), 10);
+
+    let res = client
+        .post(format!("{}/api/auth/2fa/setup", base))
+        .bearer_auth(&access)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::CONFLICT);
+
+    let me: Value = client
+        .get(format!("{}/api/auth/me", base))
+        .bearer_auth(&access)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(me["two_factor_enabled"], true);
+
+    let mfa_token = login_first_step(&client, &base, &username, &password).await;
+
+    // Le code déjà utilisé pour l'activation ne peut pas être rejoué
+    let res = login_second_step(&client, &base, &mfa_token, &first_code).await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    // Pas suivant, dans la tolérance de décalage d'horloge
+    let res = login_second_step(&client, &base, &mfa_token, &code(&secret, now() + 30)).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let cookies = res
+        .headers()
+        .get_all(reqwest::header::SET_COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .collect::<Vec<_>>();
+    assert!(cookies.iter().any(|c| c.starts_with("auth=")));
+    assert!(cookies.iter().any(|c| c.starts_with("refresh=")));
+
+    handle.abort();
+    delete_user(&username).await;
+}
+
+#[tokio::test]
+async fn codes_de_recuperation_a_usage_unique() {
+    let (uid, username, _email, password) = create_test_user("mfa_recovery").await;
+    let (base, handle) = start_server().await;
+    let client = reqwest::Client::new();
+    let access = common::access_token(uid);
+    let (_secret, _, codes) = enable_two_factor(&client, &base, &access, now()).await;
+
+    let mfa_token = login_first_step(&client, &base, &username, &password).await;
+    let res = login_second_step(&client, &base, &mfa_token, &codes[0]).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    // Token "mfa pending" consommé: rejeté avant même de regarder le code
+    let res = login_second_step(&client, &base, &mfa_token, &codes[1]).await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    let mfa_token = login_first_step(&client, &base, &username, &password).await;
+    let res = login_second_step(&client, &base, &mfa_token, &codes[0]).await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    // Saisie tolérante: majuscules, sans tiret
+    let res = login_second_step(
+        &client,
+        &base,
+        &mfa_token,
+        &codes[1].replace('-', "").to_uppercase(),
+    )
+    .await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let remaining = backend::mfa::remaining_recovery_codes(&common::connect_pool().await, uid)
+        .await
+        .unwrap();
+    assert_eq!(remaining, 8);
+
+    handle.abort();
+    delete_user(&username).await;
+}
+
+#[tokio::test]
+async fn seconde_etape_par_cookie_mfa_pending() {
+    let (uid, username, _email, password) = create_test_user("mfa_cookie").await;
+    let (base, handle) = start_server().await;
+    let client = reqwest::Client::new();
+    let access = common::access_token(uid);
+    let (secret, _, _) = enable_two_factor(&client, &base, &access, now()).await;
+
+    // Retour OIDC: le token arrive en cookie, le corps ne porte que le code
+    let mfa_token = login_first_step(&client, &base, &username, &password).await;
+    let second_step = |code: String| {
+        client
+            .post(format!("{}/api/v1/auth/2fa/login", base))
+            .header(reqwest::header::COOKIE, format!("mfa_pending={mfa_token}"))
+            .json(&json!({ "code": code }))
+            .send()
+    };
+    let res = second_step("000000".into()).await.unwrap();
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    let res = second_step(code(&secret, now() + 30)).await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let cookies = res
+        .headers()
+        .get_all(reqwest::header::SET_COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .collect::<Vec<_>>();
+    assert!(cookies.iter().any(|c| c.starts_with("auth=")));
+    assert!(cookies.iter().any(|c| c.starts_with("mfa_pending=;")));
+
+    // Cookie rejoué: refusé
+    let res = second_step(code(&secret, now() + 60)).await.unwrap();
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    handle.abort();
+    delete_user(&username).await;
+}
+
+#[tokio::test]
+async fn token_mfa_pending_inutilisable_et_desactivation() {
+    let (uid, username, _email, password) = create_test_user("mfa_disable").await;
+    let (base, handle) = start_server().await;
+    let client = reqwest::Client::new();
+    let access = common::access_token(uid);
+    let (_secret, _, codes) = enable_two_factor(&client, &base, &access, now()).await;
+
+    // Le token intermédiaire n'ouvre aucune route authentifiée
+    let mfa_token = login_first_step(&client, &base, &username, &password).await;
+    let res = client
+        .get(format!("{}/api/auth/me", base))
+        .bearer_auth(&mfa_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    let res = client
+        .post(format!("{}/api/auth/2fa/disable", base))
+        .bearer_auth(&access)
+        .json(&json!({ "code": "000000" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    let res = client
+        .post(format!("{}/api/auth/2fa/disable", base))
+        .bearer_auth(&access)
+        .json(&json!({ "code": codes[0] }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+
+    // Le token émis avant la désactivation ne sert plus; le login redevient direct
+    let res = login_second_step(&client, &base, &mfa_token, &codes[1]).await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    let res = client
+        .post(format!("{}/api/auth/login", base))
+        .json(&json!({ "username": username, "password": password }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+
+    handle.abort();
+    delete_user(&username).await;
+}
+
+#[tokio::test]
+async fn horloge_injectee_decide_du_pas_totp() {
+    let (uid, username, _email, password) = create_test_user("mfa_clock").await;
+    // 2030-01-01 12:00 UTC
+    let t0 = 1_893_499_200u64;
+    let clock = FakeClock::at(time::OffsetDateTime::from_unix_timestamp(t0 as i64).unwrap());
+    let (base, handle) = start_server_with(|state| state.with_clock(clock.clone())).await;
+    let client = reqwest::Client::new();
+    let access = common::access_token(uid);
+
+    let (secret, _, _) = enable_two_factor(&client, &base, &access, t0).await;
+    let mfa_token = login_first_step(&client, &base, &username, &password).await;
+
+    // Trois pas plus tard: hors tolérance pour le serveur, tant que l'horloge n'a pas avancé
+    let later = code(&secret, t0 + 90);
+    let res = login_second_step(&client, &base, &mfa_token, &later).await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    clock.advance(time::Duration::seconds(90));
+    let res = login_second_step(&client, &base, &mfa_token, &later).await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    handle.abort();
+    delete_user(&username).await;
+}
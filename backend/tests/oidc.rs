@@ -0,0 +1,451 @@
+use pokedex_rncp_backend as backend;
+
+use axum::extract::{Form, Query, State};
+use axum::http::StatusCode as AxumStatus;
+use axum::response::{IntoResponse, Redirect, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use reqwest::StatusCode;
+use reqwest::header::{COOKIE, LOCATION};
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use uuid::Uuid;
+
+mod common;
+use common::{connect_pool, create_test_user, delete_user, set_cookie_header, set_cookie_value};
+
+const CLIENT_ID: &str = "pokedex-test";
+const KID: &str = "mock-1";
+// Module public de tests/fixtures/oidc_mock_rsa.pem (exposant AQAB)
+const MOCK_N: &str = "qU4Mx8q5r_I-o-vNEN8nAAIPQu0R-zPMQE6ky0Kf1yc_GkxssjDCq3A7UOcupwR3cM_0eSVR6AYXpPlyvpShE9st2lqhEj8f4OMsYTYFSkmQ1s3KnfsjCk9Lv_I-RhLX_yiEz3eP0Plv7PGa-rh24jgXblZJVJqy0LqvV7yPA2VOO4kr8yOkg_1qtwOfvpSQNOWZq7cs0zblMCeTZmKljpI6anTGn2pg1qOwyMezOnYB0fY1sgKGbhrDYY--64i1OJwOKiA8e37u7zmvmdisjy7-aWr1wQMbU1QMlaceHrNhcJ7GU7HruOLvXTL8QTzldDEsLeVlu3kExWpb1BEiHQ";
+
+// Fournisseur OIDC factice: l'identité renvoyée est choisie par les paramètres
+// mock_* ajoutés à l'URL d'autorisation.
+#[derive(Clone)]
+struct Grant {
+    params: HashMap<String, String>,
+}
+
+#[derive(Clone)]
+struct Mock {
+    issuer: String,
+    grants: Arc<Mutex<HashMap<String, Grant>>>,
+}
+
+async fn discovery(State(mock): State<Mock>) -> Json<Value> {
+    Json(json!({
+        "issuer": mock.issuer,
+        "authorization_endpoint": format!("{}/authorize", mock.issuer),
+        "token_endpoint": format!("{}/token", mock.issuer),
+        "jwks_uri": format!("{}/jwks", mock.issuer),
+    }))
+}
+
+async fn jwks() -> Json<Value> {
+    Json(json!({ "keys": [{
+        "kty": "RSA", "use": "sig", "alg": "RS256", "kid": KID, "n": MOCK_N, "e": "AQAB"
+    }] }))
+}
+
+async fn authorize(
+    State(mock): State<Mock>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Response {
+    assert_eq!(params["client_id"], CLIENT_ID);
+    assert_eq!(params["code_challenge_method"], "S256");
+    let code = Uuid::new_v4().simple().to_string();
+    let location = format!(
+        "{}?code={code}&state={}",
+        params["redirect_uri"], params["state"]
+    );
+    mock.grants.lock().unwrap().insert(code, Grant { params });
+    Redirect::to(&location).into_response()
+}
+
+async fn token(State(mock): State<Mock>, Form(form): Form<HashMap<String, String>>) -> Response {
+    let Some(grant) = mock.grants.lock().unwrap().remove(&form["code"]) else {
+        return (
+            AxumStatus::BAD_REQUEST,
+            Json(json!({ "error": "invalid_grant" })),
+        )
+            .into_response();
+    };
+    let p = &grant.params;
+    let challenge = p
+        .get("mock_challenge")
+        .cloned()
+        .unwrap_or_else(|| p["code_challenge"].clone());
+    let verifier = form.get("code_verifier").cloned().unwrap_or_default();
+    if URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes())) != challenge
+        || form["redirect_uri"] != p["redirect_uri"]
+    {
+        return (
+            AxumStatus::BAD_REQUEST,
+            Json(json!({ "error": "invalid_grant" })),
+        )
+            .into_response();
+    }
+
+    let now = time::OffsetDateTime::now_utc().unix_timestamp();
+    let claims = json!({
+        "iss": mock.issuer,
+        "aud": CLIENT_ID,
+        "sub": p["mock_sub"],
+        "iat": now,
+        "exp": now + 300,
+        "nonce": p.get("mock_nonce").unwrap_or(&p["nonce"]),
+        "email": p.get("mock_email"),
+        "email_verified": p.get("mock_verified").is_some_and(|v| v == "true"),
+        "preferred_username": p.get("mock_username"),
+    });
+    let mut header = Header::new(Algorithm::RS256);
+    header.kid = Some(KID.to_string());
+    let key = EncodingKey::from_rsa_pem(include_bytes!("fixtures/oidc_mock_rsa.pem")).unwrap();
+    let id_token = jsonwebtoken::encode(&header, &claims, &key).unwrap();
+    Json(json!({ "access_token": "opaque", "token_type": "Bearer", "id_token": id_token }))
+        .into_response()
+}
+
+// Le mock tourne sur son propre runtime: chaque #[tokio::test] a le sien
+fn mock_provider() -> &'static str {
+    static ISSUER: OnceLock<String> = OnceLock::new();
+    ISSUER.get_or_init(|| {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.set_nonblocking(true).unwrap();
+        let issuer = format!("http://{}", listener.local_addr().unwrap());
+        let mock = Mock {
+            issuer: issuer.clone(),
+            grants: Arc::default(),
+        };
+        std::thread::spawn(move || {
+            tokio::runtime::Runtime::new()
+                .unwrap()
+                .block_on(async move {
+                    let app = Router::new()
+                        .route("/.well-known/openid-configuration", get(discovery))
+                        .route("/jwks", get(jwks))
+                        .route("/authorize", get(authorize))
+                        .route("/token", post(token))
+                        .with_state(mock);
+                    let listener = tokio::net::TcpListener::from_std(listener).unwrap();
+                    axum::serve(listener, app).await.unwrap();
+                })
+        });
+        issuer
+    })
+}
+
+// Serveur de test avec le fournisseur "mock" en plus de ceux de la configuration
+async fn start_server() -> (String, tokio::task::JoinHandle<()>) {
+    let provider =
+        backend::oidc::OidcProvider::new("mock", mock_provider(), CLIENT_ID, Some("mock-secret"));
+    common::start_server_with(|state| state.with_oidc_provider(provider)).await
+}
+
+fn no_redirect_client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .unwrap()
+}
+
+fn location(res: &reqwest::Response) -> String {
+    res.headers()[LOCATION].to_str().unwrap().to_string()
+}
+
+struct Flow {
+    cookie: String,
+    code: String,
+    state: String,
+}
+
+// Départ depuis le backend puis passage chez le fournisseur, jusqu'au retour (non suivi)
+async fn authorize_flow(client: &reqwest::Client, base: &str, identity: &[(&str, &str)]) -> Flow {
+    mock_provider();
+    let res = client
+        .get(format!("{base}/api/auth/oidc/mock/start"))
+        .query(&[("redirect_to", "/pokedex")])
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::FOUND);
+    let cookie = set_cookie_value(&res, "oidc_flow").expect("cookie oidc_flow attendu");
+
+    let mut url = reqwest::Url::parse(&location(&res)).unwrap();
+    url.query_pairs_mut().extend_pairs(identity);
+    let res = client.get(url).send().await.unwrap();
+    assert_eq!(res.status(), StatusCode::SEE_OTHER);
+    let back = reqwest::Url::parse(&location(&res)).unwrap();
+    // URI historique par défaut: celle déjà déclarée chez les fournisseurs
+    assert!(back.path().ends_with("/api/auth/oidc/mock/callback"));
+    let param = |k: &str| {
+        back.query_pairs()
+            .find(|(key, _)| key == k)
+            .map(|(_, v)| v.into_owned())
+            .unwrap()
+    };
+    Flow {
+        cookie,
+        code: param("code"),
+        state: param("state"),
+    }
+}
+
+async fn callback(client: &reqwest::Client, base: &str, flow: &Flow) -> reqwest::Response {
+    client
+        .get(format!("{base}/api/auth/oidc/mock/callback"))
+        .query(&[("code", &flow.code), ("state", &flow.state)])
+        .header(COOKIE, format!("oidc_flow={}", flow.cookie))
+        .send()
+        .await
+        .unwrap()
+}
+
+async fn me(client: &reqwest::Client, base: &str, auth: &str) -> Value {
+    client
+        .get(format!("{base}/api/auth/me"))
+        .header(COOKIE, format!("auth={auth}"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn premier_login_cree_le_compte_puis_le_reutilise() {
+    let (base, handle) = start_server().await;
+    let client = no_redirect_client();
+    let short = &Uuid::new_v4().simple().to_string()[..8];
+    let sub = format!("sub-{short}");
+    let email = format!("oidc_{short}@example.com");
+    let identity = [
+        ("mock_sub", sub.as_str()),
+        ("mock_email", email.as_str()),
+        ("mock_verified", "true"),
+        ("mock_username", &format!("Sacha.{short}")),
+    ];
+
+    let flow = authorize_flow(&client, &base, &identity).await;
+    let res = callback(&client, &base, &flow).await;
+    assert_eq!(res.status(), StatusCode::FOUND);
+    assert!(location(&res).ends_with("/pokedex"));
+    assert!(set_cookie_value(&res, "refresh").is_some());
+    assert_eq!(set_cookie_value(&res, "oidc_flow").as_deref(), Some(""));
+    let profile = me(&client, &base, &set_cookie_value(&res, "auth").unwrap()).await;
+    let username = profile["username"].as_str().unwrap().to_string();
+    assert_eq!(username, format!("sacha_{short}"));
+    assert_eq!(profile["email"], email.as_str());
+
+    // Même identité: même compte, pas de doublon
+    let flow = authorize_flow(&client, &base, &identity).await;
+    let res = callback(&client, &base, &flow).await;
+    assert_eq!(res.status(), StatusCode::FOUND);
+    let again = me(&client, &base, &set_cookie_value(&res, "auth").unwrap()).await;
+    assert_eq!(again["id"], profile["id"]);
+
+    // Le code est à usage unique
+    assert_eq!(
+        callback(&client, &base, &flow).await.status(),
+        StatusCode::BAD_GATEWAY
+    );
+
+    handle.abort();
+    delete_user(&username).await;
+}
+
+#[tokio::test]
+async fn collision_de_nom_et_rattachement_par_email_verifie() {
+    let (_, taken, taken_email, _) = create_test_user("oidc_clash").await;
+    let (uid_verified, verified_user, verified_email, _) = create_test_user("oidc_mail").await;
+    sqlx::query("UPDATE users SET email_verified_at = NOW() WHERE id = $1")
+        .bind(uid_verified)
+        .execute(&connect_pool().await)
+        .await
+        .unwrap();
+    let (base, handle) = start_server().await;
+    let client = no_redirect_client();
+
+    // Nom déjà pris, email existant mais non vérifié localement: nouveau compte suffixé, sans email
+    let sub = format!("sub-{}", Uuid::new_v4().simple());
+    let flow = authorize_flow(
+        &client,
+        &base,
+        &[
+            ("mock_sub", sub.as_str()),
+            ("mock_email", taken_email.as_str()),
+            ("mock_verified", "true"),
+            ("mock_username", taken.as_str()),
+        ],
+    )
+    .await;
+    let res = callback(&client, &base, &flow).await;
+    assert_eq!(res.status(), StatusCode::FOUND);
+    let profile = me(&client, &base, &set_cookie_value(&res, "auth").unwrap()).await;
+    let created = profile["username"].as_str().unwrap().to_string();
+    assert_eq!(created, format!("{taken}_2"));
+    assert!(profile["email"].is_null());
+
+    // Email vérifié des deux côtés: l'identité est rattachée au compte existant
+    let sub = format!("sub-{}", Uuid::new_v4().simple());
+    let flow = authorize_flow(
+        &client,
+        &base,
+        &[
+            ("mock_sub", sub.as_str()),
+            ("mock_email", verified_email.as_str()),
+            ("mock_verified", "true"),
+        ],
+    )
+    .await;
+    let res = callback(&client, &base, &flow).await;
+    assert_eq!(res.status(), StatusCode::FOUND);
+    let auth = set_cookie_value(&res, "auth").unwrap();
+    let csrf = set_cookie_value(&res, "csrf").expect("cookie csrf attendu");
+    let profile = me(&client, &base, &auth).await;
+    assert_eq!(profile["username"], verified_user.as_str());
+
+    let identities: Vec<Value> = client
+        .get(format!("{base}/api/auth/identities"))
+        .header(COOKIE, format!("auth={auth}"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(identities.len(), 1);
+    assert_eq!(identities[0]["provider"], "mock");
+    let res = client
+        .delete(format!(
+            "{base}/api/auth/identities/{}",
+            identities[0]["id"].as_str().unwrap()
+        ))
+        .header(COOKIE, format!("auth={auth}; csrf={csrf}"))
+        .header("X-CSRF-Token", &csrf)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+
+    handle.abort();
+    delete_user(&taken).await;
+    delete_user(&created).await;
+    delete_user(&verified_user).await;
+}
+
+#[tokio::test]
+async fn callback_refuse_state_nonce_et_pkce_invalides() {
+    let (base, handle) = start_server().await;
+    let client = no_redirect_client();
+    let sub = format!("sub-{}", Uuid::new_v4().simple());
+
+    let flow = authorize_flow(&client, &base, &[("mock_sub", sub.as_str())]).await;
+    // Sans le cookie du navigateur qui a démarré le flux
+    let res = client
+        .get(format!("{base}/api/auth/oidc/mock/callback"))
+        .query(&[("code", &flow.code), ("state", &flow.state)])
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    // state d'un autre flux
+    let forged = Flow {
+        state: "autre".into(),
+        ..flow
+    };
+    assert_eq!(
+        callback(&client, &base, &forged).await.status(),
+        StatusCode::BAD_REQUEST
+    );
+
+    let flow = authorize_flow(
+        &client,
+        &base,
+        &[("mock_sub", sub.as_str()), ("mock_nonce", "rejoue")],
+    )
+    .await;
+    assert_eq!(
+        callback(&client, &base, &flow).await.status(),
+        StatusCode::UNAUTHORIZED
+    );
+
+    let flow = authorize_flow(
+        &client,
+        &base,
+        &[("mock_sub", sub.as_str()), ("mock_challenge", "autre")],
+    )
+    .await;
+    assert_eq!(
+        callback(&client, &base, &flow).await.status(),
+        StatusCode::BAD_GATEWAY
+    );
+
+    // Aucun compte créé pour cette identité
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM user_identities WHERE subject = $1")
+        .bind(&sub)
+        .fetch_one(&connect_pool().await)
+        .await
+        .unwrap();
+    assert_eq!(count, 0);
+
+    handle.abort();
+}
+
+#[tokio::test]
+async fn compte_2fa_recoit_l_etape_du_code_par_cookie() {
+    let (base, handle) = start_server().await;
+    let client = no_redirect_client();
+    let short = &Uuid::new_v4().simple().to_string()[..8];
+    let sub = format!("sub-{short}");
+    let identity = [
+        ("mock_sub", sub.as_str()),
+        ("mock_username", &format!("mfa.{short}")),
+    ];
+    let flow = authorize_flow(&client, &base, &identity).await;
+    let res = callback(&client, &base, &flow).await;
+    let profile = me(&client, &base, &set_cookie_value(&res, "auth").unwrap()).await;
+    let username = profile["username"].as_str().unwrap().to_string();
+
+    let secret = backend::mfa::new_secret();
+    sqlx::query("UPDATE users SET totp_secret = $2, totp_enabled_at = NOW() WHERE username = $1")
+        .bind(&username)
+        .bind(&secret)
+        .execute(&connect_pool().await)
+        .await
+        .unwrap();
+
+    // Pas de session ni de token dans l'URL: le token attend le code en cookie HttpOnly
+    let flow = authorize_flow(&client, &base, &identity).await;
+    let res = callback(&client, &base, &flow).await;
+    assert_eq!(res.status(), StatusCode::FOUND);
+    assert!(location(&res).ends_with("/login?mfa=pending"));
+    assert!(set_cookie_value(&res, "auth").is_none());
+    let pending = set_cookie_value(&res, "mfa_pending").expect("cookie mfa_pending attendu");
+    let header = set_cookie_header(&res, "mfa_pending").unwrap();
+    assert!(header.contains("HttpOnly") && header.contains("Path=/api"));
+
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let res = client
+        .post(format!("{base}/api/v1/auth/2fa/login"))
+        .header(COOKIE, format!("mfa_pending={pending}"))
+        .json(&json!({ "code": backend::mfa::code_at(&secret, now).unwrap() }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let again = me(&client, &base, &set_cookie_value(&res, "auth").unwrap()).await;
+    assert_eq!(again["id"], profile["id"]);
+
+    handle.abort();
+    delete_user(&username).await;
+}
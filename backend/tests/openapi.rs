@@ -0,0 +1,117 @@
+use pokedex_rncp_backend::openapi;
+use reqwest::StatusCode;
+use serde_json::Value;
+use std::collections::BTreeSet;
+
+mod common;
+
+const SNAPSHOT: &str = "openapi.json";
+
+// Modifier un handler ou un modèle documenté change la spécification:
+// UPDATE_OPENAPI=1 cargo test --test openapi régénère backend/openapi.json
+#[test]
+fn specification_a_jour() {
+    let generated = openapi::to_json() + "\n";
+    if std::env::var_os("UPDATE_OPENAPI").is_some() {
+        std::fs::write(SNAPSHOT, &generated).unwrap();
+        return;
+    }
+    let committed = std::fs::read_to_string(SNAPSHOT).unwrap_or_default();
+    assert!(
+        committed == generated,
+        "backend/openapi.json n'est plus à jour: UPDATE_OPENAPI=1 cargo test --test openapi"
+    );
+}
+
+#[test]
+fn specification_3_1_et_modeles_documentes() {
+    let spec: Value = serde_json::from_str(&openapi::to_json()).unwrap();
+    assert!(spec["openapi"].as_str().unwrap().starts_with("3.1"));
+
+    let schemas = &spec["components"]["schemas"];
+    for model in [
+        "PokemonWithCaught",
+        "PokemonDetail",
+        "CatchByNamePayload",
+        "CreateUser",
+        "LoginUser",
+        "UpdateUser",
+        "MeInfo",
+        "SessionInfo",
+        "MfaChallenge",
+        "ValidationReport",
+    ] {
+        assert!(schemas[model].is_object(), "schéma {model} absent");
+    }
+    let detail = &spec["paths"]["/api/v1/pokemons/{pokemon_id}"]["get"];
+    assert_eq!(detail["parameters"][0]["name"], "pokemon_id");
+    assert_eq!(
+        detail["responses"]["200"]["content"]["application/json"]["schema"]["$ref"],
+        "#/components/schemas/PokemonDetail"
+    );
+    assert!(spec["components"]["securitySchemes"]["bearer"].is_object());
+}
+
+/// (méthode, chemin) de chaque route déclarée dans `src/routes/*.rs`, sous le
+/// préfixe `/api/v1/<nest>` que lui donne `src/app.rs`.
+fn routes_declarees() -> BTreeSet<(String, String)> {
+    let app = std::fs::read_to_string("src/app.rs").unwrap();
+    let mut routes = BTreeSet::new();
+    // .nest("/auth", routes::auth::user_routes())
+    for nest in app.split(".nest(\"").skip(1) {
+        let Some((prefix, rest)) = nest.split_once('"') else {
+            continue;
+        };
+        let Some(module) = rest
+            .trim_start_matches([',', ' ', '\n'])
+            .strip_prefix("routes::")
+            .and_then(|r| r.split("::").next())
+        else {
+            continue;
+        };
+        let source = std::fs::read_to_string(format!("src/routes/{module}.rs")).unwrap();
+        for route in source.split(".route(").skip(1) {
+            let path = route.split('"').nth(1).unwrap();
+            let path = format!("/api/v1{prefix}{}", path.trim_end_matches('/'));
+            for method in ["get", "post", "put", "patch", "delete"] {
+                if route.contains(&format!("{method}(")) {
+                    routes.insert((method.to_string(), path.clone()));
+                }
+            }
+        }
+    }
+    routes
+}
+
+// Une route ajoutée au routeur sans annotation #[utoipa::path] (ou absente de
+// `ApiDoc`) ferait diverger la spécification du code: on le vérifie ici.
+#[test]
+fn toutes_les_routes_documentees() {
+    let spec: Value = serde_json::from_str(&openapi::to_json()).unwrap();
+    let routes = routes_declarees();
+    assert!(routes.len() > 40, "routes introuvables: {routes:?}");
+    let absentes: Vec<_> = routes
+        .iter()
+        .filter(|(method, path)| spec["paths"][path][method].is_null())
+        .collect();
+    assert!(absentes.is_empty(), "routes non documentées: {absentes:?}");
+}
+
+#[tokio::test]
+async fn specification_et_swagger_ui_servies() {
+    let (base, handle) = common::start_server().await;
+
+    let res = reqwest::get(format!("{base}/api/openapi.json"))
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let served: Value = res.json().await.unwrap();
+    let expected: Value = serde_json::from_str(&openapi::to_json()).unwrap();
+    assert_eq!(served, expected);
+
+    let res = reqwest::get(format!("{base}/api/docs/")).await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert!(res.text().await.unwrap().contains("swagger-ui"));
+
+    handle.abort();
+}
@@ -0,0 +1,243 @@
+use pokedex_rncp_backend as backend;
+
+use backend::clock::SystemClock;
+use backend::password_reset;
+use backend::rng::Rng;
+use reqwest::StatusCode;
+use serde_json::json;
+use std::sync::Arc;
+
+mod common;
+use common::{
+    FakeClock, FakeMailer, SeqRng, connect_pool, create_test_user, delete_user, outbox_for,
+    start_server, start_server_with,
+};
+
+async fn request_reset(client: &reqwest::Client, base: &str, login: &str) -> Option<String> {
+    let res = client
+        .post(format!("{}/api/auth/request-password-reset", base))
+        .json(&json!({ "email_or_username": login }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::ACCEPTED);
+    let body = res.json::<serde_json::Value>().await.unwrap();
+    body["reset_token"].as_str().map(|s| s.to_string())
+}
+
+async fn confirm_reset(client: &reqwest::Client, base: &str, token: &str) -> StatusCode {
+    client
+        .post(format!("{}/api/auth/confirm-password-reset", base))
+        .json(&json!({ "token": token, "new_password": "ResetPass123!" }))
+        .send()
+        .await
+        .unwrap()
+        .status()
+}
+
+#[tokio::test]
+async fn token_de_reset_a_usage_unique() {
+    let (_uid, username, _email, _pwd) = create_test_user("reset_once").await;
+    let (base, handle) = start_server().await;
+    let client = reqwest::Client::new();
+
+    let token = request_reset(&client, &base, &username).await.unwrap();
+    assert_eq!(confirm_reset(&client, &base, &token).await, StatusCode::OK);
+    assert_eq!(
+        confirm_reset(&client, &base, &token).await,
+        StatusCode::BAD_REQUEST
+    );
+
+    handle.abort();
+    delete_user(&username).await;
+}
+
+#[tokio::test]
+async fn nouvelle_demande_invalide_la_precedente() {
+    let (_uid, username, _email, _pwd) = create_test_user("reset_newer").await;
+    let (base, handle) = start_server().await;
+    let client = reqwest::Client::new();
+
+    let first = request_reset(&client, &base, &username).await.unwrap();
+    let second = request_reset(&client, &base, &username).await.unwrap();
+    assert_eq!(
+        confirm_reset(&client, &base, &first).await,
+        StatusCode::BAD_REQUEST
+    );
+    assert_eq!(confirm_reset(&client, &base, &second).await, StatusCode::OK);
+
+    handle.abort();
+    delete_user(&username).await;
+}
+
+#[tokio::test]
+async fn changement_de_mot_de_passe_invalide_le_lien() {
+    let (uid, username, _email, password) = create_test_user("reset_changed").await;
+    let (base, handle) = start_server().await;
+    let client = reqwest::Client::new();
+    let access = common::access_token(uid);
+
+    let token = request_reset(&client, &base, &username).await.unwrap();
+    let res = client
+        .put(format!("{}/api/auth/change-password", base))
+        .bearer_auth(&access)
+        .json(&json!({ "current_password": password, "new_password": "ChangedPass123!" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(
+        confirm_reset(&client, &base, &token).await,
+        StatusCode::BAD_REQUEST
+    );
+
+    handle.abort();
+    delete_user(&username).await;
+}
+
+#[tokio::test]
+async fn limite_par_compte_sans_changer_la_reponse() {
+    let (_uid, username, email, _pwd) = create_test_user("reset_limit").await;
+    let (base, handle) = start_server().await;
+    let client = reqwest::Client::new();
+
+    for _ in 0..3 {
+        assert!(request_reset(&client, &base, &username).await.is_some());
+    }
+    // 4e demande: même statut 202, mais aucun lien émis
+    assert!(request_reset(&client, &base, &username).await.is_none());
+
+    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+    assert_eq!(outbox_for(&email).await.len(), 3);
+
+    handle.abort();
+    delete_user(&username).await;
+}
+
+#[tokio::test]
+async fn limite_par_ip() {
+    let (uid, username, _email, _pwd) = create_test_user("reset_ip").await;
+    let pool = &connect_pool().await;
+    // IP de documentation (RFC 5737): n'interfère pas avec les autres tests
+    let ip = Some("203.0.113.31");
+
+    assert_eq!(
+        password_reset::ip_retry_after(
+            pool,
+            &SystemClock,
+            &common::test_config().password_reset,
+            ip
+        )
+        .await
+        .unwrap(),
+        None
+    );
+    for _ in 0..10 {
+        password_reset::log_request(pool, &SystemClock, ip, Some(uid), false)
+            .await
+            .unwrap();
+    }
+    let retry_after = password_reset::ip_retry_after(
+        pool,
+        &SystemClock,
+        &common::test_config().password_reset,
+        ip,
+    )
+    .await
+    .unwrap()
+    .expect("limite par IP atteinte");
+    assert!((1..=3600).contains(&retry_after));
+
+    delete_user(&username).await;
+}
+
+#[tokio::test]
+async fn demandes_hors_fenetre_purgees() {
+    let pool = &connect_pool().await;
+    let ip = "203.0.113.32";
+    password_reset::log_request(pool, &SystemClock, Some(ip), None, false)
+        .await
+        .unwrap();
+    // Demande vieille de deux fenêtres
+    sqlx::query(
+        r#"INSERT INTO password_reset_requests (ip, sent, created_at)
+           VALUES ($1, false, NOW() - make_interval(secs => 7200))"#,
+    )
+    .bind(ip)
+    .execute(pool)
+    .await
+    .unwrap();
+
+    assert!(
+        password_reset::prune_requests(pool, common::test_config().password_reset.rate_window_secs)
+            .await
+            .unwrap()
+            >= 1
+    );
+    let left: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM password_reset_requests WHERE ip = $1")
+            .bind(ip)
+            .fetch_one(pool)
+            .await
+            .unwrap();
+    assert_eq!(left, 1);
+
+    sqlx::query("DELETE FROM password_reset_requests WHERE ip = $1")
+        .bind(ip)
+        .execute(pool)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn mailer_et_alea_injectes() {
+    let (_uid, username, email, _pwd) = create_test_user("reset_fakes").await;
+    let start = uuid::Uuid::new_v4().as_u64_pair().0;
+    let mailer = FakeMailer::default();
+    let (base, handle) = start_server_with(|state| {
+        state
+            .with_mailer(Arc::new(mailer.clone()))
+            .with_rng(Arc::new(SeqRng::starting_at(start)))
+    })
+    .await;
+    let client = reqwest::Client::new();
+
+    request_reset(&client, &base, &username).await;
+    let expected = SeqRng::starting_at(start).hex(32);
+    let sent = mailer.sent_to(&email).await;
+    assert_eq!(sent.len(), 1);
+    assert!(sent[0].body.contains(&expected), "{}", sent[0].body);
+
+    assert_eq!(
+        confirm_reset(&client, &base, &expected).await,
+        StatusCode::OK
+    );
+
+    handle.abort();
+    delete_user(&username).await;
+}
+
+#[tokio::test]
+async fn expiration_du_token_selon_l_horloge_injectee() {
+    let (_uid, username, _email, _pwd) = create_test_user("reset_clock").await;
+    // Dans le passé: les demandes datées par l'horloge ne pèsent pas sur les autres tests
+    let clock = FakeClock::at(time::OffsetDateTime::now_utc() - time::Duration::days(1));
+    let (base, handle) = start_server_with(|state| state.with_clock(clock.clone())).await;
+    let client = reqwest::Client::new();
+    let ttl = common::test_config().auth.reset_ttl_secs;
+
+    // Encore valide pour l'horloge injectée, bien qu'échu en temps réel
+    let token = request_reset(&client, &base, &username).await.unwrap();
+    clock.advance(time::Duration::seconds(ttl - 60));
+    assert_eq!(confirm_reset(&client, &base, &token).await, StatusCode::OK);
+
+    let token = request_reset(&client, &base, &username).await.unwrap();
+    clock.advance(time::Duration::seconds(ttl + 1));
+    assert_eq!(
+        confirm_reset(&client, &base, &token).await,
+        StatusCode::BAD_REQUEST
+    );
+
+    handle.abort();
+    delete_user(&username).await;
+}
@@ -21,7 +21,7 @@ async fn list_search_catch_get() {
     let (uid, username, _email, _pwd) = create_test_user("poke_user").await;
     let (base, handle) = start_server().await;
     let client = reqwest::Client::new();
-    let access = backend::auth::generate_access_token(uid).unwrap();
+    let access = common::access_token(uid);
     let res = client
         .get(format!("{}/api/pokemons", base))
         .header(reqwest::header::COOKIE, cookie_header(&[("auth", &access)]))
@@ -48,9 +48,15 @@ async fn list_search_catch_get() {
         .collect::<Vec<_>>();
     assert!(names.iter().any(|n| n == "Pikachu"));
 
+    // Token sans session: le jeton CSRF est lié à l'utilisateur
+    let csrf = backend::csrf::issue(common::test_config(), uid);
     let res = client
         .post(format!("{}/api/pokemons/catch", base))
-        .header(reqwest::header::COOKIE, cookie_header(&[("auth", &access)]))
+        .header(
+            reqwest::header::COOKIE,
+            cookie_header(&[("auth", &access), ("csrf", &csrf)]),
+        )
+        .header("X-CSRF-Token", &csrf)
         .json(&json!({"name": "Pikachu"}))
         .send()
         .await
@@ -0,0 +1,155 @@
+use pokedex_rncp_backend as backend;
+
+use backend::rate_limit::{Policy, RateLimiter, TrustedNet, client_ip};
+use reqwest::StatusCode;
+use reqwest::header::HeaderMap;
+use std::net::IpAddr;
+
+mod common;
+use common::{create_test_user, delete_user, start_server};
+
+fn header_u64(headers: &HeaderMap, name: &str) -> u64 {
+    headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse().ok())
+        .unwrap_or_else(|| panic!("en-tête {name} attendu"))
+}
+
+#[tokio::test]
+async fn login_limite_avec_en_tetes() {
+    let (base, handle) = start_server().await;
+    let client = reqwest::Client::new();
+
+    // Corps vide: rejeté par le handler sans toucher à la base, mais compté par le limiteur
+    let mut remaining = Vec::new();
+    for _ in 0..Policy::LOGIN.limit {
+        let res = client
+            .post(format!("{}/api/auth/login", base))
+            .send()
+            .await
+            .unwrap();
+        assert_ne!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(
+            header_u64(res.headers(), "ratelimit-limit"),
+            Policy::LOGIN.limit as u64
+        );
+        remaining.push(header_u64(res.headers(), "ratelimit-remaining"));
+    }
+    assert_eq!(remaining.first(), Some(&(Policy::LOGIN.limit as u64 - 1)));
+    assert_eq!(remaining.last(), Some(&0));
+
+    let res = client
+        .post(format!("{}/api/auth/login", base))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!(header_u64(res.headers(), "retry-after") >= 1);
+    assert_eq!(header_u64(res.headers(), "ratelimit-remaining"), 0);
+
+    // Les autres routes ont leur propre seau
+    let res = client
+        .get(format!("{}/api/pokemons", base))
+        .send()
+        .await
+        .unwrap();
+    assert_ne!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(
+        header_u64(res.headers(), "ratelimit-limit"),
+        Policy::POKEMONS.limit as u64
+    );
+
+    handle.abort();
+}
+
+#[tokio::test]
+async fn utilisateurs_authentifies_ont_chacun_leur_seau() {
+    let (uid_a, user_a, _, _) = create_test_user("rl_a").await;
+    let (uid_b, user_b, _, _) = create_test_user("rl_b").await;
+    let (base, handle) = start_server().await;
+    let client = reqwest::Client::new();
+    let access_a = common::access_token(uid_a);
+    let access_b = common::access_token(uid_b);
+
+    for _ in 0..Policy::DEFAULT.limit {
+        let res = client
+            .get(format!("{}/api/auth/me", base))
+            .bearer_auth(&access_a)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+    let res = client
+        .get(format!("{}/api/auth/me", base))
+        .bearer_auth(&access_a)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+
+    // Même IP, autre utilisateur: non affecté
+    let res = client
+        .get(format!("{}/api/auth/me", base))
+        .bearer_auth(&access_b)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+
+    handle.abort();
+    delete_user(&user_a).await;
+    delete_user(&user_b).await;
+}
+
+#[test]
+fn seau_se_recharge_avec_le_temps() {
+    let limiter = RateLimiter::new(common::test_config(), Policy::new("test_refill", 2, 1));
+    assert!(limiter.check("k").allowed);
+    assert!(limiter.check("k").allowed);
+    let refused = limiter.check("k");
+    assert!(!refused.allowed);
+    assert_eq!(refused.reset_secs, 1);
+    assert!(limiter.check("autre").allowed);
+
+    std::thread::sleep(std::time::Duration::from_millis(600));
+    assert!(limiter.check("k").allowed);
+}
+
+fn xff(value: &str) -> reqwest::header::HeaderMap {
+    let mut headers = reqwest::header::HeaderMap::new();
+    headers.insert("x-forwarded-for", value.parse().unwrap());
+    headers
+}
+
+#[test]
+fn x_forwarded_for_seulement_derriere_un_proxy_de_confiance() {
+    let ip = |s: &str| s.parse::<IpAddr>().unwrap();
+    let trusted = [
+        TrustedNet::parse("10.0.0.0/8").unwrap(),
+        TrustedNet::parse("192.168.1.5").unwrap(),
+    ];
+
+    // Connexion directe d'un client: l'en-tête est ignoré (falsifiable)
+    assert_eq!(
+        client_ip(ip("203.0.113.9"), &xff("1.2.3.4"), &trusted),
+        ip("203.0.113.9")
+    );
+    // Derrière le proxy: premier saut non fiable en partant de la droite
+    assert_eq!(
+        client_ip(
+            ip("10.1.2.3"),
+            &xff("1.2.3.4, 198.51.100.7, 192.168.1.5"),
+            &trusted
+        ),
+        ip("198.51.100.7")
+    );
+    // Sans en-tête: l'adresse du proxy
+    assert_eq!(
+        client_ip(ip("10.1.2.3"), &HeaderMap::new(), &trusted),
+        ip("10.1.2.3")
+    );
+    assert!(TrustedNet::parse("10.0.0.0/33").is_none());
+    assert!(TrustedNet::parse("pas-une-ip").is_none());
+}
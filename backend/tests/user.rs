@@ -19,7 +19,7 @@ async fn create_user_201_et_conflit_409() {
         .json(&json!({
             "username": username,
             "email": email,
-            "password": "Password123!"
+            "password": "SignupPass123!"
         }))
         .send()
         .await
@@ -31,7 +31,7 @@ async fn create_user_201_et_conflit_409() {
         .json(&json!({
             "username": username,
             "email": email,
-            "password": "Password123!"
+            "password": "SignupPass123!"
         }))
         .send()
         .await
@@ -48,7 +48,7 @@ async fn update_user_refuse_si_different() {
     let (b_id, b_username, _b_email, _b_pwd) = create_test_user("userB").await;
     let (base, handle) = start_server().await;
     let client = reqwest::Client::new();
-    let access = backend::auth::generate_access_token(a_id).unwrap();
+    let access = common::access_token(a_id);
     let res = client
         .patch(format!("{}/api/users/{}", base, b_id))
         .bearer_auth(access)
@@ -69,7 +69,7 @@ async fn update_user_soi_meme_modifie_username_email_et_password() {
     let (base, handle) = start_server().await;
     let client = reqwest::Client::new();
 
-    let access = backend::auth::generate_access_token(uid).unwrap();
+    let access = common::access_token(uid);
 
     let new_username = format!("{}_new", username);
     let new_email = format!("new_{}", email);
@@ -122,7 +122,7 @@ async fn delete_user_soi_meme() {
     let (base, handle) = start_server().await;
     let client = reqwest::Client::new();
 
-    let access = backend::auth::generate_access_token(uid).unwrap();
+    let access = common::access_token(uid);
 
     let res = client
         .delete(format!("{}/api/users/{}", base, uid))
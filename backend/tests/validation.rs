@@ -0,0 +1,222 @@
+use pokedex_rncp_backend as backend;
+
+use backend::clock::SystemClock;
+use backend::rng::SystemRng;
+use backend::validation::{PasswordPolicy, ValidationErrors, check_email, check_username};
+use reqwest::StatusCode;
+use serde_json::{Value, json};
+use std::path::PathBuf;
+
+mod common;
+use common::{connect_pool, create_test_user, delete_user, start_server};
+
+fn field_errors(body: &Value, field: &str) -> usize {
+    body["fields"][field].as_array().map_or(0, |a| a.len())
+}
+
+#[test]
+fn regles_nom_email_et_politique_de_mot_de_passe() {
+    let mut errors = ValidationErrors::default();
+    check_username("sacha.du-bourg_42", &mut errors);
+    check_email("sacha+dex@bourg-palette.kanto.fr", &mut errors);
+    assert!(errors.is_empty());
+
+    for username in ["ab", "_sacha", "sacha dupont", "sacha!", &"a".repeat(51)] {
+        let mut errors = ValidationErrors::default();
+        check_username(username, &mut errors);
+        assert!(errors.field("username").is_some(), "{username}");
+    }
+    for email in [
+        "sacha",
+        "sacha@",
+        "@kanto.fr",
+        "sacha@kanto",
+        "sa cha@kanto.fr",
+        "sacha@-kanto.fr",
+        "sacha..dex@kanto.fr",
+        &format!("{}@kanto.fr", "a".repeat(95)),
+    ] {
+        let mut errors = ValidationErrors::default();
+        check_email(email, &mut errors);
+        assert!(errors.field("email").is_some(), "{email}");
+    }
+
+    let breached = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/breached.txt");
+    let policy = PasswordPolicy {
+        breached_list: Some(breached),
+        ..PasswordPolicy::default()
+    };
+    let check = |password: &str, inputs: &[&str]| {
+        let mut errors = ValidationErrors::default();
+        policy.check(password, inputs, &mut errors);
+        errors.field("password").map_or(0, |e| e.len())
+    };
+    assert_eq!(check("Sacha-Bourg-Palette-42", &["sacha"]), 0);
+    // Trop court et trop faible
+    assert_eq!(check("pika", &[]), 2);
+    assert!(check("password123", &[]) > 0);
+    // Identique au nom d'utilisateur (casse ignorée)
+    assert!(check("Dresseur-Legendaire-Kanto", &["dresseur-legendaire-kanto"]) > 0);
+    // Robuste pour zxcvbn, mais présent dans la liste de fuites
+    assert_eq!(check("Tr0ub4dour&3-Kanto", &[]), 1);
+    // Au-delà de la longueur maximale: refusé avant toute autre vérification
+    assert_eq!(check(&"x7#Qk".repeat(30), &[]), 1);
+}
+
+#[tokio::test]
+async fn inscription_erreurs_par_champ_et_unicite_insensible_a_la_casse() {
+    let (base, handle) = start_server().await;
+    let client = reqwest::Client::new();
+
+    let res = client
+        .post(format!("{base}/api/users"))
+        .json(&json!({ "username": "x".repeat(60), "email": "pas-un-email", "password": "" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(field_errors(&body, "username"), 1);
+    assert_eq!(field_errors(&body, "email"), 1);
+    assert!(field_errors(&body, "password") >= 1);
+
+    let username = format!("Casse_{}", &uuid::Uuid::new_v4().simple().to_string()[..8]);
+    let res = client
+        .post(format!("{base}/api/users"))
+        .json(&json!({ "username": username, "password": username }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    let res = client
+        .post(format!("{base}/api/users"))
+        .json(&json!({
+            "username": username,
+            "email": format!("{username}@example.com"),
+            "password": "SignupPass123!"
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::CREATED);
+
+    let res = client
+        .post(format!("{base}/api/users"))
+        .json(&json!({
+            "username": username.to_uppercase(),
+            "email": format!("{username}@EXAMPLE.com"),
+            "password": "SignupPass123!"
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::CONFLICT);
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(field_errors(&body, "username"), 1);
+    assert_eq!(field_errors(&body, "email"), 1);
+
+    // Même règle à la connexion et pour le reset: "casse_…" désigne ce compte
+    for login in [
+        username.to_lowercase(),
+        format!("{}@EXAMPLE.COM", username.to_uppercase()),
+    ] {
+        let res = client
+            .post(format!("{base}/api/auth/login"))
+            .json(&json!({ "username": login, "password": "SignupPass123!" }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK, "connexion avec {login}");
+    }
+    let res = client
+        .post(format!("{base}/api/auth/request-password-reset"))
+        .json(&json!({ "email_or_username": username.to_lowercase() }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::ACCEPTED);
+    let body: Value = res.json().await.unwrap();
+    assert!(body["reset_token"].is_string());
+
+    handle.abort();
+    delete_user(&username).await;
+}
+
+#[tokio::test]
+async fn profil_changement_et_reset_appliquent_la_politique() {
+    let (uid, username, _email, password) = create_test_user("policy").await;
+    let (_other_id, other, _other_email, _pwd) = create_test_user("policy_other").await;
+    let (base, handle) = start_server().await;
+    let client = reqwest::Client::new();
+    let access = common::access_token(uid);
+
+    // Nom trop long: 422 au lieu d'une erreur SQL
+    let res = client
+        .patch(format!("{base}/api/users/{uid}"))
+        .bearer_auth(&access)
+        .json(&json!({ "username": "n".repeat(80) }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    let res = client
+        .patch(format!("{base}/api/users/{uid}"))
+        .bearer_auth(&access)
+        .json(&json!({ "username": other.to_uppercase() }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::CONFLICT);
+
+    let res = client
+        .patch(format!("{base}/api/users/{uid}"))
+        .bearer_auth(&access)
+        .json(&json!({ "password": "azerty" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    let res = client
+        .put(format!("{base}/api/auth/change-password"))
+        .bearer_auth(&access)
+        .json(&json!({ "current_password": password, "new_password": username }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let body: Value = res.json().await.unwrap();
+    assert!(field_errors(&body, "password") >= 1);
+
+    // Reset: mot de passe refusé sans consommer le token
+    let pool = connect_pool().await;
+    let token = backend::password_reset::issue(
+        &pool,
+        &SystemClock,
+        &SystemRng,
+        uid,
+        common::test_config().auth.reset_ttl_secs,
+    )
+    .await
+    .unwrap();
+    let confirm = |new_password: &'static str| {
+        client
+            .post(format!("{base}/api/auth/confirm-password-reset"))
+            .json(&json!({ "token": token, "new_password": new_password }))
+            .send()
+    };
+    assert_eq!(
+        confirm("12345678").await.unwrap().status(),
+        StatusCode::UNPROCESSABLE_ENTITY
+    );
+    assert_eq!(
+        confirm("ResetPass123!").await.unwrap().status(),
+        StatusCode::OK
+    );
+
+    handle.abort();
+    delete_user(&username).await;
+    delete_user(&other).await;
+}
@@ -0,0 +1,96 @@
+use pokedex_rncp_backend::versioning::LEGACY_DEPRECATED_AT;
+use reqwest::StatusCode;
+use reqwest::header::LINK;
+use serde_json::Value;
+
+mod common;
+use common::{create_test_user, delete_user, start_server};
+
+async fn get(client: &reqwest::Client, url: String, token: &str) -> reqwest::Response {
+    client.get(url).bearer_auth(token).send().await.unwrap()
+}
+
+#[tokio::test]
+async fn v1_et_alias_obsolete() {
+    let (uid, username, _, _) = create_test_user("version").await;
+    let token = common::access_token(uid);
+    let (base, handle) = start_server().await;
+    let client = reqwest::Client::new();
+
+    let res = get(&client, format!("{base}/api/v1/pokemons"), &token).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert!(res.headers().get("deprecation").is_none());
+    let v1: Value = res.json().await.unwrap();
+    assert!(v1.as_array().is_some_and(|list| !list.is_empty()));
+
+    // Même réponse sous l'alias, annoncée obsolète avec son remplaçant
+    let res = get(&client, format!("{base}/api/pokemons?limit=2"), &token).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let headers = res.headers().clone();
+    assert_eq!(
+        headers["deprecation"].to_str().unwrap(),
+        format!("@{LEGACY_DEPRECATED_AT}")
+    );
+    assert_eq!(
+        headers["sunset"].to_str().unwrap(),
+        "Fri, 30 Apr 2027 00:00:00 GMT"
+    );
+    assert_eq!(
+        headers[LINK].to_str().unwrap(),
+        "</api/v1/pokemons>; rel=\"successor-version\""
+    );
+    assert_eq!(res.json::<Value>().await.unwrap(), v1);
+
+    let res = reqwest::get(format!("{base}/api")).await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(
+        res.headers()[LINK].to_str().unwrap(),
+        "</api/v1>; rel=\"successor-version\""
+    );
+    let res = reqwest::get(format!("{base}/api/v1")).await.unwrap();
+    assert_eq!(res.text().await.unwrap(), "Bienvenue sur le pokeRncp");
+
+    delete_user(&username).await;
+    handle.abort();
+}
+
+#[tokio::test]
+async fn v2_ne_sert_que_le_catalogue_pagine() {
+    let (uid, username, _, _) = create_test_user("version2").await;
+    let token = common::access_token(uid);
+    let (base, handle) = start_server().await;
+    let client = reqwest::Client::new();
+
+    let all: Vec<Value> = get(&client, format!("{base}/api/v1/pokemons"), &token)
+        .await
+        .json()
+        .await
+        .unwrap();
+    let res = get(
+        &client,
+        format!("{base}/api/v2/pokemons?limit=2&offset=1"),
+        &token,
+    )
+    .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert!(res.headers().get("deprecation").is_none());
+    let page: Value = res.json().await.unwrap();
+    assert_eq!(page["total"], all.len() as i64);
+    assert_eq!(page["pokemons"], Value::Array(all[1..3].to_vec()));
+
+    // Paramètres vérifiés en v2, ignorés en v1 comme auparavant
+    let res = get(&client, format!("{base}/api/v2/pokemons?limit=x"), &token).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    let res = get(&client, format!("{base}/api/v1/pokemons?limit=x"), &token).await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    // Endpoint sans v2 dédiée: servi sous /api/v1 seulement
+    let res = get(&client, format!("{base}/api/v1/auth/me"), &token).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let res = get(&client, format!("{base}/api/v2/auth/me"), &token).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    assert!(res.text().await.unwrap().contains("/api/v1"));
+
+    delete_user(&username).await;
+    handle.abort();
+}
@@ -1,5 +1,5 @@
 use gloo_net::http::Request;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use wasm_bindgen_futures::spawn_local;
 use yew::prelude::*;
 
@@ -15,34 +15,124 @@ struct LoginBody {
     password: String,
 }
 
+// Réponse 202 du login quand la double authentification est activée
+#[derive(Deserialize)]
+struct MfaRequired {
+    mfa_token: String,
+}
+
+#[derive(Serialize)]
+struct MfaLoginBody {
+    // Absent après un login OIDC: le backend lit son cookie HttpOnly
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mfa_token: Option<String>,
+    // Code à 6 chiffres de l'application, ou code de récupération
+    code: String,
+}
+
+// Fournisseur OpenID Connect proposé en plus du mot de passe
+#[derive(Clone, PartialEq, Deserialize)]
+struct OidcProvider {
+    name: String,
+    display_name: String,
+}
+
+// Retour d'un login OIDC quand la 2FA est activée: /login?mfa=pending
+// (le token intermédiaire est resté dans un cookie que la page ne lit pas)
+fn oidc_mfa_pending() -> bool {
+    crate::url::query_param("mfa").is_some_and(|v| v == "pending")
+}
+
 #[function_component]
 pub fn LoginForm(props: &Props) -> Html {
     let username = use_state(|| String::new());
     let password = use_state(|| String::new());
+    let code = use_state(|| String::new());
+    let mfa_token = use_state(|| None as Option<String>);
+    let oidc_pending = use_state(oidc_mfa_pending);
     let error = use_state(|| None as Option<String>);
+    let providers = use_state(Vec::<OidcProvider>::new);
+
+    {
+        let providers = providers.clone();
+        use_effect_with((), move |_| {
+            spawn_local(async move {
+                if let Ok(resp) = Request::get("/api/v1/auth/oidc/providers").send().await {
+                    if let Ok(list) = resp.json::<Vec<OidcProvider>>().await {
+                        providers.set(list);
+                    }
+                }
+            });
+            || ()
+        });
+    }
 
     let on_submit = {
         let username = username.clone();
         let password = password.clone();
+        let code = code.clone();
+        let mfa_token = mfa_token.clone();
+        let oidc_pending = oidc_pending.clone();
         let on_logged_in = props.on_logged_in.clone();
         let error = error.clone();
         Callback::from(move |e: SubmitEvent| {
             e.prevent_default();
+            let on_logged_in = on_logged_in.clone();
+            let error = error.clone();
+            let mfa_token = mfa_token.clone();
+
+            // Seconde étape: code 2FA
+            if mfa_token.is_some() || *oidc_pending {
+                let body = MfaLoginBody {
+                    mfa_token: (*mfa_token).clone(),
+                    code: (*code).trim().to_string(),
+                };
+                spawn_local(async move {
+                    match Request::post("/api/v1/auth/2fa/login")
+                        .credentials(web_sys::RequestCredentials::Include)
+                        .header("X-CSRF-Token", &crate::csrf::token())
+                        .json(&body)
+                        .unwrap()
+                        .send()
+                        .await
+                    {
+                        Ok(resp) if resp.status() == 200 => {
+                            crate::url::replace("/");
+                            on_logged_in.emit(())
+                        }
+                        Ok(resp) if resp.status() == 401 => {
+                            error.set(Some("Code invalide ou expiré.".into()))
+                        }
+                        Ok(resp) => {
+                            error.set(Some(format!("Échec connexion ({}).", resp.status())))
+                        }
+                        Err(err) => error.set(Some(format!("Erreur réseau: {}", err))),
+                    }
+                });
+                return;
+            }
+
             let body = LoginBody {
                 username: (*username).clone(),
                 password: (*password).clone(),
             };
-            let on_logged_in = on_logged_in.clone();
-            let error = error.clone();
             spawn_local(async move {
-                match Request::post("/api/auth/login")
+                match Request::post("/api/v1/auth/login")
                     .credentials(web_sys::RequestCredentials::Include)
+                    .header("X-CSRF-Token", &crate::csrf::token())
                     .json(&body)
                     .unwrap()
                     .send()
                     .await
                 {
                     Ok(resp) if resp.status() == 200 => on_logged_in.emit(()),
+                    Ok(resp) if resp.status() == 202 => match resp.json::<MfaRequired>().await {
+                        Ok(r) => {
+                            error.set(None);
+                            mfa_token.set(Some(r.mfa_token));
+                        }
+                        Err(err) => error.set(Some(format!("Réponse inattendue: {}", err))),
+                    },
                     Ok(resp) => error.set(Some(format!("Échec connexion ({}).", resp.status()))),
                     Err(err) => error.set(Some(format!("Erreur réseau: {}", err))),
                 }
@@ -50,18 +140,31 @@ pub fn LoginForm(props: &Props) -> Html {
         })
     };
 
+    let code_step = mfa_token.is_some() || *oidc_pending;
     html! {
         <form onsubmit={on_submit} class="form auth-form">
-            <div class="field">
-                <label>{"Email ou nom d'utilisateur"}</label>
-                <input type="text" placeholder="ex: ash ou ash@example.com" value={(*username).clone()} oninput={{ let username = username.clone(); Callback::from(move |e: InputEvent| { if let Some(t) = e.target_dyn_into::<web_sys::HtmlInputElement>() { username.set(t.value()); } }) }} />
-            </div>
-            <div class="field">
-                <label>{"Mot de passe"}</label>
-                <input type="password" value={(*password).clone()} oninput={{ let password = password.clone(); Callback::from(move |e: InputEvent| { if let Some(t) = e.target_dyn_into::<web_sys::HtmlInputElement>() { password.set(t.value()); } }) }} />
-            </div>
+            if code_step {
+                <div class="field">
+                    <label>{"Code de double authentification"}</label>
+                    <input type="text" inputmode="numeric" autocomplete="one-time-code" placeholder="123456 ou code de récupération" value={(*code).clone()} oninput={{ let code = code.clone(); Callback::from(move |e: InputEvent| { if let Some(t) = e.target_dyn_into::<web_sys::HtmlInputElement>() { code.set(t.value()); } }) }} />
+                </div>
+            } else {
+                <div class="field">
+                    <label>{"Email ou nom d'utilisateur"}</label>
+                    <input type="text" placeholder="ex: ash ou ash@example.com" value={(*username).clone()} oninput={{ let username = username.clone(); Callback::from(move |e: InputEvent| { if let Some(t) = e.target_dyn_into::<web_sys::HtmlInputElement>() { username.set(t.value()); } }) }} />
+                </div>
+                <div class="field">
+                    <label>{"Mot de passe"}</label>
+                    <input type="password" value={(*password).clone()} oninput={{ let password = password.clone(); Callback::from(move |e: InputEvent| { if let Some(t) = e.target_dyn_into::<web_sys::HtmlInputElement>() { password.set(t.value()); } }) }} />
+                </div>
+            }
             if let Some(err) = &*error { <p class="error">{err}</p> }
-            <button class="loginbutton" type="submit">{"Se connecter"}</button>
+            <button class="loginbutton" type="submit">{ if code_step { "Valider le code" } else { "Se connecter" } }</button>
+            if !code_step {
+                { for providers.iter().map(|p| html! {
+                    <a class="loginbutton oidc" href={format!("/api/v1/auth/oidc/{}/start", p.name)}>{ format!("Se connecter avec {}", p.display_name) }</a>
+                }) }
+            }
         </form>
     }
 }
@@ -2,10 +2,16 @@ mod layout;
 mod login_form;
 mod pokedex;
 mod profile;
+mod reset_password;
 mod signup_form;
+mod unlock_account;
+mod verify_email;
 
 pub use layout::Layout;
 pub use login_form::LoginForm;
 pub use pokedex::Pokedex;
 pub use profile::Profile;
+pub use reset_password::ResetPasswordPage;
 pub use signup_form::SignUpForm;
+pub use unlock_account::UnlockAccountPage;
+pub use verify_email::VerifyEmailPage;
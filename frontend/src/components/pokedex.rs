@@ -67,11 +67,11 @@ pub fn Pokedex() -> Html {
                 spawn_local(async move {
                     let make_req = |term: &String| {
                         if term.trim().is_empty() {
-                            Request::get("/api/pokemons")
+                            Request::get("/api/v1/pokemons")
                                 .credentials(web_sys::RequestCredentials::Include)
                         } else {
                             let url =
-                                format!("/api/pokemons/search?q={}", urlencoding::encode(term));
+                                format!("/api/v1/pokemons/search?q={}", urlencoding::encode(term));
                             Request::get(&url).credentials(web_sys::RequestCredentials::Include)
                         }
                     };
@@ -79,8 +79,9 @@ pub fn Pokedex() -> Html {
                     let mut resp = make_req(&term).send().await;
                     if let Ok(r) = &resp {
                         if r.status() == 401 || r.status() == 403 {
-                            if let Ok(rr) = Request::post("/api/auth/refresh-token")
+                            if let Ok(rr) = Request::post("/api/v1/auth/refresh-token")
                                 .credentials(web_sys::RequestCredentials::Include)
+                                .header("X-CSRF-Token", &crate::csrf::token())
                                 .send()
                                 .await
                             {
@@ -122,23 +123,26 @@ pub fn Pokedex() -> Html {
                 let body = serde_json::json!({ "name": name.clone() });
                 let target_name = name.clone();
                 let mut did_refresh = false;
-                let mut resp = Request::post("/api/pokemons/catch")
+                let mut resp = Request::post("/api/v1/pokemons/catch")
                     .credentials(web_sys::RequestCredentials::Include)
+                    .header("X-CSRF-Token", &crate::csrf::token())
                     .json(&body)
                     .unwrap()
                     .send()
                     .await;
                 if let Ok(r) = &resp {
                     if r.status() == 401 || r.status() == 403 {
-                        if let Ok(rr) = Request::post("/api/auth/refresh-token")
+                        if let Ok(rr) = Request::post("/api/v1/auth/refresh-token")
                             .credentials(web_sys::RequestCredentials::Include)
+                            .header("X-CSRF-Token", &crate::csrf::token())
                             .send()
                             .await
                         {
                             if rr.status() == 200 {
                                 did_refresh = true;
-                                resp = Request::post("/api/pokemons/catch")
+                                resp = Request::post("/api/v1/pokemons/catch")
                                     .credentials(web_sys::RequestCredentials::Include)
+                                    .header("X-CSRF-Token", &crate::csrf::token())
                                     .json(&body)
                                     .unwrap()
                                     .send()
@@ -188,7 +192,7 @@ pub fn Pokedex() -> Html {
             let detail = detail.clone();
             let detail_error = detail_error.clone();
             spawn_local(async move {
-                let url = format!("/api/pokemons/{}", id);
+                let url = format!("/api/v1/pokemons/{}", id);
                 let mut did_refresh = false;
                 let mut resp = Request::get(&url)
                     .credentials(web_sys::RequestCredentials::Include)
@@ -196,8 +200,9 @@ pub fn Pokedex() -> Html {
                     .await;
                 if let Ok(r) = &resp {
                     if r.status() == 401 || r.status() == 403 {
-                        if let Ok(rr) = Request::post("/api/auth/refresh-token")
+                        if let Ok(rr) = Request::post("/api/v1/auth/refresh-token")
                             .credentials(web_sys::RequestCredentials::Include)
+                            .header("X-CSRF-Token", &crate::csrf::token())
                             .send()
                             .await
                         {
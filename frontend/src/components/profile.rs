@@ -35,7 +35,7 @@ pub fn Profile(props: &Props) -> Html {
             let me = me.clone();
             let me_error = me_error.clone();
             spawn_local(async move {
-                let res = Request::get("/api/auth/me")
+                let res = Request::get("/api/v1/auth/me")
                     .credentials(web_sys::RequestCredentials::Include)
                     .send()
                     .await;
@@ -62,7 +62,7 @@ pub fn Profile(props: &Props) -> Html {
             let caught_count = caught_count.clone();
             let caught_error = caught_error.clone();
             spawn_local(async move {
-                match Request::get("/api/pokemons")
+                match Request::get("/api/v1/pokemons")
                     .credentials(web_sys::RequestCredentials::Include)
                     .send()
                     .await
@@ -87,8 +87,9 @@ pub fn Profile(props: &Props) -> Html {
         Callback::from(move |_| {
             let on_logged_out = on_logged_out.clone();
             spawn_local(async move {
-                let _ = Request::post("/api/auth/logout")
+                let _ = Request::post("/api/v1/auth/logout")
                     .credentials(web_sys::RequestCredentials::Include)
+                    .header("X-CSRF-Token", &crate::csrf::token())
                     .send()
                     .await;
                 on_logged_out.emit(());
@@ -127,8 +128,9 @@ pub fn Profile(props: &Props) -> Html {
             let pwd_error = pwd_error.clone();
             let success = success.clone();
             spawn_local(async move {
-                match Request::put("/api/auth/change-password")
+                match Request::put("/api/v1/auth/change-password")
                     .credentials(web_sys::RequestCredentials::Include)
+                    .header("X-CSRF-Token", &crate::csrf::token())
                     .json(&body)
                     .unwrap()
                     .send()
@@ -137,6 +139,14 @@ pub fn Profile(props: &Props) -> Html {
                     Ok(r) if r.status() == 200 => {
                         success.set(Some("Mot de passe mis à jour.".into()))
                     }
+                    Ok(r) if r.status() == 422 => {
+                        let status = r.status();
+                        pwd_error.set(Some(
+                            crate::validation::field_messages(r)
+                                .await
+                                .unwrap_or_else(|| format!("Échec mise à jour ({status}).")),
+                        ))
+                    }
                     Ok(r) => pwd_error.set(Some(format!("Échec mise à jour ({}).", r.status()))),
                     Err(e) => pwd_error.set(Some(format!("Erreur réseau: {}", e))),
                 }
@@ -0,0 +1,117 @@
+use gloo_net::http::Request;
+use serde::Serialize;
+use wasm_bindgen_futures::spawn_local;
+use yew::prelude::*;
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    /// Retour à l'écran de connexion
+    pub on_done: Callback<()>,
+}
+
+#[derive(Serialize)]
+struct ConfirmBody {
+    token: String,
+    new_password: String,
+}
+
+// Page du lien reçu par email: /reset-password?token=...
+#[function_component]
+pub fn ResetPasswordPage(props: &Props) -> Html {
+    let token = use_state(|| crate::url::query_param("token"));
+    let password = use_state(String::new);
+    let confirm = use_state(String::new);
+    let error = use_state(|| None as Option<String>);
+    let done = use_state(|| false);
+
+    let on_submit = {
+        let token = token.clone();
+        let password = password.clone();
+        let confirm = confirm.clone();
+        let error = error.clone();
+        let done = done.clone();
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+            error.set(None);
+            let Some(token) = (*token).clone() else {
+                return;
+            };
+            if password.is_empty() {
+                error.set(Some("Veuillez choisir un mot de passe.".into()));
+                return;
+            }
+            if *password != *confirm {
+                error.set(Some("La confirmation ne correspond pas.".into()));
+                return;
+            }
+            let body = ConfirmBody {
+                token,
+                new_password: (*password).clone(),
+            };
+            let error = error.clone();
+            let done = done.clone();
+            spawn_local(async move {
+                match Request::post("/api/v1/auth/confirm-password-reset")
+                    .credentials(web_sys::RequestCredentials::Include)
+                    .header("X-CSRF-Token", &crate::csrf::token())
+                    .json(&body)
+                    .unwrap()
+                    .send()
+                    .await
+                {
+                    Ok(r) if r.status() == 200 => {
+                        // Token consommé: on le retire de l'URL
+                        crate::url::replace("/");
+                        done.set(true)
+                    }
+                    Ok(r) if r.status() == 400 => error.set(Some(
+                        "Lien invalide ou expiré: demandez une nouvelle réinitialisation.".into(),
+                    )),
+                    Ok(r) if r.status() == 422 => {
+                        let status = r.status();
+                        error.set(Some(
+                            crate::validation::field_messages(r)
+                                .await
+                                .unwrap_or_else(|| format!("Échec réinitialisation ({status}).")),
+                        ))
+                    }
+                    Ok(r) => error.set(Some(format!("Échec réinitialisation ({}).", r.status()))),
+                    Err(e) => error.set(Some(format!("Erreur réseau: {}", e))),
+                }
+            });
+        })
+    };
+
+    let back = {
+        let on_done = props.on_done.clone();
+        Callback::from(move |_| {
+            crate::url::replace("/");
+            on_done.emit(())
+        })
+    };
+
+    html! {
+        <section class="form auth-form">
+            <h2>{"Nouveau mot de passe"}</h2>
+            if *done {
+                <p class="success">{"Mot de passe réinitialisé. Vous pouvez vous connecter."}</p>
+            } else if token.is_none() {
+                <p class="error">{"Lien incomplet: ouvrez le lien reçu par email."}</p>
+            } else {
+                <form onsubmit={on_submit} class="form">
+                    <div class="field">
+                        <label>{"Nouveau mot de passe"}</label>
+                        <input type="password" autocomplete="new-password" value={(*password).clone()} oninput={{ let password = password.clone(); Callback::from(move |e: InputEvent| { if let Some(t) = e.target_dyn_into::<web_sys::HtmlInputElement>() { password.set(t.value()); } }) }} />
+                    </div>
+                    <div class="field">
+                        <label>{"Confirmer le mot de passe"}</label>
+                        <input type="password" autocomplete="new-password" value={(*confirm).clone()} oninput={{ let confirm = confirm.clone(); Callback::from(move |e: InputEvent| { if let Some(t) = e.target_dyn_into::<web_sys::HtmlInputElement>() { confirm.set(t.value()); } }) }} />
+                    </div>
+                    if let Some(err) = &*error { <p class="error">{err}</p> }
+                    <button class="loginbutton" type="submit">{"Réinitialiser"}</button>
+                </form>
+            }
+            <button class="inscriptionbutton" onclick={back}>{"Retour à la connexion"}</button>
+        </section>
+    }
+}
@@ -42,9 +42,10 @@ pub fn SignUpForm(props: &Props) -> Html {
             let on_logged_in = on_logged_in.clone();
             let error = error.clone();
             spawn_local(async move {
-                // Backend expects POST /api/users (CreateUser { username, email?, password })
-                match Request::post("/api/users")
+                // Backend expects POST /api/v1/users (CreateUser { username, email?, password })
+                match Request::post("/api/v1/users")
                     .credentials(web_sys::RequestCredentials::Include)
+                    .header("X-CSRF-Token", &crate::csrf::token())
                     .json(&body)
                     .unwrap()
                     .send()
@@ -53,6 +54,14 @@ pub fn SignUpForm(props: &Props) -> Html {
                     Ok(resp) if resp.status() == 201 || resp.status() == 200 => {
                         on_logged_in.emit(())
                     }
+                    Ok(resp) if resp.status() == 422 || resp.status() == 409 => {
+                        let status = resp.status();
+                        error.set(Some(
+                            crate::validation::field_messages(resp)
+                                .await
+                                .unwrap_or_else(|| format!("Échec inscription ({status}).")),
+                        ))
+                    }
                     Ok(resp) => error.set(Some(format!("Échec inscription ({}).", resp.status()))),
                     Err(err) => error.set(Some(format!("Erreur réseau: {}", err))),
                 }
@@ -0,0 +1,80 @@
+use gloo_net::http::Request;
+use serde::Serialize;
+use wasm_bindgen_futures::spawn_local;
+use yew::prelude::*;
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    /// Retour à l'application
+    pub on_done: Callback<()>,
+}
+
+#[derive(Serialize)]
+struct VerifyBody {
+    token: String,
+}
+
+// Page du lien reçu par email: /verify-email?token=...
+// La confirmation attend un clic: les scanners de liens des messageries ne la déclenchent pas.
+#[function_component]
+pub fn VerifyEmailPage(props: &Props) -> Html {
+    let token = use_state(|| crate::url::query_param("token"));
+    let error = use_state(|| None as Option<String>);
+    let done = use_state(|| false);
+
+    let on_confirm = {
+        let token = token.clone();
+        let error = error.clone();
+        let done = done.clone();
+        Callback::from(move |_| {
+            let Some(token) = (*token).clone() else {
+                return;
+            };
+            let error = error.clone();
+            let done = done.clone();
+            spawn_local(async move {
+                match Request::post("/api/v1/auth/verify-email")
+                    .credentials(web_sys::RequestCredentials::Include)
+                    .header("X-CSRF-Token", &crate::csrf::token())
+                    .json(&VerifyBody { token })
+                    .unwrap()
+                    .send()
+                    .await
+                {
+                    Ok(r) if r.status() == 200 => {
+                        crate::url::replace("/");
+                        done.set(true)
+                    }
+                    Ok(r) if r.status() == 400 => {
+                        error.set(Some("Lien invalide, expiré ou déjà utilisé.".into()))
+                    }
+                    Ok(r) => error.set(Some(format!("Échec vérification ({}).", r.status()))),
+                    Err(e) => error.set(Some(format!("Erreur réseau: {}", e))),
+                }
+            });
+        })
+    };
+
+    let back = {
+        let on_done = props.on_done.clone();
+        Callback::from(move |_| {
+            crate::url::replace("/");
+            on_done.emit(())
+        })
+    };
+
+    html! {
+        <section class="form auth-form">
+            <h2>{"Vérification de l'adresse email"}</h2>
+            if *done {
+                <p class="success">{"Adresse email vérifiée."}</p>
+            } else if token.is_none() {
+                <p class="error">{"Lien incomplet: ouvrez le lien reçu par email."}</p>
+            } else {
+                if let Some(err) = &*error { <p class="error">{err}</p> }
+                <button class="loginbutton" onclick={on_confirm}>{"Confirmer mon adresse"}</button>
+            }
+            <button class="inscriptionbutton" onclick={back}>{"Continuer"}</button>
+        </section>
+    }
+}
@@ -0,0 +1,16 @@
+use web_sys::wasm_bindgen::JsCast;
+
+/// Jeton CSRF posé par le serveur à la connexion (cookie `csrf`), à renvoyer
+/// dans l'en-tête `X-CSRF-Token` de chaque mutation.
+pub fn token() -> String {
+    let cookies = web_sys::window()
+        .and_then(|w| w.document())
+        .and_then(|d| d.dyn_into::<web_sys::HtmlDocument>().ok())
+        .and_then(|d| d.cookie().ok())
+        .unwrap_or_default();
+    cookies
+        .split(';')
+        .find_map(|c| c.trim().strip_prefix("csrf="))
+        .unwrap_or_default()
+        .to_string()
+}
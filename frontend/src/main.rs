@@ -1,12 +1,36 @@
 use yew::prelude::*;
 mod components;
-use components::{Layout, LoginForm, Pokedex, Profile, SignUpForm};
+mod csrf;
+mod url;
+mod validation;
+use components::{
+    Layout, LoginForm, Pokedex, Profile, ResetPasswordPage, SignUpForm, UnlockAccountPage,
+    VerifyEmailPage,
+};
 use gloo_net::http::Request;
 use gloo_storage::{LocalStorage, Storage};
 use wasm_bindgen_futures::spawn_local;
 
+// Pages ouvertes par un lien reçu par email (servies par le fallback SPA)
+#[derive(Clone, Copy, PartialEq)]
+enum LinkPage {
+    ResetPassword,
+    VerifyEmail,
+    UnlockAccount,
+}
+
+fn link_page() -> Option<LinkPage> {
+    match url::path().as_str() {
+        "/reset-password" => Some(LinkPage::ResetPassword),
+        "/verify-email" => Some(LinkPage::VerifyEmail),
+        "/unlock-account" => Some(LinkPage::UnlockAccount),
+        _ => None,
+    }
+}
+
 #[function_component]
 fn App() -> Html {
+    let link = use_state(link_page);
     let logged_in = use_state(|| false);
     const SESSION_HINT_KEY: &str = "session_hint";
 
@@ -33,15 +57,16 @@ fn App() -> Html {
                 == Some("1");
             if should_probe {
                 spawn_local(async move {
-                    match Request::get("/api/auth/me")
+                    match Request::get("/api/v1/auth/me")
                         .credentials(web_sys::RequestCredentials::Include)
                         .send()
                         .await
                     {
                         Ok(r) if r.status() == 200 => logged_in.set(true),
                         Ok(r) if r.status() == 401 || r.status() == 403 => {
-                            if let Ok(rf) = Request::post("/api/auth/refresh-token")
+                            if let Ok(rf) = Request::post("/api/v1/auth/refresh-token")
                                 .credentials(web_sys::RequestCredentials::Include)
+                                .header("X-CSRF-Token", &crate::csrf::token())
                                 .send()
                                 .await
                             {
@@ -63,8 +88,9 @@ fn App() -> Html {
         Callback::from(move |_| {
             let logged_in = logged_in.clone();
             spawn_local(async move {
-                let _ = Request::post("/api/auth/logout")
+                let _ = Request::post("/api/v1/auth/logout")
                     .credentials(web_sys::RequestCredentials::Include)
+                    .header("X-CSRF-Token", &crate::csrf::token())
                     .send()
                     .await;
                 logged_in.set(false);
@@ -73,9 +99,20 @@ fn App() -> Html {
         })
     };
 
+    let on_link_done = {
+        let link = link.clone();
+        Callback::from(move |_| link.set(None))
+    };
+
     html! {
         <Layout>
-            if !*logged_in {
+            if let Some(page) = *link {
+                { match page {
+                    LinkPage::ResetPassword => html! { <ResetPasswordPage on_done={on_link_done} /> },
+                    LinkPage::VerifyEmail => html! { <VerifyEmailPage on_done={on_link_done} /> },
+                    LinkPage::UnlockAccount => html! { <UnlockAccountPage on_done={on_link_done} /> },
+                }}
+            } else if !*logged_in {
                 <div class="page-header-actions" style="margin-bottom: 16px;">
                     <button class="loginbutton" onclick={{ let mode = mode.clone(); Callback::from(move |_| mode.set(AuthMode::Login)) }}>{"Se connecter"}</button>
                     <button class="inscriptionbutton" onclick={{ let mode = mode.clone(); Callback::from(move |_| mode.set(AuthMode::Signup)) }}>{"Créer un compte"}</button>
@@ -0,0 +1,32 @@
+/// Chemin de la page courante (`/reset-password`…): le backend sert
+/// `index.html` pour tout chemin inconnu, l'application choisit la vue.
+pub fn path() -> String {
+    web_sys::window()
+        .and_then(|w| w.location().pathname().ok())
+        .unwrap_or_default()
+}
+
+/// Paramètre de l'URL courante (ex: `token` des liens envoyés par email), décodé.
+pub fn query_param(name: &str) -> Option<String> {
+    let search = web_sys::window()?.location().search().ok()?;
+    let prefix = format!("{name}=");
+    search
+        .trim_start_matches('?')
+        .split('&')
+        .find_map(|pair| pair.strip_prefix(prefix.as_str()))
+        .map(|v| {
+            urlencoding::decode(v)
+                .map(|v| v.into_owned())
+                .unwrap_or_default()
+        })
+        .filter(|v| !v.is_empty())
+}
+
+/// Remplace l'URL affichée sans recharger: le lien utilisé (et son token)
+/// disparaît de la barre d'adresse et de l'historique.
+pub fn replace(path: &str) {
+    if let Some(history) = web_sys::window().and_then(|w| w.history().ok()) {
+        let _ =
+            history.replace_state_with_url(&web_sys::wasm_bindgen::JsValue::NULL, "", Some(path));
+    }
+}
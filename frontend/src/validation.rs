@@ -0,0 +1,19 @@
+use gloo_net::http::Response;
+
+/// Messages par champ d'une réponse 422/409 (`{ "fields": { "password": ["…"] } }`), sur une ligne.
+pub async fn field_messages(resp: Response) -> Option<String> {
+    let body: serde_json::Value = resp.json().await.ok()?;
+    let messages: Vec<&str> = body
+        .get("fields")?
+        .as_object()?
+        .values()
+        .filter_map(|v| v.as_array())
+        .flatten()
+        .filter_map(|m| m.as_str())
+        .collect();
+    if messages.is_empty() {
+        None
+    } else {
+        Some(messages.join(" · "))
+    }
+}